@@ -0,0 +1,131 @@
+-- Schema of the SQLite migrations up to 20250715000000, in one go.
+-- Later schema changes need a migration in both directories.
+
+CREATE TABLE IF NOT EXISTS users (
+    id TEXT PRIMARY KEY NOT NULL,
+    username TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    role TEXT NOT NULL DEFAULT 'member',
+    must_change_password BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Identity of users created or linked through OIDC or a trusted auth proxy
+    external_id TEXT
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_external_id ON users(external_id);
+
+CREATE TABLE IF NOT EXISTS folders (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    path TEXT NOT NULL,
+    lyrics_enabled BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX IF NOT EXISTS idx_folders_user_id ON folders(user_id);
+
+CREATE TABLE IF NOT EXISTS folder_shares (
+    folder_id TEXT NOT NULL REFERENCES folders(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    can_write BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (folder_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_folder_shares_user_id ON folder_shares(user_id);
+
+CREATE TABLE IF NOT EXISTS user_settings (
+    user_id TEXT PRIMARY KEY NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    default_metadata_provider TEXT DEFAULT 'musicbrainz',
+    last_search_type TEXT DEFAULT 'track',
+    lastfm_username TEXT
+);
+
+CREATE TABLE IF NOT EXISTS app_config (
+    key TEXT PRIMARY KEY NOT NULL,
+    value TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS invites (
+    token TEXT PRIMARY KEY NOT NULL,
+    created_by TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL DEFAULT 'member',
+    created_at BIGINT NOT NULL,
+    expires_at BIGINT NOT NULL,
+    used_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    used_at BIGINT
+);
+
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    -- SHA-256 of the token, the token itself is only shown once
+    token_hash TEXT NOT NULL UNIQUE,
+    scopes TEXT NOT NULL,
+    created_at BIGINT NOT NULL,
+    last_used_at BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_tokens_user_id ON api_tokens(user_id);
+
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    user_agent TEXT,
+    ip TEXT,
+    created_at BIGINT NOT NULL,
+    last_seen_at BIGINT NOT NULL,
+    expires_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_user_id ON sessions(user_id);
+
+-- Security relevant events. Users are stored by id and name without a foreign key
+-- so their history survives deleting the account.
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    created_at BIGINT NOT NULL,
+    event TEXT NOT NULL,
+    user_id TEXT,
+    username TEXT,
+    ip TEXT,
+    target TEXT,
+    details TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log(created_at);
+
+-- Limits for a single user, used instead of the default limits.
+-- NULL means no limit, sizes are in bytes.
+CREATE TABLE IF NOT EXISTS user_download_limits (
+    user_id TEXT PRIMARY KEY NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    max_concurrent BIGINT,
+    max_queued BIGINT,
+    daily_bytes BIGINT,
+    monthly_bytes BIGINT,
+    folder_bytes BIGINT
+);
+
+-- Size of each finished download, for the daily and monthly quotas
+CREATE TABLE IF NOT EXISTS download_usage (
+    id BIGSERIAL PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at BIGINT NOT NULL,
+    bytes BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_download_usage_user_created ON download_usage(user_id, created_at);
+
+-- Default user: admin / admin, who has to pick a real password on first login
+INSERT INTO users (id, username, password_hash, role, must_change_password)
+VALUES (
+    '00000000-0000-0000-0000-000000000000',
+    'admin',
+    '$argon2id$v=19$m=19456,t=2,p=1$llsT7N68SnCXwaqcvFP08g$W+5l4cDaOfsY9nK2jFs7JGwkxtVtmN+VLIWC7ZOM9/E',
+    'admin',
+    TRUE
+)
+ON CONFLICT DO NOTHING;
+
+INSERT INTO user_settings (user_id)
+SELECT id FROM users
+ON CONFLICT DO NOTHING;
@@ -0,0 +1 @@
+ALTER TABLE folders ADD COLUMN lyrics_enabled BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'member';
+
+-- The bootstrap account administers the instance
+UPDATE users SET role = 'admin' WHERE id = '00000000-0000-0000-0000-000000000000';
+
+-- If the bootstrap account is gone, promote the oldest account (users has no
+-- creation time, rowid follows insertion order) rather than locking the
+-- instance out
+UPDATE users SET role = 'admin'
+WHERE id = (SELECT id FROM users ORDER BY rowid LIMIT 1)
+AND NOT EXISTS (SELECT 1 FROM users WHERE role = 'admin');
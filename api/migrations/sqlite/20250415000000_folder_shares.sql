@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS folder_shares (
+    folder_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    can_write INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (folder_id, user_id),
+    FOREIGN KEY (folder_id) REFERENCES folders(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_folder_shares_user_id ON folder_shares(user_id);
@@ -0,0 +1,18 @@
+ALTER TABLE users ADD COLUMN must_change_password INTEGER NOT NULL DEFAULT 0;
+
+-- The seeded admin/admin account has to pick a real password on first login
+UPDATE users SET must_change_password = 1
+WHERE id = '00000000-0000-0000-0000-000000000000'
+  AND password_hash = '$argon2id$v=19$m=19456,t=2,p=1$llsT7N68SnCXwaqcvFP08g$W+5l4cDaOfsY9nK2jFs7JGwkxtVtmN+VLIWC7ZOM9/E';
+
+CREATE TABLE IF NOT EXISTS invites (
+    token TEXT PRIMARY KEY NOT NULL,
+    created_by TEXT NOT NULL,
+    role TEXT NOT NULL DEFAULT 'member',
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    used_by TEXT,
+    used_at INTEGER,
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (used_by) REFERENCES users(id) ON DELETE SET NULL
+);
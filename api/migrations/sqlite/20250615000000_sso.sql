@@ -0,0 +1,4 @@
+-- Identity of users created or linked through OIDC or a trusted auth proxy
+ALTER TABLE users ADD COLUMN external_id TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_external_id ON users(external_id);
@@ -0,0 +1,14 @@
+-- Security relevant events. Users are stored by id and name without a foreign key
+-- so their history survives deleting the account.
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    created_at INTEGER NOT NULL,
+    event TEXT NOT NULL,
+    user_id TEXT,
+    username TEXT,
+    ip TEXT,
+    target TEXT,
+    details TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log(created_at);
@@ -0,0 +1,22 @@
+-- Limits for a single user, used instead of the default limits.
+-- NULL means no limit, sizes are in bytes.
+CREATE TABLE IF NOT EXISTS user_download_limits (
+    user_id TEXT PRIMARY KEY NOT NULL,
+    max_concurrent INTEGER,
+    max_queued INTEGER,
+    daily_bytes INTEGER,
+    monthly_bytes INTEGER,
+    folder_bytes INTEGER,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Size of each finished download, for the daily and monthly quotas
+CREATE TABLE IF NOT EXISTS download_usage (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    bytes INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_download_usage_user_created ON download_usage(user_id, created_at);
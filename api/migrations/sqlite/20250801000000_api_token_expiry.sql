@@ -0,0 +1,2 @@
+-- Unix timestamp after which the token is refused, NULL for tokens that do not expire
+ALTER TABLE api_tokens ADD COLUMN expires_at INTEGER;
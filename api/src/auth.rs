@@ -1,40 +1,271 @@
 use serde::{Deserialize, Serialize};
 
+/// What a user is allowed to do.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Manages users and application config
+    Admin,
+    /// Searches, downloads and manages their own folders
+    #[default]
+    Member,
+    /// Searches and browses, cannot download or change anything
+    ReadOnly,
+}
+
+impl Role {
+    pub fn is_admin(&self) -> bool {
+        matches!(self, Role::Admin)
+    }
+
+    /// Whether the role may queue downloads and modify folders.
+    pub fn can_write(&self) -> bool {
+        matches!(self, Role::Admin | Role::Member)
+    }
+
+    pub fn all() -> [Role; 3] {
+        [Role::Admin, Role::Member, Role::ReadOnly]
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Admin => write!(f, "admin"),
+            Role::Member => write!(f, "member"),
+            Role::ReadOnly => write!(f, "readonly"),
+        }
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "admin" => Ok(Role::Admin),
+            "member" => Ok(Role::Member),
+            "readonly" => Ok(Role::ReadOnly),
+            _ => Err(format!("Unknown role: {}", s)),
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+crate::db::impl_text_type!(Role);
+
+/// What an API token is allowed to do.
+///
+/// Scopes build on each other: `search` includes `read`, `download` includes both,
+/// and `admin` includes everything. The user's role still applies on top.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiScope {
+    /// Browse folders, settings and job status
+    Read,
+    /// Run metadata and Soulseek searches
+    Search,
+    /// Queue downloads and manage folders
+    Download,
+    /// Admin endpoints
+    Admin,
+}
+
+impl ApiScope {
+    pub fn all() -> [ApiScope; 4] {
+        [
+            ApiScope::Read,
+            ApiScope::Search,
+            ApiScope::Download,
+            ApiScope::Admin,
+        ]
+    }
+
+    /// Whether this scope grants access to endpoints requiring `required`.
+    pub fn allows(&self, required: ApiScope) -> bool {
+        *self >= required
+    }
+}
+
+impl std::fmt::Display for ApiScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiScope::Read => write!(f, "read"),
+            ApiScope::Search => write!(f, "search"),
+            ApiScope::Download => write!(f, "download"),
+            ApiScope::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl std::str::FromStr for ApiScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "read" => Ok(ApiScope::Read),
+            "search" => Ok(ApiScope::Search),
+            "download" => Ok(ApiScope::Download),
+            "admin" => Ok(ApiScope::Admin),
+            _ => Err(format!("Unknown scope: {}", s)),
+        }
+    }
+}
+
+/// Set of scopes granted to an API token, stored comma separated.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct ApiScopes(pub Vec<ApiScope>);
+
+impl ApiScopes {
+    pub fn allows(&self, required: ApiScope) -> bool {
+        self.0.iter().any(|s| s.allows(required))
+    }
+}
+
+impl std::fmt::Display for ApiScopes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let scopes: Vec<String> = self.0.iter().map(ApiScope::to_string).collect();
+        write!(f, "{}", scopes.join(","))
+    }
+}
+
+impl From<String> for ApiScopes {
+    /// Unknown scopes are dropped
+    fn from(value: String) -> Self {
+        ApiScopes(value.split(',').filter_map(|s| s.parse().ok()).collect())
+    }
+}
+
+/// Who can create an account from the login page.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistrationMode {
+    /// Anyone who can reach the server
+    Open,
+    /// Only with an invite code generated by an admin
+    Invite,
+    /// Accounts are created by admins only
+    #[default]
+    Disabled,
+}
+
+impl RegistrationMode {
+    pub fn all() -> [RegistrationMode; 3] {
+        [
+            RegistrationMode::Open,
+            RegistrationMode::Invite,
+            RegistrationMode::Disabled,
+        ]
+    }
+}
+
+impl std::fmt::Display for RegistrationMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistrationMode::Open => write!(f, "open"),
+            RegistrationMode::Invite => write!(f, "invite"),
+            RegistrationMode::Disabled => write!(f, "disabled"),
+        }
+    }
+}
+
+impl std::str::FromStr for RegistrationMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "open" => Ok(RegistrationMode::Open),
+            "invite" => Ok(RegistrationMode::Invite),
+            "disabled" => Ok(RegistrationMode::Disabled),
+            _ => Err(format!("Unknown registration mode: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AuthResponse {
     pub username: String,
     pub user_id: String,
+    #[serde(default)]
+    pub role: Role,
+    /// The user has to pick a new password before using the app
+    #[serde(default)]
+    pub must_change_password: bool,
+}
+
+/// Path that starts an OIDC login, a plain link since it redirects to the provider.
+pub const OIDC_LOGIN_ROUTE: &str = "/api/auth/oidc/login";
+
+/// Login methods offered on the login page.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LoginOptions {
+    /// Username and password form
+    pub password_login: bool,
+    /// Label of the OIDC button, `None` when OIDC is not configured
+    pub oidc_name: Option<String>,
+}
+
+impl Default for LoginOptions {
+    fn default() -> Self {
+        Self {
+            password_login: true,
+            oidc_name: None,
+        }
+    }
 }
 
 #[cfg(feature = "server")]
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 #[cfg(feature = "server")]
 use crate::config::CONFIG;
+#[cfg(feature = "server")]
+use crate::models::user::User;
 
+/// How long a session can be refreshed without activity.
 pub static EXPIRATION_DAYS: i64 = 30;
 
+/// Lifetime of the access token stored in the cookie, renewed with `refresh_token`.
+pub static ACCESS_TOKEN_MINUTES: i64 = 15;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String, // user_id
     pub username: String,
+    /// Tokens issued before roles existed decode as `Member`
+    #[serde(default)]
+    pub role: Role,
+    /// Only the password change endpoints accept such a token
+    #[serde(default)]
+    pub must_change_password: bool,
+    /// Set when authenticated with an API token, cookie sessions have every scope
+    #[serde(skip)]
+    pub scopes: Option<ApiScopes>,
+    /// Id of the server-side session, tokens without one are rejected
+    #[serde(default)]
+    pub jti: Option<String>,
     pub iat: usize,
     pub exp: usize,
 }
 
+/// Issue a short-lived access token for the session `session_id`.
 #[cfg(feature = "server")]
-pub fn create_token(user_id: String, username: String) -> Result<String, String> {
+pub fn create_token(user: &User, session_id: &str) -> Result<String, String> {
     let encoding_key = EncodingKey::from_secret(CONFIG.secret_key().as_bytes());
     let now = chrono::Utc::now();
     let iat = now.timestamp() as usize;
 
     let exp = now
-        .checked_add_signed(chrono::Duration::days(EXPIRATION_DAYS))
+        .checked_add_signed(chrono::Duration::minutes(ACCESS_TOKEN_MINUTES))
         .expect("valid timestamp")
         .timestamp();
 
     let claims = Claims {
-        sub: user_id.clone(),
-        username: username.clone(),
+        sub: user.id.clone(),
+        username: user.username.clone(),
+        role: user.role,
+        must_change_password: user.must_change_password,
+        scopes: None,
+        jti: Some(session_id.to_string()),
         iat,
         exp: exp as usize,
     };
@@ -55,3 +286,35 @@ pub fn verify_token(token: &str) -> Result<Claims, String> {
 
     Ok(token_data.claims)
 }
+
+/// Short, non-reversible identifier of a token for logs.
+///
+/// Lets failures be correlated without writing usable credentials to the log.
+#[cfg(feature = "server")]
+pub fn token_fingerprint(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .take(6)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Verify the signature of a token that may have expired.
+///
+/// Only for refreshing and logging out, where the session record decides.
+#[cfg(feature = "server")]
+pub fn verify_token_allow_expired(token: &str) -> Result<Claims, String> {
+    let mut validation = Validation::default();
+    validation.validate_exp = false;
+
+    let token_data = decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(CONFIG.secret_key().as_bytes()),
+        &validation,
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(token_data.claims)
+}
@@ -3,8 +3,12 @@
 //! All environment variables are loaded and validated at startup through this module.
 //! This prevents scattered `env::var()` calls and ensures early failure on missing config.
 
+#[cfg(feature = "server")]
+use crate::auth::Role;
 #[cfg(feature = "server")]
 use std::path::PathBuf;
+#[cfg(feature = "server")]
+use std::time::Duration;
 
 #[cfg(feature = "server")]
 const DEFAULT_SECRET_KEY: &str = "secret";
@@ -33,20 +37,207 @@ fn parse_bool_env(key: &str, default: bool) -> bool {
         .unwrap_or(default)
 }
 
+/// Read an optional environment variable, treating empty values as unset.
+#[cfg(feature = "server")]
+fn optional_env(key: &str) -> Option<String> {
+    std::env::var(key)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Parse an unsigned number from an environment variable.
+///
+/// Returns the default for missing or invalid values.
+#[cfg(feature = "server")]
+fn u64_env(key: &str, default: u64) -> u64 {
+    match optional_env(key) {
+        Some(v) => v.parse().unwrap_or_else(|_| {
+            tracing::warn!(
+                "Invalid number '{}' for {}, using default: {}",
+                v,
+                key,
+                default
+            );
+            default
+        }),
+        None => default,
+    }
+}
+
+/// Parse a comma separated list from an environment variable.
+#[cfg(feature = "server")]
+fn list_env(key: &str) -> Vec<String> {
+    optional_env(key)
+        .map(|v| {
+            v.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// OpenID Connect login, enabled when `OIDC_ISSUER_URL` is set.
+#[cfg(feature = "server")]
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    /// Issuer URL, `/.well-known/openid-configuration` is appended for discovery
+    pub issuer_url: String,
+    pub client_id: String,
+    /// Optional for public clients, PKCE is always used
+    pub client_secret: Option<String>,
+    /// Must point at `/api/auth/oidc/callback` and be registered with the provider
+    pub redirect_url: String,
+    /// Space separated scopes (default: "openid profile email")
+    pub scopes: String,
+    /// Shown on the login button (default: "SSO")
+    pub display_name: String,
+    /// Claim used as username (default: "preferred_username")
+    pub username_claim: String,
+    /// Claim holding the user's groups (default: "groups")
+    pub groups_claim: String,
+}
+
+/// Trusted-header login from an authenticating reverse proxy, enabled when
+/// `AUTH_PROXY_HEADER` is set.
+#[cfg(feature = "server")]
+#[derive(Debug, Clone)]
+pub struct ProxyAuthConfig {
+    /// Header carrying the username, e.g. "Remote-User"
+    pub user_header: String,
+    /// Header carrying comma separated groups, e.g. "Remote-Groups"
+    pub groups_header: Option<String>,
+    /// Addresses the headers are accepted from, other clients are ignored
+    pub trusted_proxies: Vec<ipnet::IpNet>,
+}
+
+/// How single sign-on identities become users.
+#[cfg(feature = "server")]
+#[derive(Debug, Clone)]
+pub struct SsoConfig {
+    /// Create unknown users on first login (default: true)
+    pub auto_provision: bool,
+    /// Role for users without a matching group (default: member)
+    pub default_role: Role,
+    pub admin_groups: Vec<String>,
+    pub member_groups: Vec<String>,
+}
+
+#[cfg(feature = "server")]
+impl SsoConfig {
+    /// Role for a set of groups, `None` when no group mapping is configured.
+    pub fn role_for_groups(&self, groups: &[String]) -> Option<Role> {
+        if self.admin_groups.is_empty() && self.member_groups.is_empty() {
+            return None;
+        }
+        let in_any = |list: &[String]| groups.iter().any(|g| list.contains(g));
+        Some(if in_any(&self.admin_groups) {
+            Role::Admin
+        } else if in_any(&self.member_groups) {
+            Role::Member
+        } else {
+            self.default_role
+        })
+    }
+}
+
+#[cfg(feature = "server")]
+fn oidc_from_env() -> Option<OidcConfig> {
+    let issuer_url = optional_env("OIDC_ISSUER_URL")?;
+    let (Some(client_id), Some(redirect_url)) = (
+        optional_env("OIDC_CLIENT_ID"),
+        optional_env("OIDC_REDIRECT_URL"),
+    ) else {
+        tracing::error!("OIDC_ISSUER_URL is set but OIDC_CLIENT_ID or OIDC_REDIRECT_URL is missing, OIDC login is disabled");
+        return None;
+    };
+
+    Some(OidcConfig {
+        issuer_url: issuer_url.trim_end_matches('/').to_string(),
+        client_id,
+        client_secret: optional_env("OIDC_CLIENT_SECRET"),
+        redirect_url,
+        scopes: optional_env("OIDC_SCOPES").unwrap_or_else(|| "openid profile email".to_string()),
+        display_name: optional_env("OIDC_DISPLAY_NAME").unwrap_or_else(|| "SSO".to_string()),
+        username_claim: optional_env("OIDC_USERNAME_CLAIM")
+            .unwrap_or_else(|| "preferred_username".to_string()),
+        groups_claim: optional_env("OIDC_GROUPS_CLAIM").unwrap_or_else(|| "groups".to_string()),
+    })
+}
+
+#[cfg(feature = "server")]
+fn trusted_proxies_from_env() -> Vec<ipnet::IpNet> {
+    list_env("AUTH_PROXY_TRUSTED_IPS")
+        .iter()
+        .filter_map(|entry| {
+            entry
+                .parse::<ipnet::IpNet>()
+                .or_else(|_| entry.parse::<std::net::IpAddr>().map(ipnet::IpNet::from))
+                .map_err(|_| tracing::error!("Invalid AUTH_PROXY_TRUSTED_IPS entry: {}", entry))
+                .ok()
+        })
+        .collect()
+}
+
+#[cfg(feature = "server")]
+fn proxy_auth_from_env(trusted_proxies: &[ipnet::IpNet]) -> Option<ProxyAuthConfig> {
+    let user_header = optional_env("AUTH_PROXY_HEADER")?;
+
+    if trusted_proxies.is_empty() {
+        tracing::error!("AUTH_PROXY_HEADER is set but AUTH_PROXY_TRUSTED_IPS is empty, header login is disabled");
+        return None;
+    }
+
+    Some(ProxyAuthConfig {
+        user_header,
+        groups_header: optional_env("AUTH_PROXY_GROUPS_HEADER"),
+        trusted_proxies: trusted_proxies.to_vec(),
+    })
+}
+
 /// Application configuration loaded from environment variables.
 #[cfg(feature = "server")]
 #[derive(Debug, Clone)]
 pub struct AppConfig {
-    /// SQLite database URL (default: "sqlite:soulbeet.db")
+    /// SQLite or PostgreSQL database URL (default: "sqlite:soulbeet.db")
     database_url: String,
     /// JWT signing secret (MUST be set in production)
     secret_key: String,
+    /// Secret the key for encrypting stored API keys is derived from
+    config_encryption_key: Option<String>,
+    /// Previous encryption secrets, only used to decrypt during key rotation
+    config_encryption_old_keys: Vec<String>,
     /// Directory where downloads are saved (default: "/downloads")
     download_path: PathBuf,
     /// Path to beets configuration file (default: "beets_config.yaml")
     beets_config: PathBuf,
     /// Enable album mode for beets import (groups tracks by folder)
     beets_album_mode: bool,
+    /// Directory where resized cover art is cached (default: "covers")
+    cover_cache_path: PathBuf,
+    /// Covers unused for longer are removed from the cache (default: 90 days)
+    cover_cache_max_age: Duration,
+    /// Size the cover cache is kept under, in bytes (default: 1 GiB)
+    cover_cache_max_bytes: u64,
+    /// Write cover.jpg into album folders before import so beets can embed it
+    write_cover_art: bool,
+    /// Base URL of the LRCLIB-compatible lyrics API (default: "https://lrclib.net")
+    lyrics_api_url: String,
+    /// Write .lrc sidecar files next to imported tracks
+    lyrics_sidecar: bool,
+    /// Embed lyrics into the tags of imported tracks
+    lyrics_embed: bool,
+    /// OpenID Connect login
+    oidc: Option<OidcConfig>,
+    /// Reverse proxies whose forwarding and login headers are trusted
+    trusted_proxies: Vec<ipnet::IpNet>,
+    /// Trusted-header login from a reverse proxy
+    proxy_auth: Option<ProxyAuthConfig>,
+    /// User provisioning and role mapping for both SSO methods
+    sso: SsoConfig,
+    /// Allow logging in with a username and password (default: true)
+    password_login: bool,
     /// HTTP server port (default: 9765)
     pub port: u16,
     /// HTTP server bind address (default: "0.0.0.0")
@@ -75,10 +266,14 @@ impl AppConfig {
             );
         }
 
+        let trusted_proxies = trusted_proxies_from_env();
+
         Self {
             database_url: std::env::var("DATABASE_URL")
                 .unwrap_or_else(|_| "sqlite:soulbeet.db".to_string()),
             secret_key,
+            config_encryption_key: optional_env("CONFIG_ENCRYPTION_KEY"),
+            config_encryption_old_keys: list_env("CONFIG_ENCRYPTION_OLD_KEYS"),
             download_path: PathBuf::from(
                 std::env::var("DOWNLOAD_PATH").unwrap_or_else(|_| "/downloads".to_string()),
             ),
@@ -86,6 +281,34 @@ impl AppConfig {
                 std::env::var("BEETS_CONFIG").unwrap_or_else(|_| "beets_config.yaml".to_string()),
             ),
             beets_album_mode: parse_bool_env("BEETS_ALBUM_MODE", false),
+            cover_cache_path: PathBuf::from(
+                std::env::var("COVER_CACHE_PATH").unwrap_or_else(|_| "covers".to_string()),
+            ),
+            cover_cache_max_age: Duration::from_secs(
+                u64_env("COVER_CACHE_MAX_AGE_DAYS", 90) * 24 * 60 * 60,
+            ),
+            cover_cache_max_bytes: u64_env("COVER_CACHE_MAX_SIZE_MB", 1024) * 1024 * 1024,
+            write_cover_art: parse_bool_env("WRITE_COVER_ART", false),
+            lyrics_api_url: std::env::var("LYRICS_API_URL")
+                .unwrap_or_else(|_| "https://lrclib.net".to_string()),
+            lyrics_sidecar: parse_bool_env("LYRICS_SIDECAR", true),
+            lyrics_embed: parse_bool_env("LYRICS_EMBED", true),
+            oidc: oidc_from_env(),
+            proxy_auth: proxy_auth_from_env(&trusted_proxies),
+            trusted_proxies,
+            sso: SsoConfig {
+                auto_provision: parse_bool_env("SSO_AUTO_PROVISION", true),
+                default_role: optional_env("SSO_DEFAULT_ROLE")
+                    .and_then(|r| {
+                        r.parse()
+                            .map_err(|e| tracing::warn!("Invalid SSO_DEFAULT_ROLE: {}", e))
+                            .ok()
+                    })
+                    .unwrap_or_default(),
+                admin_groups: list_env("SSO_ADMIN_GROUPS"),
+                member_groups: list_env("SSO_MEMBER_GROUPS"),
+            },
+            password_login: parse_bool_env("PASSWORD_LOGIN", true),
             port: std::env::var("PORT")
                 .ok()
                 .and_then(|p| p.parse().ok())
@@ -104,6 +327,16 @@ impl AppConfig {
         &self.secret_key
     }
 
+    /// Get the secret stored API keys are encrypted with, if set.
+    pub fn config_encryption_key(&self) -> Option<&str> {
+        self.config_encryption_key.as_deref()
+    }
+
+    /// Get the previous encryption secrets.
+    pub fn config_encryption_old_keys(&self) -> &[String] {
+        &self.config_encryption_old_keys
+    }
+
     /// Get the download path.
     pub fn download_path(&self) -> &PathBuf {
         &self.download_path
@@ -118,6 +351,68 @@ impl AppConfig {
     pub fn is_album_mode(&self) -> bool {
         self.beets_album_mode
     }
+
+    /// Get the cover art cache path.
+    pub fn cover_cache_path(&self) -> &PathBuf {
+        &self.cover_cache_path
+    }
+
+    /// Get how long an unused cover stays cached.
+    pub fn cover_cache_max_age(&self) -> Duration {
+        self.cover_cache_max_age
+    }
+
+    /// Get the size the cover cache is kept under, in bytes.
+    pub fn cover_cache_max_bytes(&self) -> u64 {
+        self.cover_cache_max_bytes
+    }
+
+    /// Check if cover.jpg should be written before import.
+    pub fn write_cover_art(&self) -> bool {
+        self.write_cover_art
+    }
+
+    /// Get the lyrics API base URL.
+    pub fn lyrics_api_url(&self) -> &str {
+        &self.lyrics_api_url
+    }
+
+    /// Check if .lrc sidecar files should be written.
+    pub fn lyrics_sidecar(&self) -> bool {
+        self.lyrics_sidecar
+    }
+
+    /// Check if lyrics should be embedded into tags.
+    pub fn lyrics_embed(&self) -> bool {
+        self.lyrics_embed
+    }
+
+    /// Get the OpenID Connect settings, if enabled.
+    pub fn oidc(&self) -> Option<&OidcConfig> {
+        self.oidc.as_ref()
+    }
+
+    /// Check if an address belongs to a trusted reverse proxy.
+    pub fn is_trusted_proxy(&self, ip: std::net::IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Get the trusted-header settings, if enabled.
+    pub fn proxy_auth(&self) -> Option<&ProxyAuthConfig> {
+        self.proxy_auth.as_ref()
+    }
+
+    /// Get the SSO provisioning settings.
+    pub fn sso(&self) -> &SsoConfig {
+        &self.sso
+    }
+
+    /// Check if username and password login is allowed.
+    ///
+    /// Always true when no SSO method is configured, so nobody gets locked out.
+    pub fn password_login(&self) -> bool {
+        self.password_login || (self.oidc.is_none() && self.proxy_auth.is_none())
+    }
 }
 
 #[cfg(feature = "server")]
@@ -0,0 +1,109 @@
+//! HTTP endpoint serving cached cover art.
+//!
+//! Covers are fetched and resized server-side by [`soulbeet::CoverArtService`], so the
+//! browser only ever talks to Soulbeet.
+
+use axum::{
+    extract::Query,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use soulbeet::{error::SoulseekError, CoverSource};
+use std::sync::Once;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::config::CONFIG;
+use crate::services::cover_art_service;
+use crate::AuthSession;
+
+/// Path the cover endpoint is mounted on.
+pub const COVER_ROUTE: &str = "/api/covers";
+
+const DEFAULT_SIZE: u32 = 250;
+
+/// Interval between two prunes of the cover cache (1 hour).
+const PRUNE_INTERVAL_SECS: u64 = 3600;
+
+/// Ensures the cover cache task is started exactly once.
+static PRUNE_TASK_INIT: Once = Once::new();
+
+#[derive(Debug, Deserialize)]
+pub struct CoverQuery {
+    /// MusicBrainz release MBID
+    pub mbid: Option<String>,
+    /// Remote image URL (e.g. Last.fm), only used when no MBID is given
+    pub url: Option<String>,
+    /// Requested size in pixels, rounded up to a cached size
+    pub size: Option<u32>,
+}
+
+fn is_valid_mbid(mbid: &str) -> bool {
+    mbid.len() == 36 && mbid.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+}
+
+pub async fn cover_handler(_auth: AuthSession, Query(query): Query<CoverQuery>) -> Response {
+    let source = match (query.mbid, query.url) {
+        (Some(mbid), _) if is_valid_mbid(&mbid) => CoverSource::Release(mbid),
+        (None, Some(url)) if !url.is_empty() => CoverSource::Url(url),
+        _ => return (StatusCode::BAD_REQUEST, "Invalid cover query").into_response(),
+    };
+
+    let path = match cover_art_service()
+        .get(&source, query.size.unwrap_or(DEFAULT_SIZE))
+        .await
+    {
+        Ok(path) => path,
+        Err(SoulseekError::Api { status: 404, .. }) => {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        Err(SoulseekError::Api {
+            status: 400,
+            message,
+        }) => {
+            return (StatusCode::BAD_REQUEST, message).into_response();
+        }
+        Err(e) => {
+            warn!("Failed to get cover for {:?}: {}", source, e);
+            return StatusCode::BAD_GATEWAY.into_response();
+        }
+    };
+
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => (
+            [
+                (header::CONTENT_TYPE, "image/jpeg"),
+                (header::CACHE_CONTROL, "private, max-age=604800"),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Err(e) => {
+            warn!("Failed to read cached cover {:?}: {}", path, e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Start the background task keeping the cover cache under its age and size limits.
+pub fn start_cover_cache_task() {
+    PRUNE_TASK_INIT.call_once(|| {
+        tokio::spawn(async {
+            let mut interval = tokio::time::interval(Duration::from_secs(PRUNE_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                if let Err(e) = cover_art_service()
+                    .prune(CONFIG.cover_cache_max_age(), CONFIG.cover_cache_max_bytes())
+                    .await
+                {
+                    warn!("Failed to prune the cover cache: {}", e);
+                }
+            }
+        });
+        info!(
+            "Started cover cache task (interval: {}s)",
+            PRUNE_INTERVAL_SECS
+        );
+    });
+}
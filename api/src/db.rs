@@ -1,23 +1,72 @@
 #[cfg(feature = "server")]
 use dioxus::fullstack::Lazy;
 #[cfg(feature = "server")]
-use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+use sqlx::{postgres::PgPoolOptions, sqlite::SqlitePoolOptions, PgPool, SqlitePool};
 
 #[cfg(feature = "server")]
 use crate::config::CONFIG;
 
+/// Connection pool of the configured database.
+///
+/// Queries go through [`with_db`], which runs them on whichever backend is in use.
 #[cfg(feature = "server")]
-pub static DB: Lazy<SqlitePool> = Lazy::new(|| async move {
+pub enum Db {
+    Sqlite(SqlitePool),
+    Postgres(PgPool),
+}
+
+/// Whether a `DATABASE_URL` points at PostgreSQL rather than SQLite.
+#[cfg(feature = "server")]
+pub fn is_postgres_url(url: &str) -> bool {
+    url.starts_with("postgres://") || url.starts_with("postgresql://")
+}
+
+#[cfg(feature = "server")]
+pub static DB: Lazy<Db> = Lazy::new(|| async move {
     let database_url = CONFIG.database_url();
 
+    if is_postgres_url(database_url) {
+        let pool = connect_postgres(database_url)
+            .await
+            .expect("Failed to set up the PostgreSQL database");
+        return dioxus::Ok(Db::Postgres(pool));
+    }
+
+    let pool = connect_sqlite(database_url)
+        .await
+        .expect("Failed to set up the SQLite database");
+    dioxus::Ok(Db::Sqlite(pool))
+});
+
+// Database of the running test, see `models::test_db`
+#[cfg(all(test, feature = "server"))]
+tokio::task_local! {
+    pub(crate) static TEST_DB: &'static Db;
+}
+
+/// The database queries run on.
+#[cfg(feature = "server")]
+pub fn current() -> &'static Db {
+    #[cfg(test)]
+    if let Ok(db) = TEST_DB.try_with(|db| *db) {
+        return db;
+    }
+    &DB
+}
+
+/// Open a SQLite database, creating the file if needed, and bring the schema up to date.
+#[cfg(feature = "server")]
+pub(crate) async fn connect_sqlite(database_url: &str) -> Result<SqlitePool, String> {
     if database_url.starts_with("sqlite:") {
         let path_str = database_url.trim_start_matches("sqlite:");
         let path = std::path::Path::new(path_str);
         if !path.exists() {
             if let Some(parent) = path.parent() {
-                std::fs::create_dir_all(parent).expect("Failed to create database directory");
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create database directory: {}", e))?;
             }
-            std::fs::File::create(path).expect("Failed to create database file");
+            std::fs::File::create(path)
+                .map_err(|e| format!("Failed to create database file: {}", e))?;
         }
     }
 
@@ -25,12 +74,90 @@ pub static DB: Lazy<SqlitePool> = Lazy::new(|| async move {
         .max_connections(5)
         .connect(database_url)
         .await
-        .expect("Failed to connect to database");
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
 
-    sqlx::migrate!("./migrations")
+    sqlx::migrate!("./migrations/sqlite")
         .run(&pool)
         .await
-        .expect("Failed to run migrations");
+        .map_err(|e| format!("Failed to run migrations: {}", e))?;
 
-    dioxus::Ok(pool)
-});
+    Ok(pool)
+}
+
+/// Connect to PostgreSQL and bring the schema up to date.
+#[cfg(feature = "server")]
+pub(crate) async fn connect_postgres(database_url: &str) -> Result<PgPool, String> {
+    let pool = PgPoolOptions::new()
+        .max_connections(10)
+        .connect(database_url)
+        .await
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    sqlx::migrate!("./migrations/postgres")
+        .run(&pool)
+        .await
+        .map_err(|e| format!("Failed to run migrations: {}", e))?;
+
+    Ok(pool)
+}
+
+/// Run a query on the configured database: `with_db!(|db| { query.fetch_one(db).await })`.
+///
+/// The body is compiled once per backend with `db` bound to its pool, so the SQL has
+/// to work on both: `$1` style placeholders, and results must not depend on the
+/// backend (map a `QueryResult` to `rows_affected()` inside the body).
+#[cfg(feature = "server")]
+macro_rules! with_db {
+    (|$db:ident| $body:expr) => {
+        match $crate::db::current() {
+            $crate::db::Db::Sqlite($db) => $body,
+            $crate::db::Db::Postgres($db) => $body,
+        }
+    };
+}
+#[cfg(feature = "server")]
+pub(crate) use with_db;
+
+/// Store a type as text through its `Display` and `FromStr` impls, on every backend.
+#[cfg(feature = "server")]
+macro_rules! impl_text_type {
+    ($ty:ty) => {
+        impl<DB: sqlx::Database> sqlx::Type<DB> for $ty
+        where
+            String: sqlx::Type<DB>,
+        {
+            fn type_info() -> DB::TypeInfo {
+                <String as sqlx::Type<DB>>::type_info()
+            }
+
+            fn compatible(ty: &DB::TypeInfo) -> bool {
+                <String as sqlx::Type<DB>>::compatible(ty)
+            }
+        }
+
+        impl<'q, DB: sqlx::Database> sqlx::Encode<'q, DB> for $ty
+        where
+            String: sqlx::Encode<'q, DB>,
+        {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'q>,
+            ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+                <String as sqlx::Encode<'q, DB>>::encode(self.to_string(), buf)
+            }
+        }
+
+        impl<'r, DB: sqlx::Database> sqlx::Decode<'r, DB> for $ty
+        where
+            String: sqlx::Decode<'r, DB>,
+        {
+            fn decode(
+                value: <DB as sqlx::Database>::ValueRef<'r>,
+            ) -> Result<Self, sqlx::error::BoxDynError> {
+                Ok(<String as sqlx::Decode<'r, DB>>::decode(value)?.parse()?)
+            }
+        }
+    };
+}
+#[cfg(feature = "server")]
+pub(crate) use impl_text_type;
@@ -8,6 +8,8 @@ use std::time::Duration;
 #[cfg(feature = "server")]
 use shared::download::DownloadProgress;
 #[cfg(feature = "server")]
+use shared::playlist::PlaylistImportReport;
+#[cfg(feature = "server")]
 use tokio::sync::{broadcast, RwLock};
 #[cfg(feature = "server")]
 use tokio_util::sync::CancellationToken;
@@ -191,3 +193,16 @@ pub fn start_channel_cleanup_task() {
         );
     });
 }
+
+/// A running or finished playlist import, kept in memory so the UI can poll it.
+#[cfg(feature = "server")]
+pub struct PlaylistJob {
+    /// Username of the user who started the import
+    pub owner: String,
+    pub report: PlaylistImportReport,
+}
+
+/// Playlist import jobs by job id.
+#[cfg(feature = "server")]
+pub static PLAYLIST_JOBS: LazyLock<RwLock<HashMap<String, PlaylistJob>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
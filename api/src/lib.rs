@@ -1,9 +1,19 @@
 pub mod auth;
 pub mod config;
+#[cfg(feature = "server")]
+pub mod covers;
 pub mod db;
 pub mod globals;
+#[cfg(feature = "server")]
+pub mod login_throttle;
 pub mod models;
+#[cfg(feature = "server")]
+pub mod secrets;
 pub mod services;
+#[cfg(feature = "server")]
+pub mod sqlite_import;
+#[cfg(feature = "server")]
+pub mod sso;
 
 pub mod server_fns;
 
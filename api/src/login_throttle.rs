@@ -0,0 +1,118 @@
+//! Brute-force protection for password checks.
+//!
+//! Failed attempts are counted per client address and per username. After
+//! [`FREE_ATTEMPTS`] failures the key is locked out, twice as long after each
+//! further failure, up to [`MAX_LOCKOUT`]. A successful login clears the counters.
+//! State is kept in memory, so a restart resets it.
+//!
+//! The address is the peer's, or the one forwarded by a proxy listed in
+//! `AUTH_PROXY_TRUSTED_IPS`. At most [`MAX_KEYS`] counters are kept: beyond that the
+//! address counters that are not locked out are dropped first, then the oldest.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// Failures allowed before the first lockout.
+pub const FREE_ATTEMPTS: u32 = 5;
+/// Length of the first lockout.
+pub const BASE_LOCKOUT: Duration = Duration::from_secs(30);
+/// Longest lockout.
+pub const MAX_LOCKOUT: Duration = Duration::from_secs(60 * 60);
+/// Counters without failures for this long are forgotten.
+const FORGET_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+/// Most counters kept at once.
+pub const MAX_KEYS: usize = 10_000;
+
+#[derive(Debug)]
+struct Failures {
+    count: u32,
+    last_failure: Instant,
+    locked_until: Option<Instant>,
+}
+
+static FAILURES: LazyLock<Mutex<HashMap<String, Failures>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Keys a login attempt is counted under.
+fn keys(ip: Option<&str>, username: &str) -> Vec<String> {
+    let mut keys = vec![format!("user:{}", username.trim().to_lowercase())];
+    if let Some(ip) = ip {
+        keys.push(format!("ip:{}", ip));
+    }
+    keys
+}
+
+/// Make room for new counters, keeping lockouts and username counters if possible.
+fn prune(failures: &mut HashMap<String, Failures>, now: Instant) {
+    failures.retain(|key, f| {
+        !key.starts_with("ip:") || f.locked_until.is_some_and(|until| until > now)
+    });
+    if failures.len() + 2 <= MAX_KEYS {
+        return;
+    }
+
+    let mut by_age: Vec<(Instant, String)> = failures
+        .iter()
+        .map(|(key, f)| (f.last_failure, key.clone()))
+        .collect();
+    by_age.sort();
+    let excess = failures.len() - MAX_KEYS / 2;
+    for (_, key) in by_age.into_iter().take(excess) {
+        failures.remove(&key);
+    }
+}
+
+fn lockout(count: u32) -> Duration {
+    let exponent = count.saturating_sub(FREE_ATTEMPTS).min(16);
+    BASE_LOCKOUT.saturating_mul(1 << exponent).min(MAX_LOCKOUT)
+}
+
+/// Time left before the client or username may try again, if locked out.
+pub fn retry_after(ip: Option<&str>, username: &str) -> Option<Duration> {
+    let failures = FAILURES.lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+
+    keys(ip, username)
+        .iter()
+        .filter_map(|key| failures.get(key)?.locked_until)
+        .filter(|until| *until > now)
+        .map(|until| until - now)
+        .max()
+}
+
+/// Count a failed attempt, returns the lockout it caused if any.
+pub fn record_failure(ip: Option<&str>, username: &str) -> Option<Duration> {
+    let mut failures = FAILURES.lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+
+    failures.retain(|_, f| now.duration_since(f.last_failure) < FORGET_AFTER);
+    if failures.len() + 2 > MAX_KEYS {
+        prune(&mut failures, now);
+    }
+
+    let mut longest = None;
+    for key in keys(ip, username) {
+        let entry = failures.entry(key).or_insert(Failures {
+            count: 0,
+            last_failure: now,
+            locked_until: None,
+        });
+        entry.count += 1;
+        entry.last_failure = now;
+        if entry.count >= FREE_ATTEMPTS {
+            let duration = lockout(entry.count);
+            entry.locked_until = Some(now + duration);
+            longest = longest.max(Some(duration));
+        }
+    }
+    longest
+}
+
+/// Clear the counters after a successful login.
+pub fn record_success(ip: Option<&str>, username: &str) {
+    let mut failures = FAILURES.lock().unwrap_or_else(|e| e.into_inner());
+    for key in keys(ip, username) {
+        failures.remove(&key);
+    }
+}
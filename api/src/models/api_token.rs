@@ -0,0 +1,268 @@
+#[cfg(feature = "server")]
+use super::user::User;
+use crate::auth::ApiScopes;
+#[cfg(feature = "server")]
+use crate::auth::Claims;
+#[cfg(feature = "server")]
+use crate::db::with_db;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use uuid::Uuid;
+
+/// Prefix of generated tokens, makes them easy to spot in scripts and logs.
+pub const API_TOKEN_PREFIX: &str = "sb_";
+
+/// Named token a user created to call the API from scripts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+pub struct ApiToken {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    #[serde(skip)]
+    pub token_hash: String,
+    #[cfg_attr(feature = "server", sqlx(try_from = "String"))]
+    pub scopes: ApiScopes,
+    /// Unix timestamps, in seconds
+    pub created_at: i64,
+    pub last_used_at: Option<i64>,
+    /// Refused from then on, `None` for tokens that do not expire
+    pub expires_at: Option<i64>,
+}
+
+impl ApiToken {
+    /// Last use formatted for display.
+    pub fn last_used_label(&self) -> String {
+        self.last_used_at
+            .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+            .map(|d| d.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_else(|| "never".to_string())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|t| t <= chrono::Utc::now().timestamp())
+    }
+
+    /// Expiry date formatted for display.
+    pub fn expires_label(&self) -> String {
+        self.expires_at
+            .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+            .map(|d| d.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_else(|| "never".to_string())
+    }
+}
+
+#[cfg(feature = "server")]
+fn hash_token(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(feature = "server")]
+impl ApiToken {
+    /// Create a token valid for `valid_days`, or without expiry, and return it along
+    /// with its secret, which is not stored.
+    pub async fn create(
+        user_id: &str,
+        name: &str,
+        scopes: &ApiScopes,
+        valid_days: Option<i64>,
+    ) -> Result<(ApiToken, String), String> {
+        use rand::distr::{Alphanumeric, SampleString};
+
+        let secret = format!(
+            "{}{}",
+            API_TOKEN_PREFIX,
+            Alphanumeric.sample_string(&mut rand::rng(), 40)
+        );
+
+        let now = chrono::Utc::now().timestamp();
+        let token = with_db!(|db| {
+            sqlx::query_as::<_, ApiToken>(
+                "INSERT INTO api_tokens (id, user_id, name, token_hash, scopes, created_at, expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(user_id)
+            .bind(name)
+            .bind(hash_token(&secret))
+            .bind(scopes.to_string())
+            .bind(now)
+            .bind(valid_days.map(|days| now + days * 86400))
+            .fetch_one(db)
+            .await
+        })
+        .map_err(|e| e.to_string())?;
+
+        Ok((token, secret))
+    }
+
+    pub async fn get_all_by_user(user_id: &str) -> Result<Vec<ApiToken>, String> {
+        with_db!(|db| {
+            sqlx::query_as::<_, ApiToken>(
+                "SELECT * FROM api_tokens WHERE user_id = $1 ORDER BY created_at DESC",
+            )
+            .bind(user_id)
+            .fetch_all(db)
+            .await
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// Resolve a bearer token to session claims and record its use.
+    ///
+    /// Returns `None` for unknown and expired tokens.
+    pub async fn authenticate(secret: &str) -> Result<Option<Claims>, String> {
+        let now = chrono::Utc::now().timestamp();
+        let Some(token) = with_db!(|db| {
+            sqlx::query_as::<_, ApiToken>(
+                "SELECT * FROM api_tokens WHERE token_hash = $1 AND (expires_at IS NULL OR expires_at > $2)",
+            )
+            .bind(hash_token(secret))
+            .bind(now)
+            .fetch_optional(db)
+            .await
+        })
+        .map_err(|e| e.to_string())?
+        else {
+            return Ok(None);
+        };
+
+        let user = User::get_by_id(&token.user_id).await?;
+
+        with_db!(|db| {
+            sqlx::query("UPDATE api_tokens SET last_used_at = $1 WHERE id = $2")
+                .bind(now)
+                .bind(&token.id)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+
+        Ok(Some(Claims {
+            sub: user.id,
+            username: user.username,
+            role: user.role,
+            // Refused by the API guard until the password is changed
+            must_change_password: user.must_change_password,
+            scopes: Some(token.scopes),
+            jti: None,
+            iat: token.created_at as usize,
+            exp: token.expires_at.map_or(usize::MAX, |t| t as usize),
+        }))
+    }
+
+    /// Delete one of the user's tokens.
+    pub async fn delete(id: &str, user_id: &str) -> Result<(), String> {
+        let rows = with_db!(|db| {
+            sqlx::query("DELETE FROM api_tokens WHERE id = $1 AND user_id = $2")
+                .bind(id)
+                .bind(user_id)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+
+        if rows == 0 {
+            return Err("Token not found".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::auth::ApiScope;
+    use crate::models::test_db::{create_user, with_test_db};
+
+    #[tokio::test]
+    async fn authenticate_with_the_secret() {
+        with_test_db(async {
+            let user = create_user("scripts").await;
+            let scopes = ApiScopes(vec![ApiScope::Read, ApiScope::Search]);
+            let (token, secret) = ApiToken::create(&user.id, "backup", &scopes, Some(30))
+                .await
+                .unwrap();
+            assert!(secret.starts_with(API_TOKEN_PREFIX));
+            assert_eq!(token.scopes, scopes);
+            assert_eq!(token.last_used_at, None);
+            assert!(!token.is_expired());
+
+            let claims = ApiToken::authenticate(&secret).await.unwrap().unwrap();
+            assert_eq!(claims.sub, user.id);
+            assert_eq!(claims.username, user.username);
+            assert_eq!(claims.scopes, Some(scopes));
+            assert_eq!(claims.exp, token.expires_at.unwrap() as usize);
+            assert!(ApiToken::authenticate("sb_unknown")
+                .await
+                .unwrap()
+                .is_none());
+
+            let tokens = ApiToken::get_all_by_user(&user.id).await.unwrap();
+            assert_eq!(tokens.len(), 1);
+            assert!(tokens[0].last_used_at.is_some());
+
+            let other = create_user("other").await;
+            assert!(ApiToken::delete(&token.id, &other.id).await.is_err());
+            ApiToken::delete(&token.id, &user.id).await.unwrap();
+            assert!(ApiToken::authenticate(&secret).await.unwrap().is_none());
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn expired_tokens_are_refused() {
+        with_test_db(async {
+            let user = create_user("scripts").await;
+            let scopes = ApiScopes(vec![ApiScope::Admin]);
+
+            let (token, secret) = ApiToken::create(&user.id, "old", &scopes, Some(-1))
+                .await
+                .unwrap();
+            assert!(token.is_expired());
+            assert!(ApiToken::authenticate(&secret).await.unwrap().is_none());
+
+            let (token, secret) = ApiToken::create(&user.id, "forever", &scopes, None)
+                .await
+                .unwrap();
+            assert_eq!(token.expires_at, None);
+            let claims = ApiToken::authenticate(&secret).await.unwrap().unwrap();
+            assert_eq!(claims.exp, usize::MAX);
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn pending_password_change_is_carried_over() {
+        with_test_db(async {
+            let user = create_user("scripts").await;
+            let scopes = ApiScopes(vec![ApiScope::Read]);
+            let (_, secret) = ApiToken::create(&user.id, "backup", &scopes, None)
+                .await
+                .unwrap();
+            let claims = ApiToken::authenticate(&secret).await.unwrap().unwrap();
+            assert!(!claims.must_change_password);
+
+            // An admin reset the password
+            User::update_password(&user.id, "a brand new password", true)
+                .await
+                .unwrap();
+            let claims = ApiToken::authenticate(&secret).await.unwrap().unwrap();
+            assert!(claims.must_change_password);
+
+            User::update_password(&user.id, "another new password", false)
+                .await
+                .unwrap();
+            let claims = ApiToken::authenticate(&secret).await.unwrap().unwrap();
+            assert!(!claims.must_change_password);
+        })
+        .await
+    }
+}
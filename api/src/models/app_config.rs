@@ -1,12 +1,52 @@
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use shared::download::{ScheduleRule, SearchOptions};
 
 #[cfg(feature = "server")]
-use crate::db::DB;
+use crate::db::with_db;
+#[cfg(feature = "server")]
+use crate::secrets;
 
 pub mod keys {
+    /// JSON encoded limits for users without their own
+    pub const DEFAULT_DOWNLOAD_LIMITS: &str = "default_download_limits";
+    /// JSON encoded search options for searches that leave them unset
+    pub const DEFAULT_SEARCH_OPTIONS: &str = "default_search_options";
+    /// JSON encoded rules of the download schedule
+    pub const DOWNLOAD_SCHEDULE: &str = "download_schedule";
     pub const LASTFM_API_KEY: &str = "lastfm_api_key";
+    /// Directory non-admin users must keep their folders under
+    pub const LIBRARY_ROOT: &str = "library_root";
+    pub const REGISTRATION_MODE: &str = "registration_mode";
     pub const SLSKD_API_KEY: &str = "slskd_api_key";
+    /// JSON encoded slskd instances besides the one of `slskd_url`, with their API keys
+    pub const SLSKD_INSTANCES: &str = "slskd_instances";
     pub const SLSKD_URL: &str = "slskd_url";
+
+    /// Settings encrypted at rest and never sent back to clients
+    pub const SECRETS: [&str; 3] = [LASTFM_API_KEY, SLSKD_API_KEY, SLSKD_INSTANCES];
+
+    pub fn is_secret(key: &str) -> bool {
+        SECRETS.contains(&key)
+    }
+}
+
+/// Shown instead of the value of a secret setting.
+pub const MASKED: &str = "********";
+
+/// An slskd instance besides the one configured with `slskd_url`.
+///
+/// The API key is write-only like the other API keys: it is `None` when read, and
+/// `None` on update keeps the stored one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub struct SlskdInstance {
+    /// Unique name, shown with the instance's health and in the logs
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub api_key_set: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -18,43 +58,219 @@ pub struct AppConfig {
 
 #[cfg(feature = "server")]
 impl AppConfig {
-    pub async fn get(key: &str) -> Result<Option<String>, String> {
-        let row = sqlx::query_as::<_, Self>("SELECT * FROM app_config WHERE key = ?")
-            .bind(key)
-            .fetch_optional(&*DB)
-            .await
-            .map_err(|e| e.to_string())?;
+    /// Stored value, encrypted if the setting is a secret.
+    async fn get_raw(key: &str) -> Result<Option<String>, String> {
+        let row = with_db!(|db| {
+            sqlx::query_as::<_, Self>("SELECT * FROM app_config WHERE key = $1")
+                .bind(key)
+                .fetch_optional(db)
+                .await
+        })
+        .map_err(|e| e.to_string())?;
 
         Ok(row.map(|r| r.value))
     }
 
-    pub async fn set(key: &str, value: &str) -> Result<(), String> {
-        sqlx::query(
-            "INSERT INTO app_config (key, value) VALUES (?, ?) ON CONFLICT(key) DO UPDATE SET value = excluded.value"
-        )
-        .bind(key)
-        .bind(value)
-        .execute(&*DB)
-        .await
+    async fn set_raw(key: &str, value: &str) -> Result<(), String> {
+        with_db!(|db| {
+            sqlx::query(
+                "INSERT INTO app_config (key, value) VALUES ($1, $2) ON CONFLICT(key) DO UPDATE SET value = excluded.value"
+            )
+            .bind(key)
+            .bind(value)
+            .execute(db)
+            .await
+            .map(|r| r.rows_affected())
+        })
         .map_err(|e| e.to_string())?;
 
         Ok(())
     }
 
+    /// Get a setting, secrets are decrypted.
+    pub async fn get(key: &str) -> Result<Option<String>, String> {
+        match Self::get_raw(key).await? {
+            Some(value) if keys::is_secret(key) => secrets::open(key, &value).map(Some),
+            value => Ok(value),
+        }
+    }
+
+    /// Store a setting, secrets are encrypted when `CONFIG_ENCRYPTION_KEY` is set.
+    pub async fn set(key: &str, value: &str) -> Result<(), String> {
+        if keys::is_secret(key) {
+            Self::set_raw(key, &secrets::seal(key, value)?).await
+        } else {
+            Self::set_raw(key, value).await
+        }
+    }
+
     pub async fn delete(key: &str) -> Result<(), String> {
-        sqlx::query("DELETE FROM app_config WHERE key = ?")
-            .bind(key)
-            .execute(&*DB)
-            .await
-            .map_err(|e| e.to_string())?;
+        with_db!(|db| {
+            sqlx::query("DELETE FROM app_config WHERE key = $1")
+                .bind(key)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
 
         Ok(())
     }
 
+    /// Search options used where a search leaves them unset.
+    pub async fn search_defaults() -> Result<SearchOptions, String> {
+        match Self::get(keys::DEFAULT_SEARCH_OPTIONS).await? {
+            Some(value) => serde_json::from_str(&value).map_err(|e| e.to_string()),
+            None => Ok(SearchOptions::default()),
+        }
+    }
+
+    pub async fn set_search_defaults(options: &SearchOptions) -> Result<(), String> {
+        if options.is_empty() {
+            return Self::delete(keys::DEFAULT_SEARCH_OPTIONS).await;
+        }
+        let value = serde_json::to_string(options).map_err(|e| e.to_string())?;
+        Self::set(keys::DEFAULT_SEARCH_OPTIONS, &value).await
+    }
+
+    /// The additional slskd instances, with their API keys.
+    pub async fn slskd_instances() -> Result<Vec<SlskdInstance>, String> {
+        match Self::get(keys::SLSKD_INSTANCES).await? {
+            Some(value) => serde_json::from_str(&value).map_err(|e| e.to_string()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub async fn set_slskd_instances(instances: &[SlskdInstance]) -> Result<(), String> {
+        if instances.is_empty() {
+            return Self::delete(keys::SLSKD_INSTANCES).await;
+        }
+        let value = serde_json::to_string(instances).map_err(|e| e.to_string())?;
+        Self::set(keys::SLSKD_INSTANCES, &value).await
+    }
+
+    /// Daily windows in which downloads are paused or limited.
+    pub async fn download_schedule() -> Result<Vec<ScheduleRule>, String> {
+        match Self::get(keys::DOWNLOAD_SCHEDULE).await? {
+            Some(value) => serde_json::from_str(&value).map_err(|e| e.to_string()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub async fn set_download_schedule(rules: &[ScheduleRule]) -> Result<(), String> {
+        if rules.is_empty() {
+            return Self::delete(keys::DOWNLOAD_SCHEDULE).await;
+        }
+        let value = serde_json::to_string(rules).map_err(|e| e.to_string())?;
+        Self::set(keys::DOWNLOAD_SCHEDULE, &value).await
+    }
+
+    /// Whether a setting has a value, without decrypting it.
+    pub async fn is_set(key: &str) -> Result<bool, String> {
+        Ok(Self::get_raw(key).await?.is_some_and(|v| !v.is_empty()))
+    }
+
+    /// All settings, with secrets replaced by [`MASKED`].
     pub async fn get_all() -> Result<Vec<Self>, String> {
-        sqlx::query_as::<_, Self>("SELECT * FROM app_config ORDER BY key")
-            .fetch_all(&*DB)
-            .await
-            .map_err(|e| e.to_string())
+        let mut all = with_db!(|db| {
+            sqlx::query_as::<_, Self>("SELECT * FROM app_config ORDER BY key")
+                .fetch_all(db)
+                .await
+        })
+        .map_err(|e| e.to_string())?;
+
+        for config in all.iter_mut().filter(|c| keys::is_secret(&c.key)) {
+            config.value = MASKED.to_string();
+        }
+        Ok(all)
+    }
+
+    /// Bring stored secrets in line with the current encryption key: encrypt
+    /// plaintext values, re-encrypt values sealed with an old key, or decrypt
+    /// them when encryption was turned off. Returns how many were rewritten.
+    pub async fn reseal_secrets() -> Result<usize, String> {
+        let mut stored_any = false;
+        let mut rewritten = 0;
+        for key in keys::SECRETS {
+            let Some(stored) = Self::get_raw(key).await? else {
+                continue;
+            };
+            stored_any = true;
+            if !secrets::needs_resealing(&stored) {
+                continue;
+            }
+            // A value nobody can decrypt is left alone, reading it reports the error
+            match secrets::open(key, &stored) {
+                Ok(value) => {
+                    Self::set(key, &value).await?;
+                    rewritten += 1;
+                }
+                Err(e) => tracing::error!("{}", e),
+            }
+        }
+
+        if rewritten > 0 {
+            tracing::info!(
+                "Rewrote {} stored secrets for the current encryption key",
+                rewritten
+            );
+        }
+        if stored_any && !secrets::enabled() {
+            tracing::warn!(
+                "CONFIG_ENCRYPTION_KEY is not set, API keys in the database are stored in plaintext"
+            );
+        }
+        Ok(rewritten)
+    }
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::models::test_db::with_test_db;
+
+    #[tokio::test]
+    async fn set_overwrites_and_delete_removes() {
+        with_test_db(async {
+            let key = "test_setting";
+            assert_eq!(AppConfig::get(key).await.unwrap(), None);
+            assert!(!AppConfig::is_set(key).await.unwrap());
+
+            AppConfig::set(key, "first").await.unwrap();
+            AppConfig::set(key, "second").await.unwrap();
+            assert_eq!(
+                AppConfig::get(key).await.unwrap().as_deref(),
+                Some("second")
+            );
+            assert!(AppConfig::is_set(key).await.unwrap());
+            let all = AppConfig::get_all().await.unwrap();
+            assert!(all.iter().any(|c| c.key == key && c.value == "second"));
+
+            AppConfig::delete(key).await.unwrap();
+            assert_eq!(AppConfig::get(key).await.unwrap(), None);
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn secrets_are_masked_in_listings() {
+        with_test_db(async {
+            let instances = vec![SlskdInstance {
+                name: "backup".to_string(),
+                url: "http://slskd-backup:5030".to_string(),
+                api_key: Some("instance-key".to_string()),
+                api_key_set: false,
+            }];
+            AppConfig::set_slskd_instances(&instances).await.unwrap();
+            assert_eq!(AppConfig::slskd_instances().await.unwrap(), instances);
+
+            let all = AppConfig::get_all().await.unwrap();
+            let listed = all.iter().find(|c| c.key == keys::SLSKD_INSTANCES).unwrap();
+            assert_eq!(listed.value, MASKED);
+
+            AppConfig::set_slskd_instances(&[]).await.unwrap();
+            assert!(!AppConfig::is_set(keys::SLSKD_INSTANCES).await.unwrap());
+        })
+        .await
     }
 }
@@ -0,0 +1,308 @@
+#[cfg(feature = "server")]
+use crate::auth::Claims;
+#[cfg(feature = "server")]
+use crate::db::with_db;
+use serde::{Deserialize, Serialize};
+
+/// Entries older than this are removed.
+pub const AUDIT_RETENTION_DAYS: i64 = 180;
+
+/// Kind of security event recorded in the audit log.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEvent {
+    Login,
+    LoginFailed,
+    /// Login refused because of too many failed attempts
+    LoginThrottled,
+    SsoLogin,
+    Logout,
+    LogoutEverywhere,
+    SessionRevoked,
+    Registered,
+    PasswordChanged,
+    /// Password set by an admin for another user
+    PasswordReset,
+    UserCreated,
+    UserDeleted,
+    RoleChanged,
+    FolderCreated,
+    FolderUpdated,
+    FolderDeleted,
+    FolderShared,
+    FolderUnshared,
+    ConfigChanged,
+    ApiTokenCreated,
+    ApiTokenDeleted,
+    InviteCreated,
+    InviteDeleted,
+    DownloadLimitsChanged,
+    SlskdSharesRescanned,
+    /// Finished downloads removed from slskd's transfer list
+    SlskdDownloadsCleared,
+}
+
+impl AuditEvent {
+    /// Failed or refused attempts, highlighted in the UI.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, AuditEvent::LoginFailed | AuditEvent::LoginThrottled)
+    }
+}
+
+// Same names as stored in the database
+impl std::fmt::Display for AuditEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AuditEvent::Login => "login",
+            AuditEvent::LoginFailed => "login_failed",
+            AuditEvent::LoginThrottled => "login_throttled",
+            AuditEvent::SsoLogin => "sso_login",
+            AuditEvent::Logout => "logout",
+            AuditEvent::LogoutEverywhere => "logout_everywhere",
+            AuditEvent::SessionRevoked => "session_revoked",
+            AuditEvent::Registered => "registered",
+            AuditEvent::PasswordChanged => "password_changed",
+            AuditEvent::PasswordReset => "password_reset",
+            AuditEvent::UserCreated => "user_created",
+            AuditEvent::UserDeleted => "user_deleted",
+            AuditEvent::RoleChanged => "role_changed",
+            AuditEvent::FolderCreated => "folder_created",
+            AuditEvent::FolderUpdated => "folder_updated",
+            AuditEvent::FolderDeleted => "folder_deleted",
+            AuditEvent::FolderShared => "folder_shared",
+            AuditEvent::FolderUnshared => "folder_unshared",
+            AuditEvent::ConfigChanged => "config_changed",
+            AuditEvent::ApiTokenCreated => "api_token_created",
+            AuditEvent::ApiTokenDeleted => "api_token_deleted",
+            AuditEvent::InviteCreated => "invite_created",
+            AuditEvent::InviteDeleted => "invite_deleted",
+            AuditEvent::DownloadLimitsChanged => "download_limits_changed",
+            AuditEvent::SlskdSharesRescanned => "slskd_shares_rescanned",
+            AuditEvent::SlskdDownloadsCleared => "slskd_downloads_cleared",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl std::str::FromStr for AuditEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "login" => AuditEvent::Login,
+            "login_failed" => AuditEvent::LoginFailed,
+            "login_throttled" => AuditEvent::LoginThrottled,
+            "sso_login" => AuditEvent::SsoLogin,
+            "logout" => AuditEvent::Logout,
+            "logout_everywhere" => AuditEvent::LogoutEverywhere,
+            "session_revoked" => AuditEvent::SessionRevoked,
+            "registered" => AuditEvent::Registered,
+            "password_changed" => AuditEvent::PasswordChanged,
+            "password_reset" => AuditEvent::PasswordReset,
+            "user_created" => AuditEvent::UserCreated,
+            "user_deleted" => AuditEvent::UserDeleted,
+            "role_changed" => AuditEvent::RoleChanged,
+            "folder_created" => AuditEvent::FolderCreated,
+            "folder_updated" => AuditEvent::FolderUpdated,
+            "folder_deleted" => AuditEvent::FolderDeleted,
+            "folder_shared" => AuditEvent::FolderShared,
+            "folder_unshared" => AuditEvent::FolderUnshared,
+            "config_changed" => AuditEvent::ConfigChanged,
+            "api_token_created" => AuditEvent::ApiTokenCreated,
+            "api_token_deleted" => AuditEvent::ApiTokenDeleted,
+            "invite_created" => AuditEvent::InviteCreated,
+            "invite_deleted" => AuditEvent::InviteDeleted,
+            "download_limits_changed" => AuditEvent::DownloadLimitsChanged,
+            "slskd_shares_rescanned" => AuditEvent::SlskdSharesRescanned,
+            "slskd_downloads_cleared" => AuditEvent::SlskdDownloadsCleared,
+            _ => return Err(format!("Unknown audit event: {}", s)),
+        })
+    }
+}
+
+#[cfg(feature = "server")]
+crate::db::impl_text_type!(AuditEvent);
+
+/// Recorded audit event.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+pub struct AuditEntry {
+    pub id: i64,
+    /// Unix timestamp, in seconds
+    pub created_at: i64,
+    pub event: AuditEvent,
+    /// User who did it, or the attempted username for failed logins
+    pub user_id: Option<String>,
+    pub username: Option<String>,
+    pub ip: Option<String>,
+    /// What was changed, e.g. a username or folder path
+    pub target: Option<String>,
+    pub details: Option<String>,
+}
+
+impl AuditEntry {
+    /// Event time formatted for display.
+    pub fn created_label(&self) -> String {
+        chrono::DateTime::from_timestamp(self.created_at, 0)
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Audit log entry being built, written with [`AuditRecord::save`].
+#[cfg(feature = "server")]
+pub struct AuditRecord {
+    event: AuditEvent,
+    user_id: Option<String>,
+    username: Option<String>,
+    ip: Option<String>,
+    target: Option<String>,
+    details: Option<String>,
+}
+
+#[cfg(feature = "server")]
+impl AuditRecord {
+    pub fn new(event: AuditEvent) -> Self {
+        Self {
+            event,
+            user_id: None,
+            username: None,
+            ip: None,
+            target: None,
+            details: None,
+        }
+    }
+
+    /// Attribute the event to the user of a session.
+    pub fn by(mut self, claims: &Claims) -> Self {
+        self.user_id = Some(claims.sub.clone());
+        self.username = Some(claims.username.clone());
+        self
+    }
+
+    pub fn user(mut self, user_id: Option<&str>, username: &str) -> Self {
+        self.user_id = user_id.map(str::to_string);
+        self.username = Some(username.to_string());
+        self
+    }
+
+    pub fn ip(mut self, ip: Option<&str>) -> Self {
+        self.ip = ip.map(str::to_string);
+        self
+    }
+
+    pub fn target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    pub fn details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+
+    /// Write the entry and drop expired ones.
+    ///
+    /// Failures are logged but do not fail the action being audited.
+    pub async fn save(self) {
+        tracing::info!(
+            target: "audit",
+            event = %self.event,
+            user = self.username.as_deref().unwrap_or("-"),
+            ip = self.ip.as_deref().unwrap_or("-"),
+            target_name = self.target.as_deref().unwrap_or("-"),
+            "{}",
+            self.details.as_deref().unwrap_or_default()
+        );
+
+        let now = chrono::Utc::now().timestamp();
+        let result = with_db!(|db| {
+            sqlx::query(
+                "INSERT INTO audit_log (created_at, event, user_id, username, ip, target, details) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            )
+            .bind(now)
+            .bind(self.event)
+            .bind(&self.user_id)
+            .bind(&self.username)
+            .bind(&self.ip)
+            .bind(&self.target)
+            .bind(&self.details)
+            .execute(db)
+            .await
+            .map(|r| r.rows_affected())
+        });
+        if let Err(e) = result {
+            tracing::error!("Failed to write audit log entry {}: {}", self.event, e);
+            return;
+        }
+
+        let cutoff = now - chrono::Duration::days(AUDIT_RETENTION_DAYS).num_seconds();
+        if let Err(e) = with_db!(|db| {
+            sqlx::query("DELETE FROM audit_log WHERE created_at < $1")
+                .bind(cutoff)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        }) {
+            tracing::error!("Failed to prune audit log: {}", e);
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+impl AuditEntry {
+    /// Latest entries, newest first, optionally only those older than `before_id`.
+    pub async fn get_page(before_id: Option<i64>, limit: i64) -> Result<Vec<AuditEntry>, String> {
+        with_db!(|db| {
+            sqlx::query_as::<_, AuditEntry>(
+                "SELECT * FROM audit_log WHERE id < $1 ORDER BY id DESC LIMIT $2",
+            )
+            .bind(before_id.unwrap_or(i64::MAX))
+            .bind(limit)
+            .fetch_all(db)
+            .await
+        })
+        .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::models::test_db::{create_user, with_test_db};
+
+    #[tokio::test]
+    async fn saved_entries_are_paged_newest_first() {
+        with_test_db(async {
+            let user = create_user("auditor").await;
+            AuditRecord::new(AuditEvent::FolderCreated)
+                .user(Some(&user.id), &user.username)
+                .ip(Some("10.0.0.1"))
+                .target("/music/auditor")
+                .save()
+                .await;
+            AuditRecord::new(AuditEvent::LoginFailed)
+                .user(None, &user.username)
+                .details("wrong password")
+                .save()
+                .await;
+
+            let page = AuditEntry::get_page(None, 10).await.unwrap();
+            assert_eq!(page.len(), 2);
+            let (failed, created) = (&page[0], &page[1]);
+            assert_eq!(failed.event, AuditEvent::LoginFailed);
+            assert_eq!(failed.user_id, None);
+            assert_eq!(failed.details.as_deref(), Some("wrong password"));
+            assert_eq!(created.event, AuditEvent::FolderCreated);
+            assert_eq!(created.user_id.as_deref(), Some(user.id.as_str()));
+            assert_eq!(created.username.as_deref(), Some(user.username.as_str()));
+            assert_eq!(created.ip.as_deref(), Some("10.0.0.1"));
+            assert_eq!(created.target.as_deref(), Some("/music/auditor"));
+
+            let older = AuditEntry::get_page(Some(failed.id), 1).await.unwrap();
+            assert_eq!(older.len(), 1);
+            assert_eq!(older[0].id, created.id);
+        })
+        .await
+    }
+}
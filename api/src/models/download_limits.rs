@@ -0,0 +1,318 @@
+#[cfg(feature = "server")]
+use crate::db::with_db;
+#[cfg(feature = "server")]
+use crate::models::app_config::{keys, AppConfig};
+#[cfg(feature = "server")]
+use chrono::{Datelike, TimeZone};
+use serde::{Deserialize, Serialize};
+use shared::download::format_size;
+
+/// Completed downloads are counted for this long, enough for the monthly quota.
+pub const USAGE_RETENTION_DAYS: i64 = 62;
+
+/// Download limits of a user, `None` means no limit.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+pub struct DownloadLimits {
+    /// Files transferring from the backend at the same time, the rest waits in Soulbeet
+    pub max_concurrent: Option<i64>,
+    /// Files queued or transferring
+    pub max_queued: Option<i64>,
+    /// Bytes downloaded per day, UTC
+    pub daily_bytes: Option<i64>,
+    /// Bytes downloaded per calendar month, UTC
+    pub monthly_bytes: Option<i64>,
+    /// Size a download target folder may grow to
+    pub folder_bytes: Option<i64>,
+}
+
+impl DownloadLimits {
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// One line description, e.g. `2 at once, 5.00 GB/day`.
+    pub fn summary(&self) -> String {
+        let size = |bytes: i64| format_size(bytes.max(0) as u64);
+        let parts: Vec<String> = [
+            self.max_concurrent.map(|n| format!("{} at once", n)),
+            self.max_queued.map(|n| format!("{} queued", n)),
+            self.daily_bytes.map(|b| format!("{}/day", size(b))),
+            self.monthly_bytes.map(|b| format!("{}/month", size(b))),
+            self.folder_bytes.map(|b| format!("{}/folder", size(b))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if parts.is_empty() {
+            "no limits".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// Limits of a user as seen by an admin.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UserDownloadLimits {
+    pub user_id: String,
+    pub username: String,
+    /// Limits set for this user, the defaults apply when `None`
+    pub custom: Option<DownloadLimits>,
+}
+
+/// Default limits and the users with their own, for the admin settings.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DownloadLimitsOverview {
+    pub default: DownloadLimits,
+    pub users: Vec<UserDownloadLimits>,
+}
+
+/// Disk usage of a folder the user can download into.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FolderUsage {
+    pub name: String,
+    pub path: String,
+    pub bytes: i64,
+}
+
+/// Current usage against the limits, shown to the user.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DownloadUsage {
+    pub limits: DownloadLimits,
+    /// Files transferring from the backend
+    pub active: i64,
+    /// Files queued or transferring
+    pub queued: i64,
+    /// Bytes of finished and pending downloads today
+    pub today_bytes: i64,
+    /// Bytes of finished and pending downloads this month
+    pub month_bytes: i64,
+    /// Only filled in when a folder limit applies
+    pub folders: Vec<FolderUsage>,
+}
+
+#[cfg(feature = "server")]
+impl DownloadLimits {
+    /// Limits applied to users without their own.
+    pub async fn get_default() -> Result<DownloadLimits, String> {
+        match AppConfig::get(keys::DEFAULT_DOWNLOAD_LIMITS).await? {
+            Some(value) => serde_json::from_str(&value).map_err(|e| e.to_string()),
+            None => Ok(DownloadLimits::default()),
+        }
+    }
+
+    pub async fn set_default(limits: &DownloadLimits) -> Result<(), String> {
+        if limits.is_unlimited() {
+            return AppConfig::delete(keys::DEFAULT_DOWNLOAD_LIMITS).await;
+        }
+        let value = serde_json::to_string(limits).map_err(|e| e.to_string())?;
+        AppConfig::set(keys::DEFAULT_DOWNLOAD_LIMITS, &value).await
+    }
+
+    /// Limits set for this user, if any.
+    pub async fn get_custom(user_id: &str) -> Result<Option<DownloadLimits>, String> {
+        with_db!(|db| {
+            sqlx::query_as::<_, DownloadLimits>(
+                "SELECT max_concurrent, max_queued, daily_bytes, monthly_bytes, folder_bytes FROM user_download_limits WHERE user_id = $1",
+            )
+            .bind(user_id)
+            .fetch_optional(db)
+            .await
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// Set the user's own limits, or go back to the defaults with `None`.
+    pub async fn set_custom(user_id: &str, limits: Option<&DownloadLimits>) -> Result<(), String> {
+        let Some(limits) = limits else {
+            with_db!(|db| {
+                sqlx::query("DELETE FROM user_download_limits WHERE user_id = $1")
+                    .bind(user_id)
+                    .execute(db)
+                    .await
+                    .map(|r| r.rows_affected())
+            })
+            .map_err(|e| e.to_string())?;
+            return Ok(());
+        };
+
+        with_db!(|db| {
+            sqlx::query(
+                "INSERT INTO user_download_limits (user_id, max_concurrent, max_queued, daily_bytes, monthly_bytes, folder_bytes) VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT(user_id) DO UPDATE SET max_concurrent = excluded.max_concurrent, max_queued = excluded.max_queued, daily_bytes = excluded.daily_bytes, monthly_bytes = excluded.monthly_bytes, folder_bytes = excluded.folder_bytes",
+            )
+            .bind(user_id)
+            .bind(limits.max_concurrent)
+            .bind(limits.max_queued)
+            .bind(limits.daily_bytes)
+            .bind(limits.monthly_bytes)
+            .bind(limits.folder_bytes)
+            .execute(db)
+            .await
+            .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// Limits that apply to the user: their own, or the defaults.
+    pub async fn for_user(user_id: &str) -> Result<DownloadLimits, String> {
+        match Self::get_custom(user_id).await? {
+            Some(limits) => Ok(limits),
+            None => Self::get_default().await,
+        }
+    }
+}
+
+/// Bytes of finished downloads, counted against the daily and monthly quotas.
+#[cfg(feature = "server")]
+pub struct UsageRecord;
+
+#[cfg(feature = "server")]
+impl UsageRecord {
+    /// Count a finished download and drop records older than [`USAGE_RETENTION_DAYS`].
+    pub async fn add(user_id: &str, bytes: i64) -> Result<(), String> {
+        let now = chrono::Utc::now().timestamp();
+
+        with_db!(|db| {
+            sqlx::query(
+                "INSERT INTO download_usage (user_id, created_at, bytes) VALUES ($1, $2, $3)",
+            )
+            .bind(user_id)
+            .bind(now)
+            .bind(bytes)
+            .execute(db)
+            .await
+            .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+
+        let cutoff = now - chrono::Duration::days(USAGE_RETENTION_DAYS).num_seconds();
+        with_db!(|db| {
+            sqlx::query("DELETE FROM download_usage WHERE created_at < $1")
+                .bind(cutoff)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// Bytes downloaded since the given Unix timestamp.
+    pub async fn bytes_since(user_id: &str, since: i64) -> Result<i64, String> {
+        let (bytes,): (i64,) = with_db!(|db| {
+            sqlx::query_as(
+                "SELECT CAST(COALESCE(SUM(bytes), 0) AS BIGINT) FROM download_usage WHERE user_id = $1 AND created_at >= $2",
+            )
+            .bind(user_id)
+            .bind(since)
+            .fetch_one(db)
+            .await
+        })
+        .map_err(|e| e.to_string())?;
+
+        Ok(bytes)
+    }
+
+    /// Bytes downloaded today and this month, UTC.
+    pub async fn today_and_month(user_id: &str) -> Result<(i64, i64), String> {
+        let now = chrono::Utc::now();
+        let today = now
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .map(|d| d.and_utc().timestamp())
+            .unwrap_or_default();
+        let month = chrono::Utc
+            .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+            .single()
+            .map(|d| d.timestamp())
+            .unwrap_or_default();
+
+        Ok((
+            Self::bytes_since(user_id, today).await?,
+            Self::bytes_since(user_id, month).await?,
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::models::test_db::{create_user, with_test_db};
+
+    #[tokio::test]
+    async fn custom_limits_replace_the_defaults() {
+        with_test_db(async {
+            let defaults = DownloadLimits {
+                max_concurrent: Some(3),
+                daily_bytes: Some(5_000_000_000),
+                ..Default::default()
+            };
+            DownloadLimits::set_default(&defaults).await.unwrap();
+            assert_eq!(DownloadLimits::get_default().await.unwrap(), defaults);
+
+            let user = create_user("limited").await;
+            assert_eq!(DownloadLimits::get_custom(&user.id).await.unwrap(), None);
+            assert_eq!(DownloadLimits::for_user(&user.id).await.unwrap(), defaults);
+
+            let custom = DownloadLimits {
+                max_queued: Some(10),
+                monthly_bytes: Some(i64::MAX),
+                folder_bytes: Some(1 << 40),
+                ..Default::default()
+            };
+            DownloadLimits::set_custom(&user.id, Some(&custom))
+                .await
+                .unwrap();
+            assert_eq!(DownloadLimits::for_user(&user.id).await.unwrap(), custom);
+
+            let unlimited = DownloadLimits::default();
+            DownloadLimits::set_custom(&user.id, Some(&unlimited))
+                .await
+                .unwrap();
+            assert_eq!(
+                DownloadLimits::get_custom(&user.id).await.unwrap(),
+                Some(unlimited)
+            );
+
+            DownloadLimits::set_custom(&user.id, None).await.unwrap();
+            assert_eq!(DownloadLimits::for_user(&user.id).await.unwrap(), defaults);
+
+            DownloadLimits::set_default(&DownloadLimits::default())
+                .await
+                .unwrap();
+            assert_eq!(
+                AppConfig::get(keys::DEFAULT_DOWNLOAD_LIMITS).await.unwrap(),
+                None
+            );
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn usage_adds_up() {
+        with_test_db(async {
+            let user = create_user("downloader").await;
+            assert_eq!(
+                UsageRecord::today_and_month(&user.id).await.unwrap(),
+                (0, 0)
+            );
+
+            UsageRecord::add(&user.id, 3_000_000_000).await.unwrap();
+            UsageRecord::add(&user.id, 250).await.unwrap();
+            assert_eq!(
+                UsageRecord::today_and_month(&user.id).await.unwrap(),
+                (3_000_000_250, 3_000_000_250)
+            );
+
+            let later = chrono::Utc::now().timestamp() + 60;
+            assert_eq!(UsageRecord::bytes_since(&user.id, later).await.unwrap(), 0);
+        })
+        .await
+    }
+}
@@ -1,9 +1,58 @@
 #[cfg(feature = "server")]
-use crate::db::DB;
+use crate::db::with_db;
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "server")]
 use uuid::Uuid;
 
+/// How the requesting user can use a folder.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FolderAccess {
+    #[default]
+    Owner,
+    /// Shared with the user, who can download into it
+    Write,
+    /// Shared with the user for browsing only
+    Read,
+}
+
+impl FolderAccess {
+    pub fn is_owner(&self) -> bool {
+        matches!(self, FolderAccess::Owner)
+    }
+
+    pub fn can_write(&self) -> bool {
+        matches!(self, FolderAccess::Owner | FolderAccess::Write)
+    }
+}
+
+// Same names as in the folder queries
+impl std::fmt::Display for FolderAccess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FolderAccess::Owner => write!(f, "owner"),
+            FolderAccess::Write => write!(f, "write"),
+            FolderAccess::Read => write!(f, "read"),
+        }
+    }
+}
+
+impl std::str::FromStr for FolderAccess {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "owner" => Ok(FolderAccess::Owner),
+            "write" => Ok(FolderAccess::Write),
+            "read" => Ok(FolderAccess::Read),
+            _ => Err(format!("Unknown folder access: {}", s)),
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+crate::db::impl_text_type!(FolderAccess);
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "server", derive(sqlx::FromRow))]
 pub struct Folder {
@@ -11,52 +60,256 @@ pub struct Folder {
     pub user_id: String,
     pub name: String,
     pub path: String,
+    /// Fetch lyrics for tracks imported into this folder
+    #[serde(default)]
+    pub lyrics_enabled: bool,
+    /// Access of the user the folder was listed for
+    #[serde(default)]
+    #[cfg_attr(feature = "server", sqlx(default))]
+    pub access: FolderAccess,
+}
+
+/// A user a folder is shared with.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+pub struct FolderShare {
+    pub folder_id: String,
+    pub user_id: String,
+    pub username: String,
+    pub can_write: bool,
 }
 
+/// Folders a user owns plus the ones shared with them, with their access level.
+/// The user id is bound as `$1`.
+#[cfg(feature = "server")]
+const ACCESSIBLE_FOLDERS: &str =
+    "SELECT f.*, 'owner' AS access FROM folders f WHERE f.user_id = $1 \
+     UNION ALL \
+     SELECT f.*, CASE WHEN s.can_write THEN 'write' ELSE 'read' END AS access \
+     FROM folders f JOIN folder_shares s ON s.folder_id = f.id WHERE s.user_id = $1";
+
 #[cfg(feature = "server")]
 impl Folder {
     pub async fn create(user_id: &str, name: &str, path: &str) -> Result<Folder, String> {
         let id = Uuid::new_v4().to_string();
 
-        let folder = sqlx::query_as::<_, Folder>(
-            "INSERT INTO folders (id, user_id, name, path) VALUES (?, ?, ?, ?) RETURNING *",
-        )
-        .bind(&id)
-        .bind(user_id)
-        .bind(name)
-        .bind(path)
-        .fetch_one(&*DB)
-        .await
+        let folder = with_db!(|db| {
+            sqlx::query_as::<_, Folder>(
+                "INSERT INTO folders (id, user_id, name, path) VALUES ($1, $2, $3, $4) RETURNING *",
+            )
+            .bind(&id)
+            .bind(user_id)
+            .bind(name)
+            .bind(path)
+            .fetch_one(db)
+            .await
+        })
         .map_err(|e| e.to_string())?;
 
         Ok(folder)
     }
 
+    /// Folders owned by or shared with the user.
     pub async fn get_all_by_user(user_id: &str) -> Result<Vec<Folder>, String> {
-        sqlx::query_as::<_, Folder>("SELECT * FROM folders WHERE user_id = ?")
+        with_db!(|db| {
+            sqlx::query_as::<_, Folder>(ACCESSIBLE_FOLDERS)
+                .bind(user_id)
+                .fetch_all(db)
+                .await
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// A folder as seen by the user, `None` if they cannot access it.
+    pub async fn get_for_user(id: &str, user_id: &str) -> Result<Option<Folder>, String> {
+        with_db!(|db| {
+            sqlx::query_as::<_, Folder>(&format!(
+                "SELECT * FROM ({}) AS accessible WHERE id = $2 LIMIT 1",
+                ACCESSIBLE_FOLDERS
+            ))
             .bind(user_id)
-            .fetch_all(&*DB)
+            .bind(id)
+            .fetch_optional(db)
             .await
-            .map_err(|e| e.to_string())
+        })
+        .map_err(|e| e.to_string())
     }
 
     pub async fn update(id: &str, name: &str, path: &str) -> Result<(), String> {
-        sqlx::query("UPDATE folders SET name = ?, path = ? WHERE id = ?")
-            .bind(name)
+        with_db!(|db| {
+            sqlx::query("UPDATE folders SET name = $1, path = $2 WHERE id = $3")
+                .bind(name)
+                .bind(path)
+                .bind(id)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub async fn set_lyrics_enabled(id: &str, enabled: bool) -> Result<(), String> {
+        with_db!(|db| {
+            sqlx::query("UPDATE folders SET lyrics_enabled = $1 WHERE id = $2")
+                .bind(enabled)
+                .bind(id)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Whether any folder pointing at `path` has lyrics enabled.
+    pub async fn lyrics_enabled_for_path(path: &str) -> Result<bool, String> {
+        let count: i64 = with_db!(|db| {
+            sqlx::query_scalar(
+                "SELECT COUNT(*) FROM folders WHERE path = $1 AND lyrics_enabled = TRUE",
+            )
             .bind(path)
-            .bind(id)
-            .execute(&*DB)
+            .fetch_one(db)
             .await
-            .map_err(|e| e.to_string())?;
-        Ok(())
+        })
+        .map_err(|e| e.to_string())?;
+        Ok(count > 0)
     }
 
     pub async fn delete(id: &str) -> Result<(), String> {
-        sqlx::query("DELETE FROM folders WHERE id = ?")
+        with_db!(|db| {
+            sqlx::query("DELETE FROM folders WHERE id = $1")
+                .bind(id)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub async fn get_shares(id: &str) -> Result<Vec<FolderShare>, String> {
+        with_db!(|db| {
+            sqlx::query_as::<_, FolderShare>(
+                "SELECT s.folder_id, s.user_id, u.username, s.can_write FROM folder_shares s \
+                 JOIN users u ON u.id = s.user_id WHERE s.folder_id = $1 ORDER BY u.username",
+            )
             .bind(id)
-            .execute(&*DB)
+            .fetch_all(db)
             .await
-            .map_err(|e| e.to_string())?;
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// Share the folder with a user, or change the access of an existing share.
+    pub async fn share(id: &str, user_id: &str, can_write: bool) -> Result<(), String> {
+        with_db!(|db| {
+            sqlx::query(
+                "INSERT INTO folder_shares (folder_id, user_id, can_write) VALUES ($1, $2, $3) \
+                 ON CONFLICT(folder_id, user_id) DO UPDATE SET can_write = excluded.can_write",
+            )
+            .bind(id)
+            .bind(user_id)
+            .bind(can_write)
+            .execute(db)
+            .await
+            .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub async fn unshare(id: &str, user_id: &str) -> Result<(), String> {
+        with_db!(|db| {
+            sqlx::query("DELETE FROM folder_shares WHERE folder_id = $1 AND user_id = $2")
+                .bind(id)
+                .bind(user_id)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::models::test_db::{create_user, with_test_db};
+
+    #[tokio::test]
+    async fn create_update_and_delete() {
+        with_test_db(async {
+            let owner = create_user("owner").await;
+            let path = "/music/library";
+            let folder = Folder::create(&owner.id, "Library", path).await.unwrap();
+            assert_eq!(folder.user_id, owner.id);
+            assert!(!folder.lyrics_enabled);
+
+            let owned = Folder::get_all_by_user(&owner.id).await.unwrap();
+            assert_eq!(owned.len(), 1);
+            assert_eq!(owned[0].access, FolderAccess::Owner);
+            assert_eq!(owner.get_folders().await.unwrap().len(), 1);
+
+            let new_path = format!("{}-renamed", path);
+            Folder::update(&folder.id, "Renamed", &new_path)
+                .await
+                .unwrap();
+            Folder::set_lyrics_enabled(&folder.id, true).await.unwrap();
+            let updated = Folder::get_for_user(&folder.id, &owner.id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(updated.name, "Renamed");
+            assert_eq!(updated.path, new_path);
+            assert!(updated.lyrics_enabled);
+            assert!(Folder::lyrics_enabled_for_path(&new_path).await.unwrap());
+            assert!(!Folder::lyrics_enabled_for_path(path).await.unwrap());
+
+            Folder::delete(&folder.id).await.unwrap();
+            assert!(Folder::get_all_by_user(&owner.id).await.unwrap().is_empty());
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn shares_set_the_access() {
+        with_test_db(async {
+            let owner = create_user("owner").await;
+            let guest = create_user("guest").await;
+            let folder = Folder::create(&owner.id, "Shared", "/music/shared")
+                .await
+                .unwrap();
+            assert!(Folder::get_for_user(&folder.id, &guest.id)
+                .await
+                .unwrap()
+                .is_none());
+
+            Folder::share(&folder.id, &guest.id, false).await.unwrap();
+            let seen = Folder::get_all_by_user(&guest.id).await.unwrap();
+            assert_eq!(seen.len(), 1);
+            assert_eq!(seen[0].id, folder.id);
+            assert_eq!(seen[0].access, FolderAccess::Read);
+
+            // Sharing again changes the access of the existing share
+            Folder::share(&folder.id, &guest.id, true).await.unwrap();
+            let seen = Folder::get_for_user(&folder.id, &guest.id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(seen.access, FolderAccess::Write);
+
+            let shares = Folder::get_shares(&folder.id).await.unwrap();
+            assert_eq!(shares.len(), 1);
+            assert_eq!(shares[0].user_id, guest.id);
+            assert_eq!(shares[0].username, guest.username);
+            assert!(shares[0].can_write);
+
+            Folder::unshare(&folder.id, &guest.id).await.unwrap();
+            assert!(Folder::get_all_by_user(&guest.id).await.unwrap().is_empty());
+            assert!(Folder::get_shares(&folder.id).await.unwrap().is_empty());
+        })
+        .await
+    }
+}
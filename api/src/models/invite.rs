@@ -0,0 +1,169 @@
+use crate::auth::Role;
+#[cfg(feature = "server")]
+use crate::db::with_db;
+use serde::{Deserialize, Serialize};
+
+/// Single-use registration code generated by an admin.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+pub struct Invite {
+    pub token: String,
+    pub created_by: String,
+    /// Role given to the account created with this invite
+    pub role: Role,
+    /// Unix timestamps, in seconds
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub used_by: Option<String>,
+    pub used_at: Option<i64>,
+}
+
+impl Invite {
+    pub fn is_used(&self) -> bool {
+        self.used_by.is_some()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= chrono::Utc::now().timestamp()
+    }
+
+    /// Expiry date formatted for display.
+    pub fn expires_label(&self) -> String {
+        chrono::DateTime::from_timestamp(self.expires_at, 0)
+            .map(|d| d.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "server")]
+impl Invite {
+    pub async fn create(created_by: &str, role: Role, valid_hours: i64) -> Result<Invite, String> {
+        use rand::distr::{Alphanumeric, SampleString};
+
+        let token = Alphanumeric.sample_string(&mut rand::rng(), 24);
+        let now = chrono::Utc::now().timestamp();
+
+        with_db!(|db| {
+            sqlx::query_as::<_, Invite>(
+                "INSERT INTO invites (token, created_by, role, created_at, expires_at) VALUES ($1, $2, $3, $4, $5) RETURNING *",
+            )
+            .bind(&token)
+            .bind(created_by)
+            .bind(role)
+            .bind(now)
+            .bind(now + valid_hours * 3600)
+            .fetch_one(db)
+            .await
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    pub async fn get_all() -> Result<Vec<Invite>, String> {
+        with_db!(|db| {
+            sqlx::query_as::<_, Invite>("SELECT * FROM invites ORDER BY created_at DESC")
+                .fetch_all(db)
+                .await
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// An invite that can still be used, if any.
+    pub async fn get_valid(token: &str) -> Result<Option<Invite>, String> {
+        with_db!(|db| {
+            sqlx::query_as::<_, Invite>(
+                "SELECT * FROM invites WHERE token = $1 AND used_by IS NULL AND expires_at > $2",
+            )
+            .bind(token)
+            .bind(chrono::Utc::now().timestamp())
+            .fetch_optional(db)
+            .await
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// Mark the invite as used by `user_id`.
+    ///
+    /// Returns false if it was used or expired in the meantime.
+    pub async fn redeem(token: &str, user_id: &str) -> Result<bool, String> {
+        let now = chrono::Utc::now().timestamp();
+        let rows = with_db!(|db| {
+            sqlx::query(
+                "UPDATE invites SET used_by = $1, used_at = $2 WHERE token = $3 AND used_by IS NULL AND expires_at > $4",
+            )
+            .bind(user_id)
+            .bind(now)
+            .bind(token)
+            .bind(now)
+            .execute(db)
+            .await
+            .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+
+        Ok(rows == 1)
+    }
+
+    pub async fn delete(token: &str) -> Result<(), String> {
+        with_db!(|db| {
+            sqlx::query("DELETE FROM invites WHERE token = $1")
+                .bind(token)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::models::test_db::{create_user, with_test_db};
+
+    #[tokio::test]
+    async fn redeem_once() {
+        with_test_db(async {
+            let admin = create_user("inviter").await;
+            let invite = Invite::create(&admin.id, Role::ReadOnly, 24).await.unwrap();
+            assert_eq!(invite.role, Role::ReadOnly);
+            assert!(!invite.is_used());
+            assert!(!invite.is_expired());
+            assert!(Invite::get_valid(&invite.token).await.unwrap().is_some());
+
+            let invited = create_user("invited").await;
+            assert!(Invite::redeem(&invite.token, &invited.id).await.unwrap());
+            assert!(!Invite::redeem(&invite.token, &invited.id).await.unwrap());
+            assert!(Invite::get_valid(&invite.token).await.unwrap().is_none());
+
+            let used = Invite::get_all()
+                .await
+                .unwrap()
+                .into_iter()
+                .find(|i| i.token == invite.token)
+                .unwrap();
+            assert_eq!(used.used_by.as_deref(), Some(invited.id.as_str()));
+            assert!(used.used_at.is_some());
+
+            Invite::delete(&invite.token).await.unwrap();
+            assert!(!Invite::get_all()
+                .await
+                .unwrap()
+                .iter()
+                .any(|i| i.token == invite.token));
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn expired_invites_cannot_be_redeemed() {
+        with_test_db(async {
+            let admin = create_user("inviter").await;
+            let invite = Invite::create(&admin.id, Role::Member, -1).await.unwrap();
+            assert!(invite.is_expired());
+            assert!(Invite::get_valid(&invite.token).await.unwrap().is_none());
+            assert!(!Invite::redeem(&invite.token, &admin.id).await.unwrap());
+        })
+        .await
+    }
+}
@@ -1,4 +1,12 @@
+pub mod api_token;
 pub mod app_config;
+pub mod audit;
+pub mod download_limits;
 pub mod folder;
+pub mod invite;
+pub mod session;
 pub mod user;
 pub mod user_settings;
+
+#[cfg(all(test, feature = "server"))]
+pub(crate) mod test_db;
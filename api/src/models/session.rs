@@ -0,0 +1,237 @@
+#[cfg(feature = "server")]
+use crate::auth::EXPIRATION_DAYS;
+#[cfg(feature = "server")]
+use crate::db::with_db;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use uuid::Uuid;
+
+/// Server-side record of a login, referenced by the `jti` of its access tokens.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(sqlx::FromRow))]
+pub struct Session {
+    pub id: String,
+    pub user_id: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    /// Unix timestamps, in seconds
+    pub created_at: i64,
+    pub last_seen_at: i64,
+    pub expires_at: i64,
+    /// Whether this is the session making the request
+    #[serde(default)]
+    #[cfg_attr(feature = "server", sqlx(skip))]
+    pub current: bool,
+}
+
+impl Session {
+    /// Last activity formatted for display.
+    pub fn last_seen_label(&self) -> String {
+        chrono::DateTime::from_timestamp(self.last_seen_at, 0)
+            .map(|d| d.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_default()
+    }
+
+    /// Short description of the client, from its user agent.
+    pub fn device_label(&self) -> String {
+        let Some(ua) = self.user_agent.as_deref() else {
+            return "Unknown device".to_string();
+        };
+
+        let browser = ["Firefox", "Edg", "Chrome", "Safari", "curl"]
+            .into_iter()
+            .find(|b| ua.contains(b))
+            .map(|b| if b == "Edg" { "Edge" } else { b });
+        let os = ["Android", "iPhone", "iPad", "Windows", "Mac OS", "Linux"]
+            .into_iter()
+            .find(|o| ua.contains(o));
+
+        match (browser, os) {
+            (Some(b), Some(o)) => format!("{} on {}", b, o),
+            (Some(b), None) => b.to_string(),
+            (None, Some(o)) => o.to_string(),
+            (None, None) => ua.chars().take(60).collect(),
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+fn session_expiry(now: i64) -> i64 {
+    now + chrono::Duration::days(EXPIRATION_DAYS).num_seconds()
+}
+
+#[cfg(feature = "server")]
+impl Session {
+    /// Open a session for a user, clearing out expired ones on the way.
+    pub async fn create(
+        user_id: &str,
+        user_agent: Option<&str>,
+        ip: Option<&str>,
+    ) -> Result<Session, String> {
+        let now = chrono::Utc::now().timestamp();
+
+        with_db!(|db| {
+            sqlx::query("DELETE FROM sessions WHERE expires_at < $1")
+                .bind(now)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+
+        with_db!(|db| {
+            sqlx::query_as::<_, Session>(
+                "INSERT INTO sessions (id, user_id, user_agent, ip, created_at, last_seen_at, expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(user_id)
+            .bind(user_agent)
+            .bind(ip)
+            .bind(now)
+            .bind(now)
+            .bind(session_expiry(now))
+            .fetch_one(db)
+            .await
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// Check that a session is still open and record the activity.
+    pub async fn touch(id: &str, user_id: &str) -> Result<bool, String> {
+        let now = chrono::Utc::now().timestamp();
+
+        let rows = with_db!(|db| {
+            sqlx::query(
+                "UPDATE sessions SET last_seen_at = $1 WHERE id = $2 AND user_id = $3 AND expires_at >= $4",
+            )
+            .bind(now)
+            .bind(id)
+            .bind(user_id)
+            .bind(now)
+            .execute(db)
+            .await
+            .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+
+        Ok(rows > 0)
+    }
+
+    /// Extend a session that is still open, returns `false` if it was revoked or expired.
+    pub async fn refresh(
+        id: &str,
+        user_id: &str,
+        user_agent: Option<&str>,
+        ip: Option<&str>,
+    ) -> Result<bool, String> {
+        let now = chrono::Utc::now().timestamp();
+
+        let rows = with_db!(|db| {
+            sqlx::query(
+                "UPDATE sessions SET last_seen_at = $1, expires_at = $2, user_agent = COALESCE($3, user_agent), ip = COALESCE($4, ip) WHERE id = $5 AND user_id = $6 AND expires_at >= $7",
+            )
+            .bind(now)
+            .bind(session_expiry(now))
+            .bind(user_agent)
+            .bind(ip)
+            .bind(id)
+            .bind(user_id)
+            .bind(now)
+            .execute(db)
+            .await
+            .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+
+        Ok(rows > 0)
+    }
+
+    pub async fn get_all_by_user(user_id: &str) -> Result<Vec<Session>, String> {
+        with_db!(|db| {
+            sqlx::query_as::<_, Session>(
+                "SELECT * FROM sessions WHERE user_id = $1 AND expires_at >= $2 ORDER BY last_seen_at DESC",
+            )
+            .bind(user_id)
+            .bind(chrono::Utc::now().timestamp())
+            .fetch_all(db)
+            .await
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// Revoke one of the user's sessions.
+    pub async fn delete(id: &str, user_id: &str) -> Result<(), String> {
+        let rows = with_db!(|db| {
+            sqlx::query("DELETE FROM sessions WHERE id = $1 AND user_id = $2")
+                .bind(id)
+                .bind(user_id)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+
+        if rows == 0 {
+            return Err("Session not found".to_string());
+        }
+        Ok(())
+    }
+
+    /// Revoke every session of a user.
+    pub async fn delete_all_by_user(user_id: &str) -> Result<(), String> {
+        with_db!(|db| {
+            sqlx::query("DELETE FROM sessions WHERE user_id = $1")
+                .bind(user_id)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::models::test_db::{create_user, with_test_db};
+
+    #[tokio::test]
+    async fn create_refresh_and_revoke() {
+        with_test_db(async {
+            let user = create_user("sessions").await;
+            let other = create_user("other").await;
+            let session = Session::create(&user.id, Some("curl/8.0"), Some("10.0.0.1"))
+                .await
+                .unwrap();
+            assert!(session.expires_at > session.created_at);
+
+            assert!(Session::touch(&session.id, &user.id).await.unwrap());
+            assert!(!Session::touch(&session.id, &other.id).await.unwrap());
+
+            // A missing user agent keeps the stored one
+            assert!(
+                Session::refresh(&session.id, &user.id, None, Some("10.0.0.2"))
+                    .await
+                    .unwrap()
+            );
+            let sessions = Session::get_all_by_user(&user.id).await.unwrap();
+            assert_eq!(sessions.len(), 1);
+            assert_eq!(sessions[0].user_agent.as_deref(), Some("curl/8.0"));
+            assert_eq!(sessions[0].ip.as_deref(), Some("10.0.0.2"));
+
+            assert!(Session::delete(&session.id, &other.id).await.is_err());
+            Session::delete(&session.id, &user.id).await.unwrap();
+            assert!(!Session::touch(&session.id, &user.id).await.unwrap());
+            assert!(!Session::refresh(&session.id, &user.id, None, None)
+                .await
+                .unwrap());
+
+            Session::create(&user.id, None, None).await.unwrap();
+            Session::create(&user.id, None, None).await.unwrap();
+            Session::delete_all_by_user(&user.id).await.unwrap();
+            assert!(Session::get_all_by_user(&user.id).await.unwrap().is_empty());
+        })
+        .await
+    }
+}
@@ -0,0 +1,86 @@
+//! Databases of the model tests.
+//!
+//! Every test runs on a database of its own: a new SQLite file in the temp directory,
+//! or a new schema of the PostgreSQL database in `TEST_DATABASE_URL`. Its queries go
+//! there through [`crate::db::current`], so tests can change global settings without
+//! seeing each other's, and a PostgreSQL database can be reused between runs.
+
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::future::Future;
+
+use crate::auth::Role;
+use crate::db::{connect_postgres, connect_sqlite, is_postgres_url, Db, TEST_DB};
+use crate::models::user::User;
+
+/// Schema of its own in the PostgreSQL test database, migrated.
+pub(crate) struct PostgresSchema {
+    pub(crate) pool: PgPool,
+    admin: PgPool,
+    name: String,
+}
+
+impl PostgresSchema {
+    /// `None` when `TEST_DATABASE_URL` does not point at PostgreSQL.
+    pub(crate) async fn create() -> Option<Self> {
+        let url = std::env::var("TEST_DATABASE_URL")
+            .ok()
+            .filter(|url| is_postgres_url(url))?;
+
+        let name = format!("test_{}", uuid::Uuid::new_v4().simple());
+        let admin = PgPoolOptions::new()
+            .max_connections(1)
+            .connect(&url)
+            .await
+            .expect("Failed to connect to TEST_DATABASE_URL");
+        sqlx::query(&format!("CREATE SCHEMA {}", name))
+            .execute(&admin)
+            .await
+            .unwrap();
+
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let pool = connect_postgres(&format!(
+            "{}{}options[search_path]={}",
+            url, separator, name
+        ))
+        .await
+        .unwrap();
+
+        Some(Self { pool, admin, name })
+    }
+
+    pub(crate) async fn remove(self) {
+        self.pool.close().await;
+        sqlx::query(&format!("DROP SCHEMA {} CASCADE", self.name))
+            .execute(&self.admin)
+            .await
+            .unwrap();
+    }
+}
+
+/// Run a test on a database of its own, which is kept if the test fails.
+pub(crate) async fn with_test_db<F: Future>(test: F) -> F::Output {
+    if let Some(schema) = PostgresSchema::create().await {
+        let db: &'static Db = Box::leak(Box::new(Db::Postgres(schema.pool.clone())));
+        let output = TEST_DB.scope(db, test).await;
+        schema.remove().await;
+        return output;
+    }
+
+    let path = std::env::temp_dir().join(format!("soulbeet-test-{}.db", uuid::Uuid::new_v4()));
+    let pool = connect_sqlite(&format!("sqlite:{}", path.display()))
+        .await
+        .unwrap();
+    let db: &'static Db = Box::leak(Box::new(Db::Sqlite(pool.clone())));
+    let output = TEST_DB.scope(db, test).await;
+    pool.close().await;
+    let _ = std::fs::remove_file(path);
+    output
+}
+
+/// A member account.
+pub(crate) async fn create_user(username: &str) -> User {
+    User::create(username, "correct horse battery", Role::Member)
+        .await
+        .unwrap()
+}
@@ -1,7 +1,10 @@
 #[cfg(feature = "server")]
 use super::folder::Folder;
 #[cfg(feature = "server")]
-use crate::db::DB;
+use super::session::Session;
+use crate::auth::Role;
+#[cfg(feature = "server")]
+use crate::db::with_db;
 #[cfg(feature = "server")]
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
@@ -18,11 +21,52 @@ pub struct User {
     pub username: String,
     #[serde(skip)]
     pub password_hash: String,
+    pub role: Role,
+    /// Set for the seeded admin account and after an admin resets the password
+    #[serde(default)]
+    pub must_change_password: bool,
+    /// Identity at the OIDC provider or auth proxy, `None` for local accounts
+    #[serde(default)]
+    pub external_id: Option<String>,
+}
+
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+pub const MAX_PASSWORD_LENGTH: usize = 128;
+
+/// Check a new password against the password policy.
+pub fn check_password_policy(username: &str, password: &str) -> Result<(), String> {
+    let length = password.chars().count();
+    if length < MIN_PASSWORD_LENGTH {
+        return Err(format!(
+            "Password must be at least {} characters long",
+            MIN_PASSWORD_LENGTH
+        ));
+    }
+    if length > MAX_PASSWORD_LENGTH {
+        return Err(format!(
+            "Password must be at most {} characters long",
+            MAX_PASSWORD_LENGTH
+        ));
+    }
+    if password.eq_ignore_ascii_case(username) {
+        return Err("Password cannot be the same as the username".to_string());
+    }
+    let first = password.chars().next().unwrap_or_default();
+    if password.chars().all(|c| c == first) {
+        return Err("Password cannot be a single repeated character".to_string());
+    }
+    Ok(())
 }
 
 #[cfg(feature = "server")]
 impl User {
-    pub async fn create(username: &str, password: &str) -> Result<User, String> {
+    pub async fn create(username: &str, password: &str, role: Role) -> Result<User, String> {
+        let username = username.trim();
+        if username.is_empty() {
+            return Err("Username is required".to_string());
+        }
+        check_password_policy(username, password)?;
+
         let salt = SaltString::generate(&mut OsRng);
         let argon2 = Argon2::default();
         let password_hash = argon2
@@ -32,26 +76,93 @@ impl User {
 
         let id = Uuid::new_v4().to_string();
 
-        let user = sqlx::query_as::<_, User>(
-            "INSERT INTO users (id, username, password_hash) VALUES (?, ?, ?) RETURNING id, username, password_hash"
-        )
-        .bind(&id)
-        .bind(username)
-        .bind(password_hash)
-        .fetch_one(&*DB)
-        .await
+        let user = with_db!(|db| {
+            sqlx::query_as::<_, User>(
+                "INSERT INTO users (id, username, password_hash, role) VALUES ($1, $2, $3, $4) RETURNING id, username, password_hash, role, must_change_password, external_id"
+            )
+            .bind(&id)
+            .bind(username)
+            .bind(password_hash)
+            .bind(role)
+            .fetch_one(db)
+            .await
+        })
         .map_err(|e| e.to_string())?;
 
         Ok(user)
     }
 
-    pub async fn verify(username: &str, password: &str) -> Result<User, String> {
-        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE username = ?")
+    /// Create a user that logs in through single sign-on.
+    ///
+    /// The account gets a random password nobody knows, so it cannot log in locally
+    /// until an admin sets one.
+    pub async fn create_external(
+        username: &str,
+        role: Role,
+        external_id: &str,
+    ) -> Result<User, String> {
+        use rand::distr::{Alphanumeric, SampleString};
+
+        let username = username.trim();
+        if username.is_empty() {
+            return Err("Username is required".to_string());
+        }
+
+        let password = Alphanumeric.sample_string(&mut rand::rng(), 48);
+        let salt = SaltString::generate(&mut OsRng);
+        let password_hash = Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| e.to_string())?
+            .to_string();
+
+        with_db!(|db| {
+            sqlx::query_as::<_, User>(
+                "INSERT INTO users (id, username, password_hash, role, external_id) VALUES ($1, $2, $3, $4, $5) RETURNING id, username, password_hash, role, must_change_password, external_id"
+            )
+            .bind(Uuid::new_v4().to_string())
             .bind(username)
-            .fetch_optional(&*DB)
+            .bind(password_hash)
+            .bind(role)
+            .bind(external_id)
+            .fetch_one(db)
             .await
-            .map_err(|e| e.to_string())?
-            .ok_or("User not found")?;
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    pub async fn get_by_external_id(external_id: &str) -> Result<Option<User>, String> {
+        with_db!(|db| {
+            sqlx::query_as::<_, User>("SELECT * FROM users WHERE external_id = $1")
+                .bind(external_id)
+                .fetch_optional(db)
+                .await
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// Attach an SSO identity to an existing account.
+    pub async fn set_external_id(id: &str, external_id: &str) -> Result<(), String> {
+        with_db!(|db| {
+            sqlx::query("UPDATE users SET external_id = $1 WHERE id = $2")
+                .bind(external_id)
+                .bind(id)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub async fn verify(username: &str, password: &str) -> Result<User, String> {
+        let user = with_db!(|db| {
+            sqlx::query_as::<_, User>("SELECT * FROM users WHERE username = $1")
+                .bind(username)
+                .fetch_optional(db)
+                .await
+        })
+        .map_err(|e| e.to_string())?
+        .ok_or("User not found")?;
 
         let parsed_hash = PasswordHash::new(&user.password_hash).map_err(|e| e.to_string())?;
         Argon2::default()
@@ -62,32 +173,59 @@ impl User {
     }
 
     pub async fn get_folders(&self) -> Result<Vec<Folder>, String> {
-        sqlx::query_as::<_, Folder>("SELECT * FROM folders WHERE user_id = ?")
-            .bind(&self.id)
-            .fetch_all(&*DB)
-            .await
-            .map_err(|e| e.to_string())
+        with_db!(|db| {
+            sqlx::query_as::<_, Folder>("SELECT * FROM folders WHERE user_id = $1")
+                .bind(&self.id)
+                .fetch_all(db)
+                .await
+        })
+        .map_err(|e| e.to_string())
     }
 
     pub async fn get_all() -> Result<Vec<User>, String> {
-        sqlx::query_as::<_, User>("SELECT * FROM users")
-            .fetch_all(&*DB)
-            .await
-            .map_err(|e| e.to_string())
+        with_db!(|db| {
+            sqlx::query_as::<_, User>("SELECT * FROM users")
+                .fetch_all(db)
+                .await
+        })
+        .map_err(|e| e.to_string())
     }
 
     pub async fn get_by_id(id: &str) -> Result<User, String> {
-        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?")
-            .bind(id)
-            .fetch_optional(&*DB)
-            .await
-            .map_err(|e| e.to_string())?
-            .ok_or("User not found")?;
+        let user = with_db!(|db| {
+            sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+                .bind(id)
+                .fetch_optional(db)
+                .await
+        })
+        .map_err(|e| e.to_string())?
+        .ok_or("User not found")?;
+
+        Ok(user)
+    }
+
+    pub async fn get_by_username(username: &str) -> Result<User, String> {
+        let user = with_db!(|db| {
+            sqlx::query_as::<_, User>("SELECT * FROM users WHERE username = $1")
+                .bind(username)
+                .fetch_optional(db)
+                .await
+        })
+        .map_err(|e| e.to_string())?
+        .ok_or("User not found")?;
 
         Ok(user)
     }
 
-    pub async fn update_password(id: &str, password: &str) -> Result<(), String> {
+    /// Set a new password. `must_change` forces another change on next login.
+    pub async fn update_password(
+        id: &str,
+        password: &str,
+        must_change: bool,
+    ) -> Result<(), String> {
+        let user = Self::get_by_id(id).await?;
+        check_password_policy(&user.username, password)?;
+
         let salt = SaltString::generate(&mut OsRng);
         let argon2 = Argon2::default();
         let password_hash = argon2
@@ -95,21 +233,164 @@ impl User {
             .map_err(|e| e.to_string())?
             .to_string();
 
-        sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+        with_db!(|db| {
+            sqlx::query(
+                "UPDATE users SET password_hash = $1, must_change_password = $2 WHERE id = $3",
+            )
             .bind(password_hash)
+            .bind(must_change)
             .bind(id)
-            .execute(&*DB)
+            .execute(db)
             .await
-            .map_err(|e| e.to_string())?;
+            .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
+
+        // Sign out everywhere, the caller opens a new session if needed
+        Session::delete_all_by_user(id).await
+    }
+
+    pub async fn set_role(id: &str, role: Role) -> Result<(), String> {
+        with_db!(|db| {
+            sqlx::query("UPDATE users SET role = $1 WHERE id = $2")
+                .bind(role)
+                .bind(id)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
         Ok(())
     }
 
+    pub async fn count_admins() -> Result<i64, String> {
+        with_db!(|db| {
+            sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE role = $1")
+                .bind(Role::Admin)
+                .fetch_one(db)
+                .await
+        })
+        .map_err(|e| e.to_string())
+    }
+
     pub async fn delete(id: &str) -> Result<(), String> {
-        sqlx::query("DELETE FROM users WHERE id = ?")
-            .bind(id)
-            .execute(&*DB)
-            .await
-            .map_err(|e| e.to_string())?;
+        with_db!(|db| {
+            sqlx::query("DELETE FROM users WHERE id = $1")
+                .bind(id)
+                .execute(db)
+                .await
+                .map(|r| r.rows_affected())
+        })
+        .map_err(|e| e.to_string())?;
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::models::test_db::{create_user, with_test_db};
+
+    #[tokio::test]
+    async fn create_and_look_up() {
+        with_test_db(async {
+            let name = "alice";
+            let user = User::create(
+                &format!(" {} ", name),
+                "correct horse battery",
+                Role::Member,
+            )
+            .await
+            .unwrap();
+            assert_eq!(user.username, name);
+            assert_eq!(user.role, Role::Member);
+            assert!(!user.must_change_password);
+            assert_eq!(user.external_id, None);
+
+            assert_eq!(User::get_by_id(&user.id).await.unwrap().username, name);
+            assert_eq!(User::get_by_username(name).await.unwrap().id, user.id);
+            assert!(User::get_all()
+                .await
+                .unwrap()
+                .iter()
+                .any(|u| u.id == user.id));
+            assert!(User::create(name, "another good password", Role::Member)
+                .await
+                .is_err());
+
+            assert_eq!(
+                User::verify(name, "correct horse battery")
+                    .await
+                    .unwrap()
+                    .id,
+                user.id
+            );
+            assert!(User::verify(name, "wrong horse battery").await.is_err());
+
+            User::delete(&user.id).await.unwrap();
+            assert!(User::get_by_id(&user.id).await.is_err());
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn external_id_and_role() {
+        with_test_db(async {
+            let external = "oidc-sub";
+            let user = User::create_external("sso", Role::ReadOnly, external)
+                .await
+                .unwrap();
+            assert_eq!(user.external_id.as_deref(), Some(external));
+            assert_eq!(
+                User::get_by_external_id(external)
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .id,
+                user.id
+            );
+
+            let local = create_user("linked").await;
+            let linked = "proxy-user";
+            User::set_external_id(&local.id, linked).await.unwrap();
+            assert_eq!(
+                User::get_by_external_id(linked).await.unwrap().unwrap().id,
+                local.id
+            );
+            assert!(User::get_by_external_id("nobody").await.unwrap().is_none());
+
+            let admins = User::count_admins().await.unwrap();
+            User::set_role(&user.id, Role::Admin).await.unwrap();
+            assert_eq!(User::get_by_id(&user.id).await.unwrap().role, Role::Admin);
+            assert_eq!(User::count_admins().await.unwrap(), admins + 1);
+            User::delete(&user.id).await.unwrap();
+            assert_eq!(User::count_admins().await.unwrap(), admins);
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn password_change_signs_out() {
+        with_test_db(async {
+            let user = create_user("bob").await;
+            Session::create(&user.id, None, None).await.unwrap();
+
+            User::update_password(&user.id, &user.username, false)
+                .await
+                .unwrap_err();
+            User::update_password(&user.id, "a brand new password", true)
+                .await
+                .unwrap();
+
+            let updated = User::verify(&user.username, "a brand new password")
+                .await
+                .unwrap();
+            assert!(updated.must_change_password);
+            assert!(User::verify(&user.username, "correct horse battery")
+                .await
+                .is_err());
+            assert!(Session::get_all_by_user(&user.id).await.unwrap().is_empty());
+        })
+        .await
+    }
+}
@@ -1,5 +1,5 @@
 #[cfg(feature = "server")]
-use crate::db::DB;
+use crate::db::with_db;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -8,6 +8,9 @@ pub struct UserSettings {
     pub user_id: String,
     pub default_metadata_provider: Option<String>,
     pub last_search_type: Option<String>,
+    /// Last.fm account used for discovery
+    #[serde(default)]
+    pub lastfm_username: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -16,24 +19,29 @@ pub struct UpdateUserSettings {
     pub default_metadata_provider: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_search_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lastfm_username: Option<String>,
 }
 
 #[cfg(feature = "server")]
 impl UserSettings {
     pub async fn get(user_id: &str) -> Result<UserSettings, String> {
         // Try to get existing settings, or return defaults
-        let settings = sqlx::query_as::<_, UserSettings>(
-            "SELECT * FROM user_settings WHERE user_id = ?",
-        )
-        .bind(user_id)
-        .fetch_optional(&*DB)
-        .await
+        let settings = with_db!(|db| {
+            sqlx::query_as::<_, UserSettings>(
+                "SELECT * FROM user_settings WHERE user_id = $1",
+            )
+            .bind(user_id)
+            .fetch_optional(db)
+            .await
+        })
         .map_err(|e| e.to_string())?;
 
         Ok(settings.unwrap_or_else(|| UserSettings {
             user_id: user_id.to_string(),
             default_metadata_provider: Some("musicbrainz".to_string()),
             last_search_type: Some("album".to_string()),
+            lastfm_username: None,
         }))
     }
 
@@ -43,23 +51,83 @@ impl UserSettings {
 
         let provider = update.default_metadata_provider.or(current.default_metadata_provider);
         let search_type = update.last_search_type.or(current.last_search_type);
+        // An empty username clears it
+        let lastfm_username = match update.lastfm_username {
+            Some(name) if name.trim().is_empty() => None,
+            Some(name) => Some(name.trim().to_string()),
+            None => current.lastfm_username,
+        };
 
-        sqlx::query(
-            r#"
-            INSERT INTO user_settings (user_id, default_metadata_provider, last_search_type)
-            VALUES (?, ?, ?)
-            ON CONFLICT(user_id) DO UPDATE SET
-                default_metadata_provider = excluded.default_metadata_provider,
-                last_search_type = excluded.last_search_type
-            "#,
-        )
-        .bind(user_id)
-        .bind(&provider)
-        .bind(&search_type)
-        .execute(&*DB)
-        .await
+        with_db!(|db| {
+            sqlx::query(
+                r#"
+                INSERT INTO user_settings (user_id, default_metadata_provider, last_search_type, lastfm_username)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT(user_id) DO UPDATE SET
+                    default_metadata_provider = excluded.default_metadata_provider,
+                    last_search_type = excluded.last_search_type,
+                    lastfm_username = excluded.lastfm_username
+                "#,
+            )
+            .bind(user_id)
+            .bind(&provider)
+            .bind(&search_type)
+            .bind(&lastfm_username)
+            .execute(db)
+            .await
+            .map(|r| r.rows_affected())
+        })
         .map_err(|e| e.to_string())?;
 
         Self::get(user_id).await
     }
 }
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::models::test_db::{create_user, with_test_db};
+
+    #[tokio::test]
+    async fn upsert_keeps_unset_fields() {
+        with_test_db(async {
+            let user = create_user("settings").await;
+            let defaults = UserSettings::get(&user.id).await.unwrap();
+            assert_eq!(
+                defaults.default_metadata_provider.as_deref(),
+                Some("musicbrainz")
+            );
+            assert_eq!(defaults.last_search_type.as_deref(), Some("album"));
+
+            let settings = UserSettings::upsert(
+                &user.id,
+                UpdateUserSettings {
+                    last_search_type: Some("track".to_string()),
+                    lastfm_username: Some(" listener ".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            assert_eq!(
+                settings.default_metadata_provider.as_deref(),
+                Some("musicbrainz")
+            );
+            assert_eq!(settings.last_search_type.as_deref(), Some("track"));
+            assert_eq!(settings.lastfm_username.as_deref(), Some("listener"));
+
+            let settings = UserSettings::upsert(
+                &user.id,
+                UpdateUserSettings {
+                    lastfm_username: Some(String::new()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            assert_eq!(settings.last_search_type.as_deref(), Some("track"));
+            assert_eq!(settings.lastfm_username, None);
+        })
+        .await
+    }
+}
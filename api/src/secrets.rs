@@ -0,0 +1,147 @@
+//! Encryption of secrets stored in the database.
+//!
+//! Values are sealed with AES-256-GCM under a key derived with HKDF-SHA256 from
+//! `CONFIG_ENCRYPTION_KEY`. A sealed value looks like `enc:v1:<key id>:<base64>`
+//! and names the key it was sealed with, so keys listed in
+//! `CONFIG_ENCRYPTION_OLD_KEYS` can still open it until it is sealed again with
+//! the current key, see [`needs_resealing`]. Without a key values stay in plaintext.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::hkdf::{Salt, HKDF_SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use sha2::{Digest, Sha256};
+use std::sync::LazyLock;
+
+use crate::config::CONFIG;
+
+const PREFIX: &str = "enc:v1:";
+const HKDF_SALT: &[u8] = b"soulbeet-app-config";
+
+struct SealingKey {
+    /// Short fingerprint of the env secret, stored with each value
+    id: String,
+    key: LessSafeKey,
+}
+
+impl SealingKey {
+    fn derive(secret: &str) -> Self {
+        let prk = Salt::new(HKDF_SHA256, HKDF_SALT).extract(secret.as_bytes());
+        let okm = prk
+            .expand(&[b"aes-256-gcm"], &AES_256_GCM)
+            .expect("HKDF output fits an AES-256 key");
+
+        let digest = Sha256::digest(format!("soulbeet-key-id:{}", secret).as_bytes());
+        let id = digest[..4].iter().map(|b| format!("{:02x}", b)).collect();
+
+        Self {
+            id,
+            key: LessSafeKey::new(UnboundKey::from(okm)),
+        }
+    }
+}
+
+/// Current key, if configured.
+static CURRENT: LazyLock<Option<SealingKey>> =
+    LazyLock::new(|| CONFIG.config_encryption_key().map(SealingKey::derive));
+
+/// Retired keys, only used to open values.
+static OLD: LazyLock<Vec<SealingKey>> = LazyLock::new(|| {
+    CONFIG
+        .config_encryption_old_keys()
+        .iter()
+        .map(|secret| SealingKey::derive(secret))
+        .collect()
+});
+
+/// Whether values are encrypted when written.
+pub fn enabled() -> bool {
+    CURRENT.is_some()
+}
+
+/// Id of the key a value was sealed with, `None` for plaintext.
+fn key_id(value: &str) -> Option<&str> {
+    value
+        .strip_prefix(PREFIX)?
+        .split_once(':')
+        .map(|(id, _)| id)
+}
+
+/// Encrypt `plaintext` stored under `name`, returned unchanged without a key.
+///
+/// The name is authenticated too, so a value cannot be moved to another setting.
+pub fn seal(name: &str, plaintext: &str) -> Result<String, String> {
+    let Some(current) = CURRENT.as_ref() else {
+        return Ok(plaintext.to_string());
+    };
+
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| "Failed to generate nonce".to_string())?;
+
+    let mut in_out = plaintext.as_bytes().to_vec();
+    current
+        .key
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(name.as_bytes()),
+            &mut in_out,
+        )
+        .map_err(|_| format!("Failed to encrypt {}", name))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(in_out);
+    Ok(format!(
+        "{}{}:{}",
+        PREFIX,
+        current.id,
+        STANDARD.encode(sealed)
+    ))
+}
+
+/// Decrypt a value stored under `name`, plaintext values are returned as is.
+pub fn open(name: &str, value: &str) -> Result<String, String> {
+    let Some(id) = key_id(value) else {
+        return Ok(value.to_string());
+    };
+
+    let key = CURRENT
+        .iter()
+        .chain(OLD.iter())
+        .find(|k| k.id == id)
+        .ok_or_else(|| {
+            format!(
+                "{} was encrypted with a key that is not configured, set it in CONFIG_ENCRYPTION_KEY or CONFIG_ENCRYPTION_OLD_KEYS",
+                name
+            )
+        })?;
+
+    let encoded = &value[PREFIX.len() + id.len() + 1..];
+    let mut sealed = STANDARD
+        .decode(encoded)
+        .map_err(|_| format!("{} is not valid encrypted data", name))?;
+    if sealed.len() < NONCE_LEN {
+        return Err(format!("{} is not valid encrypted data", name));
+    }
+    let mut in_out = sealed.split_off(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(&sealed)
+        .map_err(|_| format!("{} is not valid encrypted data", name))?;
+
+    let plaintext = key
+        .key
+        .open_in_place(nonce, Aad::from(name.as_bytes()), &mut in_out)
+        .map_err(|_| format!("Failed to decrypt {}, wrong key or tampered value", name))?;
+    String::from_utf8(plaintext.to_vec()).map_err(|e| e.to_string())
+}
+
+/// Whether a stored value should be written again: plaintext while a key is set,
+/// sealed with an old key, or sealed while encryption was turned off.
+pub fn needs_resealing(value: &str) -> bool {
+    match (CURRENT.as_ref(), key_id(value)) {
+        (Some(current), Some(id)) => current.id != id,
+        (Some(_), None) => true,
+        (None, Some(_)) => true,
+        (None, None) => false,
+    }
+}
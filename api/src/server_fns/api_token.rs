@@ -0,0 +1,121 @@
+use crate::auth::ApiScope;
+use crate::models::api_token::ApiToken;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "server")]
+use super::server_error;
+#[cfg(feature = "server")]
+use crate::auth::{ApiScopes, Claims};
+#[cfg(feature = "server")]
+use crate::models::{
+    audit::{AuditEvent, AuditRecord},
+    user::User,
+};
+#[cfg(feature = "server")]
+use crate::AuthSession;
+
+/// Longest validity an expiring API token can be created with, in days.
+pub const MAX_API_TOKEN_DAYS: i64 = 365;
+
+/// A new API token along with its secret, which cannot be retrieved later.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreatedApiToken {
+    pub token: ApiToken,
+    pub secret: String,
+}
+
+/// Tokens are managed from the web UI only, so a leaked token cannot mint others.
+#[cfg(feature = "server")]
+fn require_cookie_session(claims: &Claims) -> Result<(), ServerFnError> {
+    if claims.scopes.is_some() {
+        return Err(server_error("API tokens cannot manage API tokens"));
+    }
+    Ok(())
+}
+
+#[get("/api/tokens", auth: AuthSession)]
+pub async fn get_api_tokens() -> Result<Vec<ApiToken>, ServerFnError> {
+    let claims = auth.0;
+    require_cookie_session(&claims)?;
+
+    ApiToken::get_all_by_user(&claims.sub)
+        .await
+        .map_err(server_error)
+}
+
+/// Create a named token with the given scopes, valid for `valid_days` or without
+/// expiry. Scopes beyond the user's role are refused.
+#[post("/api/tokens", auth: AuthSession)]
+pub async fn create_api_token(
+    name: String,
+    scopes: Vec<ApiScope>,
+    valid_days: Option<i64>,
+) -> Result<CreatedApiToken, ServerFnError> {
+    let claims = auth.0;
+    require_cookie_session(&claims)?;
+
+    let name = name.trim();
+    if name.is_empty() || name.len() > 64 {
+        return Err(server_error(
+            "Token name must be between 1 and 64 characters",
+        ));
+    }
+    if scopes.is_empty() {
+        return Err(server_error("Select at least one scope"));
+    }
+    if valid_days.is_some_and(|days| !(1..=MAX_API_TOKEN_DAYS).contains(&days)) {
+        return Err(server_error(format!(
+            "Tokens must be valid between 1 and {} days",
+            MAX_API_TOKEN_DAYS
+        )));
+    }
+
+    let role = User::get_by_id(&claims.sub)
+        .await
+        .map_err(server_error)?
+        .role;
+    if scopes.contains(&ApiScope::Admin) && !role.is_admin() {
+        return Err(server_error("Only admins can create admin tokens"));
+    }
+    if scopes.contains(&ApiScope::Download) && !role.can_write() {
+        return Err(server_error(
+            "Read-only accounts cannot create download tokens",
+        ));
+    }
+
+    let (token, secret) = ApiToken::create(&claims.sub, name, &ApiScopes(scopes), valid_days)
+        .await
+        .map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::ApiTokenCreated)
+        .by(&claims)
+        .target(&token.name)
+        .details(format!(
+            "scopes {}, expires {}",
+            token.scopes,
+            token.expires_label()
+        ))
+        .save()
+        .await;
+
+    Ok(CreatedApiToken { token, secret })
+}
+
+/// Revoke one of the current user's tokens.
+#[delete("/api/tokens/delete", auth: AuthSession)]
+pub async fn delete_api_token(token_id: String) -> Result<(), ServerFnError> {
+    let claims = auth.0;
+    require_cookie_session(&claims)?;
+
+    ApiToken::delete(&token_id, &claims.sub)
+        .await
+        .map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::ApiTokenDeleted)
+        .by(&claims)
+        .target(token_id)
+        .save()
+        .await;
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use crate::models::audit::AuditEntry;
+use dioxus::prelude::*;
+
+#[cfg(feature = "server")]
+use super::server_error;
+#[cfg(feature = "server")]
+use crate::AdminSession;
+
+/// Number of entries returned per page.
+pub const AUDIT_PAGE_SIZE: i64 = 50;
+
+/// Latest audit log entries, newest first. Pass the id of the last entry received
+/// to get the next page.
+#[post("/api/audit", _: AdminSession)]
+pub async fn get_audit_log(before_id: Option<i64>) -> Result<Vec<AuditEntry>, ServerFnError> {
+    AuditEntry::get_page(before_id, AUDIT_PAGE_SIZE)
+        .await
+        .map_err(server_error)
+}
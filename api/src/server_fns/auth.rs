@@ -1,8 +1,21 @@
-use crate::auth::AuthResponse;
+use crate::auth::{AuthResponse, LoginOptions, RegistrationMode};
+use crate::models::session::Session;
 use dioxus::prelude::*;
 
 #[cfg(feature = "server")]
-use crate::{auth, models, server_fns::server_error, AuthSession};
+use crate::{
+    auth,
+    config::CONFIG,
+    login_throttle,
+    models::{
+        self,
+        audit::{AuditEvent, AuditRecord},
+        invite::Invite,
+        user::User,
+    },
+    server_fns::{server_error, settings::registration_mode},
+    AuthSession, ClientInfo, PendingSession, RefreshSession,
+};
 
 #[cfg(feature = "server")]
 use tower_cookies::{
@@ -25,62 +38,359 @@ fn build_auth_cookie(token: String) -> Cookie<'static> {
     cookie
 }
 
-#[post("/api/auth/register")]
-pub async fn register(username: String, password: String) -> Result<(), ServerFnError> {
-    models::user::User::create(&username, &password)
+/// Open a session for the user and set its access token cookie.
+#[cfg(feature = "server")]
+pub(crate) async fn start_session(
+    user: &User,
+    client: &ClientInfo,
+    cookies: &Cookies,
+) -> Result<(), ServerFnError> {
+    let session = Session::create(&user.id, client.user_agent.as_deref(), client.ip.as_deref())
         .await
-        .map_err(server_error)
-        .map(|_| ())
+        .map_err(server_error)?;
+
+    let token = auth::create_token(user, &session.id).map_err(server_error)?;
+    cookies.add(build_auth_cookie(token));
+    Ok(())
 }
 
-#[post("/api/auth/login", cookies: Cookies)]
-pub async fn login(username: String, password: String) -> Result<AuthResponse, ServerFnError> {
-    let user = match models::user::User::verify(&username, &password).await {
-        Ok(user) => user,
-        Err(e) => return Err(server_error(e)),
-    };
+#[cfg(feature = "server")]
+fn clear_auth_cookie(cookies: &Cookies) {
+    let mut cookie = Cookie::new(AUTH_COOKIE_NAME, "");
+    cookie.set_path("/");
 
-    let token = auth::create_token(user.id.clone(), user.username.clone()).map_err(server_error)?;
+    cookies.remove(cookie);
+}
 
-    cookies.add(build_auth_cookie(token));
+#[cfg(feature = "server")]
+fn too_many_attempts(retry_after: std::time::Duration) -> ServerFnError {
+    ServerFnError::ServerError {
+        message: format!(
+            "Too many failed attempts, try again in {} seconds",
+            retry_after.as_secs().max(1)
+        ),
+        code: 429,
+        details: None,
+    }
+}
+
+/// Check a password with brute-force protection and audit failed attempts.
+///
+/// `failure_message` is returned for a wrong username or password, without telling
+/// which one was wrong.
+#[cfg(feature = "server")]
+async fn verify_password(
+    username: &str,
+    password: &str,
+    client: &ClientInfo,
+    failure_message: &str,
+) -> Result<User, ServerFnError> {
+    let ip = client.ip.as_deref();
+
+    if let Some(retry_after) = login_throttle::retry_after(ip, username) {
+        AuditRecord::new(AuditEvent::LoginThrottled)
+            .user(None, username)
+            .ip(ip)
+            .save()
+            .await;
+        return Err(too_many_attempts(retry_after));
+    }
+
+    match User::verify(username, password).await {
+        Ok(user) => {
+            login_throttle::record_success(ip, username);
+            Ok(user)
+        }
+        Err(_) => {
+            let mut record = AuditRecord::new(AuditEvent::LoginFailed)
+                .user(None, username)
+                .ip(ip);
+            if let Some(lockout) = login_throttle::record_failure(ip, username) {
+                record = record.details(format!("locked out for {}s", lockout.as_secs()));
+            }
+            record.save().await;
+            Err(server_error(failure_message))
+        }
+    }
+}
 
-    Ok(AuthResponse {
+#[cfg(feature = "server")]
+fn auth_response(user: User) -> AuthResponse {
+    AuthResponse {
         username: user.username,
         user_id: user.id,
+        role: user.role,
+        must_change_password: user.must_change_password,
+    }
+}
+
+/// Registration mode, so the login page knows whether to offer sign up.
+///
+/// Always disabled when password login is turned off.
+#[get("/api/auth/registration")]
+pub async fn get_registration_mode() -> Result<RegistrationMode, ServerFnError> {
+    if !CONFIG.password_login() {
+        return Ok(RegistrationMode::Disabled);
+    }
+    registration_mode().await
+}
+
+/// Login methods to show on the login page.
+#[get("/api/auth/options")]
+pub async fn get_login_options() -> Result<LoginOptions, ServerFnError> {
+    Ok(LoginOptions {
+        password_login: CONFIG.password_login(),
+        oidc_name: CONFIG.oidc().map(|oidc| oidc.display_name.clone()),
     })
 }
 
-#[post("/api/auth/refresh", auth: AuthSession, cookies: Cookies)]
+/// Create an account from the login page.
+///
+/// Depending on the registration mode this is open to anyone, needs an invite code
+/// (the account gets the invite's role) or is refused.
+#[post("/api/auth/register", client: ClientInfo)]
+pub async fn register(
+    username: String,
+    password: String,
+    invite_code: Option<String>,
+) -> Result<(), ServerFnError> {
+    if !CONFIG.password_login() {
+        return Err(server_error("Registration is disabled"));
+    }
+
+    let user = match registration_mode().await? {
+        RegistrationMode::Disabled => return Err(server_error("Registration is disabled")),
+        RegistrationMode::Open => User::create(&username, &password, auth::Role::Member)
+            .await
+            .map_err(server_error)?,
+        RegistrationMode::Invite => {
+            let code = invite_code.unwrap_or_default();
+            let invite = Invite::get_valid(code.trim())
+                .await
+                .map_err(server_error)?
+                .ok_or_else(|| server_error("Invalid or expired invite code"))?;
+
+            let user = User::create(&username, &password, invite.role)
+                .await
+                .map_err(server_error)?;
+
+            // Someone else may have used the code while the account was created
+            if !Invite::redeem(&invite.token, &user.id)
+                .await
+                .map_err(server_error)?
+            {
+                User::delete(&user.id).await.map_err(server_error)?;
+                return Err(server_error("Invalid or expired invite code"));
+            }
+            user
+        }
+    };
+
+    AuditRecord::new(AuditEvent::Registered)
+        .user(Some(&user.id), &user.username)
+        .ip(client.ip.as_deref())
+        .details(format!("role {}", user.role))
+        .save()
+        .await;
+    Ok(())
+}
+
+#[post("/api/auth/login", cookies: Cookies, client: ClientInfo)]
+pub async fn login(username: String, password: String) -> Result<AuthResponse, ServerFnError> {
+    if !CONFIG.password_login() {
+        return Err(server_error(
+            "Password login is disabled, use single sign-on",
+        ));
+    }
+
+    let user = verify_password(
+        &username,
+        &password,
+        &client,
+        "Invalid username or password",
+    )
+    .await?;
+
+    start_session(&user, &client, &cookies).await?;
+    AuditRecord::new(AuditEvent::Login)
+        .user(Some(&user.id), &user.username)
+        .ip(client.ip.as_deref())
+        .save()
+        .await;
+
+    Ok(auth_response(user))
+}
+
+/// Replace the current password, which also lifts a forced password change.
+///
+/// All other sessions of the user are signed out.
+#[post("/api/auth/password", auth: PendingSession, cookies: Cookies, client: ClientInfo)]
+pub async fn change_password(
+    current_password: String,
+    new_password: String,
+) -> Result<AuthResponse, ServerFnError> {
+    let claims = auth.0;
+
+    let user = verify_password(
+        &claims.username,
+        &current_password,
+        &client,
+        "Current password is incorrect",
+    )
+    .await?;
+    if current_password == new_password {
+        return Err(server_error("The new password must be different"));
+    }
+
+    User::update_password(&user.id, &new_password, false)
+        .await
+        .map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::PasswordChanged)
+        .by(&claims)
+        .ip(client.ip.as_deref())
+        .save()
+        .await;
+
+    let user = User::get_by_id(&user.id).await.map_err(server_error)?;
+    start_session(&user, &client, &cookies).await?;
+
+    Ok(auth_response(user))
+}
+
+/// Issue a new access token for the current session and extend it.
+///
+/// Works with an expired access token as long as the session was not revoked.
+#[post("/api/auth/refresh", auth: RefreshSession, cookies: Cookies, client: ClientInfo)]
 pub async fn refresh_token() -> Result<(), ServerFnError> {
     let claims = auth.0;
+    // Logins through the auth proxy have no session to refresh
+    let Some(session_id) = claims.jti else {
+        return Ok(());
+    };
+
+    let active = Session::refresh(
+        &session_id,
+        &claims.sub,
+        client.user_agent.as_deref(),
+        client.ip.as_deref(),
+    )
+    .await
+    .map_err(server_error)?;
+    if !active {
+        clear_auth_cookie(&cookies);
+        return Err(ServerFnError::ServerError {
+            message: "Session expired".to_string(),
+            code: 401,
+            details: None,
+        });
+    }
 
-    let _ = models::user::User::get_by_id(&claims.sub)
+    let user = models::user::User::get_by_id(&claims.sub)
         .await
         .map_err(server_error)?;
 
-    let token = auth::create_token(claims.sub, claims.username).map_err(server_error)?;
+    let token = auth::create_token(&user, &session_id).map_err(server_error)?;
 
     cookies.add(build_auth_cookie(token));
 
     Ok(())
 }
 
-#[post("/api/auth/logout", cookies: Cookies)]
+#[post("/api/auth/logout", cookies: Cookies, client: ClientInfo)]
 pub async fn logout() -> Result<(), ServerFnError> {
-    let mut cookie = Cookie::new(AUTH_COOKIE_NAME, "");
-    cookie.set_path("/");
+    // End the session server-side too, even if the access token has expired
+    if let Some(claims) = cookies
+        .get(AUTH_COOKIE_NAME)
+        .and_then(|c| auth::verify_token_allow_expired(c.value()).ok())
+    {
+        if let Some(session_id) = &claims.jti {
+            if Session::delete(session_id, &claims.sub).await.is_ok() {
+                AuditRecord::new(AuditEvent::Logout)
+                    .by(&claims)
+                    .ip(client.ip.as_deref())
+                    .save()
+                    .await;
+            }
+        }
+    }
 
-    cookies.remove(cookie);
+    clear_auth_cookie(&cookies);
 
     Ok(())
 }
 
-#[get("/api/auth/me", auth: AuthSession)]
+/// Active sessions of the current user, the one making the request is marked.
+#[get("/api/auth/sessions", auth: AuthSession)]
+pub async fn get_sessions() -> Result<Vec<Session>, ServerFnError> {
+    let claims = auth.0;
+    if claims.jti.is_none() {
+        return Err(server_error(
+            "Sessions are not used by API tokens or proxy logins",
+        ));
+    }
+
+    let mut sessions = Session::get_all_by_user(&claims.sub)
+        .await
+        .map_err(server_error)?;
+    for session in &mut sessions {
+        session.current = claims.jti.as_deref() == Some(session.id.as_str());
+    }
+    Ok(sessions)
+}
+
+/// Sign out one of the current user's sessions.
+#[delete("/api/auth/sessions", auth: AuthSession)]
+pub async fn revoke_session(session_id: String) -> Result<(), ServerFnError> {
+    let claims = auth.0;
+    if claims.jti.is_none() {
+        return Err(server_error(
+            "Sessions are not used by API tokens or proxy logins",
+        ));
+    }
+
+    Session::delete(&session_id, &claims.sub)
+        .await
+        .map_err(server_error)?;
+    AuditRecord::new(AuditEvent::SessionRevoked)
+        .by(&claims)
+        .target(session_id)
+        .save()
+        .await;
+    Ok(())
+}
+
+/// Sign out every session of the current user, including this one.
+#[post("/api/auth/logout-everywhere", auth: AuthSession, cookies: Cookies)]
+pub async fn logout_everywhere() -> Result<(), ServerFnError> {
+    let claims = auth.0;
+    if claims.jti.is_none() {
+        return Err(server_error(
+            "Sessions are not used by API tokens or proxy logins",
+        ));
+    }
+
+    Session::delete_all_by_user(&claims.sub)
+        .await
+        .map_err(server_error)?;
+    clear_auth_cookie(&cookies);
+    AuditRecord::new(AuditEvent::LogoutEverywhere)
+        .by(&claims)
+        .save()
+        .await;
+
+    Ok(())
+}
+
+#[get("/api/auth/me", auth: PendingSession)]
 pub async fn get_current_user() -> Result<Option<AuthResponse>, ServerFnError> {
     let claims = auth.0;
 
-    Ok(Some(AuthResponse {
-        username: claims.username,
-        user_id: claims.sub,
-    }))
+    // Read the user from the database so tokens issued before a role change still
+    // show the right sections
+    let user = models::user::User::get_by_id(&claims.sub)
+        .await
+        .map_err(server_error)?;
+
+    Ok(Some(auth_response(user)))
 }
@@ -0,0 +1,146 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use shared::discovery::{DiscoveryPeriod, DiscoveryReport, DiscoveryTrack};
+use shared::playlist::PlaylistImportReport;
+
+#[cfg(feature = "server")]
+use super::folder::ensure_writable_folder;
+#[cfg(feature = "server")]
+use super::playlist::{start_playlist_job, MAX_PLAYLIST_ENTRIES};
+#[cfg(feature = "server")]
+use super::server_error;
+#[cfg(feature = "server")]
+use crate::models::{folder::Folder, user_settings::UserSettings};
+#[cfg(feature = "server")]
+use crate::services::{lastfm_client, music_importer};
+#[cfg(feature = "server")]
+use crate::{MemberSession, SearchSession};
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{info, warn};
+#[cfg(feature = "server")]
+use shared::playlist::PlaylistEntry;
+#[cfg(feature = "server")]
+use soulbeet::discovery::{discover, track_key, DiscoveryOptions};
+#[cfg(feature = "server")]
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiscoveryQuery {
+    /// Last.fm username, defaults to the one saved in the user settings
+    #[serde(default)]
+    pub lastfm_username: Option<String>,
+    #[serde(default)]
+    pub period: DiscoveryPeriod,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueueDiscoveryRequest {
+    pub tracks: Vec<DiscoveryTrack>,
+    pub target_folder: String,
+    #[serde(default)]
+    pub provider: Option<String>,
+}
+
+/// Build "missing from your library" suggestions from a Last.fm profile.
+///
+/// Suggestions come from the user's top and loved tracks and from artists similar
+/// to the ones they listen to, minus anything already in one of their folders.
+#[post("/api/discovery", auth: SearchSession)]
+pub async fn discover_missing(query: DiscoveryQuery) -> Result<DiscoveryReport, ServerFnError> {
+    let claims = auth.0;
+
+    let lastfm_username = match query.lastfm_username.filter(|u| !u.trim().is_empty()) {
+        Some(username) => username.trim().to_string(),
+        None => UserSettings::get(&claims.sub)
+            .await
+            .map_err(server_error)?
+            .lastfm_username
+            .ok_or_else(|| server_error("No Last.fm username set"))?,
+    };
+
+    let lastfm = lastfm_client().await.map_err(server_error)?;
+    let options = DiscoveryOptions {
+        period: query.period,
+        ..Default::default()
+    };
+    let suggestions = discover(&lastfm, &lastfm_username, &options)
+        .await
+        .map_err(server_error)?;
+
+    let folders = Folder::get_all_by_user(&claims.sub)
+        .await
+        .map_err(server_error)?;
+    let importer = music_importer(None).await.map_err(server_error)?;
+
+    let mut owned = HashSet::new();
+    for folder in &folders {
+        match importer
+            .library_tracks(std::path::Path::new(&folder.path))
+            .await
+        {
+            Ok(tracks) => owned.extend(tracks.iter().map(|t| track_key(&t.artist, &t.title))),
+            Err(e) => warn!("Could not read library {}: {}", folder.path, e),
+        }
+    }
+
+    let candidates = suggestions.len();
+    let missing: Vec<DiscoveryTrack> = suggestions
+        .into_iter()
+        .filter(|t| !owned.contains(&track_key(&t.artist, &t.title)))
+        .collect();
+
+    info!(
+        "Discovery for {} (Last.fm {}): {} suggestions, {} missing",
+        claims.username,
+        lastfm_username,
+        candidates,
+        missing.len()
+    );
+
+    Ok(DiscoveryReport {
+        lastfm_username,
+        candidates,
+        owned: candidates - missing.len(),
+        missing,
+    })
+}
+
+/// Send discovered tracks to the download queue.
+///
+/// Runs them through the playlist import pipeline, so progress can be polled with
+/// [`get_playlist_import`](super::get_playlist_import).
+#[post("/api/discovery/queue", auth: MemberSession)]
+pub async fn queue_discovery(
+    req: QueueDiscoveryRequest,
+) -> Result<PlaylistImportReport, ServerFnError> {
+    if req.tracks.is_empty() {
+        return Err(server_error("No tracks selected"));
+    }
+    if req.tracks.len() > MAX_PLAYLIST_ENTRIES {
+        return Err(server_error(format!(
+            "Too many tracks, the limit is {}",
+            MAX_PLAYLIST_ENTRIES
+        )));
+    }
+    ensure_writable_folder(&auth.0.sub, &req.target_folder).await?;
+
+    let entries = req
+        .tracks
+        .into_iter()
+        .map(|t| PlaylistEntry {
+            artist: t.artist,
+            title: t.title,
+            album: None,
+            mbid: t.mbid,
+        })
+        .collect();
+
+    Ok(start_playlist_job(
+        &auth.0.username,
+        Some("Last.fm discovery".to_string()),
+        req.target_folder,
+        req.provider,
+        entries,
+    )
+    .await)
+}
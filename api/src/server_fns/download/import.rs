@@ -3,14 +3,41 @@ use dioxus::logger::tracing::{info, warn};
 #[cfg(feature = "server")]
 use shared::download::{DownloadProgress, DownloadState};
 #[cfg(feature = "server")]
-use soulbeet::ImportResult;
+use shared::metadata::Album;
+#[cfg(feature = "server")]
+use soulbeet::{CoverSource, ImportResult};
 #[cfg(feature = "server")]
 use std::path::Path;
 #[cfg(feature = "server")]
 use tokio::sync::broadcast;
 
 #[cfg(feature = "server")]
-use crate::services::music_importer;
+use super::lyrics::fetch_lyrics;
+#[cfg(feature = "server")]
+use crate::config::CONFIG;
+#[cfg(feature = "server")]
+use crate::services::{cover_art_service, music_importer};
+
+/// Write cover.jpg into an album folder so the importer can pick it up and embed it.
+/// Failures are logged and never block the import.
+#[cfg(feature = "server")]
+async fn prepare_cover_art(source_dir: &Path, album: Option<&Album>) {
+    let source = album.and_then(|a| {
+        a.mbid
+            .clone()
+            .map(CoverSource::Release)
+            .or_else(|| a.cover_url.clone().map(CoverSource::Url))
+    });
+
+    match cover_art_service()
+        .write_folder_cover(source_dir, source.as_ref())
+        .await
+    {
+        Ok(Some(path)) => info!("Cover art ready at {:?}", path),
+        Ok(None) => info!("No cover art found for {:?}", source_dir),
+        Err(e) => warn!("Failed to prepare cover art for {:?}: {}", source_dir, e),
+    }
+}
 
 /// Attempt to clean up a failed download/import file
 #[cfg(feature = "server")]
@@ -26,7 +53,7 @@ async fn cleanup_failed_file(file_path: &str) {
 
 /// Attempt to clean up a directory if it's empty after cleanup
 #[cfg(feature = "server")]
-async fn cleanup_empty_parent_dir(file_path: &str) {
+pub(super) async fn cleanup_empty_parent_dir(file_path: &str) {
     let path = Path::new(file_path);
     if let Some(parent) = path.parent() {
         if parent.exists() {
@@ -53,6 +80,7 @@ pub async fn import_group(
     entries: Vec<DownloadProgress>,
     source_path: String,
     target_path: std::path::PathBuf,
+    album: Option<&Album>,
     tx: broadcast::Sender<Vec<DownloadProgress>>,
     as_album: bool,
 ) {
@@ -88,6 +116,11 @@ pub async fn import_group(
     };
 
     let source = Path::new(&source_path);
+    if as_album && CONFIG.write_cover_art() {
+        prepare_cover_art(source, album).await;
+    }
+
+    let import_started = chrono::Utc::now();
     match importer.import(&[source], &target_path, as_album).await {
         Ok(ImportResult::Success) => {
             info!("Import successful");
@@ -98,7 +131,16 @@ pub async fn import_group(
                     ..e.clone()
                 })
                 .collect();
-            let _ = tx.send(imported_entries);
+            let _ = tx.send(imported_entries.clone());
+
+            fetch_lyrics(
+                imported_entries,
+                importer.clone(),
+                &target_path,
+                import_started,
+                &tx,
+            )
+            .await;
         }
         Ok(ImportResult::Skipped) => {
             info!("Import skipped items");
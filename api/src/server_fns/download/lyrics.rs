@@ -0,0 +1,178 @@
+//! Post-import lyrics stage.
+//!
+//! Runs after a successful import into a folder with lyrics enabled: looks up the
+//! freshly imported items, fetches lyrics, writes `.lrc` sidecars and/or embeds them
+//! through the importer, and reports a status on each download item.
+
+use chrono::{DateTime, Utc};
+use dioxus::logger::tracing::{info, warn};
+use shared::download::{DownloadProgress, LyricsStatus};
+use shared::library::LibraryTrack;
+use soulbeet::{lyrics::write_sidecar, LyricsQuery, MusicImporter};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::config::CONFIG;
+use crate::models::folder::Folder;
+use crate::services::lyrics_provider;
+
+fn normalize(s: &str) -> String {
+    s.to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+/// Fetch and store lyrics for a single imported item.
+async fn process_item(
+    importer: &Arc<dyn MusicImporter>,
+    target_path: &Path,
+    item: &LibraryTrack,
+) -> LyricsStatus {
+    let query = LyricsQuery {
+        artist: item.artist.clone(),
+        title: item.title.clone(),
+        album: Some(item.album.clone()).filter(|a| !a.is_empty()),
+        duration: item.duration,
+    };
+
+    let lyrics = match lyrics_provider().lookup(&query).await {
+        Ok(Some(lyrics)) => lyrics,
+        Ok(None) => return LyricsStatus::NotFound,
+        Err(e) => {
+            warn!(
+                "Lyrics lookup failed for {} - {}: {}",
+                item.artist, item.title, e
+            );
+            return LyricsStatus::Failed(e.to_string());
+        }
+    };
+
+    if lyrics.instrumental {
+        return LyricsStatus::Instrumental;
+    }
+
+    let Some(text) = lyrics.best() else {
+        return LyricsStatus::NotFound;
+    };
+
+    let item_path = Path::new(&item.path);
+
+    if CONFIG.lyrics_sidecar() {
+        if let Err(e) = write_sidecar(item_path, &lyrics).await {
+            warn!("Failed to write lyrics sidecar for {}: {}", item.path, e);
+            return LyricsStatus::Failed(e.to_string());
+        }
+    }
+
+    if CONFIG.lyrics_embed() {
+        if let Err(e) = importer.set_lyrics(target_path, item_path, text).await {
+            warn!("Failed to embed lyrics into {}: {}", item.path, e);
+            return LyricsStatus::Failed(e.to_string());
+        }
+    }
+
+    if lyrics.synced.is_some() {
+        LyricsStatus::Synced
+    } else {
+        LyricsStatus::Plain
+    }
+}
+
+fn with_lyrics(
+    entries: &[DownloadProgress],
+    status: impl Fn(&DownloadProgress) -> LyricsStatus,
+) -> Vec<DownloadProgress> {
+    entries
+        .iter()
+        .map(|e| DownloadProgress {
+            lyrics: Some(status(e)),
+            ..e.clone()
+        })
+        .collect()
+}
+
+/// Pick the status to show for a download entry.
+///
+/// Entries are matched to imported items by title; when that fails (renamed files,
+/// one-item imports) the best status of the whole group is used.
+fn status_for_entry(
+    entry: &DownloadProgress,
+    results: &[(LibraryTrack, LyricsStatus)],
+) -> LyricsStatus {
+    let filename = normalize(&entry.item);
+    if let Some((_, status)) = results
+        .iter()
+        .find(|(item, _)| !item.title.is_empty() && filename.contains(&normalize(&item.title)))
+    {
+        return status.clone();
+    }
+
+    results
+        .iter()
+        .map(|(_, status)| status)
+        .min_by_key(|status| match status {
+            LyricsStatus::Synced => 0,
+            LyricsStatus::Plain => 1,
+            LyricsStatus::Instrumental => 2,
+            LyricsStatus::NotFound => 3,
+            LyricsStatus::Failed(_) => 4,
+            LyricsStatus::Searching => 5,
+        })
+        .cloned()
+        .unwrap_or(LyricsStatus::NotFound)
+}
+
+/// Run the lyrics stage for a group of freshly imported downloads.
+///
+/// `imported` holds the entries as last broadcast (in the `Imported` state) and
+/// `import_started` bounds which library items belong to this import.
+pub async fn fetch_lyrics(
+    imported: Vec<DownloadProgress>,
+    importer: Arc<dyn MusicImporter>,
+    target_path: &Path,
+    import_started: DateTime<Utc>,
+    tx: &broadcast::Sender<Vec<DownloadProgress>>,
+) {
+    match Folder::lyrics_enabled_for_path(&target_path.to_string_lossy()).await {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            warn!(
+                "Failed to check lyrics setting for {:?}: {}",
+                target_path, e
+            );
+            return;
+        }
+    }
+
+    let _ = tx.send(with_lyrics(&imported, |_| LyricsStatus::Searching));
+
+    let items = match importer.recent_items(target_path, import_started).await {
+        Ok(items) => items,
+        Err(e) => {
+            warn!("Failed to list imported items in {:?}: {}", target_path, e);
+            let message = e.to_string();
+            let _ = tx.send(with_lyrics(&imported, |_| {
+                LyricsStatus::Failed(message.clone())
+            }));
+            return;
+        }
+    };
+
+    if items.is_empty() {
+        info!("No imported items found in {:?} for lyrics", target_path);
+        let _ = tx.send(with_lyrics(&imported, |_| LyricsStatus::NotFound));
+        return;
+    }
+
+    let mut results = Vec::with_capacity(items.len());
+    for item in items {
+        let status = process_item(&importer, target_path, &item).await;
+        info!("Lyrics for {} - {}: {:?}", item.artist, item.title, status);
+        results.push((item, status));
+    }
+
+    let _ = tx.send(with_lyrics(&imported, |e| status_for_entry(e, &results)));
+}
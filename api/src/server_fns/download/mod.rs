@@ -2,6 +2,7 @@ use dioxus::fullstack::{WebSocketOptions, Websocket};
 use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
 use shared::download::{DownloadProgress, DownloadableItem, QueuedDownload};
+use shared::metadata::Album;
 
 #[cfg(feature = "server")]
 use dioxus::logger::tracing::{info, warn};
@@ -9,7 +10,10 @@ use dioxus::logger::tracing::{info, warn};
 use tokio::sync::broadcast;
 
 #[cfg(feature = "server")]
-use crate::{server_fns::server_error, AuthSession};
+use crate::{
+    server_fns::{folder::ensure_writable_folder, server_error},
+    AuthSession, MemberSession,
+};
 
 #[cfg(feature = "server")]
 use crate::globals::{
@@ -17,20 +21,30 @@ use crate::globals::{
     USER_CHANNELS,
 };
 #[cfg(feature = "server")]
+use crate::models::user::User;
+#[cfg(feature = "server")]
 use crate::services::download_backend;
 
 // Local modules
 #[cfg(feature = "server")]
 pub mod import;
 #[cfg(feature = "server")]
+mod lyrics;
+#[cfg(feature = "server")]
 mod monitor;
 #[cfg(feature = "server")]
 pub mod process;
 #[cfg(feature = "server")]
+pub mod quota;
+#[cfg(feature = "server")]
+pub mod schedule;
+#[cfg(feature = "server")]
+pub mod transfers;
+#[cfg(feature = "server")]
 pub mod utils;
 
 #[cfg(feature = "server")]
-use self::monitor::DownloadMonitor;
+use self::monitor::{DownloadMonitor, PendingFiles};
 
 #[cfg(feature = "server")]
 async fn do_download(
@@ -116,13 +130,48 @@ pub struct DownloadRequest {
     pub target_folder: String,
     #[serde(default)]
     pub backend: Option<String>,
+    /// Album being downloaded, used to fetch cover art before import
+    #[serde(default)]
+    pub album: Option<Album>,
+    /// Items of an album assembled from several sources, gathered in one folder
+    /// and imported together
+    #[serde(default)]
+    pub composite: bool,
 }
 
-#[post("/api/downloads/queue", auth: AuthSession)]
+#[post("/api/downloads/queue", auth: MemberSession)]
 pub async fn download(req: DownloadRequest) -> Result<Vec<QueuedDownload>, ServerFnError> {
-    let username = auth.0.username;
+    ensure_writable_folder(&auth.0.sub, &req.target_folder).await?;
+
+    queue_download(
+        &auth.0.username,
+        req.items,
+        &req.target_folder,
+        req.backend.as_deref(),
+        req.album,
+        req.composite,
+    )
+    .await
+}
+
+/// Queue items on the download backend and monitor them until they are imported
+/// into `target_folder`. Progress is broadcast on the user's download channel.
+///
+/// Fails when the user's download limits do not allow the items. Items over the
+/// concurrency limit are held back and started by the monitor later. With
+/// `composite`, the files are imported as one album once all of them are done.
+#[cfg(feature = "server")]
+pub(crate) async fn queue_download(
+    username: &str,
+    items: Vec<DownloadableItem>,
+    target_folder: &str,
+    backend: Option<&str>,
+    album: Option<Album>,
+    composite: bool,
+) -> Result<Vec<QueuedDownload>, ServerFnError> {
+    let username = username.to_string();
 
-    let target_path_buf = std::path::Path::new(&req.target_folder).to_path_buf();
+    let target_path_buf = std::path::Path::new(target_folder).to_path_buf();
     if let Err(e) = tokio::fs::create_dir_all(&target_path_buf).await {
         return Err(server_error(format!(
             "Failed to create target directory: {}",
@@ -130,10 +179,71 @@ pub async fn download(req: DownloadRequest) -> Result<Vec<QueuedDownload>, Serve
         )));
     }
 
-    let res = do_download(req.items, req.backend.as_deref()).await?;
+    let user = User::get_by_username(&username)
+        .await
+        .map_err(server_error)?;
+    let limits = quota::reserve(&user, &items, &target_path_buf)
+        .await
+        .map_err(server_error)?;
+
+    let ids: Vec<String> = items.iter().map(|i| i.id.clone()).collect();
+    let start_now = quota::start(
+        &username,
+        &ids,
+        schedule::max_concurrent(limits.max_concurrent),
+    );
+    let mut items = items;
+    let waiting = items.split_off(start_now);
+
+    let mut res = if items.is_empty() {
+        Vec::new()
+    } else {
+        match do_download(items.clone(), backend).await {
+            Ok(res) => res,
+            Err(e) => {
+                for id in &ids {
+                    quota::release(&username, id);
+                }
+                return Err(e);
+            }
+        }
+    };
+    for item in &items {
+        if !res
+            .iter()
+            .any(|d| d.item == item.id && d.source == item.source && d.error.is_none())
+        {
+            quota::release(&username, &item.id);
+        }
+    }
 
-    let (failed, successful): (Vec<_>, Vec<_>) =
+    let (failed, mut successful): (Vec<_>, Vec<_>) =
         res.iter().cloned().partition(|d| d.error.is_some());
+    // With their items, to queue them again after a pause of the download schedule
+    let started: Vec<(QueuedDownload, DownloadableItem)> = successful
+        .iter()
+        .filter_map(|d| {
+            items
+                .iter()
+                .find(|i| i.id == d.item && i.source == d.source)
+                .map(|i| (d.clone(), i.clone()))
+        })
+        .collect();
+
+    // Waiting items are accepted too, the monitor starts them when slots free up
+    let waiting_queued: Vec<QueuedDownload> = waiting
+        .iter()
+        .map(|i| {
+            QueuedDownload::success(
+                i.id.clone(),
+                i.source.clone(),
+                i.id.clone(),
+                i.size.unwrap_or(0),
+            )
+        })
+        .collect();
+    successful.extend(waiting_queued.iter().cloned());
+    res.extend(waiting_queued);
 
     let (tx, _) = get_or_create_user_channel(&username).await;
 
@@ -145,10 +255,9 @@ pub async fn download(req: DownloadRequest) -> Result<Vec<QueuedDownload>, Serve
         let _ = tx.send(failed_entries);
     }
 
-    let download_filenames: Vec<String> = successful.iter().map(|d| d.item.clone()).collect();
     let target_path = target_path_buf;
 
-    if download_filenames.is_empty() {
+    if successful.is_empty() {
         return Ok(res);
     }
 
@@ -159,20 +268,32 @@ pub async fn download(req: DownloadRequest) -> Result<Vec<QueuedDownload>, Serve
         .collect();
     let _ = tx.send(queued_entries);
 
-    info!("Started monitoring downloads: {:?}", download_filenames);
+    info!(
+        "Started monitoring {} downloads, {} waiting for a download slot",
+        started.len(),
+        waiting.len()
+    );
 
     // Register this task for cleanup tracking
     let task_username = username.clone();
     let task_cancellation = register_user_task(&username).await;
+    let pending = PendingFiles {
+        items: waiting,
+        backend: backend.map(str::to_string),
+        max_concurrent: limits.max_concurrent,
+    };
 
     // Spawn the monitoring task
     tokio::spawn(async move {
         let mut monitor = DownloadMonitor::new(
-            download_filenames,
+            started,
             target_path,
+            album,
+            composite,
             tx,
             task_cancellation,
-            task_username.clone(),
+            user,
+            pending,
         );
         monitor.run().await;
         unregister_user_task(&task_username).await;
@@ -1,23 +1,30 @@
 //! Download monitoring logic for tracking slskd download progress.
 //!
-//! This module encapsulates the polling loop that monitors downloads from slskd,
-//! handles per-track timeouts, and triggers processing when downloads complete.
+//! This module encapsulates the loop that follows downloads through the shared
+//! transfer polls, handles per-track timeouts, and triggers processing when
+//! downloads complete.
+//! Files held back by the user's concurrency limit are started from here as
+//! earlier transfers end. While the download schedule pauses downloads, files still
+//! queued on the backend are taken off its queue and queued again afterwards.
 
 use dioxus::logger::tracing::{debug, info, warn};
-use shared::download::{DownloadProgress, DownloadState};
-use std::collections::HashMap;
+use shared::download::{DownloadProgress, DownloadState, DownloadableItem, QueuedDownload};
+use shared::metadata::Album;
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::time::Duration;
 use tokio::sync::broadcast;
 use tokio::time::Instant;
 use tokio_util::sync::CancellationToken;
 
-use super::process::process_downloads;
+use super::process::{process_composite_downloads, process_downloads};
+use super::quota;
+use super::schedule;
+use super::transfers::{self, TransferChange, TransferEvent, TransferUpdate, POLL_INTERVAL_SECS};
 use crate::config::CONFIG;
+use crate::models::user::User;
 use crate::services::download_backend;
-
-/// Poll interval for checking download status (2 seconds).
-const POLL_INTERVAL_SECS: u64 = 2;
+use crate::services::downloaders::{self, PRIMARY_SLSKD_INSTANCE};
 
 /// Grace period for downloads to appear in slskd (30 seconds = 15 * 2s intervals).
 const MAX_CONSECUTIVE_EMPTY: usize = 15;
@@ -26,126 +33,498 @@ const MAX_CONSECUTIVE_EMPTY: usize = 15;
 const PER_TRACK_TIMEOUT: Duration = Duration::from_secs(60 * 60);
 
 /// State tracking for individual track downloads.
+#[derive(Default)]
 struct TrackState {
     /// When the track was first seen in slskd's download list.
     first_seen: Option<Instant>,
+    /// Time seen before the download schedule paused the track, the pause itself
+    /// does not count toward the timeout.
+    waited: Duration,
     /// Whether this track has been processed (imported or marked as failed).
     processed: bool,
+    /// Whether the transfer ended and its download slot was given back.
+    released: bool,
+}
+
+/// A file followed by the monitor.
+struct TrackedFile {
+    /// slskd transfer id, once known.
+    transfer_id: Option<String>,
+    /// Peer the file is downloaded from.
+    source: String,
+    /// Remote filename, also the key of the file's download slot.
+    filename: String,
+    /// Item the file was queued from, queued again after a pause.
+    item: DownloadableItem,
+    /// Taken off the backend's queue by the download schedule.
+    paused: bool,
+    state: TrackState,
+}
+
+impl TrackedFile {
+    fn new(queued: &QueuedDownload, item: DownloadableItem) -> Self {
+        Self {
+            transfer_id: transfer_id(queued),
+            source: queued.source.clone(),
+            filename: queued.item.clone(),
+            item,
+            paused: false,
+            state: TrackState::default(),
+        }
+    }
+
+    /// Whether the transfer belongs to this file. Transfers are matched by id only,
+    /// by peer and filename while the id is unknown.
+    fn matches(&self, transfer: &DownloadProgress) -> bool {
+        match &self.transfer_id {
+            Some(id) => *id == transfer.id,
+            None => {
+                transfer.source == self.source && filenames_match(&transfer.item, &self.filename)
+            }
+        }
+    }
+}
+
+/// Files waiting for a free download slot of the user.
+pub struct PendingFiles {
+    pub items: Vec<DownloadableItem>,
+    /// Backend the files are downloaded from
+    pub backend: Option<String>,
+    pub max_concurrent: Option<i64>,
 }
 
 /// Monitors download progress from slskd and triggers processing on completion.
 pub struct DownloadMonitor {
-    /// Filenames being monitored.
-    filenames: Vec<String>,
+    /// Files being monitored.
+    files: Vec<TrackedFile>,
     /// Target directory for imports.
     target_path: PathBuf,
+    /// Album metadata for the batch, if known (used for cover art).
+    album: Option<Album>,
+    /// Whether the files make up one album assembled from several sources.
+    composite: bool,
     /// Broadcast sender for UI updates.
     tx: broadcast::Sender<Vec<DownloadProgress>>,
-    /// Per-track state tracking.
-    track_states: HashMap<String, TrackState>,
     /// Whether album mode is enabled.
     album_mode: bool,
     /// Cancellation token for graceful shutdown.
     cancellation_token: CancellationToken,
-    /// Username for logging.
-    username: String,
+    /// User the downloads belong to, for logging and download limits.
+    user: User,
+    /// Files not handed to the backend yet.
+    pending: PendingFiles,
+    /// Whether the download schedule paused downloads at the last poll.
+    schedule_paused: bool,
 }
 
 impl DownloadMonitor {
     /// Create a new download monitor.
     pub fn new(
-        filenames: Vec<String>,
+        downloads: Vec<(QueuedDownload, DownloadableItem)>,
         target_path: PathBuf,
+        album: Option<Album>,
+        composite: bool,
         tx: broadcast::Sender<Vec<DownloadProgress>>,
         cancellation_token: CancellationToken,
-        username: String,
+        user: User,
+        pending: PendingFiles,
     ) -> Self {
-        let track_states = filenames
-            .iter()
-            .map(|f| {
-                (
-                    f.clone(),
-                    TrackState {
-                        first_seen: None,
-                        processed: false,
-                    },
-                )
-            })
-            .collect();
-
         Self {
-            filenames,
+            files: downloads
+                .into_iter()
+                .map(|(queued, item)| TrackedFile::new(&queued, item))
+                .collect(),
             target_path,
+            album,
+            composite,
             tx,
-            track_states,
             album_mode: CONFIG.is_album_mode(),
             cancellation_token,
-            username,
+            user,
+            pending,
+            schedule_paused: false,
         }
     }
 
     /// Run the monitoring loop until all downloads complete or timeout.
+    ///
+    /// Follows the transfers of the shared poll task, see [`transfers`].
     pub async fn run(&mut self) {
-        let mut interval = tokio::time::interval(Duration::from_secs(POLL_INTERVAL_SECS));
+        let mut updates = transfers::subscribe(self.pending.backend.as_deref()).await;
         let mut consecutive_empty = 0;
         let mut poll_count = 0;
 
-        // Poll immediately on first iteration
-        interval.tick().await;
-
         loop {
-            if self.cancellation_token.is_cancelled() {
-                info!(
-                    "Download monitoring cancelled for batch {:?}",
-                    self.filenames
+            let snapshot = tokio::select! {
+                _ = self.cancellation_token.cancelled() => {
+                    info!(
+                        "Download monitoring cancelled for batch {:?}",
+                        self.filenames()
+                    );
+                    break;
+                }
+                update = updates.recv() => match update {
+                    Ok(TransferUpdate::Snapshot(snapshot)) => snapshot,
+                    Ok(TransferUpdate::Unavailable(e)) => {
+                        warn!("No download backend available for monitoring: {}", e);
+                        break;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        // The next snapshot lists every transfer again
+                        debug!("Download monitor skipped {} transfer polls", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            };
+
+            // Missing transfers of an unreachable instance are not gone
+            if let Some(instance) = self.unavailable_instance(&snapshot.unavailable) {
+                debug!(
+                    "Transfers of instance '{}' could not be listed, skipping this poll",
+                    instance
                 );
-                break;
+                continue;
             }
 
             poll_count += 1;
 
-            let backend = match download_backend(None).await {
-                Ok(b) => b,
-                Err(e) => {
-                    warn!("No download backend available for monitoring: {}", e);
-                    break;
+            self.apply_schedule(&snapshot.transfers).await;
+            self.start_pending().await;
+            self.log_transitions(&snapshot.events);
+
+            let should_break = self
+                .process_poll_result(&snapshot.transfers, &mut consecutive_empty, poll_count)
+                .await;
+            if should_break {
+                break;
+            }
+        }
+
+        self.release_remaining();
+
+        info!(
+            "Download monitoring task completed for user: {}",
+            self.user.username
+        );
+    }
+
+    /// Hand waiting files to the backend as the user's download slots free up.
+    async fn start_pending(&mut self) {
+        if self.pending.items.is_empty() {
+            return;
+        }
+
+        let ids: Vec<String> = self.pending.items.iter().map(|i| i.id.clone()).collect();
+        let count = quota::start(&self.user.username, &ids, self.max_concurrent());
+        if count == 0 {
+            return;
+        }
+        let items: Vec<DownloadableItem> = self.pending.items.drain(..count).collect();
+        let queued = self.queue_items(&items).await;
+
+        let mut updates = Vec::new();
+        for item in &items {
+            match queued
+                .iter()
+                .find(|q| q.item == item.id && q.source == item.source)
+            {
+                Some(q) if q.error.is_none() => {
+                    self.files.push(TrackedFile::new(q, item.clone()));
+                    updates.push(DownloadProgress::queued(
+                        q.id.clone(),
+                        q.source.clone(),
+                        q.item.clone(),
+                        q.size,
+                    ));
+                }
+                other => {
+                    quota::release(&self.user.username, &item.id);
+                    let error = other
+                        .and_then(|q| q.error.clone())
+                        .unwrap_or_else(|| "Not accepted by the download backend".to_string());
+                    updates.push(DownloadProgress::failed(
+                        item.id.clone(),
+                        item.source.clone(),
+                        item.id.clone(),
+                        error,
+                    ));
                 }
+            }
+        }
+
+        info!(
+            "Started {} waiting downloads for user {}, {} still waiting",
+            count,
+            self.user.username,
+            self.pending.items.len()
+        );
+        let _ = self.tx.send(updates);
+    }
+
+    /// Hand items to the backend, the ones it could not take come back failed.
+    async fn queue_items(&self, items: &[DownloadableItem]) -> Vec<QueuedDownload> {
+        let result = match download_backend(self.pending.backend.as_deref()).await {
+            Ok(backend) => backend
+                .download(items.to_vec())
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        };
+        result.unwrap_or_else(|e| {
+            warn!("Failed to queue downloads: {}", e);
+            items
+                .iter()
+                .map(|i| {
+                    QueuedDownload::failed(i.id.clone(), i.source.clone(), i.id.clone(), e.clone())
+                })
+                .collect()
+        })
+    }
+
+    /// Concurrency limit of the user under the download schedule.
+    fn max_concurrent(&self) -> Option<i64> {
+        schedule::max_concurrent(self.pending.max_concurrent)
+    }
+
+    /// Follow the download schedule: while it pauses downloads, take the files
+    /// still queued on the backend off its queue, and queue them again afterwards.
+    /// Transfers already running are left to finish.
+    async fn apply_schedule(&mut self, downloads: &[DownloadProgress]) {
+        let paused = schedule::is_paused();
+        if paused != self.schedule_paused {
+            self.schedule_paused = paused;
+            // Waiting files show whether they wait for a slot or for the schedule
+            let state = if paused {
+                DownloadState::Paused
+            } else {
+                DownloadState::Queued
             };
-            match backend.get_downloads().await {
-                Ok(downloads) => {
-                    let should_break = self
-                        .process_poll_result(downloads, &mut consecutive_empty, poll_count)
-                        .await;
-                    if should_break {
-                        break;
-                    }
+            let updates: Vec<DownloadProgress> = self
+                .pending
+                .items
+                .iter()
+                .map(|i| {
+                    DownloadProgress::queued(
+                        i.id.clone(),
+                        i.source.clone(),
+                        i.id.clone(),
+                        i.size.unwrap_or(0),
+                    )
+                    .with_state(state.clone())
+                })
+                .collect();
+            if !updates.is_empty() {
+                let _ = self.tx.send(updates);
+            }
+        }
+
+        if paused {
+            self.pause_queued(downloads).await;
+        } else {
+            self.resume_paused().await;
+        }
+    }
+
+    /// Take the files that are still queued off the backend's queue.
+    async fn pause_queued(&mut self, downloads: &[DownloadProgress]) {
+        let queued: Vec<(usize, DownloadProgress)> = self
+            .files
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.transfer_id.is_some() && !f.paused && !f.state.processed)
+            .filter_map(|(index, file)| {
+                downloads
+                    .iter()
+                    .find(|d| file.matches(d) && d.state == DownloadState::Queued)
+                    .map(|d| (index, d.clone()))
+            })
+            .collect();
+        if queued.is_empty() {
+            return;
+        }
+
+        let backend = match download_backend(self.pending.backend.as_deref()).await {
+            Ok(backend) => backend,
+            Err(e) => {
+                warn!("Failed to pause queued downloads: {}", e);
+                return;
+            }
+        };
+
+        let mut updates = Vec::new();
+        for (index, transfer) in queued {
+            if let Err(e) = backend
+                .cancel_transfer(&transfer.source, &transfer.id)
+                .await
+            {
+                warn!("Failed to pause download {}: {}", transfer.item, e);
+                continue;
+            }
+            let file = &mut self.files[index];
+            file.paused = true;
+            file.transfer_id = None;
+            if let Some(first_seen) = file.state.first_seen.take() {
+                file.state.waited += first_seen.elapsed();
+            }
+            quota::unstart(&self.user.username, &file.filename);
+            updates.push(transfer.with_state(DownloadState::Paused));
+        }
+
+        if !updates.is_empty() {
+            info!(
+                "Download schedule paused {} queued downloads of user {}",
+                updates.len(),
+                self.user.username
+            );
+            let _ = self.tx.send(updates);
+        }
+    }
+
+    /// Queue the paused files again, as the user's download slots allow.
+    async fn resume_paused(&mut self) {
+        let paused: Vec<usize> = (0..self.files.len())
+            .filter(|index| self.files[*index].paused)
+            .collect();
+        if paused.is_empty() {
+            return;
+        }
+
+        let filenames: Vec<String> = paused
+            .iter()
+            .map(|index| self.files[*index].filename.clone())
+            .collect();
+        let count = quota::start(&self.user.username, &filenames, self.max_concurrent());
+        if count == 0 {
+            return;
+        }
+        let resumed = &paused[..count];
+        let items: Vec<DownloadableItem> = resumed
+            .iter()
+            .map(|index| self.files[*index].item.clone())
+            .collect();
+        let queued = self.queue_items(&items).await;
+
+        let mut updates = Vec::new();
+        for &index in resumed {
+            let file = &mut self.files[index];
+            file.paused = false;
+            match queued
+                .iter()
+                .find(|q| q.item == file.item.id && q.source == file.item.source)
+            {
+                Some(q) if q.error.is_none() => {
+                    file.transfer_id = transfer_id(q);
+                    updates.push(DownloadProgress::queued(
+                        q.id.clone(),
+                        q.source.clone(),
+                        q.item.clone(),
+                        q.size,
+                    ));
                 }
-                Err(e) => {
-                    warn!("Error fetching download status from slskd: {}", e);
-                    // Don't break on transient errors - slskd might recover
+                other => {
+                    quota::release(&self.user.username, &file.filename);
+                    file.state.released = true;
+                    file.state.processed = true;
+                    let error = other
+                        .and_then(|q| q.error.clone())
+                        .unwrap_or_else(|| "Not accepted by the download backend".to_string());
+                    updates.push(DownloadProgress::failed(
+                        file.item.id.clone(),
+                        file.item.source.clone(),
+                        file.item.id.clone(),
+                        error,
+                    ));
                 }
             }
-
-            interval.tick().await;
         }
 
         info!(
-            "Download monitoring task completed for user: {}",
-            self.username
+            "Resumed {} paused downloads of user {}",
+            count, self.user.username
         );
+        let _ = self.tx.send(updates);
+    }
+
+    /// Give back the download slots of files the monitor stops tracking.
+    fn release_remaining(&mut self) {
+        for file in self.files.iter_mut() {
+            if !file.state.released {
+                quota::release(&self.user.username, &file.filename);
+                file.state.released = true;
+            }
+        }
+        for item in self.pending.items.drain(..) {
+            quota::release(&self.user.username, &item.id);
+        }
+    }
+
+    /// Count a transfer that reached a terminal state or timed out.
+    async fn release_track(&mut self, index: usize, download: &DownloadProgress) {
+        let file = &mut self.files[index];
+        if file.state.released {
+            return;
+        }
+        file.state.released = true;
+
+        let bytes = is_completed(&download.state).then_some(download.size);
+        quota::finish(&self.user.username, &self.user.id, &file.filename, bytes).await;
+    }
+
+    /// The backend or an instance the monitored files are downloaded from that is in
+    /// `unavailable`.
+    fn unavailable_instance<'a>(&self, unavailable: &'a [String]) -> Option<&'a str> {
+        let backend = self
+            .pending
+            .backend
+            .as_deref()
+            .unwrap_or(downloaders::SLSKD);
+        unavailable.iter().map(String::as_str).find(|name| {
+            *name == backend
+                || self.files.iter().any(|f| {
+                    // Items without an instance go to the first one
+                    f.item.instance.as_deref().unwrap_or(PRIMARY_SLSKD_INSTANCE) == *name
+                })
+        })
+    }
+
+    /// Remote filenames of the monitored files, for logging.
+    fn filenames(&self) -> Vec<&str> {
+        self.files.iter().map(|f| f.filename.as_str()).collect()
+    }
+
+    /// Log the state changes of the monitored transfers.
+    fn log_transitions(&self, events: &[TransferEvent]) {
+        for event in events {
+            let item = &event.transfer.item;
+            if !self.files.iter().any(|f| f.matches(&event.transfer)) {
+                continue;
+            }
+            match &event.change {
+                TransferChange::Added => {
+                    debug!("Transfer appeared ({:?}): {}", event.transfer.state, item)
+                }
+                TransferChange::StateChanged(from) => info!(
+                    "Transfer {:?} -> {:?}: {}",
+                    from, event.transfer.state, item
+                ),
+                TransferChange::Progressed => {}
+                TransferChange::Removed => info!("Transfer removed from slskd: {}", item),
+            }
+        }
     }
 
     /// Process a poll result from slskd.
     /// Returns true if monitoring should stop.
     async fn process_poll_result(
         &mut self,
-        downloads: Vec<DownloadProgress>,
+        downloads: &[DownloadProgress],
         consecutive_empty: &mut usize,
         poll_count: u32,
     ) -> bool {
         // Debug logging for first few polls
         if poll_count <= 3 {
-            debug!("Looking for filenames: {:?}", self.filenames);
+            debug!("Looking for filenames: {:?}", self.filenames());
             let slskd_filenames: Vec<_> = downloads.iter().map(|f| &f.item).collect();
             debug!(
                 "slskd returned {} downloads: {:?}",
@@ -154,25 +533,30 @@ impl DownloadMonitor {
             );
         }
 
-        // Match downloads using fuzzy filename matching
-        let batch_status = self.find_matching_downloads(&downloads);
+        let batch_status = self.match_transfers(downloads);
 
-        if poll_count <= 3 || batch_status.len() != self.filenames.len() {
+        if poll_count <= 3 || batch_status.len() != self.files.len() {
             info!(
                 "Matched {} of {} downloads from slskd (poll {})",
                 batch_status.len(),
-                self.filenames.len(),
+                self.files.len(),
                 poll_count
             );
-            self.log_unmatched_files(&downloads, &batch_status);
+            self.log_unmatched_files(&batch_status);
         }
 
         // Send status update to UI
         if !batch_status.is_empty() {
-            self.send_status_update(&batch_status);
+            let updates: Vec<_> = batch_status.iter().map(|(_, d)| d.clone()).collect();
+            self.send_status_update(&updates);
             *consecutive_empty = 0;
         }
 
+        // Nothing on the backend, all files are waiting for a download slot or the schedule
+        if batch_status.is_empty() && self.files.iter().all(|f| f.paused) {
+            return self.files.is_empty() && self.pending.items.is_empty();
+        }
+
         // Handle grace period for downloads to appear
         if batch_status.is_empty() {
             *consecutive_empty += 1;
@@ -180,8 +564,8 @@ impl DownloadMonitor {
                 warn!(
                     "No active downloads found for batch after {} attempts ({}s), assuming completed or lost: {:?}",
                     MAX_CONSECUTIVE_EMPTY,
-                    MAX_CONSECUTIVE_EMPTY * 2,
-                    self.filenames
+                    MAX_CONSECUTIVE_EMPTY as u64 * POLL_INTERVAL_SECS,
+                    self.filenames()
                 );
                 return true;
             }
@@ -190,8 +574,8 @@ impl DownloadMonitor {
                     "Waiting for downloads to appear in slskd, attempt {}/{} ({}/{}s)",
                     *consecutive_empty,
                     MAX_CONSECUTIVE_EMPTY,
-                    *consecutive_empty * 2,
-                    MAX_CONSECUTIVE_EMPTY * 2
+                    *consecutive_empty as u64 * POLL_INTERVAL_SECS,
+                    MAX_CONSECUTIVE_EMPTY as u64 * POLL_INTERVAL_SECS
                 );
             }
             return false;
@@ -204,29 +588,51 @@ impl DownloadMonitor {
         self.check_completion(&batch_status).await
     }
 
-    /// Find downloads matching our tracked filenames.
-    fn find_matching_downloads(&self, downloads: &[DownloadProgress]) -> Vec<DownloadProgress> {
+    /// Find the transfer of every monitored file, paired with the file's index.
+    ///
+    /// A file queued without a transfer id takes the id of the first transfer of the
+    /// same peer and filename not claimed by another file, and is matched by it from
+    /// then on.
+    fn match_transfers(
+        &mut self,
+        downloads: &[DownloadProgress],
+    ) -> Vec<(usize, DownloadProgress)> {
+        let mut claimed: HashSet<String> = self
+            .files
+            .iter()
+            .filter_map(|f| f.transfer_id.clone())
+            .collect();
+
         let mut matched = Vec::new();
-        for download in downloads {
-            for target in &self.filenames {
-                if filenames_match(&download.item, target) {
-                    matched.push(download.clone());
-                    break;
-                }
+        for (index, file) in self.files.iter_mut().enumerate() {
+            // Its transfer was removed, it might still be listed
+            if file.paused {
+                continue;
+            }
+            let known = file.transfer_id.is_some();
+            let Some(transfer) = downloads
+                .iter()
+                .find(|d| file.matches(d) && (known || !claimed.contains(&d.id)))
+            else {
+                continue;
+            };
+            if !known {
+                debug!("Transfer {} is {}", transfer.id, file.filename);
+                claimed.insert(transfer.id.clone());
+                file.transfer_id = Some(transfer.id.clone());
             }
+            matched.push((index, transfer.clone()));
         }
         matched
     }
 
     /// Log any unmatched files for debugging.
-    fn log_unmatched_files(&self, downloads: &[DownloadProgress], batch_status: &[DownloadProgress]) {
-        if batch_status.len() < self.filenames.len() {
-            for target in &self.filenames {
-                let found = downloads
-                    .iter()
-                    .any(|d| filenames_match(&d.item, target));
-                if !found {
-                    debug!("Unmatched file: {}", target);
+    fn log_unmatched_files(&self, batch_status: &[(usize, DownloadProgress)]) {
+        for (index, file) in self.files.iter().enumerate() {
+            if !file.paused && !batch_status.iter().any(|(i, _)| *i == index) {
+                match &file.transfer_id {
+                    Some(id) => debug!("Unmatched file: {} (transfer {})", file.filename, id),
+                    None => debug!("Unmatched file: {}", file.filename),
                 }
             }
         }
@@ -244,80 +650,86 @@ impl DownloadMonitor {
     }
 
     /// Process each track, handling timeouts and completions.
-    async fn process_tracks(&mut self, batch_status: &[DownloadProgress]) {
-        for download in batch_status {
-            let matching_key = self
-                .track_states
-                .keys()
-                .find(|k| filenames_match(k, &download.item))
-                .cloned();
-
-            if let Some(key) = matching_key {
-                // Record first seen time
-                if self.track_states[&key].first_seen.is_none() {
-                    self.track_states.get_mut(&key).unwrap().first_seen = Some(Instant::now());
-                }
+    async fn process_tracks(&mut self, batch_status: &[(usize, DownloadProgress)]) {
+        for (index, download) in batch_status {
+            let index = *index;
 
-                // Skip already processed tracks
-                if self.track_states[&key].processed {
-                    continue;
-                }
+            // Record first seen time
+            if self.files[index].state.first_seen.is_none() {
+                self.files[index].state.first_seen = Some(Instant::now());
+            }
 
-                // Check per-track timeout
-                if let Some(first_seen) = self.track_states[&key].first_seen {
-                    if first_seen.elapsed() > PER_TRACK_TIMEOUT && !is_terminal_state(&download.state) {
-                        warn!(
-                            "Track timed out after {} minutes: {}",
-                            first_seen.elapsed().as_secs() / 60,
-                            download.item
-                        );
-                        let timeout_entry = DownloadProgress {
-                            state: DownloadState::Failed("Download timed out after 1 hour".into()),
-                            error: Some("Per-track timeout".into()),
-                            ..download.clone()
-                        };
-                        let _ = self.tx.send(vec![timeout_entry]);
-                        self.track_states.get_mut(&key).unwrap().processed = true;
-                        continue;
-                    }
-                }
+            if is_terminal_state(&download.state) {
+                self.release_track(index, download).await;
+            }
 
-                // Singleton mode: process completed tracks immediately
-                if !self.album_mode && is_completed(&download.state) {
-                    info!(
-                        "Track completed, processing immediately (singleton mode): {}",
+            // Skip already processed tracks
+            if self.files[index].state.processed {
+                continue;
+            }
+
+            // Check per-track timeout, time paused by the schedule does not count
+            if let Some(first_seen) = self.files[index].state.first_seen {
+                let elapsed = self.files[index].state.waited + first_seen.elapsed();
+                if elapsed > PER_TRACK_TIMEOUT && !is_terminal_state(&download.state) {
+                    warn!(
+                        "Track timed out after {} minutes: {}",
+                        elapsed.as_secs() / 60,
                         download.item
                     );
-                    self.track_states.get_mut(&key).unwrap().processed = true;
-                    let dl = download.clone();
-                    let tp = self.target_path.clone();
-                    let tx_clone = self.tx.clone();
-                    tokio::spawn(async move {
-                        process_downloads(vec![dl], tp, tx_clone).await;
-                    });
+                    let timeout_entry = DownloadProgress {
+                        state: DownloadState::Failed("Download timed out after 1 hour".into()),
+                        error: Some("Per-track timeout".into()),
+                        ..download.clone()
+                    };
+                    let _ = self.tx.send(vec![timeout_entry.clone()]);
+                    self.release_track(index, &timeout_entry).await;
+                    self.files[index].state.processed = true;
+                    continue;
                 }
+            }
 
-                // Mark terminal states (errored/cancelled/aborted) as processed
-                if is_terminal_state(&download.state) && !is_completed(&download.state) {
-                    self.track_states.get_mut(&key).unwrap().processed = true;
-                }
+            // Singleton mode: process completed tracks immediately
+            if !self.album_mode && !self.composite && is_completed(&download.state) {
+                info!(
+                    "Track completed, processing immediately (singleton mode): {}",
+                    download.item
+                );
+                self.files[index].state.processed = true;
+                let dl = download.clone();
+                let tp = self.target_path.clone();
+                let album = self.album.clone();
+                let tx_clone = self.tx.clone();
+                tokio::spawn(async move {
+                    process_downloads(vec![dl], tp, album, tx_clone).await;
+                });
+            }
+
+            // Mark terminal states (errored/cancelled/aborted) as processed
+            if is_terminal_state(&download.state) && !is_completed(&download.state) {
+                self.files[index].state.processed = true;
             }
         }
     }
 
     /// Check if all downloads are complete. Returns true if monitoring should stop.
-    async fn check_completion(&mut self, batch_status: &[DownloadProgress]) -> bool {
-        let all_processed = self.track_states.values().all(|s| s.processed);
-        let all_terminal = self.filenames.iter().all(|fname| {
+    async fn check_completion(&mut self, batch_status: &[(usize, DownloadProgress)]) -> bool {
+        if !self.pending.items.is_empty() {
+            return false;
+        }
+
+        let all_processed = self.files.iter().all(|f| f.state.processed);
+        // Files that could not be queued again after a pause have no transfer
+        let all_terminal = (0..self.files.len()).all(|index| {
             batch_status
                 .iter()
-                .find(|d| filenames_match(&d.item, fname))
-                .map(|d| is_terminal_state(&d.state))
-                .unwrap_or(false)
+                .find(|(i, _)| *i == index)
+                .map(|(_, d)| is_terminal_state(&d.state))
+                .unwrap_or(self.files[index].state.processed)
         });
 
         if all_processed || all_terminal {
-            if self.album_mode {
+            if self.album_mode || self.composite {
                 self.process_album_mode(batch_status).await;
             }
             info!("All downloads finished");
@@ -328,20 +740,11 @@ impl DownloadMonitor {
     }
 
     /// Process all successful downloads together in album mode.
-    async fn process_album_mode(&mut self, batch_status: &[DownloadProgress]) {
+    async fn process_album_mode(&mut self, batch_status: &[(usize, DownloadProgress)]) {
         let successful: Vec<_> = batch_status
             .iter()
-            .filter(|d| {
-                is_completed(&d.state)
-                    && self
-                        .track_states
-                        .keys()
-                        .find(|k| filenames_match(k, &d.item))
-                        .and_then(|k| self.track_states.get(k))
-                        .map(|s| !s.processed)
-                        .unwrap_or(false)
-            })
-            .cloned()
+            .filter(|(index, d)| is_completed(&d.state) && !self.files[*index].state.processed)
+            .map(|(_, d)| d.clone())
             .collect();
 
         if !successful.is_empty() {
@@ -349,13 +752,34 @@ impl DownloadMonitor {
                 "Album mode: Processing {} successful downloads together",
                 successful.len()
             );
-            process_downloads(successful, self.target_path.clone(), self.tx.clone()).await;
+            if self.composite {
+                process_composite_downloads(
+                    successful,
+                    self.target_path.clone(),
+                    self.album.clone(),
+                    self.tx.clone(),
+                )
+                .await;
+            } else {
+                process_downloads(
+                    successful,
+                    self.target_path.clone(),
+                    self.album.clone(),
+                    self.tx.clone(),
+                )
+                .await;
+            }
         } else {
             info!("Album mode: No successful downloads to process");
         }
     }
 }
 
+/// Transfer id of a queued file. Without one the backend uses the filename as the id.
+fn transfer_id(queued: &QueuedDownload) -> Option<String> {
+    (queued.id != queued.item).then(|| queued.id.clone())
+}
+
 /// Check if a download state indicates a terminal state (complete or failed).
 fn is_terminal_state(state: &DownloadState) -> bool {
     matches!(
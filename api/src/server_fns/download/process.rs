@@ -1,23 +1,30 @@
 #[cfg(feature = "server")]
-use dioxus::logger::tracing::info;
+use dioxus::logger::tracing::{info, warn};
 #[cfg(feature = "server")]
 use shared::download::{DownloadProgress, DownloadState};
 #[cfg(feature = "server")]
+use shared::metadata::Album;
+#[cfg(feature = "server")]
 use std::collections::HashMap;
 #[cfg(feature = "server")]
 use tokio::sync::broadcast;
 
 #[cfg(feature = "server")]
-use super::import::import_group;
+use super::import::{cleanup_empty_parent_dir, import_group};
 #[cfg(feature = "server")]
-use super::utils::resolve_download_path;
+use super::utils::locate_download;
 #[cfg(feature = "server")]
 use crate::config::CONFIG;
 
+/// Folder inside the download path where composite albums are gathered for import.
+#[cfg(feature = "server")]
+const STAGING_DIR: &str = ".soulbeet-staging";
+
 #[cfg(feature = "server")]
 pub async fn process_downloads(
     successful_downloads: Vec<DownloadProgress>,
     target_path: std::path::PathBuf,
+    album: Option<Album>,
     tx: broadcast::Sender<Vec<DownloadProgress>>,
 ) {
     if !successful_downloads.is_empty() {
@@ -36,7 +43,7 @@ pub async fn process_downloads(
             let mut singletons: Vec<DownloadProgress> = Vec::new();
 
             for download in successful_downloads {
-                if let Some(path) = resolve_download_path(&download.item, &download_path_buf) {
+                if let Some(path) = locate_download(&download, &download_path_buf) {
                     let p = std::path::Path::new(&path);
                     // group by parent directory (album or release)
                     if let Some(parent) = p.parent() {
@@ -64,21 +71,43 @@ pub async fn process_downloads(
             }
 
             for (source_path, entries) in pending_imports {
-                import_group(entries, source_path, target_path.clone(), tx.clone(), true).await;
+                import_group(
+                    entries,
+                    source_path,
+                    target_path.clone(),
+                    album.as_ref(),
+                    tx.clone(),
+                    true,
+                )
+                .await;
             }
 
             for download in singletons {
-                if let Some(path) = resolve_download_path(&download.item, &download_path_buf) {
-                    import_group(vec![download], path, target_path.clone(), tx.clone(), false)
-                        .await;
+                if let Some(path) = locate_download(&download, &download_path_buf) {
+                    import_group(
+                        vec![download],
+                        path,
+                        target_path.clone(),
+                        album.as_ref(),
+                        tx.clone(),
+                        false,
+                    )
+                    .await;
                 }
             }
         } else {
             // singleton mode
             for download in successful_downloads {
-                if let Some(path) = resolve_download_path(&download.item, &download_path_buf) {
-                    import_group(vec![download], path, target_path.clone(), tx.clone(), false)
-                        .await;
+                if let Some(path) = locate_download(&download, &download_path_buf) {
+                    import_group(
+                        vec![download],
+                        path,
+                        target_path.clone(),
+                        album.as_ref(),
+                        tx.clone(),
+                        false,
+                    )
+                    .await;
                 } else {
                     let failed_entry = DownloadProgress {
                         state: DownloadState::Failed("Could not resolve file path".into()),
@@ -93,3 +122,89 @@ pub async fn process_downloads(
         info!("Downloads finished but none succeeded. Skipping import.");
     }
 }
+
+/// Import the files of an album assembled from several sources as one album.
+///
+/// The files sit in a folder per source after downloading, so they are moved into
+/// one staging folder first, which is removed after the import.
+#[cfg(feature = "server")]
+pub async fn process_composite_downloads(
+    successful_downloads: Vec<DownloadProgress>,
+    target_path: std::path::PathBuf,
+    album: Option<Album>,
+    tx: broadcast::Sender<Vec<DownloadProgress>>,
+) {
+    let download_path_buf = CONFIG.download_path().clone();
+    let staging = download_path_buf
+        .join(STAGING_DIR)
+        .join(uuid::Uuid::new_v4().to_string());
+
+    if let Err(e) = tokio::fs::create_dir_all(&staging).await {
+        warn!(
+            "Failed to create staging folder {:?}, importing folder by folder: {}",
+            staging, e
+        );
+        process_downloads(successful_downloads, target_path, album, tx).await;
+        return;
+    }
+
+    info!(
+        "Gathering {} downloads from several sources in {:?}",
+        successful_downloads.len(),
+        staging
+    );
+
+    let mut staged = Vec::new();
+    for download in successful_downloads {
+        let moved = match locate_download(&download, &download_path_buf) {
+            Some(path) => {
+                let source = std::path::Path::new(&path);
+                let file_name = source
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let mut dest = staging.join(&file_name);
+                // Two sources may use the same file name for different tracks
+                if dest.exists() {
+                    dest = staging.join(format!("{} {}", staged.len() + 1, file_name));
+                }
+                match tokio::fs::rename(source, &dest).await {
+                    Ok(()) => {
+                        cleanup_empty_parent_dir(&path).await;
+                        Ok(())
+                    }
+                    Err(e) => Err(format!("Could not move file for import: {e}")),
+                }
+            }
+            None => Err("Could not resolve file path".to_string()),
+        };
+
+        match moved {
+            Ok(()) => staged.push(download),
+            Err(e) => {
+                let failed_entry = DownloadProgress {
+                    state: DownloadState::Failed(e.clone()),
+                    error: Some(e),
+                    ..download
+                };
+                let _ = tx.send(vec![failed_entry]);
+            }
+        }
+    }
+
+    if !staged.is_empty() {
+        import_group(
+            staged,
+            staging.to_string_lossy().to_string(),
+            target_path,
+            album.as_ref(),
+            tx,
+            true,
+        )
+        .await;
+    }
+
+    if let Err(e) = tokio::fs::remove_dir_all(&staging).await {
+        warn!("Failed to remove staging folder {:?}: {}", staging, e);
+    }
+}
@@ -0,0 +1,241 @@
+//! Per-user download limits.
+//!
+//! Files are tracked in memory from the moment a download is accepted until its
+//! transfer ends, so the limits also count files still waiting for a free slot.
+//! Finished transfers are written to the usage table for the byte quotas. When a
+//! byte quota applies, files whose size is not known up front are refused.
+
+use shared::download::{format_size, DownloadableItem};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+use crate::models::download_limits::{DownloadLimits, UsageRecord};
+use crate::models::user::User;
+
+/// A file accepted for download whose transfer has not ended yet.
+struct ActiveFile {
+    filename: String,
+    size: u64,
+    folder: PathBuf,
+    /// Handed to the backend, counts against the concurrency limit
+    started: bool,
+}
+
+/// Active files by username.
+static ACTIVE: LazyLock<Mutex<HashMap<String, Vec<ActiveFile>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn active() -> std::sync::MutexGuard<'static, HashMap<String, Vec<ActiveFile>>> {
+    ACTIVE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn as_bytes(size: u64) -> i64 {
+    i64::try_from(size).unwrap_or(i64::MAX)
+}
+
+/// Check the user's limits for a new download into `folder` and track its files.
+///
+/// Returns the limits that apply, the files are not started yet, see [`start`].
+pub async fn reserve(
+    user: &User,
+    items: &[DownloadableItem],
+    folder: &Path,
+) -> Result<DownloadLimits, String> {
+    let limits = DownloadLimits::for_user(&user.id).await?;
+
+    // A file of unknown size would count as 0 bytes and slip past the byte quotas
+    let byte_quota = limits.daily_bytes.is_some()
+        || limits.monthly_bytes.is_some()
+        || limits.folder_bytes.is_some();
+    if byte_quota {
+        let unknown: Vec<&str> = items
+            .iter()
+            .filter(|i| i.size.is_none())
+            .map(|i| i.title.as_str())
+            .collect();
+        if !unknown.is_empty() {
+            return Err(format!(
+                "Download quota applies but the size of {} file(s) is unknown: {}",
+                unknown.len(),
+                unknown.join(", ")
+            ));
+        }
+    }
+
+    let (today, month) = if limits.daily_bytes.is_some() || limits.monthly_bytes.is_some() {
+        UsageRecord::today_and_month(&user.id).await?
+    } else {
+        (0, 0)
+    };
+    let folder_size = match limits.folder_bytes {
+        Some(_) => dir_size(folder).await,
+        None => 0,
+    };
+    let requested: u64 = items.iter().map(|i| i.size.unwrap_or(0)).sum();
+
+    let mut active = active();
+    let files = active.entry(user.username.clone()).or_default();
+    let pending: u64 = files.iter().map(|f| f.size).sum();
+    let pending_in_folder: u64 = files
+        .iter()
+        .filter(|f| f.folder == folder)
+        .map(|f| f.size)
+        .sum();
+
+    if let Some(max) = limits.max_queued {
+        if (files.len() + items.len()) as i64 > max {
+            return Err(format!(
+                "Download queue limit reached: {} of {} files queued, {} more requested",
+                files.len(),
+                max,
+                items.len()
+            ));
+        }
+    }
+
+    let quotas = [
+        ("Daily", limits.daily_bytes, today),
+        ("Monthly", limits.monthly_bytes, month),
+    ];
+    for (name, max, used) in quotas {
+        let Some(max) = max else { continue };
+        let used = used.saturating_add(as_bytes(pending));
+        if used.saturating_add(as_bytes(requested)) > max {
+            return Err(format!(
+                "{} download quota exceeded: {} of {} used, this download needs {}",
+                name,
+                format_size(used.max(0) as u64),
+                format_size(max.max(0) as u64),
+                format_size(requested)
+            ));
+        }
+    }
+
+    if let Some(max) = limits.folder_bytes {
+        let used = folder_size.saturating_add(pending_in_folder);
+        if as_bytes(used.saturating_add(requested)) > max {
+            return Err(format!(
+                "Folder {} is limited to {}: {} used, this download needs {}",
+                folder.display(),
+                format_size(max.max(0) as u64),
+                format_size(used),
+                format_size(requested)
+            ));
+        }
+    }
+
+    files.extend(items.iter().map(|item| ActiveFile {
+        filename: item.id.clone(),
+        size: item.size.unwrap_or(0),
+        folder: folder.to_path_buf(),
+        started: false,
+    }));
+    Ok(limits)
+}
+
+/// Mark as many of `filenames` as the concurrency limit allows as started, in order.
+///
+/// Returns how many may be handed to the backend now.
+pub fn start(username: &str, filenames: &[String], max_concurrent: Option<i64>) -> usize {
+    let mut active = active();
+    let Some(files) = active.get_mut(username) else {
+        return 0;
+    };
+
+    let running = files.iter().filter(|f| f.started).count();
+    let free = match max_concurrent {
+        Some(max) => usize::try_from(max).unwrap_or(0).saturating_sub(running),
+        None => usize::MAX,
+    };
+
+    let mut started = 0;
+    for filename in filenames.iter().take(free) {
+        match files
+            .iter_mut()
+            .find(|f| !f.started && &f.filename == filename)
+        {
+            Some(file) => {
+                file.started = true;
+                started += 1;
+            }
+            None => break,
+        }
+    }
+    started
+}
+
+/// Mark a started file as waiting again, e.g. when it is taken off the backend's queue.
+pub fn unstart(username: &str, filename: &str) {
+    let mut active = active();
+    if let Some(file) = active
+        .get_mut(username)
+        .and_then(|files| files.iter_mut().find(|f| f.filename == filename))
+    {
+        file.started = false;
+    }
+}
+
+/// Stop tracking a file, e.g. when the backend refused it.
+pub fn release(username: &str, filename: &str) {
+    let mut active = active();
+    if let Some(files) = active.get_mut(username) {
+        if let Some(pos) = files.iter().position(|f| f.filename == filename) {
+            files.remove(pos);
+        }
+        if files.is_empty() {
+            active.remove(username);
+        }
+    }
+}
+
+/// Stop tracking a file whose transfer ended, counting `bytes` if it completed.
+pub async fn finish(username: &str, user_id: &str, filename: &str, bytes: Option<u64>) {
+    release(username, filename);
+
+    if let Some(bytes) = bytes.filter(|b| *b > 0) {
+        if let Err(e) = UsageRecord::add(user_id, as_bytes(bytes)).await {
+            tracing::error!("Failed to record download usage for {}: {}", username, e);
+        }
+    }
+}
+
+/// Number of the user's files transferring, and queued or transferring.
+pub fn counts(username: &str) -> (usize, usize) {
+    let active = active();
+    match active.get(username) {
+        Some(files) => (files.iter().filter(|f| f.started).count(), files.len()),
+        None => (0, 0),
+    }
+}
+
+/// Bytes of the user's files that have not finished transferring.
+pub fn pending_bytes(username: &str) -> u64 {
+    let active = active();
+    active
+        .get(username)
+        .map(|files| files.iter().map(|f| f.size).sum())
+        .unwrap_or(0)
+}
+
+/// Total size of the files under `path`, symlinks are not followed.
+pub async fn dir_size(path: &Path) -> u64 {
+    fn walk(path: &Path) -> u64 {
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return 0;
+        };
+        entries
+            .flatten()
+            .map(|entry| match entry.metadata() {
+                Ok(meta) if meta.is_dir() => walk(&entry.path()),
+                Ok(meta) if meta.is_file() => meta.len(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || walk(&path))
+        .await
+        .unwrap_or(0)
+}
@@ -0,0 +1,86 @@
+//! Download schedule.
+//!
+//! Admins set daily windows in which downloads are paused or limited to a number
+//! of files per user at once, e.g. to run at full speed only at night. A task
+//! evaluates the rules stored in the app config at the server's local time, new
+//! downloads and the download monitors follow its result.
+
+use dioxus::logger::tracing::{error, info};
+use shared::download::{scheduled_action, ScheduleAction};
+use std::sync::{Once, RwLock};
+use std::time::Duration;
+
+use crate::models::app_config::AppConfig;
+
+/// How often the rules are evaluated (30 seconds).
+const SCHEDULE_INTERVAL_SECS: u64 = 30;
+
+/// Action of the schedule as of the last evaluation.
+static CURRENT: RwLock<Option<ScheduleAction>> = RwLock::new(None);
+
+/// Ensures the schedule task is started exactly once.
+static SCHEDULE_TASK_INIT: Once = Once::new();
+
+/// Action of the schedule right now, `None` outside of its windows.
+pub fn current() -> Option<ScheduleAction> {
+    *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn is_paused() -> bool {
+    current() == Some(ScheduleAction::Pause)
+}
+
+/// Concurrency limit of a user with the limit `user_max` under the current schedule.
+pub fn max_concurrent(user_max: Option<i64>) -> Option<i64> {
+    match current() {
+        Some(ScheduleAction::Pause) => Some(0),
+        Some(ScheduleAction::Limit { max_concurrent }) => {
+            let max_concurrent = i64::from(max_concurrent);
+            Some(user_max.map_or(max_concurrent, |max| max.min(max_concurrent)))
+        }
+        None => user_max,
+    }
+}
+
+/// Evaluate the stored rules now, e.g. after they changed.
+pub async fn refresh() {
+    let rules = match AppConfig::download_schedule().await {
+        Ok(rules) => rules,
+        Err(e) => {
+            error!("Failed to load the download schedule: {}", e);
+            return;
+        }
+    };
+    let action = scheduled_action(&rules, chrono::Local::now().time());
+
+    let mut current = CURRENT.write().unwrap_or_else(|e| e.into_inner());
+    if *current == action {
+        return;
+    }
+    match action {
+        Some(ScheduleAction::Pause) => info!("Download schedule: downloads paused"),
+        Some(ScheduleAction::Limit { max_concurrent }) => info!(
+            "Download schedule: at most {} downloads per user",
+            max_concurrent
+        ),
+        None => info!("Download schedule: downloads unrestricted"),
+    }
+    *current = action;
+}
+
+/// Start the background task that follows the download schedule.
+pub fn start_schedule_task() {
+    SCHEDULE_TASK_INIT.call_once(|| {
+        tokio::spawn(async {
+            let mut interval = tokio::time::interval(Duration::from_secs(SCHEDULE_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                refresh().await;
+            }
+        });
+        info!(
+            "Started download schedule task (interval: {}s)",
+            SCHEDULE_INTERVAL_SECS
+        );
+    });
+}
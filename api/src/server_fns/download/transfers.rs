@@ -0,0 +1,390 @@
+//! Shared view of the download backend's transfers.
+//!
+//! A single task polls the backends for every download monitor, compares the
+//! transfers with the previous poll and broadcasts them together with the changes.
+//! Every backend a monitor downloads from is queried once per interval no matter how
+//! many downloads are being monitored, and the task only runs while a monitor is
+//! subscribed.
+//! When some backends or backend instances cannot be listed, their transfers are kept
+//! as they were and the snapshot names them, see [`TransferSnapshot::unavailable`].
+
+use dioxus::logger::tracing::{debug, info, warn};
+use shared::download::{DownloadProgress, DownloadState, TransferList};
+use soulbeet::DownloadBackend;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex};
+
+use crate::services::{download_backend, downloaders};
+
+/// Poll interval of the backend (2 seconds).
+pub const POLL_INTERVAL_SECS: u64 = 2;
+
+/// How a transfer changed since the previous poll.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferChange {
+    /// Listed for the first time
+    Added,
+    /// Moved on from the given state
+    StateChanged(DownloadState),
+    /// Same state, more bytes transferred
+    Progressed,
+    /// No longer listed, the transfer holds its last known values
+    Removed,
+}
+
+/// Change of a single transfer.
+#[derive(Debug, Clone)]
+pub struct TransferEvent {
+    pub change: TransferChange,
+    pub transfer: DownloadProgress,
+}
+
+/// Transfers listed by one poll of the backend.
+#[derive(Debug)]
+pub struct TransferSnapshot {
+    pub transfers: Vec<DownloadProgress>,
+    /// Changes since the previous poll
+    pub events: Vec<TransferEvent>,
+    /// Backends and backend instances whose transfers could not be listed and are missing
+    pub unavailable: Vec<String>,
+}
+
+/// Sent to the subscribed monitors after every poll.
+#[derive(Debug, Clone)]
+pub enum TransferUpdate {
+    Snapshot(Arc<TransferSnapshot>),
+    /// No download backend is configured, monitoring cannot go on
+    Unavailable(String),
+}
+
+struct TransferService {
+    tx: broadcast::Sender<TransferUpdate>,
+    /// Whether the poll task runs, locked while subscribing and while the task
+    /// decides to stop so a new subscriber never misses a running task
+    running: Mutex<bool>,
+    /// Backends the subscribed monitors download from, polled until the task stops
+    backends: std::sync::Mutex<BTreeSet<String>>,
+}
+
+static TRANSFERS: LazyLock<TransferService> = LazyLock::new(|| {
+    let (tx, _) = broadcast::channel(16);
+    TransferService {
+        tx,
+        running: Mutex::new(false),
+        backends: std::sync::Mutex::new(BTreeSet::new()),
+    }
+});
+
+/// Receive the transfers after every poll, starting the poll task if needed.
+///
+/// `backend` is the one the monitor downloads from, the default one for `None`.
+pub async fn subscribe(backend: Option<&str>) -> broadcast::Receiver<TransferUpdate> {
+    let mut running = TRANSFERS.running.lock().await;
+    TRANSFERS
+        .backends
+        .lock()
+        .unwrap()
+        .insert(backend.unwrap_or(downloaders::SLSKD).to_string());
+    let rx = TRANSFERS.tx.subscribe();
+    if !*running {
+        *running = true;
+        tokio::spawn(poll_transfers());
+        info!("Started the transfer poll task");
+    }
+    rx
+}
+
+/// Whether any download monitor follows the transfers.
+pub fn in_use() -> bool {
+    TRANSFERS.tx.receiver_count() > 0
+}
+
+/// Poll the backends until no monitor is subscribed anymore.
+async fn poll_transfers() {
+    let mut interval = tokio::time::interval(Duration::from_secs(POLL_INTERVAL_SECS));
+    let mut previous: HashMap<String, DownloadProgress> = HashMap::new();
+
+    'poll: loop {
+        interval.tick().await;
+
+        let ids = {
+            let mut running = TRANSFERS.running.lock().await;
+            let mut backends = TRANSFERS.backends.lock().unwrap();
+            if TRANSFERS.tx.receiver_count() == 0 {
+                *running = false;
+                backends.clear();
+                info!("No downloads monitored, stopped the transfer poll task");
+                return;
+            }
+            backends.clone()
+        };
+
+        let mut backends = Vec::with_capacity(ids.len());
+        for id in ids {
+            match download_backend(Some(&id)).await {
+                Ok(backend) => backends.push((id, backend)),
+                Err(e) => {
+                    let _ = TRANSFERS.tx.send(TransferUpdate::Unavailable(e));
+                    continue 'poll;
+                }
+            }
+        }
+        let list = match list_transfers(&backends).await {
+            Ok(list) => list,
+            Err(e) => {
+                // Transient errors are skipped, slskd might recover
+                warn!("Error fetching download status from slskd: {}", e);
+                continue;
+            }
+        };
+
+        let events = diff(&mut previous, &list.transfers, !list.unavailable.is_empty());
+        if !events.is_empty() {
+            debug!("{} transfers changed", events.len());
+        }
+        let snapshot = TransferSnapshot {
+            transfers: list.transfers,
+            events,
+            unavailable: list.unavailable,
+        };
+        let _ = TRANSFERS
+            .tx
+            .send(TransferUpdate::Snapshot(Arc::new(snapshot)));
+    }
+}
+
+/// Transfers of every backend by id. Backends that could not be listed are named
+/// in the result, it fails only when none could be listed.
+async fn list_transfers(
+    backends: &[(String, Arc<dyn DownloadBackend>)],
+) -> Result<TransferList, String> {
+    let mut list = TransferList::default();
+    let mut listed_any = false;
+    let mut last_error = None;
+    for (id, backend) in backends {
+        match backend.get_downloads().await {
+            Ok(listed) => {
+                listed_any = true;
+                list.transfers.extend(listed.transfers);
+                list.unavailable.extend(listed.unavailable);
+            }
+            Err(e) => {
+                warn!("Could not list the transfers of backend '{}': {}", id, e);
+                list.unavailable.push(id.clone());
+                last_error = Some(e.to_string());
+            }
+        }
+    }
+
+    match last_error {
+        Some(e) if !listed_any => Err(e),
+        _ => Ok(list),
+    }
+}
+
+/// Changes from the `previous` transfers to `current`, and remember `current`.
+///
+/// With `partial`, some instances could not be listed: transfers missing from
+/// `current` are kept instead of being reported as removed.
+fn diff(
+    previous: &mut HashMap<String, DownloadProgress>,
+    current: &[DownloadProgress],
+    partial: bool,
+) -> Vec<TransferEvent> {
+    let mut events = Vec::new();
+    let mut seen = HashMap::with_capacity(current.len());
+
+    for transfer in current {
+        let change = match previous.remove(&transfer.id) {
+            None => Some(TransferChange::Added),
+            Some(old) if old.state != transfer.state => {
+                Some(TransferChange::StateChanged(old.state))
+            }
+            Some(old) if old.transferred != transfer.transferred => {
+                Some(TransferChange::Progressed)
+            }
+            Some(_) => None,
+        };
+        if let Some(change) = change {
+            events.push(TransferEvent {
+                change,
+                transfer: transfer.clone(),
+            });
+        }
+        seen.insert(transfer.id.clone(), transfer.clone());
+    }
+
+    if partial {
+        seen.extend(previous.drain());
+    } else {
+        events.extend(previous.drain().map(|(_, transfer)| TransferEvent {
+            change: TransferChange::Removed,
+            transfer,
+        }));
+    }
+    *previous = seen;
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use shared::download::{
+        DownloadableItem, QueuedDownload, SearchOptions, SearchResult, SharedFolder,
+    };
+    use shared::metadata::{Album, Track};
+    use soulbeet::error::{Result, SoulseekError};
+    use soulbeet::MultiDownloadBackend;
+
+    /// Backend that only lists its transfers, or fails to with `None`.
+    struct ListingBackend(std::sync::Mutex<Option<Vec<DownloadProgress>>>);
+
+    impl ListingBackend {
+        fn new(transfers: Option<Vec<DownloadProgress>>) -> Arc<Self> {
+            Arc::new(Self(std::sync::Mutex::new(transfers)))
+        }
+
+        fn set(&self, transfers: Option<Vec<DownloadProgress>>) {
+            *self.0.lock().unwrap() = transfers;
+        }
+    }
+
+    #[async_trait]
+    impl DownloadBackend for ListingBackend {
+        fn id(&self) -> &'static str {
+            "listing"
+        }
+
+        fn name(&self) -> &'static str {
+            "Listing"
+        }
+
+        async fn start_search(
+            &self,
+            _album: Option<&Album>,
+            _tracks: &[Track],
+            _options: &SearchOptions,
+        ) -> Result<String> {
+            unimplemented!()
+        }
+
+        async fn poll_search(&self, _search_id: &str) -> Result<SearchResult> {
+            unimplemented!()
+        }
+
+        async fn search_changes(&self, _search_id: &str, _since: u64) -> Result<SearchResult> {
+            unimplemented!()
+        }
+
+        async fn cancel_search(&self, _search_id: &str) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn browse(&self, _source: &str) -> Result<Vec<SharedFolder>> {
+            unimplemented!()
+        }
+
+        async fn download(&self, _items: Vec<DownloadableItem>) -> Result<Vec<QueuedDownload>> {
+            unimplemented!()
+        }
+
+        async fn get_downloads(&self) -> Result<TransferList> {
+            match self.0.lock().unwrap().clone() {
+                Some(transfers) => Ok(TransferList {
+                    transfers,
+                    unavailable: vec![],
+                }),
+                None => Err(SoulseekError::Api {
+                    status: 503,
+                    message: "unreachable".to_string(),
+                }),
+            }
+        }
+
+        async fn cancel_transfer(&self, _source: &str, _id: &str) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn health_check(&self) -> bool {
+            true
+        }
+    }
+
+    fn transfer(id: &str, state: DownloadState) -> DownloadProgress {
+        DownloadProgress {
+            state,
+            ..DownloadProgress::queued(
+                id.to_string(),
+                "peer".to_string(),
+                format!("{}.flac", id),
+                100,
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn follows_transfers_of_a_second_instance() {
+        let main = ListingBackend::new(Some(vec![]));
+        let backup = ListingBackend::new(Some(vec![transfer("t1", DownloadState::Queued)]));
+        let slskd: Arc<dyn DownloadBackend> = Arc::new(MultiDownloadBackend::new(vec![
+            ("main".to_string(), main.clone() as Arc<dyn DownloadBackend>),
+            (
+                "backup".to_string(),
+                backup.clone() as Arc<dyn DownloadBackend>,
+            ),
+        ]));
+        let backends = vec![(downloaders::SLSKD.to_string(), slskd)];
+        let mut previous = HashMap::new();
+
+        let list = list_transfers(&backends).await.unwrap();
+        let events = diff(&mut previous, &list.transfers, false);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].change, TransferChange::Added);
+        assert_eq!(events[0].transfer.id, "t1");
+
+        backup.set(Some(vec![transfer("t1", DownloadState::Completed)]));
+        let list = list_transfers(&backends).await.unwrap();
+        let events = diff(&mut previous, &list.transfers, false);
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].change,
+            TransferChange::StateChanged(DownloadState::Queued)
+        );
+        assert_eq!(events[0].transfer.state, DownloadState::Completed);
+
+        // The first instance going away does not hide the transfer
+        main.set(None);
+        let list = list_transfers(&backends).await.unwrap();
+        assert_eq!(list.unavailable, ["main"]);
+        assert_eq!(list.transfers.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn polls_every_backend() {
+        let default = ListingBackend::new(Some(vec![transfer("t1", DownloadState::InProgress)]));
+        let other = ListingBackend::new(Some(vec![transfer("t2", DownloadState::Queued)]));
+        let backends: Vec<(String, Arc<dyn DownloadBackend>)> = vec![
+            (downloaders::SLSKD.to_string(), default.clone()),
+            ("other".to_string(), other.clone()),
+        ];
+
+        let list = list_transfers(&backends).await.unwrap();
+        let ids: Vec<&str> = list.transfers.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["t1", "t2"]);
+        assert!(list.unavailable.is_empty());
+
+        // Missing transfers of a backend that failed are not reported as removed
+        let mut previous = HashMap::new();
+        diff(&mut previous, &list.transfers, false);
+        default.set(None);
+        let list = list_transfers(&backends).await.unwrap();
+        assert_eq!(list.unavailable, [downloaders::SLSKD]);
+        assert!(diff(&mut previous, &list.transfers, true).is_empty());
+
+        other.set(None);
+        assert!(list_transfers(&backends).await.is_err());
+    }
+}
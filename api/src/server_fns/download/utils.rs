@@ -1,7 +1,42 @@
 #[cfg(feature = "server")]
+use shared::download::DownloadProgress;
+#[cfg(feature = "server")]
 use std::path::{Path, PathBuf};
 #[cfg(feature = "server")]
-use tracing::warn;
+use tracing::{debug, warn};
+
+/// Find a finished download on disk.
+///
+/// Uses the path slskd reported for the transfer when there is one. slskd may see
+/// the download directory under another path (e.g. in its own container), so the
+/// reported file is also looked for as `<download_base>/<folder>/<file>`, the layout
+/// slskd saves downloads in. Otherwise the remote filename is resolved with
+/// [`resolve_download_path`].
+#[cfg(feature = "server")]
+pub fn locate_download(download: &DownloadProgress, download_base: &Path) -> Option<String> {
+    if let Some(reported) = &download.path {
+        let reported_str = reported.replace('\\', "/");
+        let reported_path = Path::new(&reported_str);
+        if reported_path.is_file() {
+            return Some(reported_str);
+        }
+
+        let components: Vec<_> = reported_path.components().collect();
+        if components.len() >= 2 {
+            let relative: PathBuf = components[components.len() - 2..].iter().collect();
+            let candidate = download_base.join(relative);
+            if candidate.is_file() {
+                return Some(candidate.to_string_lossy().to_string());
+            }
+        }
+        debug!(
+            "Reported path '{}' of '{}' not found, searching the download directory",
+            reported, download.item
+        );
+    }
+
+    resolve_download_path(&download.item, download_base)
+}
 
 /// Resolve the download path from a slskd filename to an actual filesystem path.
 ///
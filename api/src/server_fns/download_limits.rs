@@ -0,0 +1,135 @@
+use crate::models::download_limits::{DownloadLimits, DownloadLimitsOverview, DownloadUsage};
+use dioxus::prelude::*;
+
+#[cfg(feature = "server")]
+use super::download::quota;
+#[cfg(feature = "server")]
+use super::server_error;
+#[cfg(feature = "server")]
+use crate::models::{
+    audit::{AuditEvent, AuditRecord},
+    download_limits::{FolderUsage, UsageRecord, UserDownloadLimits},
+    folder::Folder,
+    user::User,
+};
+#[cfg(feature = "server")]
+use crate::{AdminSession, AuthSession};
+
+#[cfg(feature = "server")]
+fn check_limits(limits: &DownloadLimits) -> Result<(), ServerFnError> {
+    let values = [
+        limits.max_concurrent,
+        limits.max_queued,
+        limits.daily_bytes,
+        limits.monthly_bytes,
+        limits.folder_bytes,
+    ];
+    if values.into_iter().flatten().any(|v| v < 1) {
+        return Err(server_error(
+            "Limits must be at least 1, leave empty for no limit",
+        ));
+    }
+    Ok(())
+}
+
+/// The current user's limits and how much of them is used.
+#[get("/api/downloads/usage", auth: AuthSession)]
+pub async fn get_download_usage() -> Result<DownloadUsage, ServerFnError> {
+    let claims = auth.0;
+    let limits = DownloadLimits::for_user(&claims.sub)
+        .await
+        .map_err(server_error)?;
+
+    let (active, queued) = quota::counts(&claims.username);
+    let pending = quota::pending_bytes(&claims.username) as i64;
+    let (today, month) = UsageRecord::today_and_month(&claims.sub)
+        .await
+        .map_err(server_error)?;
+
+    let mut folders = Vec::new();
+    if limits.folder_bytes.is_some() {
+        let user_folders = Folder::get_all_by_user(&claims.sub)
+            .await
+            .map_err(server_error)?;
+        for folder in user_folders.into_iter().filter(|f| f.access.can_write()) {
+            let bytes = quota::dir_size(std::path::Path::new(&folder.path)).await;
+            folders.push(FolderUsage {
+                name: folder.name,
+                path: folder.path,
+                bytes: bytes as i64,
+            });
+        }
+    }
+
+    Ok(DownloadUsage {
+        limits,
+        active: active as i64,
+        queued: queued as i64,
+        today_bytes: today + pending,
+        month_bytes: month + pending,
+        folders,
+    })
+}
+
+/// Default limits and the users with their own.
+#[get("/api/downloads/limits", _: AdminSession)]
+pub async fn get_download_limits() -> Result<DownloadLimitsOverview, ServerFnError> {
+    let default = DownloadLimits::get_default().await.map_err(server_error)?;
+
+    let mut users = Vec::new();
+    for user in User::get_all().await.map_err(server_error)? {
+        let custom = DownloadLimits::get_custom(&user.id)
+            .await
+            .map_err(server_error)?;
+        users.push(UserDownloadLimits {
+            user_id: user.id,
+            username: user.username,
+            custom,
+        });
+    }
+
+    Ok(DownloadLimitsOverview { default, users })
+}
+
+/// Set the limits of users without their own.
+#[post("/api/downloads/limits/default", auth: AdminSession)]
+pub async fn update_default_download_limits(limits: DownloadLimits) -> Result<(), ServerFnError> {
+    check_limits(&limits)?;
+    DownloadLimits::set_default(&limits)
+        .await
+        .map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::DownloadLimitsChanged)
+        .by(&auth.0)
+        .target("default")
+        .save()
+        .await;
+    Ok(())
+}
+
+/// Give a user their own limits, or `None` to apply the defaults again.
+#[post("/api/downloads/limits/user", auth: AdminSession)]
+pub async fn update_user_download_limits(
+    user_id: String,
+    limits: Option<DownloadLimits>,
+) -> Result<(), ServerFnError> {
+    if let Some(limits) = &limits {
+        check_limits(limits)?;
+    }
+    let user = User::get_by_id(&user_id).await.map_err(server_error)?;
+    DownloadLimits::set_custom(&user.id, limits.as_ref())
+        .await
+        .map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::DownloadLimitsChanged)
+        .by(&auth.0)
+        .target(&user.username)
+        .details(if limits.is_some() {
+            "custom"
+        } else {
+            "default"
+        })
+        .save()
+        .await;
+    Ok(())
+}
@@ -5,11 +5,84 @@ use shared::library::DuplicateReport;
 #[cfg(feature = "server")]
 use super::server_error;
 #[cfg(feature = "server")]
+use crate::models::{
+    app_config::{keys, AppConfig},
+    audit::{AuditEvent, AuditRecord},
+    folder::Folder,
+    user::User,
+};
+#[cfg(feature = "server")]
 use crate::services::music_importer;
 #[cfg(feature = "server")]
-use crate::AuthSession;
+use crate::{AuthSession, MemberSession};
+#[cfg(feature = "server")]
+use std::path::{Component, Path};
+
+/// Check a folder path before storing it.
+///
+/// Paths must be absolute without `..`, and non-admins must stay under the library
+/// root when one is configured.
+#[cfg(feature = "server")]
+async fn check_folder_path(user_id: &str, path: &str) -> Result<(), ServerFnError> {
+    let path = Path::new(path);
+    if !path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+        return Err(server_error(
+            "Folder path must be absolute and cannot contain '..'",
+        ));
+    }
+
+    let Some(root) = AppConfig::get(keys::LIBRARY_ROOT)
+        .await
+        .map_err(server_error)?
+    else {
+        return Ok(());
+    };
+    if path.starts_with(&root) {
+        return Ok(());
+    }
+
+    let user = User::get_by_id(user_id).await.map_err(server_error)?;
+    if user.role.is_admin() {
+        Ok(())
+    } else {
+        Err(server_error(format!("Folder must be inside {}", root)))
+    }
+}
+
+/// Load a folder the user owns, for operations reserved to the owner.
+#[cfg(feature = "server")]
+async fn owned_folder(folder_id: &str, user_id: &str) -> Result<Folder, ServerFnError> {
+    match Folder::get_for_user(folder_id, user_id)
+        .await
+        .map_err(server_error)?
+    {
+        Some(folder) if folder.access.is_owner() => Ok(folder),
+        Some(_) => Err(server_error("Only the owner can change this folder")),
+        None => Err(server_error("Folder not found")),
+    }
+}
+
+/// Make sure downloads for the user can go into `path`.
+///
+/// The path has to be one of the folders the user owns or has write access to.
 #[cfg(feature = "server")]
-use std::path::Path;
+pub(crate) async fn ensure_writable_folder(user_id: &str, path: &str) -> Result<(), ServerFnError> {
+    let target = Path::new(path);
+    let folders = Folder::get_all_by_user(user_id)
+        .await
+        .map_err(server_error)?;
+
+    if folders
+        .iter()
+        .any(|f| f.access.can_write() && Path::new(&f.path) == target)
+    {
+        Ok(())
+    } else {
+        Err(server_error(
+            "Target folder is not one of your writable folders",
+        ))
+    }
+}
 
 #[get("/api/folders", auth: AuthSession)]
 pub async fn get_user_folders() -> Result<Vec<models::folder::Folder>, ServerFnError> {
@@ -20,41 +93,151 @@ pub async fn get_user_folders() -> Result<Vec<models::folder::Folder>, ServerFnE
         .map_err(server_error)
 }
 
-#[post("/api/folders", auth: AuthSession)]
+#[post("/api/folders", auth: MemberSession)]
 pub async fn create_user_folder(
     name: String,
     path: String,
 ) -> Result<models::folder::Folder, ServerFnError> {
     let claims = auth.0;
 
+    check_folder_path(&claims.sub, &path).await?;
+
     if let Err(e) = tokio::fs::create_dir_all(&path).await {
         return Err(server_error(format!("Failed to create directory: {}", e)));
     }
 
-    models::folder::Folder::create(&claims.sub, &name, &path)
+    let folder = models::folder::Folder::create(&claims.sub, &name, &path)
         .await
-        .map_err(server_error)
+        .map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::FolderCreated)
+        .by(&claims)
+        .target(&folder.path)
+        .save()
+        .await;
+    Ok(folder)
 }
 
-#[put("/api/folders/update", _: AuthSession)]
+#[put("/api/folders/update", auth: MemberSession)]
 pub async fn update_folder(
     folder_id: String,
     name: String,
     path: String,
 ) -> Result<(), ServerFnError> {
+    let claims = auth.0;
+
+    let folder = owned_folder(&folder_id, &claims.sub).await?;
+    check_folder_path(&claims.sub, &path).await?;
+
     models::folder::Folder::update(&folder_id, &name, &path)
+        .await
+        .map_err(server_error)?;
+
+    let mut record = AuditRecord::new(AuditEvent::FolderUpdated)
+        .by(&claims)
+        .target(&path);
+    if folder.path != path {
+        record = record.details(format!("moved from {}", folder.path));
+    }
+    record.save().await;
+    Ok(())
+}
+
+/// Enable or disable the post-import lyrics stage for a folder.
+#[put("/api/folders/lyrics", auth: MemberSession)]
+pub async fn set_folder_lyrics(folder_id: String, enabled: bool) -> Result<(), ServerFnError> {
+    owned_folder(&folder_id, &auth.0.sub).await?;
+
+    models::folder::Folder::set_lyrics_enabled(&folder_id, enabled)
         .await
         .map_err(server_error)
 }
 
-#[delete("/api/folders/delete", _: AuthSession)]
+#[delete("/api/folders/delete", auth: MemberSession)]
 pub async fn delete_folder(folder_id: String) -> Result<(), ServerFnError> {
+    let folder = owned_folder(&folder_id, &auth.0.sub).await?;
+
     models::folder::Folder::delete(&folder_id)
+        .await
+        .map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::FolderDeleted)
+        .by(&auth.0)
+        .target(&folder.path)
+        .save()
+        .await;
+    Ok(())
+}
+
+/// Users a folder is shared with. Only visible to the owner.
+#[post("/api/folders/shares", auth: AuthSession)]
+pub async fn get_folder_shares(
+    folder_id: String,
+) -> Result<Vec<models::folder::FolderShare>, ServerFnError> {
+    owned_folder(&folder_id, &auth.0.sub).await?;
+
+    models::folder::Folder::get_shares(&folder_id)
         .await
         .map_err(server_error)
 }
 
-/// Find duplicate tracks across all of the user's library folders.
+/// Share a folder with another user, read-only or with download access.
+/// Sharing again with the same user updates their access.
+#[post("/api/folders/share", auth: MemberSession)]
+pub async fn share_folder(
+    folder_id: String,
+    username: String,
+    can_write: bool,
+) -> Result<(), ServerFnError> {
+    let claims = auth.0;
+    let folder = owned_folder(&folder_id, &claims.sub).await?;
+
+    let user = User::get_by_username(username.trim())
+        .await
+        .map_err(server_error)?;
+    if user.id == claims.sub {
+        return Err(server_error("You already own this folder"));
+    }
+
+    models::folder::Folder::share(&folder_id, &user.id, can_write)
+        .await
+        .map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::FolderShared)
+        .by(&claims)
+        .target(&folder.path)
+        .details(format!(
+            "with {} ({})",
+            user.username,
+            if can_write { "write" } else { "read" }
+        ))
+        .save()
+        .await;
+    Ok(())
+}
+
+#[delete("/api/folders/share", auth: MemberSession)]
+pub async fn unshare_folder(folder_id: String, user_id: String) -> Result<(), ServerFnError> {
+    let folder = owned_folder(&folder_id, &auth.0.sub).await?;
+
+    models::folder::Folder::unshare(&folder_id, &user_id)
+        .await
+        .map_err(server_error)?;
+
+    let username = User::get_by_id(&user_id)
+        .await
+        .map(|u| u.username)
+        .unwrap_or(user_id);
+    AuditRecord::new(AuditEvent::FolderUnshared)
+        .by(&auth.0)
+        .target(&folder.path)
+        .details(format!("with {}", username))
+        .save()
+        .await;
+    Ok(())
+}
+
+/// Find duplicate tracks across all of the user's library folders, shared ones included.
 /// Returns tracks that exist in multiple libraries (same artist + title).
 #[get("/api/folders/duplicates", auth: AuthSession)]
 pub async fn get_folder_duplicates() -> Result<DuplicateReport, ServerFnError> {
@@ -3,10 +3,198 @@ use crate::auth::Claims;
 #[cfg(feature = "server")]
 use axum::{extract::FromRequestParts, http::StatusCode};
 #[cfg(feature = "server")]
-use crate::auth;
+use crate::auth::{self, ApiScope};
+#[cfg(feature = "server")]
+use crate::models::{api_token::ApiToken, session::Session, user::User};
+#[cfg(feature = "server")]
+use crate::config::CONFIG;
+#[cfg(feature = "server")]
+use crate::sso;
+#[cfg(feature = "server")]
+use std::net::IpAddr;
 
+/// Session of any logged in user. API tokens need the `read` scope.
 pub struct AuthSession(pub Claims);
 
+/// Session allowed to run searches. API tokens need the `search` scope.
+pub struct SearchSession(pub Claims);
+
+/// Session of a user with the admin role.
+pub struct AdminSession(pub Claims);
+
+/// Session of a user allowed to download and change their folders (admin or member).
+pub struct MemberSession(pub Claims);
+
+/// Session that is still accepted while the user has to change their password.
+/// Only for the endpoints needed to do that, API tokens are refused.
+pub struct PendingSession(pub Claims);
+
+/// Cookie session whose access token may have expired, for `refresh_token`.
+/// The session record decides whether it is still valid. Also accepts auth proxy logins.
+pub struct RefreshSession(pub Claims);
+
+/// Device and address of the client, recorded on its session.
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+}
+
+/// Token from an `Authorization: Bearer` header, if any.
+#[cfg(feature = "server")]
+fn bearer_token(parts: &axum::http::request::Parts) -> Option<&str> {
+    parts
+        .headers
+        .get(axum::http::header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// Claims from an API token in the `Authorization` header, or from the auth cookie.
+#[cfg(feature = "server")]
+async fn claims_from_parts(
+    parts: &axum::http::request::Parts,
+) -> Result<Claims, (StatusCode, String)> {
+    if let Some(token) = bearer_token(parts) {
+        return match ApiToken::authenticate(token).await {
+            Ok(Some(claims)) => Ok(claims),
+            Ok(None) => {
+                tracing::warn!("Auth failure: Unknown or expired API token");
+                Err((StatusCode::UNAUTHORIZED, "Invalid API token".to_string()))
+            }
+            Err(e) => {
+                tracing::error!("Auth failure: Could not check API token: {}", e);
+                Err((StatusCode::UNAUTHORIZED, "Invalid API token".to_string()))
+            }
+        };
+    }
+
+    if let Some(claims) = proxy_claims(parts).await? {
+        return Ok(claims);
+    }
+
+    let token = cookie_token(parts)?;
+    let claims = match auth::verify_token(&token) {
+        Ok(claims) => claims,
+        Err(e) => {
+            tracing::warn!(
+                "Auth failure: Invalid token {}: {}",
+                auth::token_fingerprint(&token),
+                e
+            );
+            return Err((StatusCode::UNAUTHORIZED, format!("Invalid token: {}", e)));
+        }
+    };
+
+    let Some(session_id) = claims.jti.as_deref() else {
+        return Err(session_expired());
+    };
+    match Session::touch(session_id, &claims.sub).await {
+        Ok(true) => Ok(claims),
+        Ok(false) => {
+            tracing::warn!("Auth failure: Revoked session for {}", claims.username);
+            Err(session_expired())
+        }
+        Err(e) => {
+            tracing::error!("Auth failure: Could not check session: {}", e);
+            Err(session_expired())
+        }
+    }
+}
+
+/// Claims of a user named by a trusted auth proxy, if header login applies.
+#[cfg(feature = "server")]
+async fn proxy_claims(
+    parts: &axum::http::request::Parts,
+) -> Result<Option<Claims>, (StatusCode, String)> {
+    match sso::proxy_user(parts).await {
+        Some(Ok(user)) => Ok(Some(sso::proxy_claims(user))),
+        Some(Err(e)) => {
+            tracing::warn!("Auth failure: Header login refused: {}", e);
+            Err((StatusCode::UNAUTHORIZED, e))
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(feature = "server")]
+fn session_expired() -> (StatusCode, String) {
+    (StatusCode::UNAUTHORIZED, "Session expired".to_string())
+}
+
+/// Raw token from the auth cookie.
+#[cfg(feature = "server")]
+fn cookie_token(parts: &axum::http::request::Parts) -> Result<String, (StatusCode, String)> {
+    let cookies = parts
+        .extensions
+        .get::<tower_cookies::Cookies>()
+        .ok_or_else(|| {
+            (
+                StatusCode::UNAUTHORIZED,
+                String::from("Missing cookie middleware"),
+            )
+        })?;
+
+    match cookies.get(crate::AUTH_COOKIE_NAME) {
+        Some(cookie) => Ok(cookie.value().to_string()),
+        None => {
+            tracing::warn!("Auth failure: No auth token found in request cookies");
+            Err((StatusCode::UNAUTHORIZED, "No auth token found".to_string()))
+        }
+    }
+}
+
+/// First value of a request header, if it is valid text.
+#[cfg(feature = "server")]
+fn header_value(parts: &axum::http::request::Parts, name: &str) -> Option<String> {
+    parts
+        .headers
+        .get(name)?
+        .to_str()
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Claims of a session that is not waiting for a password change.
+///
+/// Sessions opened with an API token must also carry the `scope`.
+#[cfg(feature = "server")]
+async fn active_claims(
+    parts: &axum::http::request::Parts,
+    scope: ApiScope,
+) -> Result<Claims, (StatusCode, String)> {
+    let claims = claims_from_parts(parts).await?;
+    if claims.must_change_password {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Password change required".to_string(),
+        ));
+    }
+    if let Some(scopes) = &claims.scopes {
+        if !scopes.allows(scope) {
+            return Err((
+                StatusCode::FORBIDDEN,
+                format!("API token is missing the {} scope", scope),
+            ));
+        }
+    }
+    Ok(claims)
+}
+
+/// Role of the user behind a token.
+///
+/// The role stored in the database wins over the one in the token, so role changes
+/// apply to live sessions.
+#[cfg(feature = "server")]
+async fn current_role(claims: &Claims) -> Result<auth::Role, (StatusCode, String)> {
+    User::get_by_id(&claims.sub)
+        .await
+        .map(|user| user.role)
+        .map_err(|e| (StatusCode::UNAUTHORIZED, e))
+}
+
 #[cfg(feature = "server")]
 impl<S> FromRequestParts<S> for AuthSession
 where
@@ -18,32 +206,242 @@ where
         parts: &mut axum::http::request::Parts,
         _state: &S,
     ) -> Result<Self, Self::Rejection> {
-        let cookies = parts
-            .extensions
-            .get::<tower_cookies::Cookies>()
-            .ok_or_else(|| {
-                (
-                    StatusCode::UNAUTHORIZED,
-                    String::from("Missing cookie middleware"),
-                )
-            })?;
-
-        let token = cookies
-            .get(crate::AUTH_COOKIE_NAME)
-            .map(|c| c.value().to_string());
-
-        match token {
-            Some(token) => match auth::verify_token(&token) {
-                Ok(claims) => Ok(AuthSession(claims)),
-                Err(e) => {
-                    tracing::error!("Auth failure: Invalid token {}: {}", token, e);
-                    Err((StatusCode::UNAUTHORIZED, format!("Invalid token: {}", e)))
-                }
-            },
-            None => {
-                tracing::warn!("Auth failure: No auth token found in request cookies");
-                Err((StatusCode::UNAUTHORIZED, "No auth token found".to_string()))
+        active_claims(parts, ApiScope::Read).await.map(AuthSession)
+    }
+}
+
+#[cfg(feature = "server")]
+impl<S> FromRequestParts<S> for SearchSession
+where
+    S: Send + Sync,
+{
+    type Rejection = (axum::http::StatusCode, String);
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        active_claims(parts, ApiScope::Search)
+            .await
+            .map(SearchSession)
+    }
+}
+
+#[cfg(feature = "server")]
+impl<S> FromRequestParts<S> for PendingSession
+where
+    S: Send + Sync,
+{
+    type Rejection = (axum::http::StatusCode, String);
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let claims = claims_from_parts(parts).await?;
+        if claims.scopes.is_some() {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Not available to API tokens".to_string(),
+            ));
+        }
+        Ok(PendingSession(claims))
+    }
+}
+
+#[cfg(feature = "server")]
+impl<S> FromRequestParts<S> for AdminSession
+where
+    S: Send + Sync,
+{
+    type Rejection = (axum::http::StatusCode, String);
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let claims = active_claims(parts, ApiScope::Admin).await?;
+
+        if !current_role(&claims).await?.is_admin() {
+            tracing::warn!("Admin access denied for user {}", claims.username);
+            return Err((StatusCode::FORBIDDEN, "Admin access required".to_string()));
+        }
+
+        Ok(AdminSession(claims))
+    }
+}
+
+#[cfg(feature = "server")]
+impl<S> FromRequestParts<S> for MemberSession
+where
+    S: Send + Sync,
+{
+    type Rejection = (axum::http::StatusCode, String);
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let claims = active_claims(parts, ApiScope::Download).await?;
+
+        if !current_role(&claims).await?.can_write() {
+            tracing::warn!("Write access denied for read-only user {}", claims.username);
+            return Err((StatusCode::FORBIDDEN, "Read-only account".to_string()));
+        }
+
+        Ok(MemberSession(claims))
+    }
+}
+
+#[cfg(feature = "server")]
+impl<S> FromRequestParts<S> for RefreshSession
+where
+    S: Send + Sync,
+{
+    type Rejection = (axum::http::StatusCode, String);
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        if let Some(claims) = proxy_claims(parts).await? {
+            return Ok(RefreshSession(claims));
+        }
+
+        let token = cookie_token(parts)?;
+        let claims = auth::verify_token_allow_expired(&token).map_err(|e| {
+            tracing::warn!(
+                "Auth failure: Invalid token {}: {}",
+                auth::token_fingerprint(&token),
+                e
+            );
+            (StatusCode::UNAUTHORIZED, format!("Invalid token: {}", e))
+        })?;
+
+        if claims.jti.is_none() {
+            return Err(session_expired());
+        }
+
+        Ok(RefreshSession(claims))
+    }
+}
+
+/// Address of the client a request came from through `peer`.
+///
+/// Forwarding headers are only read when the peer is a trusted proxy, and the
+/// `X-Forwarded-For` entries added by trusted proxies are skipped, so a client
+/// cannot pick the address it is throttled and audited under.
+#[cfg(feature = "server")]
+fn client_ip(
+    peer: IpAddr,
+    forwarded_for: Option<&str>,
+    real_ip: Option<&str>,
+    is_trusted: impl Fn(IpAddr) -> bool,
+) -> IpAddr {
+    if !is_trusted(peer) {
+        return peer;
+    }
+
+    if let Some(forwarded_for) = forwarded_for {
+        let mut client = peer;
+        for entry in forwarded_for.rsplit(',') {
+            let Ok(ip) = entry.trim().parse::<IpAddr>() else {
+                break;
+            };
+            client = ip;
+            if !is_trusted(ip) {
+                break;
             }
         }
+        return client;
+    }
+
+    real_ip
+        .and_then(|ip| ip.trim().parse().ok())
+        .unwrap_or(peer)
+}
+
+#[cfg(feature = "server")]
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let ip = parts
+            .extensions
+            .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+            .map(|info| {
+                client_ip(
+                    info.0.ip(),
+                    header_value(parts, "x-forwarded-for").as_deref(),
+                    header_value(parts, "x-real-ip").as_deref(),
+                    |ip| CONFIG.is_trusted_proxy(ip),
+                )
+                .to_string()
+            });
+
+        Ok(ClientInfo {
+            user_agent: header_value(parts, "user-agent"),
+            ip,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::client_ip;
+    use std::net::IpAddr;
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    /// 10.0.0.0/8 stands in for AUTH_PROXY_TRUSTED_IPS.
+    fn trusted(ip: IpAddr) -> bool {
+        matches!(ip, IpAddr::V4(v4) if v4.octets()[0] == 10)
+    }
+
+    #[test]
+    fn untrusted_peer_cannot_forward() {
+        let peer = ip("203.0.113.7");
+        assert_eq!(
+            client_ip(peer, Some("198.51.100.9"), Some("198.51.100.9"), trusted),
+            peer
+        );
+    }
+
+    #[test]
+    fn trusted_peer_forwards_the_last_untrusted_address() {
+        // The client prepended a fake address, two trusted proxies appended theirs
+        let forwarded_for = "1.2.3.4, 198.51.100.9, 10.0.0.3";
+        assert_eq!(
+            client_ip(ip("10.0.0.2"), Some(forwarded_for), None, trusted),
+            ip("198.51.100.9")
+        );
+    }
+
+    #[test]
+    fn trusted_peer_stops_at_invalid_entries() {
+        assert_eq!(
+            client_ip(ip("10.0.0.2"), Some("garbage, 10.0.0.3"), None, trusted),
+            ip("10.0.0.3")
+        );
+    }
+
+    #[test]
+    fn trusted_peer_falls_back_to_real_ip() {
+        assert_eq!(
+            client_ip(ip("10.0.0.2"), None, Some(" 198.51.100.9 "), trusted),
+            ip("198.51.100.9")
+        );
+        assert_eq!(
+            client_ip(ip("10.0.0.2"), None, Some("unknown"), trusted),
+            ip("10.0.0.2")
+        );
     }
 }
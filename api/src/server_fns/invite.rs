@@ -0,0 +1,51 @@
+use crate::auth::Role;
+use crate::models::invite::Invite;
+use dioxus::prelude::*;
+
+#[cfg(feature = "server")]
+use super::server_error;
+#[cfg(feature = "server")]
+use crate::models::audit::{AuditEvent, AuditRecord};
+#[cfg(feature = "server")]
+use crate::AdminSession;
+
+/// Longest validity an invite can be created with, in hours.
+pub const MAX_INVITE_HOURS: i64 = 24 * 30;
+
+#[get("/api/invites", _: AdminSession)]
+pub async fn get_invites() -> Result<Vec<Invite>, ServerFnError> {
+    Invite::get_all().await.map_err(server_error)
+}
+
+/// Generate a single-use invite code valid for `valid_hours`.
+#[post("/api/invites", auth: AdminSession)]
+pub async fn create_invite(role: Role, valid_hours: i64) -> Result<Invite, ServerFnError> {
+    if !(1..=MAX_INVITE_HOURS).contains(&valid_hours) {
+        return Err(server_error(format!(
+            "Invites must be valid between 1 and {} hours",
+            MAX_INVITE_HOURS
+        )));
+    }
+
+    let invite = Invite::create(&auth.0.sub, role, valid_hours)
+        .await
+        .map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::InviteCreated)
+        .by(&auth.0)
+        .details(format!("role {}, valid {}h", role, valid_hours))
+        .save()
+        .await;
+    Ok(invite)
+}
+
+#[delete("/api/invites/delete", auth: AdminSession)]
+pub async fn delete_invite(token: String) -> Result<(), ServerFnError> {
+    Invite::delete(&token).await.map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::InviteDeleted)
+        .by(&auth.0)
+        .save()
+        .await;
+    Ok(())
+}
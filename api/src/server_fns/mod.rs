@@ -1,20 +1,34 @@
 use dioxus::prelude::*;
 
+pub mod api_token;
+pub mod audit;
 pub mod auth;
+pub mod discovery;
 pub mod download;
+pub mod download_limits;
 pub mod folder;
 pub mod guard;
+pub mod invite;
+pub mod playlist;
 pub mod search;
 pub mod settings;
+pub mod slskd;
 pub mod system;
 pub mod user;
 
+pub use api_token::*;
+pub use audit::*;
 pub use auth::*;
+pub use discovery::*;
 pub use download::*;
+pub use download_limits::*;
 pub use folder::*;
 pub use guard::*;
+pub use invite::*;
+pub use playlist::*;
 pub use search::*;
 pub use settings::*;
+pub use slskd::*;
 pub use system::*;
 pub use user::*;
 
@@ -0,0 +1,417 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use shared::playlist::{PlaylistFormat, PlaylistImportReport};
+
+#[cfg(feature = "server")]
+use super::{download::queue_download, folder::ensure_writable_folder, server_error};
+#[cfg(feature = "server")]
+use crate::globals::{PlaylistJob, PLAYLIST_JOBS};
+#[cfg(feature = "server")]
+use crate::models::app_config::AppConfig;
+#[cfg(feature = "server")]
+use crate::services::{download_backend, metadata_provider, music_importer};
+#[cfg(feature = "server")]
+use crate::{AuthSession, MemberSession};
+#[cfg(feature = "server")]
+use dioxus::logger::tracing::{info, warn};
+#[cfg(feature = "server")]
+use shared::download::SearchState;
+#[cfg(feature = "server")]
+use shared::metadata::{SearchResult, Track};
+#[cfg(feature = "server")]
+use shared::playlist::{PlaylistEntry, PlaylistItemReport, PlaylistItemStatus};
+#[cfg(feature = "server")]
+use std::collections::HashSet;
+#[cfg(feature = "server")]
+use std::time::Duration;
+
+/// Maximum number of entries accepted in a single playlist import.
+#[cfg(feature = "server")]
+pub(crate) const MAX_PLAYLIST_ENTRIES: usize = 500;
+
+/// How long to wait for download search results for a single entry.
+#[cfg(feature = "server")]
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(45);
+
+#[cfg(feature = "server")]
+const SEARCH_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistImportRequest {
+    /// Raw playlist content (pasted or uploaded)
+    #[serde(default)]
+    pub content: Option<String>,
+    /// URL to fetch the playlist from, used when `content` is empty
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Format override, detected from the content or URL when missing
+    #[serde(default)]
+    pub format: Option<PlaylistFormat>,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub target_folder: String,
+    #[serde(default)]
+    pub provider: Option<String>,
+}
+
+/// Parse a playlist and start importing it in the background.
+///
+/// Every entry is resolved through the metadata provider, skipped if it is already in
+/// the target library, and otherwise searched for and queued on the download backend.
+/// The returned report can be polled with [`get_playlist_import`].
+#[post("/api/playlists/import", auth: MemberSession)]
+pub async fn import_playlist(
+    req: PlaylistImportRequest,
+) -> Result<PlaylistImportReport, ServerFnError> {
+    let claims = auth.0;
+    ensure_writable_folder(&claims.sub, &req.target_folder).await?;
+
+    let content = req.content.as_deref().map(str::trim).unwrap_or_default();
+    let url = req.url.as_deref().map(str::trim).unwrap_or_default();
+
+    let entries = if !content.is_empty() {
+        soulbeet::playlist::parse_playlist(content, req.format).map_err(server_error)?
+    } else if !url.is_empty() {
+        soulbeet::playlist::fetch_playlist(url, req.format)
+            .await
+            .map_err(server_error)?
+    } else {
+        return Err(server_error("Paste a playlist or enter a URL"));
+    };
+
+    if entries.is_empty() {
+        return Err(server_error("No tracks found in the playlist"));
+    }
+    if entries.len() > MAX_PLAYLIST_ENTRIES {
+        return Err(server_error(format!(
+            "Playlist has {} tracks, the limit is {}",
+            entries.len(),
+            MAX_PLAYLIST_ENTRIES
+        )));
+    }
+
+    Ok(start_playlist_job(
+        &claims.username,
+        req.name,
+        req.target_folder,
+        req.provider,
+        entries,
+    )
+    .await)
+}
+
+/// Register a playlist import job and process it in the background.
+///
+/// Also used by discovery to queue suggested tracks through the same pipeline.
+#[cfg(feature = "server")]
+pub(crate) async fn start_playlist_job(
+    username: &str,
+    name: Option<String>,
+    target_folder: String,
+    provider: Option<String>,
+    entries: Vec<PlaylistEntry>,
+) -> PlaylistImportReport {
+    let report = PlaylistImportReport {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.filter(|n| !n.trim().is_empty()),
+        target_folder,
+        items: entries
+            .into_iter()
+            .map(|entry| PlaylistItemReport {
+                entry,
+                status: PlaylistItemStatus::Pending,
+                matched: false,
+                detail: None,
+            })
+            .collect(),
+        finished: false,
+        error: None,
+    };
+
+    {
+        let mut jobs = PLAYLIST_JOBS.write().await;
+        // Only keep the latest finished job per user around
+        jobs.retain(|_, job| job.owner != username || !job.report.finished);
+        jobs.insert(
+            report.id.clone(),
+            PlaylistJob {
+                owner: username.to_string(),
+                report: report.clone(),
+            },
+        );
+    }
+
+    info!(
+        "User {} started playlist import {} ({} tracks)",
+        username,
+        report.id,
+        report.total()
+    );
+
+    let job_id = report.id.clone();
+    let username = username.to_string();
+    tokio::spawn(async move {
+        run_import(job_id, username, provider).await;
+    });
+
+    report
+}
+
+/// Current progress of a playlist import started by the caller.
+#[post("/api/playlists/import/status", auth: AuthSession)]
+pub async fn get_playlist_import(job_id: String) -> Result<PlaylistImportReport, ServerFnError> {
+    let jobs = PLAYLIST_JOBS.read().await;
+    match jobs.get(&job_id) {
+        Some(job) if job.owner == auth.0.username => Ok(job.report.clone()),
+        _ => Err(server_error("Playlist import not found")),
+    }
+}
+
+#[cfg(feature = "server")]
+fn normalize(s: &str) -> String {
+    s.to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+#[cfg(feature = "server")]
+fn track_key(artist: &str, title: &str) -> String {
+    format!("{}|{}", normalize(artist), normalize(title))
+}
+
+#[cfg(feature = "server")]
+async fn update_item(
+    job_id: &str,
+    index: usize,
+    status: PlaylistItemStatus,
+    matched: Option<bool>,
+    detail: Option<String>,
+) {
+    let mut jobs = PLAYLIST_JOBS.write().await;
+    if let Some(item) = jobs
+        .get_mut(job_id)
+        .and_then(|job| job.report.items.get_mut(index))
+    {
+        item.status = status;
+        if let Some(matched) = matched {
+            item.matched = matched;
+        }
+        if detail.is_some() {
+            item.detail = detail;
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+async fn finish_job(job_id: &str, error: Option<String>) {
+    let mut jobs = PLAYLIST_JOBS.write().await;
+    if let Some(job) = jobs.get_mut(job_id) {
+        job.report.finished = true;
+        job.report.error = error;
+    }
+}
+
+/// Resolve a playlist entry to a track through the metadata provider.
+///
+/// Prefers an MBID match, then an exact (normalized) artist and title match. Returns
+/// `None` when the provider has nothing that matches.
+#[cfg(feature = "server")]
+async fn resolve_entry(provider: Option<&str>, entry: &PlaylistEntry) -> Option<Track> {
+    let provider = match metadata_provider(provider).await {
+        Ok(provider) => provider,
+        Err(e) => {
+            warn!("Metadata provider not available: {}", e);
+            return None;
+        }
+    };
+
+    let results = match provider
+        .search_tracks(Some(&entry.artist), &entry.title, 5)
+        .await
+    {
+        Ok(results) => results,
+        Err(e) => {
+            warn!(
+                "Track lookup failed for {} - {}: {}",
+                entry.artist, entry.title, e
+            );
+            return None;
+        }
+    };
+
+    let tracks: Vec<Track> = results
+        .into_iter()
+        .filter_map(|r| match r {
+            SearchResult::Track(t) => Some(t),
+            SearchResult::Album(_) => None,
+        })
+        .collect();
+
+    if let Some(mbid) = &entry.mbid {
+        if let Some(track) = tracks.iter().find(|t| t.mbid.as_ref() == Some(mbid)) {
+            return Some(track.clone());
+        }
+    }
+
+    let wanted = track_key(&entry.artist, &entry.title);
+    tracks
+        .into_iter()
+        .find(|t| track_key(&t.artist, &t.title) == wanted)
+}
+
+/// Track to search for when the metadata provider could not match an entry.
+#[cfg(feature = "server")]
+fn entry_as_track(entry: &PlaylistEntry) -> Track {
+    Track {
+        id: entry.mbid.clone().unwrap_or_default(),
+        title: entry.title.clone(),
+        artist: entry.artist.clone(),
+        album_id: None,
+        album_title: entry.album.clone(),
+        release_date: None,
+        duration: None,
+        mbid: entry.mbid.clone(),
+        release_mbid: None,
+        sources: vec![],
+    }
+}
+
+/// Search the download backend for a track and queue the best candidate.
+///
+/// Returns a short description of the chosen file, or `Ok(None)` when nothing
+/// was found.
+#[cfg(feature = "server")]
+async fn search_and_queue(
+    username: &str,
+    target_folder: &str,
+    track: Track,
+) -> Result<Option<String>, String> {
+    let backend = download_backend(None)
+        .await
+        .map_err(|e| format!("Download backend not available: {}", e))?;
+
+    let options = AppConfig::search_defaults().await?;
+    let search_id = backend
+        .start_search(None, &[track], &options)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let started = std::time::Instant::now();
+    let result = loop {
+        tokio::time::sleep(SEARCH_POLL_INTERVAL).await;
+        let result = backend
+            .poll_search(&search_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        if result.state != SearchState::InProgress || started.elapsed() >= SEARCH_TIMEOUT {
+            break result;
+        }
+    };
+
+    let best = result
+        .groups
+        .into_iter()
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+        .and_then(|group| {
+            group
+                .items
+                .into_iter()
+                .max_by(|a, b| a.quality_score.total_cmp(&b.quality_score))
+        });
+
+    let Some(item) = best else {
+        return Ok(None);
+    };
+
+    let detail = format!("{} from {}", item.quality, item.source);
+    let queued = queue_download(username, vec![item], target_folder, None, None, false)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match queued.into_iter().find_map(|q| q.error) {
+        Some(error) => Err(error),
+        None => Ok(Some(detail)),
+    }
+}
+
+/// Process every entry of a playlist import job, updating its report as it goes.
+#[cfg(feature = "server")]
+async fn run_import(job_id: String, username: String, provider: Option<String>) {
+    let (entries, target_folder) = {
+        let jobs = PLAYLIST_JOBS.read().await;
+        let Some(job) = jobs.get(&job_id) else {
+            return;
+        };
+        let entries: Vec<PlaylistEntry> =
+            job.report.items.iter().map(|i| i.entry.clone()).collect();
+        (entries, job.report.target_folder.clone())
+    };
+
+    let owned: HashSet<String> = match music_importer(None).await {
+        Ok(importer) => match importer
+            .library_tracks(std::path::Path::new(&target_folder))
+            .await
+        {
+            Ok(tracks) => tracks
+                .iter()
+                .map(|t| track_key(&t.artist, &t.title))
+                .collect(),
+            Err(e) => {
+                warn!("Could not read library {}: {}", target_folder, e);
+                HashSet::new()
+            }
+        },
+        Err(e) => {
+            finish_job(&job_id, Some(e)).await;
+            return;
+        }
+    };
+
+    for (index, entry) in entries.iter().enumerate() {
+        update_item(&job_id, index, PlaylistItemStatus::Resolving, None, None).await;
+
+        let resolved = resolve_entry(provider.as_deref(), entry).await;
+        let matched = resolved.is_some();
+        let track = resolved.unwrap_or_else(|| entry_as_track(entry));
+
+        if owned.contains(&track_key(&track.artist, &track.title))
+            || owned.contains(&track_key(&entry.artist, &entry.title))
+        {
+            update_item(
+                &job_id,
+                index,
+                PlaylistItemStatus::AlreadyOwned,
+                Some(matched),
+                None,
+            )
+            .await;
+            continue;
+        }
+
+        update_item(
+            &job_id,
+            index,
+            PlaylistItemStatus::Searching,
+            Some(matched),
+            None,
+        )
+        .await;
+
+        let (status, detail) = match search_and_queue(&username, &target_folder, track).await {
+            Ok(Some(detail)) => (PlaylistItemStatus::Queued, Some(detail)),
+            Ok(None) => (PlaylistItemStatus::NotFound, None),
+            Err(e) => {
+                warn!(
+                    "Playlist entry {} - {} failed: {}",
+                    entry.artist, entry.title, e
+                );
+                (PlaylistItemStatus::Failed(e), None)
+            }
+        };
+        update_item(&job_id, index, status, None, detail).await;
+    }
+
+    info!("Playlist import {} finished", job_id);
+    finish_job(&job_id, None).await;
+}
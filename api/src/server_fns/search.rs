@@ -1,12 +1,20 @@
+use dioxus::fullstack::{WebSocketOptions, Websocket};
 use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
 use shared::{
-    download::{DownloadQuery, SearchResult as DownloadSearchResult},
+    download::{DownloadQuery, SearchResult as DownloadSearchResult, SharedFolder},
     metadata::{AlbumWithTracks, Provider, SearchResults},
 };
 
 #[cfg(feature = "server")]
-use crate::{server_fns::server_error, AuthSession};
+use dioxus::logger::tracing::{info, warn};
+#[cfg(feature = "server")]
+use shared::download::SearchState;
+
+#[cfg(feature = "server")]
+use crate::{server_fns::server_error, SearchSession};
+#[cfg(feature = "server")]
+use crate::models::app_config::AppConfig;
 #[cfg(feature = "server")]
 use crate::services::{download_backend, metadata_provider};
 
@@ -32,7 +40,26 @@ pub struct PollQuery {
     pub backend: Option<String>,
 }
 
-#[post("/api/metadata/search/album", _: AuthSession)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BrowseQuery {
+    /// Source to browse, e.g. the username of a search result
+    pub source: String,
+    #[serde(default)]
+    pub backend: Option<String>,
+}
+
+/// Sent by the client on a search stream.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SearchStreamCommand {
+    /// Stop the search, the stream closes afterwards
+    Cancel,
+}
+
+/// How often a streamed search picks up new responses.
+#[cfg(feature = "server")]
+const SEARCH_STREAM_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+#[post("/api/metadata/search/album", _: SearchSession)]
 pub async fn search_album(input: SearchQuery) -> Result<SearchResults, ServerFnError> {
     let provider = metadata_provider(input.provider.as_deref())
         .await
@@ -50,7 +77,7 @@ pub async fn search_album(input: SearchQuery) -> Result<SearchResults, ServerFnE
     })
 }
 
-#[post("/api/metadata/search/track", _: AuthSession)]
+#[post("/api/metadata/search/track", _: SearchSession)]
 pub async fn search_track(input: SearchQuery) -> Result<SearchResults, ServerFnError> {
     let provider = metadata_provider(input.provider.as_deref())
         .await
@@ -68,7 +95,7 @@ pub async fn search_track(input: SearchQuery) -> Result<SearchResults, ServerFnE
     })
 }
 
-#[post("/api/metadata/album", _: AuthSession)]
+#[post("/api/metadata/album", _: SearchSession)]
 pub async fn find_album(input: AlbumQuery) -> Result<AlbumWithTracks, ServerFnError> {
     let provider_str = input.provider.map(|p| p.to_string());
     let provider = metadata_provider(provider_str.as_deref())
@@ -78,19 +105,22 @@ pub async fn find_album(input: AlbumQuery) -> Result<AlbumWithTracks, ServerFnEr
     provider.get_album(&input.id).await.map_err(server_error)
 }
 
-#[post("/api/download/search/start", _: AuthSession)]
+#[post("/api/download/search/start", _: SearchSession)]
 pub async fn start_download_search(data: DownloadQuery) -> Result<String, ServerFnError> {
     let backend = download_backend(data.backend.as_deref())
         .await
         .map_err(|e| server_error(format!("download backend not available: {}", e)))?;
 
+    let defaults = AppConfig::search_defaults().await.map_err(server_error)?;
+    let options = data.options.or(&defaults);
+
     backend
-        .start_search(data.album.as_ref(), &data.tracks)
+        .start_search(data.album.as_ref(), &data.tracks, &options)
         .await
         .map_err(server_error)
 }
 
-#[post("/api/download/search/poll", _: AuthSession)]
+#[post("/api/download/search/poll", _: SearchSession)]
 pub async fn poll_download_search(input: PollQuery) -> Result<DownloadSearchResult, ServerFnError> {
     let backend = download_backend(input.backend.as_deref())
         .await
@@ -101,3 +131,71 @@ pub async fn poll_download_search(input: PollQuery) -> Result<DownloadSearchResu
         .await
         .map_err(server_error)
 }
+
+/// WebSocket endpoint streaming the results of a download search.
+///
+/// Each message holds the groups changed since the previous one, the last one has a
+/// final state. The search is cancelled when the client sends
+/// [`SearchStreamCommand::Cancel`] or disconnects before it is done.
+#[get("/api/download/search/stream?search_id&backend", _: SearchSession)]
+pub async fn download_search_stream(
+    search_id: String,
+    backend: Option<String>,
+    options: WebSocketOptions,
+) -> Result<Websocket<SearchStreamCommand, DownloadSearchResult>, ServerFnError> {
+    let backend = download_backend(backend.as_deref())
+        .await
+        .map_err(|e| server_error(format!("download backend not available: {}", e)))?;
+
+    Ok(options.on_upgrade(move |mut socket| async move {
+        let mut revision = 0;
+        let mut interval = tokio::time::interval(SEARCH_STREAM_INTERVAL);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let update = match backend.search_changes(&search_id, revision).await {
+                        Ok(update) => update,
+                        Err(e) => {
+                            warn!("Search {} failed: {}", search_id, e);
+                            break;
+                        }
+                    };
+                    let done = update.state != SearchState::InProgress;
+                    let changed = done || !update.groups.is_empty();
+                    revision = update.revision;
+
+                    if changed && socket.send(update).await.is_err() {
+                        info!("Search stream closed (client disconnected)");
+                        if !done {
+                            let _ = backend.cancel_search(&search_id).await;
+                        }
+                        break;
+                    }
+                    if done {
+                        break;
+                    }
+                }
+                result = socket.recv() => {
+                    match result {
+                        Ok(SearchStreamCommand::Cancel) => info!("Search {} cancelled", search_id),
+                        Err(_) => info!("Search stream closed (client disconnected)"),
+                    }
+                    if let Err(e) = backend.cancel_search(&search_id).await {
+                        warn!("Failed to cancel search {}: {}", search_id, e);
+                    }
+                    break;
+                }
+            }
+        }
+    }))
+}
+
+#[post("/api/download/browse", _: SearchSession)]
+pub async fn browse_source(input: BrowseQuery) -> Result<Vec<SharedFolder>, ServerFnError> {
+    let backend = download_backend(input.backend.as_deref())
+        .await
+        .map_err(|e| server_error(format!("download backend not available: {}", e)))?;
+
+    backend.browse(&input.source).await.map_err(server_error)
+}
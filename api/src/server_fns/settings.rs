@@ -1,15 +1,29 @@
 use dioxus::prelude::*;
+use shared::download::{ScheduleRule, SearchOptions};
 
+pub use crate::auth::RegistrationMode;
+pub use crate::models::app_config::SlskdInstance;
 pub use crate::models::user_settings::{UpdateUserSettings, UserSettings};
 
 #[cfg(feature = "server")]
 use crate::models::app_config::AppConfig;
 #[cfg(feature = "server")]
-use crate::AuthSession;
+use crate::{AdminSession, AuthSession};
 
 #[cfg(feature = "server")]
 use super::server_error;
 
+/// Current registration mode, disabled unless an admin changed it.
+#[cfg(feature = "server")]
+pub(crate) async fn registration_mode() -> Result<RegistrationMode, ServerFnError> {
+    use crate::models::app_config::keys;
+
+    let mode = AppConfig::get(keys::REGISTRATION_MODE)
+        .await
+        .map_err(server_error)?;
+    Ok(mode.and_then(|m| m.parse().ok()).unwrap_or_default())
+}
+
 /// Get current user's settings
 #[get("/api/settings", auth: AuthSession)]
 pub async fn get_user_settings() -> Result<UserSettings, ServerFnError> {
@@ -49,41 +63,92 @@ pub struct ProviderInfo {
     pub available: bool,
 }
 
+/// App config as edited by admins. On update `None` leaves a setting unchanged and
+/// an empty string removes it.
+///
+/// API keys are write-only: they are always `None` when read, the `*_set` flags
+/// tell whether one is stored.
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Default)]
 pub struct AppConfigValues {
     pub lastfm_api_key: Option<String>,
+    #[serde(default)]
+    pub lastfm_api_key_set: bool,
     pub slskd_url: Option<String>,
     pub slskd_api_key: Option<String>,
+    #[serde(default)]
+    pub slskd_api_key_set: bool,
+    /// slskd instances besides the one above, their API keys are write-only too
+    #[serde(default)]
+    pub slskd_instances: Option<Vec<SlskdInstance>>,
+    #[serde(default)]
+    pub library_root: Option<String>,
+    #[serde(default)]
+    pub registration_mode: Option<RegistrationMode>,
+    /// Options of searches that leave them unset, empty options restore the built-in ones
+    #[serde(default)]
+    pub search_defaults: Option<SearchOptions>,
+    /// Daily windows pausing or limiting downloads, an empty list removes the schedule
+    #[serde(default)]
+    pub download_schedule: Option<Vec<ScheduleRule>>,
 }
 
-#[get("/api/config", _: AuthSession)]
+#[get("/api/config", _: AdminSession)]
 pub async fn get_app_config() -> Result<AppConfigValues, ServerFnError> {
     use crate::models::app_config::keys;
 
-    let lastfm_api_key = AppConfig::get(keys::LASTFM_API_KEY)
+    let lastfm_api_key_set = AppConfig::is_set(keys::LASTFM_API_KEY)
         .await
         .map_err(server_error)?;
     let slskd_url = AppConfig::get(keys::SLSKD_URL)
         .await
         .map_err(server_error)?;
-    let slskd_api_key = AppConfig::get(keys::SLSKD_API_KEY)
+    let slskd_api_key_set = AppConfig::is_set(keys::SLSKD_API_KEY)
         .await
         .map_err(server_error)?;
+    let slskd_instances = AppConfig::slskd_instances()
+        .await
+        .map_err(server_error)?
+        .into_iter()
+        .map(|instance| SlskdInstance {
+            api_key_set: instance.api_key.as_deref().is_some_and(|k| !k.is_empty()),
+            api_key: None,
+            ..instance
+        })
+        .collect();
+    let library_root = AppConfig::get(keys::LIBRARY_ROOT)
+        .await
+        .map_err(server_error)?;
+    let registration_mode = Some(registration_mode().await?);
+    let search_defaults = Some(AppConfig::search_defaults().await.map_err(server_error)?);
+    let download_schedule = Some(AppConfig::download_schedule().await.map_err(server_error)?);
 
     Ok(AppConfigValues {
-        lastfm_api_key,
+        lastfm_api_key: None,
+        lastfm_api_key_set,
         slskd_url,
-        slskd_api_key,
+        slskd_api_key: None,
+        slskd_api_key_set,
+        slskd_instances: Some(slskd_instances),
+        library_root,
+        registration_mode,
+        search_defaults,
+        download_schedule,
     })
 }
 
-#[post("/api/config", _: AuthSession)]
+#[post("/api/config", auth: AdminSession)]
 pub async fn update_app_config(config: AppConfigValues) -> Result<AppConfigValues, ServerFnError> {
-    use crate::models::app_config::keys;
+    use crate::models::app_config::{keys, MASKED};
+    use crate::models::audit::{AuditEvent, AuditRecord};
+    use crate::server_fns::download::schedule;
     use crate::services::reload_providers;
 
     async fn set_or_delete(key: &str, value: &Option<String>) -> Result<(), ServerFnError> {
         if let Some(v) = value {
+            // A masked secret sent back unchanged
+            if keys::is_secret(key) && v == MASKED {
+                return Ok(());
+            }
             if v.is_empty() {
                 AppConfig::delete(key).await.map_err(server_error)?;
             } else {
@@ -93,11 +158,117 @@ pub async fn update_app_config(config: AppConfigValues) -> Result<AppConfigValue
         Ok(())
     }
 
+    if let Some(root) = config.library_root.as_deref().filter(|r| !r.is_empty()) {
+        if !std::path::Path::new(root).is_absolute() {
+            return Err(server_error("Library root must be an absolute path"));
+        }
+    }
+
+    if let Some(instances) = &config.slskd_instances {
+        let stored = AppConfig::slskd_instances().await.map_err(server_error)?;
+        let instances = merge_slskd_instances(instances, &stored)?;
+        AppConfig::set_slskd_instances(&instances)
+            .await
+            .map_err(server_error)?;
+    }
+
     set_or_delete(keys::LASTFM_API_KEY, &config.lastfm_api_key).await?;
     set_or_delete(keys::SLSKD_URL, &config.slskd_url).await?;
     set_or_delete(keys::SLSKD_API_KEY, &config.slskd_api_key).await?;
+    set_or_delete(keys::LIBRARY_ROOT, &config.library_root).await?;
+    set_or_delete(
+        keys::REGISTRATION_MODE,
+        &config.registration_mode.map(|m| m.to_string()),
+    )
+    .await?;
+    if let Some(options) = &config.search_defaults {
+        AppConfig::set_search_defaults(options)
+            .await
+            .map_err(server_error)?;
+    }
+    if let Some(rules) = &config.download_schedule {
+        for rule in rules {
+            rule.validate().map_err(server_error)?;
+        }
+        AppConfig::set_download_schedule(rules)
+            .await
+            .map_err(server_error)?;
+        schedule::refresh().await;
+    }
+
+    // Only the names of the settings, values can be secrets
+    let changed: Vec<&str> = [
+        (keys::LASTFM_API_KEY, config.lastfm_api_key.is_some()),
+        (keys::SLSKD_URL, config.slskd_url.is_some()),
+        (keys::SLSKD_API_KEY, config.slskd_api_key.is_some()),
+        (keys::SLSKD_INSTANCES, config.slskd_instances.is_some()),
+        (keys::LIBRARY_ROOT, config.library_root.is_some()),
+        (keys::REGISTRATION_MODE, config.registration_mode.is_some()),
+        (
+            keys::DEFAULT_SEARCH_OPTIONS,
+            config.search_defaults.is_some(),
+        ),
+        (keys::DOWNLOAD_SCHEDULE, config.download_schedule.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(key, set)| set.then_some(key))
+    .collect();
+    AuditRecord::new(AuditEvent::ConfigChanged)
+        .by(&auth.0)
+        .details(changed.join(", "))
+        .save()
+        .await;
 
     reload_providers().await;
 
     get_app_config().await
 }
+
+/// Check edited slskd instances and give the ones without a new API key their
+/// stored key.
+#[cfg(feature = "server")]
+fn merge_slskd_instances(
+    instances: &[SlskdInstance],
+    stored: &[SlskdInstance],
+) -> Result<Vec<SlskdInstance>, ServerFnError> {
+    use crate::services::downloaders::PRIMARY_SLSKD_INSTANCE;
+
+    let mut merged: Vec<SlskdInstance> = Vec::with_capacity(instances.len());
+    for instance in instances {
+        let name = instance.name.trim();
+        if name.is_empty() || instance.url.trim().is_empty() {
+            return Err(server_error("slskd instances need a name and a URL"));
+        }
+        if name == PRIMARY_SLSKD_INSTANCE || merged.iter().any(|i| i.name == name) {
+            return Err(server_error(format!(
+                "slskd instance name '{}' is already used",
+                name
+            )));
+        }
+
+        let api_key = instance
+            .api_key
+            .clone()
+            .filter(|k| !k.is_empty())
+            .or_else(|| {
+                stored
+                    .iter()
+                    .find(|s| s.name == name)
+                    .and_then(|s| s.api_key.clone())
+            });
+        if api_key.is_none() {
+            return Err(server_error(format!(
+                "slskd instance '{}' needs an API key",
+                name
+            )));
+        }
+
+        merged.push(SlskdInstance {
+            name: name.to_string(),
+            url: instance.url.trim().to_string(),
+            api_key,
+            api_key_set: false,
+        });
+    }
+    Ok(merged)
+}
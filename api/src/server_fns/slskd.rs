@@ -0,0 +1,116 @@
+use dioxus::prelude::*;
+use shared::system::SlskdOverview;
+
+#[cfg(feature = "server")]
+use shared::slskd::{DownloadState, TransferCounts};
+#[cfg(feature = "server")]
+use soulbeet::slskd::SoulseekClient;
+#[cfg(feature = "server")]
+use std::sync::Arc;
+
+#[cfg(feature = "server")]
+use super::{download::transfers, server_error};
+#[cfg(feature = "server")]
+use crate::models::audit::{AuditEvent, AuditRecord};
+#[cfg(feature = "server")]
+use crate::services::slskd_instances;
+#[cfg(feature = "server")]
+use crate::AdminSession;
+
+#[cfg(feature = "server")]
+async fn overview(name: String, client: Arc<SoulseekClient>) -> SlskdOverview {
+    let state = async {
+        let server = client.server_state().await?;
+        let shares = client.share_state().await?;
+        let downloads = client.get_all_downloads().await?;
+        let uploads = client.get_all_uploads().await?;
+        Ok::<_, soulbeet::error::SoulseekError>((server, shares, downloads, uploads))
+    };
+
+    match state.await {
+        Ok((server, shares, downloads, uploads)) => SlskdOverview {
+            instance: name,
+            error: None,
+            server,
+            shares,
+            downloads: TransferCounts::from_entries(&downloads),
+            uploads: TransferCounts::from_entries(&uploads),
+            active_uploads: uploads
+                .into_iter()
+                .filter(|u| u.state.contains(&DownloadState::InProgress))
+                .collect(),
+        },
+        Err(e) => SlskdOverview {
+            instance: name,
+            error: Some(e.to_string()),
+            ..Default::default()
+        },
+    }
+}
+
+#[cfg(feature = "server")]
+async fn instance(name: &str) -> Result<Arc<SoulseekClient>, ServerFnError> {
+    slskd_instances()
+        .await
+        .map_err(server_error)?
+        .into_iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, client)| client)
+        .ok_or_else(|| server_error(format!("Unknown slskd instance: {}", name)))
+}
+
+/// Connection, shares and transfers of every slskd instance.
+#[get("/api/slskd", _: AdminSession)]
+pub async fn get_slskd_overview() -> Result<Vec<SlskdOverview>, ServerFnError> {
+    // Queried concurrently, an unreachable instance waits for its timeout
+    let tasks: Vec<_> = slskd_instances()
+        .await
+        .map_err(server_error)?
+        .into_iter()
+        .map(|(name, client)| tokio::spawn(overview(name, client)))
+        .collect();
+
+    let mut overviews = Vec::new();
+    for task in tasks {
+        overviews.push(task.await.map_err(server_error)?);
+    }
+    Ok(overviews)
+}
+
+/// Have an slskd instance scan its shared folders again.
+#[post("/api/slskd/rescan", auth: AdminSession)]
+pub async fn rescan_slskd_shares(name: String) -> Result<(), ServerFnError> {
+    let client = instance(&name).await?;
+    client.rescan_shares().await.map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::SlskdSharesRescanned)
+        .by(&auth.0)
+        .target(&name)
+        .save()
+        .await;
+    Ok(())
+}
+
+/// Remove the finished downloads from an slskd instance's transfer list.
+#[post("/api/slskd/clear", auth: AdminSession)]
+pub async fn clear_slskd_downloads(name: String) -> Result<(), ServerFnError> {
+    // Monitors read the final state of transfers from the list
+    if transfers::in_use() {
+        return Err(server_error(
+            "Downloads are still being monitored, try again once they are imported",
+        ));
+    }
+
+    let client = instance(&name).await?;
+    client
+        .clear_all_completed_downloads()
+        .await
+        .map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::SlskdDownloadsCleared)
+        .by(&auth.0)
+        .target(&name)
+        .save()
+        .await;
+    Ok(())
+}
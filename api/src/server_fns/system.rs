@@ -2,12 +2,12 @@ use dioxus::prelude::*;
 use shared::system::{AvailableBackends, SystemHealth};
 
 #[cfg(feature = "server")]
-use shared::system::BackendInfo;
+use shared::system::{BackendInfo, InstanceHealth};
 
 #[cfg(feature = "server")]
 use crate::services::{
-    available_download_backends, available_importers, available_metadata_providers,
-    download_backend, music_importer,
+    available_download_backends, available_importers, available_metadata_providers, music_importer,
+    slskd_instances,
 };
 #[cfg(feature = "server")]
 use crate::AuthSession;
@@ -16,10 +16,27 @@ use crate::AuthSession;
 pub async fn get_system_health() -> Result<SystemHealth, ServerFnError> {
     #[cfg(feature = "server")]
     {
-        let downloader_online = match download_backend(None).await {
-            Ok(backend) => backend.health_check().await,
-            Err(_) => false,
-        };
+        // Instances are checked concurrently, an unreachable one waits for its timeout
+        let checks: Vec<_> = slskd_instances()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, client)| {
+                tokio::spawn(async move {
+                    InstanceHealth {
+                        online: client.check_connection().await,
+                        name,
+                    }
+                })
+            })
+            .collect();
+        let mut download_instances = Vec::new();
+        for check in checks {
+            if let Ok(health) = check.await {
+                download_instances.push(health);
+            }
+        }
+        let downloader_online = download_instances.iter().any(|i| i.online);
 
         let beets_ready = match music_importer(None).await {
             Ok(importer) => importer.health_check().await,
@@ -29,6 +46,7 @@ pub async fn get_system_health() -> Result<SystemHealth, ServerFnError> {
         Ok(SystemHealth {
             downloader_online,
             beets_ready,
+            download_instances,
         })
     }
     #[cfg(not(feature = "server"))]
@@ -1,25 +1,123 @@
 #[cfg(feature = "server")]
 use super::server_error;
+use crate::auth::Role;
 use crate::models;
 #[cfg(feature = "server")]
-use crate::AuthSession;
+use crate::models::audit::{AuditEvent, AuditRecord};
+#[cfg(feature = "server")]
+use crate::{AdminSession, AuthSession};
 use dioxus::prelude::*;
 
-#[get("/api/users", _: AuthSession)]
+#[get("/api/users", _: AdminSession)]
 pub async fn get_users() -> Result<Vec<models::user::User>, ServerFnError> {
     models::user::User::get_all().await.map_err(server_error)
 }
 
-#[post("/api/users/password", _: AuthSession)]
+/// Create an account with the given role.
+#[post("/api/users", auth: AdminSession)]
+pub async fn create_user(
+    username: String,
+    password: String,
+    role: Role,
+) -> Result<models::user::User, ServerFnError> {
+    let user = models::user::User::create(&username, &password, role)
+        .await
+        .map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::UserCreated)
+        .by(&auth.0)
+        .target(&user.username)
+        .details(format!("role {}", role))
+        .save()
+        .await;
+    Ok(user)
+}
+
+/// Change a password. Admins can change any password, other users only their own.
+/// A password reset by an admin has to be changed again on the next login.
+#[post("/api/users/password", auth: AuthSession)]
 pub async fn update_user_password(user_id: String, password: String) -> Result<(), ServerFnError> {
-    models::user::User::update_password(&user_id, &password)
+    let claims = auth.0;
+
+    if user_id != claims.sub {
+        let current = models::user::User::get_by_id(&claims.sub)
+            .await
+            .map_err(server_error)?;
+        if !current.role.is_admin() {
+            return Err(server_error("Admin access required"));
+        }
+    }
+
+    let reset = user_id != claims.sub;
+    models::user::User::update_password(&user_id, &password, reset)
         .await
-        .map_err(server_error)
+        .map_err(server_error)?;
+
+    let target = models::user::User::get_by_id(&user_id)
+        .await
+        .map(|u| u.username)
+        .unwrap_or(user_id);
+    let event = if reset {
+        AuditEvent::PasswordReset
+    } else {
+        AuditEvent::PasswordChanged
+    };
+    AuditRecord::new(event)
+        .by(&claims)
+        .target(target)
+        .save()
+        .await;
+    Ok(())
 }
 
-#[delete("/api/users/delete", _: AuthSession)]
+#[post("/api/users/role", auth: AdminSession)]
+pub async fn update_user_role(user_id: String, role: Role) -> Result<(), ServerFnError> {
+    let user = models::user::User::get_by_id(&user_id)
+        .await
+        .map_err(server_error)?;
+
+    if user.role.is_admin() && !role.is_admin() {
+        if user_id == auth.0.sub {
+            return Err(server_error("You cannot remove your own admin role"));
+        }
+        let admins = models::user::User::count_admins()
+            .await
+            .map_err(server_error)?;
+        if admins <= 1 {
+            return Err(server_error("At least one admin is required"));
+        }
+    }
+
+    models::user::User::set_role(&user_id, role)
+        .await
+        .map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::RoleChanged)
+        .by(&auth.0)
+        .target(&user.username)
+        .details(format!("{} -> {}", user.role, role))
+        .save()
+        .await;
+    Ok(())
+}
+
+#[delete("/api/users/delete", auth: AdminSession)]
 pub async fn delete_user(user_id: String) -> Result<(), ServerFnError> {
+    if user_id == auth.0.sub {
+        return Err(server_error("You cannot delete your own account"));
+    }
+
+    let user = models::user::User::get_by_id(&user_id)
+        .await
+        .map_err(server_error)?;
     models::user::User::delete(&user_id)
         .await
-        .map_err(server_error)
+        .map_err(server_error)?;
+
+    AuditRecord::new(AuditEvent::UserDeleted)
+        .by(&auth.0)
+        .target(&user.username)
+        .save()
+        .await;
+    Ok(())
 }
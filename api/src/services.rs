@@ -5,22 +5,28 @@ use std::{collections::HashMap, sync::{Arc, LazyLock}};
 use soulbeet::{
     beets::BeetsImporter,
     musicbrainz::MusicBrainzProvider,
-    slskd::{DownloadConfig, SoulseekClientBuilder},
-    DownloadBackend, LastFmProvider, MetadataProvider, MusicImporter,
+    slskd::{DownloadConfig, SoulseekClient, SoulseekClientBuilder},
+    CoverArtService, DownloadBackend, LastFmProvider, LrclibProvider, LyricsProvider,
+    MergingMetadataProvider, MetadataProvider, MultiDownloadBackend, MusicImporter,
 };
 #[cfg(feature = "server")]
 use tokio::sync::RwLock;
 
+#[cfg(feature = "server")]
+use crate::config::CONFIG;
 #[cfg(feature = "server")]
 use crate::models::app_config::{keys, AppConfig};
 
 pub mod providers {
     pub const MUSICBRAINZ: &str = "musicbrainz";
     pub const LASTFM: &str = "lastfm";
+    pub const MERGED: &str = "merged";
 }
 
 pub mod downloaders {
     pub const SLSKD: &str = "slskd";
+    /// Name of the slskd instance configured with `slskd_url`
+    pub const PRIMARY_SLSKD_INSTANCE: &str = "main";
 }
 
 pub mod importers {
@@ -35,15 +41,42 @@ static METADATA_PROVIDERS: LazyLock<RwLock<HashMap<String, Arc<dyn MetadataProvi
 static DOWNLOAD_BACKENDS: LazyLock<RwLock<HashMap<String, Arc<dyn DownloadBackend>>>> =
     LazyLock::new(|| RwLock::new(HashMap::new()));
 
+/// slskd instances by name, the first one is the default for downloads.
+#[cfg(feature = "server")]
+type SlskdInstances = Vec<(String, Arc<SoulseekClient>)>;
+
+#[cfg(feature = "server")]
+static SLSKD_INSTANCES: LazyLock<RwLock<Option<SlskdInstances>>> =
+    LazyLock::new(|| RwLock::new(None));
+
 #[cfg(feature = "server")]
 static MUSIC_IMPORTERS: LazyLock<RwLock<HashMap<String, Arc<dyn MusicImporter>>>> =
     LazyLock::new(|| RwLock::new(HashMap::new()));
 
+#[cfg(feature = "server")]
+static COVER_ART: LazyLock<CoverArtService> =
+    LazyLock::new(|| CoverArtService::new(CONFIG.cover_cache_path().clone()));
+
+#[cfg(feature = "server")]
+pub fn cover_art_service() -> &'static CoverArtService {
+    &COVER_ART
+}
+
+#[cfg(feature = "server")]
+static LYRICS_PROVIDER: LazyLock<Arc<dyn LyricsProvider>> =
+    LazyLock::new(|| Arc::new(LrclibProvider::with_base_url(CONFIG.lyrics_api_url())));
+
+#[cfg(feature = "server")]
+pub fn lyrics_provider() -> Arc<dyn LyricsProvider> {
+    LYRICS_PROVIDER.clone()
+}
+
 #[cfg(feature = "server")]
 pub fn available_metadata_providers() -> Vec<(&'static str, &'static str)> {
     vec![
         (providers::MUSICBRAINZ, "MusicBrainz"),
         (providers::LASTFM, "Last.fm"),
+        (providers::MERGED, "All providers (merged)"),
     ]
 }
 
@@ -57,6 +90,15 @@ pub fn available_importers() -> Vec<(&'static str, &'static str)> {
     vec![(importers::BEETS, "Beets")]
 }
 
+/// Last.fm client for features beyond metadata search (discovery).
+#[cfg(feature = "server")]
+pub async fn lastfm_client() -> Result<LastFmProvider, String> {
+    match AppConfig::get(keys::LASTFM_API_KEY).await? {
+        Some(api_key) if !api_key.is_empty() => Ok(LastFmProvider::new(api_key)),
+        _ => Err("Last.fm API key not configured".to_string()),
+    }
+}
+
 #[cfg(feature = "server")]
 async fn init_metadata_provider(id: &str) -> Result<Arc<dyn MetadataProvider>, String> {
     match id {
@@ -69,38 +111,89 @@ async fn init_metadata_provider(id: &str) -> Result<Arc<dyn MetadataProvider>, S
             }
             Ok(Arc::new(LastFmProvider::new(api_key)))
         }
+        providers::MERGED => {
+            let mut inner: Vec<Box<dyn MetadataProvider>> =
+                vec![Box::new(MusicBrainzProvider::new())];
+            // Last.fm is optional here, merging with a single provider is still useful
+            if let Some(api_key) = AppConfig::get(keys::LASTFM_API_KEY)
+                .await?
+                .filter(|k| !k.is_empty())
+            {
+                inner.push(Box::new(LastFmProvider::new(api_key)));
+            }
+            Ok(Arc::new(MergingMetadataProvider::new(inner)))
+        }
         _ => Ok(Arc::new(MusicBrainzProvider::new())),
     }
 }
 
+#[cfg(feature = "server")]
+fn slskd_client(url: &str, api_key: &str) -> Result<Arc<SoulseekClient>, String> {
+    let client = SoulseekClientBuilder::new()
+        .base_url(url)
+        .api_key(api_key)
+        .download_config(DownloadConfig {
+            batch_size: 3,
+            batch_delay_ms: 3000,
+            max_retries: 3,
+            retry_base_delay_ms: 1000,
+        })
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    Ok(Arc::new(client))
+}
+
+/// Every configured slskd instance by name, the one of `slskd_url` first.
+#[cfg(feature = "server")]
+pub async fn slskd_instances() -> Result<SlskdInstances, String> {
+    if let Some(instances) = SLSKD_INSTANCES.read().await.as_ref() {
+        return Ok(instances.clone());
+    }
+
+    let mut instances = SlskdInstances::new();
+    let url = AppConfig::get(keys::SLSKD_URL).await?;
+    let api_key = AppConfig::get(keys::SLSKD_API_KEY).await?;
+    if let (Some(url), Some(api_key)) = (url, api_key) {
+        if !url.is_empty() && !api_key.is_empty() {
+            instances.push((
+                downloaders::PRIMARY_SLSKD_INSTANCE.to_string(),
+                slskd_client(&url, &api_key)?,
+            ));
+        }
+    }
+    for instance in AppConfig::slskd_instances().await? {
+        match instance.api_key.as_deref() {
+            Some(api_key) if !instance.url.is_empty() && !api_key.is_empty() => {
+                instances.push((instance.name, slskd_client(&instance.url, api_key)?));
+            }
+            _ => tracing::warn!(
+                "slskd instance '{}' has no URL or API key, skipping it",
+                instance.name
+            ),
+        }
+    }
+
+    if instances.is_empty() {
+        return Err("slskd not configured".to_string());
+    }
+    *SLSKD_INSTANCES.write().await = Some(instances.clone());
+    Ok(instances)
+}
+
 #[cfg(feature = "server")]
 async fn init_download_backend(id: &str) -> Result<Arc<dyn DownloadBackend>, String> {
     match id {
         downloaders::SLSKD => {
-            let url = AppConfig::get(keys::SLSKD_URL)
-                .await?
-                .ok_or("slskd URL not configured")?;
-            let api_key = AppConfig::get(keys::SLSKD_API_KEY)
+            let mut instances: Vec<(String, Arc<dyn DownloadBackend>)> = slskd_instances()
                 .await?
-                .ok_or("slskd API key not configured")?;
-
-            if url.is_empty() || api_key.is_empty() {
-                return Err("slskd not configured".to_string());
+                .into_iter()
+                .map(|(name, client)| (name, client as Arc<dyn DownloadBackend>))
+                .collect();
+            if instances.len() == 1 {
+                return Ok(instances.remove(0).1);
             }
-
-            let client = SoulseekClientBuilder::new()
-                .base_url(&url)
-                .api_key(&api_key)
-                .download_config(DownloadConfig {
-                    batch_size: 3,
-                    batch_delay_ms: 3000,
-                    max_retries: 3,
-                    retry_base_delay_ms: 1000,
-                })
-                .build()
-                .map_err(|e| e.to_string())?;
-
-            Ok(Arc::new(client))
+            Ok(Arc::new(MultiDownloadBackend::new(instances)))
         }
         _ => Err(format!("Unknown download backend: {}", id)),
     }
@@ -164,12 +257,11 @@ pub async fn music_importer(id: Option<&str>) -> Result<Arc<dyn MusicImporter>,
 pub async fn reload_providers() {
     METADATA_PROVIDERS.write().await.clear();
     DOWNLOAD_BACKENDS.write().await.clear();
+    *SLSKD_INSTANCES.write().await = None;
     MUSIC_IMPORTERS.write().await.clear();
 }
 
 #[cfg(feature = "server")]
 pub async fn is_slskd_configured() -> bool {
-    let url = AppConfig::get(keys::SLSKD_URL).await.ok().flatten();
-    let api_key = AppConfig::get(keys::SLSKD_API_KEY).await.ok().flatten();
-    matches!((url, api_key), (Some(u), Some(k)) if !u.is_empty() && !k.is_empty())
+    slskd_instances().await.is_ok()
 }
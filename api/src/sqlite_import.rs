@@ -0,0 +1,338 @@
+//! One-shot copy of an existing SQLite database into PostgreSQL.
+//!
+//! Run with `--import-sqlite <path>` while `DATABASE_URL` points at the new,
+//! empty PostgreSQL database. Users, folders and their shares, user settings,
+//! download limits and the app config are copied; sessions, API tokens, invites,
+//! the audit log and download usage are not.
+
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{PgPool, SqlitePool};
+
+use crate::config::CONFIG;
+use crate::db::{connect_postgres, is_postgres_url};
+use crate::models::app_config::AppConfig;
+use crate::models::folder::Folder;
+use crate::models::user::User;
+use crate::models::user_settings::UserSettings;
+
+/// Account seeded by the first migration, replaced by the imported users.
+const SEEDED_ADMIN_ID: &str = "00000000-0000-0000-0000-000000000000";
+
+type LimitsRow = (
+    String,
+    Option<i64>,
+    Option<i64>,
+    Option<i64>,
+    Option<i64>,
+    Option<i64>,
+);
+
+/// Copy the SQLite database at `path` into the configured PostgreSQL database.
+///
+/// Returns the number of rows copied per table.
+pub fn run(path: &str) -> Result<Vec<(&'static str, usize)>, String> {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?
+        .block_on(import(path))
+}
+
+async fn import(path: &str) -> Result<Vec<(&'static str, usize)>, String> {
+    let database_url = CONFIG.database_url();
+    if !is_postgres_url(database_url) {
+        return Err(
+            "DATABASE_URL must point at the PostgreSQL database to import into".to_string(),
+        );
+    }
+    if !std::path::Path::new(path).is_file() {
+        return Err(format!("{} does not exist", path));
+    }
+
+    let source = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(SqliteConnectOptions::new().filename(path))
+        .await
+        .map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    // Same as on startup, so databases of older versions have every column
+    sqlx::migrate!("./migrations/sqlite")
+        .run(&source)
+        .await
+        .map_err(|e| format!("Failed to migrate {}: {}", path, e))?;
+
+    let target = connect_postgres(database_url).await?;
+    check_empty(&target).await?;
+
+    copy(&source, &target).await.map_err(|e| e.to_string())
+}
+
+/// Refuse to import over data, only the seeded admin account may exist.
+///
+/// Settings are not checked, the imported ones replace those of a first start.
+async fn check_empty(target: &PgPool) -> Result<(), String> {
+    let users: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE id <> $1")
+        .bind(SEEDED_ADMIN_ID)
+        .fetch_one(target)
+        .await
+        .map_err(|e| e.to_string())?;
+    let folders: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM folders")
+        .fetch_one(target)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if users > 0 || folders > 0 {
+        return Err(
+            "The PostgreSQL database already has users or folders, import into a new database"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Copy every table in one transaction, so a failed import leaves nothing behind.
+async fn copy(
+    source: &SqlitePool,
+    target: &PgPool,
+) -> Result<Vec<(&'static str, usize)>, sqlx::Error> {
+    let users: Vec<User> = sqlx::query_as("SELECT * FROM users")
+        .fetch_all(source)
+        .await?;
+    let folders: Vec<Folder> = sqlx::query_as("SELECT * FROM folders")
+        .fetch_all(source)
+        .await?;
+    let shares: Vec<(String, String, bool)> =
+        sqlx::query_as("SELECT folder_id, user_id, can_write FROM folder_shares")
+            .fetch_all(source)
+            .await?;
+    let settings: Vec<UserSettings> = sqlx::query_as("SELECT * FROM user_settings")
+        .fetch_all(source)
+        .await?;
+    let limits: Vec<LimitsRow> = sqlx::query_as(
+        "SELECT user_id, max_concurrent, max_queued, daily_bytes, monthly_bytes, folder_bytes FROM user_download_limits",
+    )
+    .fetch_all(source)
+    .await?;
+    let config: Vec<AppConfig> = sqlx::query_as("SELECT * FROM app_config")
+        .fetch_all(source)
+        .await?;
+
+    let mut tx = target.begin().await?;
+
+    // The seeded admin and its settings, the source has its own
+    sqlx::query("DELETE FROM users").execute(&mut *tx).await?;
+
+    for user in &users {
+        sqlx::query(
+            "INSERT INTO users (id, username, password_hash, role, must_change_password, external_id) VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(&user.id)
+        .bind(&user.username)
+        .bind(&user.password_hash)
+        .bind(user.role)
+        .bind(user.must_change_password)
+        .bind(&user.external_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for folder in &folders {
+        sqlx::query(
+            "INSERT INTO folders (id, user_id, name, path, lyrics_enabled) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(&folder.id)
+        .bind(&folder.user_id)
+        .bind(&folder.name)
+        .bind(&folder.path)
+        .bind(folder.lyrics_enabled)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for (folder_id, user_id, can_write) in &shares {
+        sqlx::query(
+            "INSERT INTO folder_shares (folder_id, user_id, can_write) VALUES ($1, $2, $3)",
+        )
+        .bind(folder_id)
+        .bind(user_id)
+        .bind(can_write)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for s in &settings {
+        sqlx::query(
+            "INSERT INTO user_settings (user_id, default_metadata_provider, last_search_type, lastfm_username) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(&s.user_id)
+        .bind(&s.default_metadata_provider)
+        .bind(&s.last_search_type)
+        .bind(&s.lastfm_username)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for (user_id, max_concurrent, max_queued, daily, monthly, folder) in &limits {
+        sqlx::query(
+            "INSERT INTO user_download_limits (user_id, max_concurrent, max_queued, daily_bytes, monthly_bytes, folder_bytes) VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(user_id)
+        .bind(max_concurrent)
+        .bind(max_queued)
+        .bind(daily)
+        .bind(monthly)
+        .bind(folder)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    // Copied as stored: encrypted secrets stay readable with the same key. The
+    // imported values replace the ones written by a first start on the new database
+    for entry in &config {
+        sqlx::query(
+            "INSERT INTO app_config (key, value) VALUES ($1, $2) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        )
+            .bind(&entry.key)
+            .bind(&entry.value)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(vec![
+        ("users", users.len()),
+        ("folders", folders.len()),
+        ("folder_shares", shares.len()),
+        ("user_settings", settings.len()),
+        ("user_download_limits", limits.len()),
+        ("app_config", config.len()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Role;
+    use crate::models::test_db::PostgresSchema;
+
+    /// SQLite database of an older install with a bit of everything.
+    async fn source() -> SqlitePool {
+        let path =
+            std::env::temp_dir().join(format!("soulbeet-import-{}.db", uuid::Uuid::new_v4()));
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(path)
+                    .create_if_missing(true),
+            )
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations/sqlite")
+            .run(&pool)
+            .await
+            .unwrap();
+
+        for statement in [
+            "INSERT INTO users (id, username, password_hash, role, external_id) VALUES ('u1', 'alice', 'hash-a', 'member', 'oidc-alice')",
+            "INSERT INTO users (id, username, password_hash, role, must_change_password) VALUES ('u2', 'bob', 'hash-b', 'readonly', 1)",
+            "INSERT INTO folders (id, user_id, name, path, lyrics_enabled) VALUES ('f1', 'u1', 'Music', '/music/alice', 1)",
+            "INSERT INTO folder_shares (folder_id, user_id, can_write) VALUES ('f1', 'u2', 0)",
+            "INSERT INTO user_settings (user_id, default_metadata_provider, last_search_type, lastfm_username) VALUES ('u1', 'musicbrainz', 'track', 'alice_fm')",
+            "INSERT INTO user_download_limits (user_id, max_concurrent, daily_bytes) VALUES ('u2', 2, 5000000000)",
+            "INSERT INTO app_config (key, value) VALUES ('registration_mode', 'invite')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    #[tokio::test]
+    async fn copies_every_table() {
+        // Needs a PostgreSQL database, the import runs in a schema of its own
+        let Some(schema) = PostgresSchema::create().await else {
+            eprintln!("TEST_DATABASE_URL is not PostgreSQL, skipping the SQLite import test");
+            return;
+        };
+        let target = schema.pool.clone();
+
+        // Written on the first start of the new database
+        sqlx::query("INSERT INTO app_config (key, value) VALUES ('registration_mode', 'open')")
+            .execute(&target)
+            .await
+            .unwrap();
+
+        let source = source().await;
+        check_empty(&target).await.unwrap();
+        let counts = copy(&source, &target).await.unwrap();
+        assert_eq!(
+            counts,
+            vec![
+                ("users", 3),
+                ("folders", 1),
+                ("folder_shares", 1),
+                ("user_settings", 2),
+                ("user_download_limits", 1),
+                ("app_config", 1),
+            ]
+        );
+
+        let users: Vec<User> = sqlx::query_as("SELECT * FROM users ORDER BY username")
+            .fetch_all(&target)
+            .await
+            .unwrap();
+        let names: Vec<&str> = users.iter().map(|u| u.username.as_str()).collect();
+        assert_eq!(names, ["admin", "alice", "bob"]);
+        assert_eq!(users[1].password_hash, "hash-a");
+        assert_eq!(users[1].external_id.as_deref(), Some("oidc-alice"));
+        assert_eq!(users[2].role, Role::ReadOnly);
+        assert!(users[2].must_change_password);
+
+        let folder: Folder = sqlx::query_as("SELECT * FROM folders")
+            .fetch_one(&target)
+            .await
+            .unwrap();
+        assert_eq!(folder.path, "/music/alice");
+        assert!(folder.lyrics_enabled);
+        let share: (String, bool) = sqlx::query_as("SELECT user_id, can_write FROM folder_shares")
+            .fetch_one(&target)
+            .await
+            .unwrap();
+        assert_eq!(share, ("u2".to_string(), false));
+
+        let settings: UserSettings =
+            sqlx::query_as("SELECT * FROM user_settings WHERE user_id = 'u1'")
+                .fetch_one(&target)
+                .await
+                .unwrap();
+        assert_eq!(settings.lastfm_username.as_deref(), Some("alice_fm"));
+        let limits: LimitsRow = sqlx::query_as(
+            "SELECT user_id, max_concurrent, max_queued, daily_bytes, monthly_bytes, folder_bytes FROM user_download_limits",
+        )
+        .fetch_one(&target)
+        .await
+        .unwrap();
+        assert_eq!(
+            limits,
+            (
+                "u2".to_string(),
+                Some(2),
+                None,
+                Some(5_000_000_000),
+                None,
+                None
+            )
+        );
+        let mode: String =
+            sqlx::query_scalar("SELECT value FROM app_config WHERE key = 'registration_mode'")
+                .fetch_one(&target)
+                .await
+                .unwrap();
+        assert_eq!(mode, "invite");
+
+        // A second import would overwrite what was copied
+        assert!(check_empty(&target).await.is_err());
+
+        schema.remove().await;
+    }
+}
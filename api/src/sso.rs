@@ -0,0 +1,625 @@
+//! Single sign-on through OpenID Connect or an authenticating reverse proxy.
+//!
+//! Both methods map the external identity to a [`User`], creating it on first login
+//! when `SSO_AUTO_PROVISION` allows, and sync its role from the configured groups.
+//! OIDC uses the authorization code flow with PKCE; the callback opens a regular
+//! session. Proxy headers are only trusted from the addresses in
+//! `AUTH_PROXY_TRUSTED_IPS` and are checked on every request.
+
+use std::net::SocketAddr;
+use std::sync::LazyLock;
+
+use axum::{
+    extract::{ConnectInfo, Query},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ipnet::IpNet;
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use rand::distr::{Alphanumeric, SampleString};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::OnceCell;
+use tower_cookies::{
+    cookie::{time, SameSite},
+    Cookie, Cookies,
+};
+use tracing::{info, warn};
+
+use crate::auth::Claims;
+use crate::config::{OidcConfig, CONFIG};
+use crate::models::audit::{AuditEvent, AuditRecord};
+use crate::models::user::User;
+use crate::server_fns::auth::start_session;
+use crate::ClientInfo;
+
+pub use crate::auth::OIDC_LOGIN_ROUTE;
+
+/// Path the provider redirects back to, must match `OIDC_REDIRECT_URL`.
+pub const OIDC_CALLBACK_ROUTE: &str = "/api/auth/oidc/callback";
+
+/// Cookie holding the state, nonce and PKCE verifier between redirect and callback.
+const FLOW_COOKIE_NAME: &str = "oidc_flow";
+const FLOW_MINUTES: i64 = 10;
+
+static HTTP: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+
+/// Parts of the provider's discovery document we use.
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+/// Discovery is fetched once; failures are retried on the next login.
+static METADATA: OnceCell<ProviderMetadata> = OnceCell::const_new();
+
+async fn provider_metadata(config: &OidcConfig) -> Result<&'static ProviderMetadata, String> {
+    METADATA
+        .get_or_try_init(|| async {
+            let url = format!("{}/.well-known/openid-configuration", config.issuer_url);
+            HTTP.get(&url)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| format!("OIDC discovery failed: {}", e))?
+                .json::<ProviderMetadata>()
+                .await
+                .map_err(|e| format!("Invalid OIDC discovery document: {}", e))
+        })
+        .await
+}
+
+/// Login attempt in progress, signed and stored in [`FLOW_COOKIE_NAME`].
+#[derive(Debug, Serialize, Deserialize)]
+struct FlowState {
+    state: String,
+    nonce: String,
+    verifier: String,
+    exp: usize,
+}
+
+/// S256 PKCE challenge for a code verifier.
+fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// User as seen by the identity provider or auth proxy.
+struct ExternalIdentity {
+    external_id: String,
+    username: String,
+    groups: Vec<String>,
+}
+
+/// Find or create the user for an external identity and apply the role mapping.
+///
+/// Existing local accounts are only taken over when `link_by_username` is set, which
+/// is the case for the auth proxy since it is trusted to name users.
+async fn resolve_user(identity: ExternalIdentity, link_by_username: bool) -> Result<User, String> {
+    let sso = CONFIG.sso();
+    let mapped_role = sso.role_for_groups(&identity.groups);
+
+    let user = match User::get_by_external_id(&identity.external_id).await? {
+        Some(user) => user,
+        None => match User::get_by_username(identity.username.trim()).await.ok() {
+            Some(user) if link_by_username => {
+                info!("Linking {} to user {}", identity.external_id, user.username);
+                User::set_external_id(&user.id, &identity.external_id).await?;
+                user
+            }
+            Some(_) => {
+                return Err(format!(
+                    "The username {} is already used by a local account",
+                    identity.username
+                ));
+            }
+            None if sso.auto_provision => {
+                let user = User::create_external(
+                    &identity.username,
+                    mapped_role.unwrap_or(sso.default_role),
+                    &identity.external_id,
+                )
+                .await?;
+                AuditRecord::new(AuditEvent::UserCreated)
+                    .target(&user.username)
+                    .details(format!(
+                        "role {}, provisioned for {}",
+                        user.role, identity.external_id
+                    ))
+                    .save()
+                    .await;
+                return Ok(user);
+            }
+            None => {
+                return Err(format!(
+                    "No account for {}, ask an admin to create one",
+                    identity.username
+                ));
+            }
+        },
+    };
+
+    match mapped_role {
+        Some(role) if role != user.role => {
+            User::set_role(&user.id, role).await?;
+            AuditRecord::new(AuditEvent::RoleChanged)
+                .target(&user.username)
+                .details(format!("{} -> {} from SSO groups", user.role, role))
+                .save()
+                .await;
+            Ok(User { role, ..user })
+        }
+        _ => Ok(user),
+    }
+}
+
+/// Redirect to the identity provider.
+pub async fn oidc_login_handler(cookies: Cookies) -> Response {
+    let Some(config) = CONFIG.oidc() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let metadata = match provider_metadata(config).await {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            warn!("{}", e);
+            return login_error(&e);
+        }
+    };
+
+    let flow = FlowState {
+        state: Alphanumeric.sample_string(&mut rand::rng(), 32),
+        nonce: Alphanumeric.sample_string(&mut rand::rng(), 32),
+        verifier: Alphanumeric.sample_string(&mut rand::rng(), 64),
+        exp: (chrono::Utc::now() + chrono::Duration::minutes(FLOW_MINUTES)).timestamp() as usize,
+    };
+    let challenge = pkce_challenge(&flow.verifier);
+
+    let url = match reqwest::Url::parse_with_params(
+        &metadata.authorization_endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", config.client_id.as_str()),
+            ("redirect_uri", config.redirect_url.as_str()),
+            ("scope", config.scopes.as_str()),
+            ("state", flow.state.as_str()),
+            ("nonce", flow.nonce.as_str()),
+            ("code_challenge", challenge.as_str()),
+            ("code_challenge_method", "S256"),
+        ],
+    ) {
+        Ok(url) => url,
+        Err(e) => return login_error(&format!("Invalid authorization endpoint: {}", e)),
+    };
+
+    let token = match encode(
+        &Header::default(),
+        &flow,
+        &EncodingKey::from_secret(CONFIG.secret_key().as_bytes()),
+    ) {
+        Ok(token) => token,
+        Err(e) => return login_error(&e.to_string()),
+    };
+
+    let mut cookie = Cookie::new(FLOW_COOKIE_NAME, token);
+    cookie.set_path(OIDC_CALLBACK_ROUTE);
+    cookie.set_http_only(true);
+    // Lax so the cookie comes along on the redirect back from the provider
+    cookie.set_same_site(SameSite::Lax);
+    cookie.set_max_age(time::Duration::minutes(FLOW_MINUTES));
+    cookies.add(cookie);
+
+    Redirect::to(url.as_str()).into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+/// Finish the OIDC login and open a session.
+pub async fn oidc_callback_handler(
+    cookies: Cookies,
+    client: ClientInfo,
+    Query(query): Query<CallbackQuery>,
+) -> Response {
+    let Some(config) = CONFIG.oidc() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let flow = cookies.get(FLOW_COOKIE_NAME).map(|c| c.value().to_string());
+    let mut expired = Cookie::new(FLOW_COOKIE_NAME, "");
+    expired.set_path(OIDC_CALLBACK_ROUTE);
+    cookies.remove(expired);
+
+    let user = match complete_login(config, flow, query).await {
+        Ok(user) => user,
+        Err(e) => {
+            warn!("OIDC login failed: {}", e);
+            AuditRecord::new(AuditEvent::LoginFailed)
+                .ip(client.ip.as_deref())
+                .details(format!("OIDC: {}", e))
+                .save()
+                .await;
+            return login_error(&e);
+        }
+    };
+
+    if let Err(e) = start_session(&user, &client, &cookies).await {
+        return login_error(&e.to_string());
+    }
+    AuditRecord::new(AuditEvent::SsoLogin)
+        .user(Some(&user.id), &user.username)
+        .ip(client.ip.as_deref())
+        .details("OIDC")
+        .save()
+        .await;
+
+    Redirect::to("/").into_response()
+}
+
+/// Send the browser back to the login page with an error to show.
+fn login_error(message: &str) -> Response {
+    let url = reqwest::Url::parse_with_params("http://localhost/login", &[("sso_error", message)])
+        .map(|u| format!("/login?{}", u.query().unwrap_or_default()))
+        .unwrap_or_else(|_| "/login".to_string());
+    Redirect::to(&url).into_response()
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+async fn complete_login(
+    config: &OidcConfig,
+    flow: Option<String>,
+    query: CallbackQuery,
+) -> Result<User, String> {
+    if let Some(error) = query.error {
+        return Err(query.error_description.unwrap_or(error));
+    }
+
+    let flow = flow.ok_or("Login expired, please try again")?;
+    let flow = decode::<FlowState>(
+        &flow,
+        &DecodingKey::from_secret(CONFIG.secret_key().as_bytes()),
+        &Validation::default(),
+    )
+    .map_err(|_| "Login expired, please try again")?
+    .claims;
+
+    if query.state.as_deref() != Some(flow.state.as_str()) {
+        return Err("Invalid login state".to_string());
+    }
+    let code = query.code.ok_or("Missing authorization code")?;
+    let metadata = provider_metadata(config).await?;
+
+    let claims = exchange_code(config, metadata, &code, &flow.verifier).await?;
+    if claims.get("nonce").and_then(|n| n.as_str()) != Some(flow.nonce.as_str()) {
+        return Err("Invalid ID token nonce".to_string());
+    }
+
+    let subject = claims
+        .get("sub")
+        .and_then(|s| s.as_str())
+        .ok_or("ID token has no subject")?;
+    let username = claims
+        .get(&config.username_claim)
+        .and_then(|u| u.as_str())
+        .ok_or_else(|| format!("ID token has no {} claim", config.username_claim))?;
+    let groups = match claims.get(&config.groups_claim) {
+        Some(serde_json::Value::Array(groups)) => groups
+            .iter()
+            .filter_map(|g| g.as_str().map(str::to_string))
+            .collect(),
+        Some(serde_json::Value::String(group)) => vec![group.clone()],
+        _ => Vec::new(),
+    };
+
+    resolve_user(
+        ExternalIdentity {
+            external_id: format!("oidc:{}#{}", metadata.issuer, subject),
+            username: username.to_string(),
+            groups,
+        },
+        false,
+    )
+    .await
+}
+
+/// Redeem the authorization code with the PKCE verifier and return the claims of
+/// the verified ID token.
+async fn exchange_code(
+    config: &OidcConfig,
+    metadata: &ProviderMetadata,
+    code: &str,
+    verifier: &str,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", config.redirect_url.as_str()),
+        ("client_id", config.client_id.as_str()),
+        ("code_verifier", verifier),
+    ];
+    if let Some(secret) = &config.client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+
+    let tokens = HTTP
+        .post(&metadata.token_endpoint)
+        .form(&form)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Token request failed: {}", e))?
+        .json::<TokenResponse>()
+        .await
+        .map_err(|e| format!("Invalid token response: {}", e))?;
+
+    verify_id_token(config, metadata, &tokens.id_token).await
+}
+
+/// Check the ID token signature, issuer and audience and return its claims.
+///
+/// HMAC signed tokens are verified with the client secret, others with the
+/// provider's published keys.
+async fn verify_id_token(
+    config: &OidcConfig,
+    metadata: &ProviderMetadata,
+    id_token: &str,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let header = jsonwebtoken::decode_header(id_token).map_err(|e| e.to_string())?;
+
+    let key = match header.alg {
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+            let secret = config
+                .client_secret
+                .as_ref()
+                .ok_or("HMAC signed ID tokens need OIDC_CLIENT_SECRET")?;
+            DecodingKey::from_secret(secret.as_bytes())
+        }
+        _ => {
+            let jwks = HTTP
+                .get(&metadata.jwks_uri)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| format!("Could not fetch provider keys: {}", e))?
+                .json::<jsonwebtoken::jwk::JwkSet>()
+                .await
+                .map_err(|e| format!("Invalid provider keys: {}", e))?;
+            let jwk = match &header.kid {
+                Some(kid) => jwks.find(kid),
+                None => jwks.keys.first(),
+            }
+            .ok_or("No matching provider key for the ID token")?;
+            DecodingKey::from_jwk(jwk).map_err(|e| e.to_string())?
+        }
+    };
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_audience(&[&config.client_id]);
+    validation.set_issuer(&[&metadata.issuer]);
+
+    decode::<serde_json::Map<String, serde_json::Value>>(id_token, &key, &validation)
+        .map(|data| data.claims)
+        .map_err(|e| format!("Invalid ID token: {}", e))
+}
+
+/// User named by a trusted auth proxy, if the request came through one.
+///
+/// Returns `None` when header login is disabled, the peer is not a trusted proxy or
+/// the header is missing, so other authentication methods can apply.
+pub async fn proxy_user(parts: &Parts) -> Option<Result<User, String>> {
+    let config = CONFIG.proxy_auth()?;
+    if !from_trusted_proxy(parts, &config.trusted_proxies) {
+        return None;
+    }
+
+    let header = |name: &str| {
+        parts
+            .headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let username = header(&config.user_header)?;
+    let groups = config
+        .groups_header
+        .as_deref()
+        .and_then(header)
+        .map(|g| {
+            g.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(
+        resolve_user(
+            ExternalIdentity {
+                external_id: format!("proxy:{}", username),
+                username,
+                groups,
+            },
+            true,
+        )
+        .await,
+    )
+}
+
+/// Whether the request comes straight from one of the `trusted_proxies`.
+fn from_trusted_proxy(parts: &Parts, trusted_proxies: &[IpNet]) -> bool {
+    let Some(peer) = parts.extensions.get::<ConnectInfo<SocketAddr>>() else {
+        warn!("Header login is enabled but the peer address is unknown, ignoring headers");
+        return false;
+    };
+    trusted_proxies.iter().any(|net| net.contains(&peer.0.ip()))
+}
+
+/// Claims for a request authenticated by the auth proxy.
+///
+/// They carry no session id, the proxy is asked again on every request.
+pub fn proxy_claims(user: User) -> Claims {
+    let now = chrono::Utc::now().timestamp() as usize;
+    Claims {
+        sub: user.id,
+        username: user.username,
+        role: user.role,
+        must_change_password: false,
+        scopes: None,
+        jti: None,
+        iat: now,
+        exp: now,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Form, Json, Router};
+    use std::collections::HashMap;
+
+    const CLIENT_SECRET: &str = "mock-secret";
+    const CODE: &str = "mock-code";
+
+    fn config() -> OidcConfig {
+        OidcConfig {
+            issuer_url: String::new(),
+            client_id: "soulbeet".to_string(),
+            client_secret: Some(CLIENT_SECRET.to_string()),
+            redirect_url: "http://localhost:9765/api/auth/oidc/callback".to_string(),
+            scopes: "openid profile email".to_string(),
+            display_name: "SSO".to_string(),
+            username_claim: "preferred_username".to_string(),
+            groups_claim: "groups".to_string(),
+        }
+    }
+
+    /// Provider whose token endpoint only redeems [`CODE`] with the verifier of
+    /// `challenge`, like `scripts/mock_oidc.py`.
+    async fn mock_provider(challenge: String) -> ProviderMetadata {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+
+        let token_issuer = issuer.clone();
+        let token = move |Form(form): Form<HashMap<String, String>>| async move {
+            let field = |name: &str| form.get(name).map(String::as_str).unwrap_or_default();
+            if field("grant_type") != "authorization_code"
+                || field("code") != CODE
+                || field("client_id") != "soulbeet"
+                || field("client_secret") != CLIENT_SECRET
+                || pkce_challenge(field("code_verifier")) != challenge
+            {
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            let claims = serde_json::json!({
+                "iss": token_issuer,
+                "aud": "soulbeet",
+                "sub": "alice-id",
+                "preferred_username": "alice",
+                "nonce": "mock-nonce",
+                "exp": chrono::Utc::now().timestamp() + 300,
+            });
+            let id_token = encode(
+                &Header::new(Algorithm::HS256),
+                &claims,
+                &EncodingKey::from_secret(CLIENT_SECRET.as_bytes()),
+            )
+            .unwrap();
+            Ok(Json(serde_json::json!({ "id_token": id_token })))
+        };
+        let app = Router::new().route("/token", post(token));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        ProviderMetadata {
+            authorization_endpoint: format!("{}/authorize", issuer),
+            token_endpoint: format!("{}/token", issuer),
+            jwks_uri: format!("{}/jwks", issuer),
+            issuer,
+        }
+    }
+
+    fn request_from(peer: Option<&str>) -> Parts {
+        let mut request = axum::http::Request::new(());
+        if let Some(peer) = peer {
+            let peer: SocketAddr = peer.parse().unwrap();
+            request.extensions_mut().insert(ConnectInfo(peer));
+        }
+        request.into_parts().0
+    }
+
+    #[test]
+    fn pkce_challenge_is_unpadded_url_safe_sha256() {
+        // Same as base64.urlsafe_b64encode(sha256(verifier)).rstrip("=") in mock_oidc.py
+        assert_eq!(
+            pkce_challenge("soulbeet-pkce-verifier"),
+            "EmNru_hVMltz9FdMY9okh384aBZtmFnU15ppVCXU4Jk"
+        );
+    }
+
+    #[tokio::test]
+    async fn code_exchange_sends_the_pkce_verifier() {
+        let verifier = "verifier-of-this-login-0123456789-abcdefghijklmnopqrstuvwxyz";
+        let metadata = mock_provider(pkce_challenge(verifier)).await;
+
+        let claims = exchange_code(&config(), &metadata, CODE, verifier)
+            .await
+            .unwrap();
+        assert_eq!(claims.get("sub").and_then(|s| s.as_str()), Some("alice-id"));
+        assert_eq!(
+            claims.get("nonce").and_then(|n| n.as_str()),
+            Some("mock-nonce")
+        );
+    }
+
+    #[tokio::test]
+    async fn code_exchange_fails_with_another_verifier() {
+        let metadata = mock_provider(pkce_challenge("verifier-of-this-login")).await;
+
+        let result = exchange_code(&config(), &metadata, CODE, "verifier-of-another-login").await;
+        assert!(result.unwrap_err().starts_with("Token request failed"));
+    }
+
+    #[tokio::test]
+    async fn code_exchange_rejects_tokens_for_another_issuer() {
+        let verifier = "verifier-of-this-login";
+        let mut metadata = mock_provider(pkce_challenge(verifier)).await;
+        metadata.issuer = "https://other.example".to_string();
+
+        let result = exchange_code(&config(), &metadata, CODE, verifier).await;
+        assert!(result.unwrap_err().starts_with("Invalid ID token"));
+    }
+
+    #[test]
+    fn header_login_needs_a_trusted_peer() {
+        let trusted: Vec<IpNet> = vec!["10.0.0.0/8".parse().unwrap(), "::1/128".parse().unwrap()];
+
+        assert!(from_trusted_proxy(
+            &request_from(Some("10.1.2.3:443")),
+            &trusted
+        ));
+        assert!(from_trusted_proxy(
+            &request_from(Some("[::1]:443")),
+            &trusted
+        ));
+        assert!(!from_trusted_proxy(
+            &request_from(Some("192.168.1.5:443")),
+            &trusted
+        ));
+        assert!(!from_trusted_proxy(&request_from(None), &trusted));
+        assert!(!from_trusted_proxy(
+            &request_from(Some("10.1.2.3:443")),
+            &[]
+        ));
+    }
+}
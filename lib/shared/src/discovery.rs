@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+/// Time range used for a user's top tracks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum DiscoveryPeriod {
+    Week,
+    Month,
+    #[default]
+    Quarter,
+    HalfYear,
+    Year,
+    Overall,
+}
+
+impl DiscoveryPeriod {
+    /// Period identifier as understood by the Last.fm API.
+    pub fn as_lastfm(&self) -> &'static str {
+        match self {
+            DiscoveryPeriod::Week => "7day",
+            DiscoveryPeriod::Month => "1month",
+            DiscoveryPeriod::Quarter => "3month",
+            DiscoveryPeriod::HalfYear => "6month",
+            DiscoveryPeriod::Year => "12month",
+            DiscoveryPeriod::Overall => "overall",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiscoveryPeriod::Week => "Last 7 days",
+            DiscoveryPeriod::Month => "Last month",
+            DiscoveryPeriod::Quarter => "Last 3 months",
+            DiscoveryPeriod::HalfYear => "Last 6 months",
+            DiscoveryPeriod::Year => "Last year",
+            DiscoveryPeriod::Overall => "All time",
+        }
+    }
+
+    pub fn all() -> [DiscoveryPeriod; 6] {
+        [
+            DiscoveryPeriod::Week,
+            DiscoveryPeriod::Month,
+            DiscoveryPeriod::Quarter,
+            DiscoveryPeriod::HalfYear,
+            DiscoveryPeriod::Year,
+            DiscoveryPeriod::Overall,
+        ]
+    }
+}
+
+impl std::fmt::Display for DiscoveryPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_lastfm())
+    }
+}
+
+impl std::str::FromStr for DiscoveryPeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DiscoveryPeriod::all()
+            .into_iter()
+            .find(|p| p.as_lastfm() == s)
+            .ok_or_else(|| format!("Unknown period: {}", s))
+    }
+}
+
+/// Why a track was suggested.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DiscoverySource {
+    /// One of the user's most played tracks
+    TopTrack,
+    /// A track the user marked as loved
+    Loved,
+    /// Popular track of an artist similar to one the user listens to
+    SimilarArtist(String),
+}
+
+/// A track suggested by discovery.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiscoveryTrack {
+    pub artist: String,
+    pub title: String,
+    #[serde(default)]
+    pub mbid: Option<String>,
+    #[serde(default)]
+    pub cover_url: Option<String>,
+    /// Number of plays by the user (top tracks only)
+    #[serde(default)]
+    pub playcount: Option<u64>,
+    pub sources: Vec<DiscoverySource>,
+    /// Relevance used for ordering, higher is better
+    pub score: f64,
+}
+
+/// Discovery results for a Last.fm user, cross-referenced with their libraries.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiscoveryReport {
+    pub lastfm_username: String,
+    /// Number of suggestions before filtering out owned tracks
+    pub candidates: usize,
+    /// Number of suggestions already present in one of the user's libraries
+    pub owned: usize,
+    /// Suggestions missing from every library, best first
+    pub missing: Vec<DiscoveryTrack>,
+}
@@ -1,3 +1,4 @@
+use chrono::NaiveTime;
 use serde::{Deserialize, Serialize};
 
 use crate::metadata::{Album, Track};
@@ -8,6 +9,8 @@ pub struct DownloadQuery {
     pub tracks: Vec<Track>,
     #[serde(default)]
     pub backend: Option<String>,
+    #[serde(default)]
+    pub options: SearchOptions,
 }
 
 impl DownloadQuery {
@@ -16,6 +19,7 @@ impl DownloadQuery {
             album: None,
             tracks,
             backend: None,
+            options: SearchOptions::default(),
         }
     }
 
@@ -28,6 +32,11 @@ impl DownloadQuery {
         self.backend = Some(backend.into());
         self
     }
+
+    pub fn options(mut self, options: SearchOptions) -> Self {
+        self.options = options;
+        self
+    }
 }
 
 impl From<Track> for DownloadQuery {
@@ -36,6 +45,123 @@ impl From<Track> for DownloadQuery {
     }
 }
 
+/// Parameters of a download search, `None` uses the configured default.
+#[derive(Serialize, Clone, PartialEq, Deserialize, Debug, Default)]
+pub struct SearchOptions {
+    /// Seconds to collect responses for
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Peers uploading slower are ignored, in bytes per second
+    #[serde(default)]
+    pub min_upload_speed: Option<u32>,
+    /// Files with a lower bitrate are ignored, in kbps. Files without one, like most
+    /// lossless files, are kept
+    #[serde(default)]
+    pub min_bitrate: Option<u32>,
+    /// Peer responses collected per query
+    #[serde(default)]
+    pub response_limit: Option<u32>,
+    /// Files collected per query
+    #[serde(default)]
+    pub file_limit: Option<u32>,
+    /// Also show files peers only share with some users
+    #[serde(default)]
+    pub include_locked: Option<bool>,
+    /// Results kept, the search ends early once there are more
+    #[serde(default)]
+    pub max_results: Option<usize>,
+}
+
+impl SearchOptions {
+    /// Whether nothing is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// These options, with the unset ones taken from `defaults`.
+    pub fn or(self, defaults: &SearchOptions) -> Self {
+        Self {
+            timeout_secs: self.timeout_secs.or(defaults.timeout_secs),
+            min_upload_speed: self.min_upload_speed.or(defaults.min_upload_speed),
+            min_bitrate: self.min_bitrate.or(defaults.min_bitrate),
+            response_limit: self.response_limit.or(defaults.response_limit),
+            file_limit: self.file_limit.or(defaults.file_limit),
+            include_locked: self.include_locked.or(defaults.include_locked),
+            max_results: self.max_results.or(defaults.max_results),
+        }
+    }
+}
+
+/// What a rule of the download schedule does while its window is active
+#[derive(Serialize, Clone, Copy, PartialEq, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduleAction {
+    /// Start no downloads and take the ones still queued off the backend's queue
+    Pause,
+    /// Run at most this many downloads of a user at once
+    Limit { max_concurrent: u32 },
+}
+
+/// A daily window of the download schedule, "HH:MM" in the server's local time.
+/// A window ending before it starts runs past midnight, equal times cover the day.
+#[derive(Serialize, Clone, PartialEq, Deserialize, Debug)]
+pub struct ScheduleRule {
+    pub start: String,
+    pub end: String,
+    pub action: ScheduleAction,
+}
+
+impl Default for ScheduleRule {
+    fn default() -> Self {
+        Self {
+            start: "08:00".to_string(),
+            end: "23:00".to_string(),
+            action: ScheduleAction::Pause,
+        }
+    }
+}
+
+impl ScheduleRule {
+    /// Start and end of the window.
+    pub fn window(&self) -> Result<(NaiveTime, NaiveTime), String> {
+        let parse = |time: &str| {
+            NaiveTime::parse_from_str(time.trim(), "%H:%M")
+                .map_err(|_| format!("Invalid schedule time '{}', expected HH:MM", time))
+        };
+        Ok((parse(&self.start)?, parse(&self.end)?))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.window()?;
+        if self.action == (ScheduleAction::Limit { max_concurrent: 0 }) {
+            return Err("A schedule limit must allow at least one download".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether `time` falls into the window.
+    pub fn is_active(&self, time: NaiveTime) -> bool {
+        match self.window() {
+            Ok((start, end)) if start < end => start <= time && time < end,
+            Ok((start, end)) => time >= start || time < end,
+            Err(_) => false,
+        }
+    }
+}
+
+/// Action of the schedule at `time`. Pausing wins over limits, and the lowest limit
+/// over the others.
+pub fn scheduled_action(rules: &[ScheduleRule], time: NaiveTime) -> Option<ScheduleAction> {
+    rules
+        .iter()
+        .filter(|rule| rule.is_active(time))
+        .map(|rule| rule.action)
+        .min_by_key(|action| match action {
+            ScheduleAction::Pause => 0,
+            ScheduleAction::Limit { max_concurrent } => u64::from(*max_concurrent) + 1,
+        })
+}
+
 /// A downloadable item from a search result
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DownloadableItem {
@@ -60,6 +186,9 @@ pub struct DownloadableItem {
     /// Backend-specific data (JSON serialized)
     #[serde(default)]
     pub backend_data: Option<String>,
+    /// Backend instance the item was found on, when several are configured
+    #[serde(default)]
+    pub instance: Option<String>,
 }
 
 /// A group of downloadable items (e.g., album from one source)
@@ -83,6 +212,9 @@ pub struct DownloadableGroup {
     pub quality: String,
     /// Overall score for ranking
     pub score: f64,
+    /// Items come from several sources and are imported together as one album
+    #[serde(default)]
+    pub composite: bool,
 }
 
 impl DownloadableGroup {
@@ -91,6 +223,17 @@ impl DownloadableGroup {
     }
 }
 
+/// A folder shared by a source, as listed when browsing it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedFolder {
+    /// Source identifier
+    pub source: String,
+    /// Full path of the folder on the source
+    pub path: String,
+    /// Downloadable files in this folder
+    pub items: Vec<DownloadableItem>,
+}
+
 /// State of a search operation
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SearchState {
@@ -107,12 +250,17 @@ pub struct SearchResult {
     pub groups: Vec<DownloadableGroup>,
     pub has_more: bool,
     pub state: SearchState,
+    /// Revision of the results, groups changed after it are sent on the next update
+    #[serde(default)]
+    pub revision: u64,
 }
 
 /// State of a download operation
 #[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum DownloadState {
     Queued,
+    /// Held back by the download schedule, see [`ScheduleAction::Pause`]
+    Paused,
     InProgress,
     Completed,
     Importing,
@@ -122,6 +270,19 @@ pub enum DownloadState {
     Cancelled,
 }
 
+/// Outcome of the post-import lyrics stage for a download
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LyricsStatus {
+    Searching,
+    /// Time-synced lyrics were found
+    Synced,
+    /// Only unsynced lyrics were found
+    Plain,
+    Instrumental,
+    NotFound,
+    Failed(String),
+}
+
 /// Progress of a single download
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DownloadProgress {
@@ -144,6 +305,12 @@ pub struct DownloadProgress {
     pub speed: f64,
     /// Error message if failed
     pub error: Option<String>,
+    /// Lyrics lookup status, set once the item has been imported
+    #[serde(default)]
+    pub lyrics: Option<LyricsStatus>,
+    /// Where the backend saved the file, if it reports it
+    #[serde(default)]
+    pub path: Option<String>,
 }
 
 impl DownloadProgress {
@@ -158,6 +325,8 @@ impl DownloadProgress {
             percent: 0.0,
             speed: 0.0,
             error: None,
+            lyrics: None,
+            path: None,
         }
     }
 
@@ -172,6 +341,8 @@ impl DownloadProgress {
             percent: 0.0,
             speed: 0.0,
             error: Some(error),
+            lyrics: None,
+            path: None,
         }
     }
 
@@ -181,6 +352,15 @@ impl DownloadProgress {
     }
 }
 
+/// Transfers listed by a download backend
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransferList {
+    pub transfers: Vec<DownloadProgress>,
+    /// Instances that could not be listed, their transfers are missing
+    #[serde(default)]
+    pub unavailable: Vec<String>,
+}
+
 /// Response from queueing downloads
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueuedDownload {
@@ -204,3 +384,20 @@ impl QueuedDownload {
         self.error.is_none()
     }
 }
+
+/// Human readable size, e.g. `1.50 GB`.
+pub fn format_size(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    const GB: u64 = MB * 1024;
+
+    if bytes >= GB {
+        format!("{:.2} GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.2} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.2} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{bytes} B")
+    }
+}
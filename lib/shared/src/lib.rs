@@ -1,5 +1,7 @@
+pub mod discovery;
 pub mod download;
 pub mod library;
 pub mod metadata;
+pub mod playlist;
 pub mod slskd;
 pub mod system;
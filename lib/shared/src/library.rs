@@ -9,6 +9,9 @@ pub struct LibraryTrack {
     pub album: String,
     pub album_artist: String,
     pub library_path: String,
+    /// Track length in seconds
+    #[serde(default)]
+    pub duration: Option<u32>,
 }
 
 /// A group of duplicate tracks (same artist + title across different libraries)
@@ -7,6 +7,7 @@ pub enum Provider {
     #[default]
     MusicBrainz,
     LastFm,
+    Merged,
 }
 
 impl std::fmt::Display for Provider {
@@ -14,6 +15,7 @@ impl std::fmt::Display for Provider {
         match self {
             Provider::MusicBrainz => write!(f, "musicbrainz"),
             Provider::LastFm => write!(f, "lastfm"),
+            Provider::Merged => write!(f, "merged"),
         }
     }
 }
@@ -25,6 +27,7 @@ impl std::str::FromStr for Provider {
         match s.to_lowercase().as_str() {
             "musicbrainz" => Ok(Provider::MusicBrainz),
             "lastfm" => Ok(Provider::LastFm),
+            "merged" => Ok(Provider::Merged),
             _ => Err(format!("Unknown provider: {}", s)),
         }
     }
@@ -68,6 +71,9 @@ pub struct Track {
     /// The MusicBrainz release ID for the album, if known.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub release_mbid: Option<String>,
+    /// IDs of the metadata providers this result came from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
 }
 
 /// An album from a metadata provider.
@@ -87,6 +93,9 @@ pub struct Album {
     /// URL to the album cover image.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cover_url: Option<String>,
+    /// IDs of the metadata providers this result came from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
 }
 
 /// An album with its full track listing.
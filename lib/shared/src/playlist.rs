@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+/// Supported playlist file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaylistFormat {
+    Csv,
+    Json,
+    M3u,
+    Xspf,
+}
+
+impl std::fmt::Display for PlaylistFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlaylistFormat::Csv => write!(f, "csv"),
+            PlaylistFormat::Json => write!(f, "json"),
+            PlaylistFormat::M3u => write!(f, "m3u"),
+            PlaylistFormat::Xspf => write!(f, "xspf"),
+        }
+    }
+}
+
+/// A single line of an imported playlist.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistEntry {
+    pub artist: String,
+    pub title: String,
+    #[serde(default)]
+    pub album: Option<String>,
+    /// MusicBrainz recording ID, if the source provides one
+    #[serde(default)]
+    pub mbid: Option<String>,
+}
+
+/// Progress of a single playlist entry through the import pipeline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PlaylistItemStatus {
+    Pending,
+    Resolving,
+    Searching,
+    /// A download was queued for the entry
+    Queued,
+    /// Already present in the target library, nothing to do
+    AlreadyOwned,
+    NotFound,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistItemReport {
+    pub entry: PlaylistEntry,
+    pub status: PlaylistItemStatus,
+    /// Whether the entry was matched by the metadata provider
+    pub matched: bool,
+    /// Human readable detail (chosen source, error...)
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+/// Progress report of a playlist import job.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistImportReport {
+    pub id: String,
+    pub name: Option<String>,
+    pub target_folder: String,
+    pub items: Vec<PlaylistItemReport>,
+    pub finished: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl PlaylistImportReport {
+    pub fn total(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn matched(&self) -> usize {
+        self.items.iter().filter(|i| i.matched).count()
+    }
+
+    pub fn count(&self, status: &PlaylistItemStatus) -> usize {
+        self.items.iter().filter(|i| &i.status == status).count()
+    }
+
+    pub fn queued(&self) -> usize {
+        self.count(&PlaylistItemStatus::Queued)
+    }
+
+    pub fn not_found(&self) -> usize {
+        self.count(&PlaylistItemStatus::NotFound)
+    }
+
+    pub fn already_owned(&self) -> usize {
+        self.count(&PlaylistItemStatus::AlreadyOwned)
+    }
+
+    pub fn failed(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|i| matches!(i.status, PlaylistItemStatus::Failed(_)))
+            .count()
+    }
+
+    /// Number of entries that reached a final status
+    pub fn processed(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|i| {
+                !matches!(
+                    i.status,
+                    PlaylistItemStatus::Pending
+                        | PlaylistItemStatus::Resolving
+                        | PlaylistItemStatus::Searching
+                )
+            })
+            .count()
+    }
+}
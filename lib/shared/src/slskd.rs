@@ -16,6 +16,9 @@ pub struct DownloadRequest {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DownloadResponse {
+    /// Transfer id, if slskd reported it or it was looked up
+    #[serde(default)]
+    pub id: Option<String>,
     pub username: String,
     pub filename: String,
     pub size: u64,
@@ -87,6 +90,9 @@ pub struct FileEntry {
     pub remaining_time: Option<String>,
     #[serde(default)]
     pub exception: Option<String>,
+    /// Local path of the downloaded file, reported by slskd versions that have it
+    #[serde(default)]
+    pub destination: Option<String>,
 }
 
 impl FileEntry {
@@ -104,7 +110,10 @@ impl FileEntry {
         state_description: String,
     ) -> Self {
         Self {
-            id: uuid::Uuid::new_v4().to_string(),
+            id: response
+                .id
+                .clone()
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
             username: response.username.clone(),
             direction: "Download".to_string(),
             filename: response.filename.clone(),
@@ -123,6 +132,7 @@ impl FileEntry {
             percent_complete: 0.0,
             remaining_time: None,
             exception: response.error.clone(),
+            destination: None,
         }
     }
 
@@ -175,6 +185,7 @@ impl FileEntry {
             percent_complete: self.percent_complete,
             remaining_time: None,
             exception: Some("Per-track timeout".to_string()),
+            destination: self.destination.clone(),
         }
     }
 }
@@ -286,6 +297,85 @@ impl<'de> Deserialize<'de> for FlattenedFiles {
     }
 }
 
+/// Connection of slskd to the Soulseek server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerState {
+    #[serde(default)]
+    pub address: Option<String>,
+    /// Description of the state, e.g. "Connected, LoggedIn"
+    #[serde(default)]
+    pub state: String,
+    #[serde(default)]
+    pub is_connected: bool,
+    #[serde(default)]
+    pub is_logged_in: bool,
+    /// Soulseek username slskd is logged in as
+    #[serde(default)]
+    pub username: Option<String>,
+}
+
+/// State of the folders slskd shares
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareState {
+    #[serde(default)]
+    pub scanning: bool,
+    #[serde(default)]
+    pub scan_pending: bool,
+    /// The last scan failed
+    #[serde(default)]
+    pub faulted: bool,
+    /// Between 0 and 1 while scanning
+    #[serde(default)]
+    pub scan_progress: f64,
+    #[serde(default)]
+    pub directories: u64,
+    #[serde(default)]
+    pub files: u64,
+}
+
+/// Transfers of one direction counted by state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct TransferCounts {
+    pub queued: usize,
+    pub in_progress: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Combined speed of the running transfers, in bytes per second
+    pub speed: f64,
+}
+
+impl TransferCounts {
+    pub fn from_entries(entries: &[FileEntry]) -> Self {
+        let mut counts = Self::default();
+        for entry in entries {
+            // slskd reports e.g. "Completed, Errored", the first state alone is not enough
+            let failed = entry.state.iter().any(|s| {
+                matches!(
+                    s,
+                    DownloadState::Errored | DownloadState::Aborted | DownloadState::Cancelled
+                ) || matches!(s, DownloadState::Unknown(u) if u == "Rejected" || u == "TimedOut")
+            });
+            if failed {
+                counts.failed += 1;
+            } else if entry.state.contains(&DownloadState::Downloaded) {
+                counts.succeeded += 1;
+            } else if entry.state.contains(&DownloadState::InProgress) {
+                counts.in_progress += 1;
+                counts.speed += entry.average_speed;
+            } else {
+                counts.queued += 1;
+            }
+        }
+        counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.queued + self.in_progress + self.succeeded + self.failed
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct MatchResult {
     pub guessed_artist: String,
@@ -395,6 +485,9 @@ pub struct AlbumResult {
     pub upload_speed: i32,
     pub queue_length: i32,
     pub score: f64,
+    /// Assembled from the files of several peers
+    #[serde(default)]
+    pub composite: bool,
 }
 
 impl AlbumResult {
@@ -454,6 +547,7 @@ impl From<TrackResult> for crate::download::DownloadableItem {
             quality: track.base.quality(),
             quality_score: track.base.quality_score(),
             backend_data: Some(serde_json::to_string(&track.base).unwrap_or_default()),
+            instance: None,
         }
     }
 }
@@ -470,6 +564,7 @@ impl From<AlbumResult> for crate::download::DownloadableGroup {
             items: album.tracks.into_iter().map(Into::into).collect(),
             quality: album.dominant_quality,
             score: album.score,
+            composite: album.composite,
         }
     }
 }
@@ -487,6 +582,8 @@ impl From<FileEntry> for crate::download::DownloadProgress {
             percent: entry.percent_complete,
             speed: entry.average_speed,
             error: entry.exception,
+            lyrics: None,
+            path: entry.destination,
         }
     }
 }
@@ -512,8 +609,9 @@ impl From<DownloadState> for crate::download::DownloadState {
 
 impl From<DownloadResponse> for crate::download::QueuedDownload {
     fn from(resp: DownloadResponse) -> Self {
+        // Without a transfer id the filename identifies the download
         Self {
-            id: resp.filename.clone(),
+            id: resp.id.unwrap_or_else(|| resp.filename.clone()),
             source: resp.username,
             item: resp.filename,
             size: resp.size,
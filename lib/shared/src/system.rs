@@ -1,9 +1,21 @@
 use serde::{Deserialize, Serialize};
 
+use crate::slskd::{FileEntry, ServerState, ShareState, TransferCounts};
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct SystemHealth {
+    /// Whether any download backend instance is reachable
     pub downloader_online: bool,
     pub beets_ready: bool,
+    /// Each configured download backend instance
+    #[serde(default)]
+    pub download_instances: Vec<InstanceHealth>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InstanceHealth {
+    pub name: String,
+    pub online: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -18,3 +30,17 @@ pub struct AvailableBackends {
     pub download: Vec<BackendInfo>,
     pub importer: Vec<BackendInfo>,
 }
+
+/// State of one slskd instance, as shown to admins
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct SlskdOverview {
+    pub instance: String,
+    /// Why the instance could not be queried, the other fields are empty then
+    pub error: Option<String>,
+    pub server: ServerState,
+    pub shares: ShareState,
+    pub downloads: TransferCounts,
+    pub uploads: TransferCounts,
+    /// Uploads running right now
+    pub active_uploads: Vec<FileEntry>,
+}
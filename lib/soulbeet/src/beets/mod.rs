@@ -217,8 +217,19 @@ fn process_beets_output(
     }
 }
 
-/// Query tracks from a beets library database
-async fn query_library(library_path: &Path) -> Result<Vec<LibraryTrack>, String> {
+/// Parse a beets formatted `$length` ("3:45" or "1:02:03") into seconds
+fn parse_length(length: &str) -> Option<u32> {
+    length
+        .trim()
+        .split(':')
+        .try_fold(0u32, |acc, part| Some(acc * 60 + part.parse::<u32>().ok()?))
+}
+
+/// Query tracks from a beets library database, optionally filtered by a beets query
+async fn query_library(
+    library_path: &Path,
+    query: &[String],
+) -> Result<Vec<LibraryTrack>, String> {
     if !library_path.exists() {
         return Ok(Vec::new());
     }
@@ -227,7 +238,7 @@ async fn query_library(library_path: &Path) -> Result<Vec<LibraryTrack>, String>
         std::env::var("BEETS_CONFIG").unwrap_or_else(|_| "beets_config.yaml".to_string());
 
     // Use beet ls with format to get track info
-    // Format: path|artist|title|album|albumartist
+    // Format: path|artist|title|album|albumartist|length
     let output = Command::new("beet")
         .arg("-c")
         .arg(&config_path)
@@ -235,7 +246,8 @@ async fn query_library(library_path: &Path) -> Result<Vec<LibraryTrack>, String>
         .arg(library_path)
         .arg("ls")
         .arg("-f")
-        .arg("$path|||$artist|||$title|||$album|||$albumartist")
+        .arg("$path|||$artist|||$title|||$album|||$albumartist|||$length")
+        .args(query)
         .output()
         .await
         .map_err(|e| format!("Failed to query library: {}", e))?;
@@ -264,6 +276,7 @@ async fn query_library(library_path: &Path) -> Result<Vec<LibraryTrack>, String>
                     album: parts[3].to_string(),
                     album_artist: parts[4].to_string(),
                     library_path: library_str.clone(),
+                    duration: parts.get(5).and_then(|l| parse_length(l)),
                 })
             } else {
                 None
@@ -292,7 +305,7 @@ pub async fn find_duplicates_across_libraries(
         let db_path = lib_path.join(".beets_library.db");
         info!("Scanning library: {:?}", db_path);
 
-        match query_library(&db_path).await {
+        match query_library(&db_path, &[]).await {
             Ok(tracks) => {
                 info!("Found {} tracks in {:?}", tracks.len(), lib_path);
                 all_tracks.extend(tracks);
@@ -345,6 +358,45 @@ pub async fn find_duplicates_across_libraries(
     })
 }
 
+/// List tracks added to the library at `target` since the given time
+pub async fn items_added_since(
+    target: &Path,
+    since: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<LibraryTrack>, String> {
+    // beets stores `added` in local time and date queries are interpreted the same way
+    let since = since.with_timezone(&chrono::Local);
+    let query = format!("added:{}..", since.format("%Y-%m-%dT%H:%M:%S"));
+    query_library(&target.join(".beets_library.db"), &[query]).await
+}
+
+/// Store lyrics for a single library item and write them to the file's tags
+pub async fn set_item_lyrics(target: &Path, item_path: &Path, lyrics: &str) -> Result<(), String> {
+    let config_path =
+        std::env::var("BEETS_CONFIG").unwrap_or_else(|_| "beets_config.yaml".to_string());
+
+    let output = Command::new("beet")
+        .arg("-c")
+        .arg(&config_path)
+        .arg("-l")
+        .arg(target.join(".beets_library.db"))
+        .arg("modify")
+        .arg("-y") // do not ask for confirmation
+        .arg("-w") // write tags to the file
+        .arg(format!("path:{}", item_path.to_string_lossy()))
+        .arg(format!("lyrics={}", lyrics))
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run beet modify: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Beet modify failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(())
+}
+
 pub struct BeetsImporter {
     #[allow(dead_code)] // will be used when we pass config to import()
     config_path: std::path::PathBuf,
@@ -407,6 +459,42 @@ impl crate::MusicImporter for BeetsImporter {
             })
     }
 
+    async fn library_tracks(&self, target: &Path) -> crate::error::Result<Vec<LibraryTrack>> {
+        query_library(&target.join(".beets_library.db"), &[])
+            .await
+            .map_err(|e| crate::error::SoulseekError::Api {
+                status: 500,
+                message: e,
+            })
+    }
+
+    async fn recent_items(
+        &self,
+        target: &Path,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> crate::error::Result<Vec<LibraryTrack>> {
+        items_added_since(target, since)
+            .await
+            .map_err(|e| crate::error::SoulseekError::Api {
+                status: 500,
+                message: e,
+            })
+    }
+
+    async fn set_lyrics(
+        &self,
+        target: &Path,
+        item_path: &Path,
+        lyrics: &str,
+    ) -> crate::error::Result<()> {
+        set_item_lyrics(target, item_path, lyrics)
+            .await
+            .map_err(|e| crate::error::SoulseekError::Api {
+                status: 500,
+                message: e,
+            })
+    }
+
     async fn health_check(&self) -> bool {
         Command::new("beet")
             .arg("--version")
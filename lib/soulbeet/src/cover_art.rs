@@ -0,0 +1,613 @@
+//! Server-side cover art fetching and caching.
+//!
+//! Covers are looked up on the Cover Art Archive (by release MBID), fetched from a
+//! provider-supplied URL (Last.fm) or picked up from image files shipped inside a
+//! download folder. Everything is resized to a handful of fixed sizes and cached on
+//! disk, so the browser never talks to third parties directly.
+
+use image::imageops::FilterType;
+use reqwest::Client;
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, info, warn};
+
+use crate::error::{Result, SoulseekError};
+
+const COVER_ART_ARCHIVE_URL: &str = "https://coverartarchive.org/release";
+
+/// Sizes (in pixels, longest edge) covers are cached at. Requests are rounded up.
+pub const COVER_SIZES: [u32; 3] = [250, 500, 1200];
+
+/// Hosts remote cover URLs may point to. Anything else is refused to avoid
+/// turning the endpoint into an open proxy.
+const ALLOWED_HOSTS: &[&str] = &[
+    "coverartarchive.org",
+    "archive.org",
+    "lastfm.freetls.fastly.net",
+    "lastfm-img2.akamaized.net",
+    "last.fm",
+];
+
+/// Image names looked for (in order) inside a download folder.
+const FOLDER_IMAGE_NAMES: &[&str] = &["cover", "folder", "front", "album", "albumart"];
+const FOLDER_IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp"];
+
+/// How long a "no cover exists" marker is trusted before trying again.
+const MISSING_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Age after which a temp file is a leftover of an interrupted write.
+const STALE_TEMP_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// JPEG quality used for cached and written covers.
+const JPEG_QUALITY: u8 = 90;
+
+/// Where a cover comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoverSource {
+    /// MusicBrainz release MBID, resolved through the Cover Art Archive
+    Release(String),
+    /// Direct image URL (e.g. Last.fm `cover_url`)
+    Url(String),
+}
+
+impl CoverSource {
+    fn cache_key(&self) -> String {
+        let raw = match self {
+            CoverSource::Release(mbid) => format!("release:{}", mbid),
+            CoverSource::Url(url) => format!("url:{}", url),
+        };
+        hex::encode(Sha256::digest(raw.as_bytes()))
+    }
+
+    fn remote_url(&self) -> String {
+        match self {
+            CoverSource::Release(mbid) => format!("{}/{}/front", COVER_ART_ARCHIVE_URL, mbid),
+            CoverSource::Url(url) => url.clone(),
+        }
+    }
+}
+
+/// Round a requested size up to the nearest cached size.
+pub fn normalize_size(size: u32) -> u32 {
+    COVER_SIZES
+        .iter()
+        .copied()
+        .find(|s| *s >= size)
+        .unwrap_or(COVER_SIZES[COVER_SIZES.len() - 1])
+}
+
+/// Check that a remote URL points to a known cover art host.
+pub fn is_allowed_url(raw: &str) -> bool {
+    let Ok(url) = url::Url::parse(raw) else {
+        return false;
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        return false;
+    }
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    ALLOWED_HOSTS
+        .iter()
+        .any(|allowed| host == *allowed || host.ends_with(&format!(".{}", allowed)))
+}
+
+/// Find a cover image shipped inside a folder (cover.jpg, folder.png, ...).
+///
+/// Falls back to the only image in the folder if none has a well-known name.
+pub fn find_folder_image(dir: &Path) -> Option<PathBuf> {
+    let entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .and_then(|e| e.to_str())
+                    .map(|e| FOLDER_IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+                    .unwrap_or(false)
+        })
+        .collect();
+
+    for name in FOLDER_IMAGE_NAMES {
+        if let Some(found) = entries.iter().find(|p| {
+            p.file_stem()
+                .and_then(|s| s.to_str())
+                .map(|s| s.eq_ignore_ascii_case(name))
+                .unwrap_or(false)
+        }) {
+            return Some(found.clone());
+        }
+    }
+
+    match entries.as_slice() {
+        [only] => Some(only.clone()),
+        _ => None,
+    }
+}
+
+/// Decode an image, shrink it to fit `size` and re-encode it as JPEG.
+fn resize_to_jpeg(bytes: &[u8], size: u32) -> Result<Vec<u8>> {
+    let img = image::load_from_memory(bytes).map_err(|e| SoulseekError::Api {
+        status: 422,
+        message: format!("Invalid cover image: {}", e),
+    })?;
+
+    let img = if img.width() > size || img.height() > size {
+        img.resize(size, size, FilterType::Lanczos3)
+    } else {
+        img
+    };
+
+    let mut out = Vec::new();
+    let encoder =
+        image::codecs::jpeg::JpegEncoder::new_with_quality(Cursor::new(&mut out), JPEG_QUALITY);
+    img.to_rgb8()
+        .write_with_encoder(encoder)
+        .map_err(|e| SoulseekError::Api {
+            status: 500,
+            message: format!("Failed to encode cover: {}", e),
+        })?;
+    Ok(out)
+}
+
+async fn resize_blocking(bytes: Vec<u8>, size: u32) -> Result<Vec<u8>> {
+    tokio::task::spawn_blocking(move || resize_to_jpeg(&bytes, size))
+        .await
+        .map_err(|e| SoulseekError::Api {
+            status: 500,
+            message: format!("Cover resize task failed: {}", e),
+        })?
+}
+
+/// Temp file next to `path`, unique to this write so concurrent writers don't clash.
+fn temp_path(path: &Path) -> PathBuf {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        name,
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Write a file atomically (temp file + rename) so readers never see partial images.
+async fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let tmp = temp_path(path);
+    if let Err(e) = tokio::fs::write(&tmp, bytes).await {
+        let _ = tokio::fs::remove_file(&tmp).await;
+        return Err(e.into());
+    }
+    if let Err(e) = tokio::fs::rename(&tmp, path).await {
+        let _ = tokio::fs::remove_file(&tmp).await;
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// A file of the cover cache directory.
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+    /// Written or last served
+    modified: SystemTime,
+}
+
+/// Pick the cache files to delete.
+///
+/// Covers unused for longer than `max_age` go first, then the least recently used ones
+/// until the rest fits in `max_bytes`. Expired "missing" markers and leftover temp files
+/// are removed as well, temp files still being written are left alone.
+fn files_to_evict(
+    mut entries: Vec<CacheEntry>,
+    now: SystemTime,
+    max_age: Duration,
+    max_bytes: u64,
+) -> Vec<PathBuf> {
+    let age = |entry: &CacheEntry| now.duration_since(entry.modified).unwrap_or_default();
+    let extension = |entry: &CacheEntry| {
+        entry
+            .path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_string)
+            .unwrap_or_default()
+    };
+
+    let mut evicted = Vec::new();
+    entries.retain(|entry| {
+        let expired = match extension(entry).as_str() {
+            "jpg" => age(entry) > max_age,
+            "missing" => age(entry) > MISSING_TTL,
+            "tmp" => age(entry) > STALE_TEMP_AGE,
+            _ => return false,
+        };
+        if expired {
+            evicted.push(entry.path.clone());
+        }
+        !expired && extension(entry) == "jpg"
+    });
+
+    // Oldest first
+    entries.sort_by_key(|entry| entry.modified);
+    let mut total: u64 = entries.iter().map(|entry| entry.size).sum();
+    for entry in entries {
+        if total <= max_bytes {
+            break;
+        }
+        total -= entry.size;
+        evicted.push(entry.path);
+    }
+    evicted
+}
+
+fn read_cache_dir(dir: &Path) -> std::io::Result<Vec<CacheEntry>> {
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_file() {
+            entries.push(CacheEntry {
+                path: entry.path(),
+                size: metadata.len(),
+                modified: metadata.modified()?,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+pub struct CoverArtService {
+    cache_dir: PathBuf,
+    client: Client,
+}
+
+impl CoverArtService {
+    pub fn new(cache_dir: impl Into<PathBuf>) -> Self {
+        let client = Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .user_agent(concat!("soulbeet/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            cache_dir: cache_dir.into(),
+            client,
+        }
+    }
+
+    fn cached_path(&self, key: &str, size: u32) -> PathBuf {
+        self.cache_dir.join(format!("{}-{}.jpg", key, size))
+    }
+
+    fn missing_marker(&self, key: &str) -> PathBuf {
+        self.cache_dir.join(format!("{}.missing", key))
+    }
+
+    fn is_marked_missing(&self, key: &str) -> bool {
+        std::fs::metadata(self.missing_marker(key))
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .map(|age| age < MISSING_TTL)
+            .unwrap_or(false)
+    }
+
+    /// Download the original image for a source.
+    async fn fetch(&self, source: &CoverSource) -> Result<Vec<u8>> {
+        let url = source.remote_url();
+        if let CoverSource::Url(raw) = source {
+            if !is_allowed_url(raw) {
+                return Err(SoulseekError::Api {
+                    status: 400,
+                    message: format!("Cover URL host not allowed: {}", raw),
+                });
+            }
+        }
+
+        debug!("Fetching cover art from {}", url);
+        let response = self.client.get(&url).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(SoulseekError::Api {
+                status: status.as_u16(),
+                message: format!("Cover fetch failed for {}", url),
+            });
+        }
+
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Get a cached cover for a source at the given size, fetching it if needed.
+    ///
+    /// Returns the path to a JPEG file inside the cache directory.
+    pub async fn get(&self, source: &CoverSource, size: u32) -> Result<PathBuf> {
+        let size = normalize_size(size);
+        let key = source.cache_key();
+        let path = self.cached_path(&key, size);
+
+        if path.exists() {
+            // Keep covers in use from being evicted first
+            let _ = std::fs::File::options()
+                .append(true)
+                .open(&path)
+                .and_then(|f| f.set_modified(SystemTime::now()));
+            return Ok(path);
+        }
+
+        if self.is_marked_missing(&key) {
+            return Err(SoulseekError::Api {
+                status: 404,
+                message: "No cover art available".to_string(),
+            });
+        }
+
+        tokio::fs::create_dir_all(&self.cache_dir).await?;
+
+        let original = match self.fetch(source).await {
+            Ok(bytes) => bytes,
+            Err(SoulseekError::Api {
+                status: 404,
+                message,
+            }) => {
+                // Remember the miss so every page load doesn't hit the archive again
+                let _ = tokio::fs::write(self.missing_marker(&key), b"").await;
+                return Err(SoulseekError::Api {
+                    status: 404,
+                    message,
+                });
+            }
+            Err(e) => return Err(e),
+        };
+
+        let resized = resize_blocking(original, size).await?;
+        write_atomic(&path, &resized).await?;
+
+        Ok(path)
+    }
+
+    /// Delete cached covers unused for longer than `max_age`, then the least recently
+    /// used ones until the cache fits in `max_bytes`.
+    ///
+    /// Returns the number of files removed.
+    pub async fn prune(&self, max_age: Duration, max_bytes: u64) -> Result<usize> {
+        let dir = self.cache_dir.clone();
+        let removed = tokio::task::spawn_blocking(move || -> std::io::Result<usize> {
+            if !dir.exists() {
+                return Ok(0);
+            }
+            let evicted =
+                files_to_evict(read_cache_dir(&dir)?, SystemTime::now(), max_age, max_bytes);
+            let mut removed = 0;
+            for path in evicted {
+                match std::fs::remove_file(&path) {
+                    Ok(()) => removed += 1,
+                    Err(e) => warn!("Failed to remove cached cover {:?}: {}", path, e),
+                }
+            }
+            Ok(removed)
+        })
+        .await
+        .map_err(|e| SoulseekError::Api {
+            status: 500,
+            message: format!("Cover cache prune task failed: {}", e),
+        })??;
+
+        if removed > 0 {
+            debug!("Removed {} files from the cover cache", removed);
+        }
+        Ok(removed)
+    }
+
+    /// Make sure a download folder contains a `cover.jpg` before it is imported.
+    ///
+    /// An image already shipped in the folder wins; otherwise the cover is fetched
+    /// from `source`. Returns the written path, or `None` when no cover was found.
+    pub async fn write_folder_cover(
+        &self,
+        dir: &Path,
+        source: Option<&CoverSource>,
+    ) -> Result<Option<PathBuf>> {
+        let target = dir.join("cover.jpg");
+        if target.exists() {
+            return Ok(Some(target));
+        }
+
+        let dir_owned = dir.to_path_buf();
+        let existing = tokio::task::spawn_blocking(move || find_folder_image(&dir_owned))
+            .await
+            .ok()
+            .flatten();
+
+        let original = if let Some(existing) = existing {
+            info!("Using folder image {:?} as cover", existing);
+            tokio::fs::read(&existing).await?
+        } else if let Some(source) = source {
+            let largest = COVER_SIZES[COVER_SIZES.len() - 1];
+            let cached = self.get(source, largest).await?;
+            tokio::fs::copy(&cached, &target).await?;
+            return Ok(Some(target));
+        } else {
+            return Ok(None);
+        };
+
+        match resize_blocking(original, COVER_SIZES[COVER_SIZES.len() - 1]).await {
+            Ok(resized) => {
+                write_atomic(&target, &resized).await?;
+                Ok(Some(target))
+            }
+            Err(e) => {
+                warn!("Folder image in {:?} could not be converted: {}", dir, e);
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn entry(name: &str, size: u64, age: Duration, now: SystemTime) -> CacheEntry {
+        CacheEntry {
+            path: PathBuf::from(name),
+            size,
+            modified: now - age,
+        }
+    }
+
+    #[test]
+    fn sizes_are_rounded_up_to_a_cached_size() {
+        assert_eq!(normalize_size(0), 250);
+        assert_eq!(normalize_size(250), 250);
+        assert_eq!(normalize_size(251), 500);
+        assert_eq!(normalize_size(800), 1200);
+        assert_eq!(normalize_size(5000), 1200);
+    }
+
+    #[test]
+    fn only_cover_hosts_are_allowed() {
+        assert!(is_allowed_url(
+            "https://coverartarchive.org/release/abc/front"
+        ));
+        assert!(is_allowed_url(
+            "https://lastfm.freetls.fastly.net/i/u/300x300/abc.png"
+        ));
+        assert!(is_allowed_url("http://ia800.us.archive.org/cover.jpg"));
+
+        assert!(!is_allowed_url("https://example.com/cover.jpg"));
+        assert!(!is_allowed_url("https://notlast.fm/cover.jpg"));
+        assert!(!is_allowed_url("https://last.fm.example.com/cover.jpg"));
+        assert!(!is_allowed_url("file:///etc/passwd"));
+        assert!(!is_allowed_url("not a url"));
+    }
+
+    #[test]
+    fn sources_resolve_to_their_remote_url_and_cache_key() {
+        let release = CoverSource::Release("abc".to_string());
+        assert_eq!(
+            release.remote_url(),
+            "https://coverartarchive.org/release/abc/front"
+        );
+        let url = CoverSource::Url("https://last.fm/abc.png".to_string());
+        assert_eq!(url.remote_url(), "https://last.fm/abc.png");
+
+        // Stable, filesystem-safe and distinct per source
+        assert_eq!(release.cache_key(), release.cache_key());
+        assert_eq!(release.cache_key().len(), 64);
+        assert!(release.cache_key().chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(release.cache_key(), url.cache_key());
+        assert_ne!(
+            CoverSource::Release("x".to_string()).cache_key(),
+            CoverSource::Url("x".to_string()).cache_key()
+        );
+    }
+
+    #[test]
+    fn cache_paths_are_inside_the_cache_dir() {
+        let service = CoverArtService::new("/cache");
+        assert_eq!(
+            service.cached_path("key", 500),
+            PathBuf::from("/cache/key-500.jpg")
+        );
+        assert_eq!(
+            service.missing_marker("key"),
+            PathBuf::from("/cache/key.missing")
+        );
+    }
+
+    #[test]
+    fn temp_paths_are_unique_siblings() {
+        let path = Path::new("/albums/One/cover.jpg");
+        let first = temp_path(path);
+        let second = temp_path(path);
+        assert_ne!(first, second);
+        for tmp in [first, second] {
+            assert_eq!(tmp.parent(), path.parent());
+            let name = tmp.file_name().unwrap().to_str().unwrap();
+            assert!(name.starts_with(".cover.jpg."));
+            assert!(name.ends_with(".tmp"));
+        }
+    }
+
+    #[test]
+    fn folder_images_are_found_by_name_then_alone() {
+        let dir = std::env::temp_dir().join(format!(
+            "soulbeet-covers-{}-{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let touch = |name: &str| std::fs::write(dir.join(name), b"").unwrap();
+
+        touch("01 Track.flac");
+        assert_eq!(find_folder_image(&dir), None);
+
+        // The only image is used whatever its name
+        touch("scan.PNG");
+        assert_eq!(find_folder_image(&dir), Some(dir.join("scan.PNG")));
+
+        // Well-known names win, in order
+        touch("back.jpg");
+        assert_eq!(find_folder_image(&dir), None);
+        touch("Folder.jpeg");
+        assert_eq!(find_folder_image(&dir), Some(dir.join("Folder.jpeg")));
+        touch("cover.webp");
+        assert_eq!(find_folder_image(&dir), Some(dir.join("cover.webp")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn evicts_old_covers_then_the_least_recently_used() {
+        let now = SystemTime::now();
+        let entries = vec![
+            entry("fresh-250.jpg", 100, DAY, now),
+            entry("older-250.jpg", 100, 3 * DAY, now),
+            entry("oldest-250.jpg", 100, 5 * DAY, now),
+            entry("expired-250.jpg", 100, 40 * DAY, now),
+            entry("gone.missing", 0, 2 * DAY, now),
+            entry("recent.missing", 0, DAY / 2, now),
+            entry(".a-250.jpg.1-0.tmp", 100, 2 * STALE_TEMP_AGE, now),
+            entry(".b-250.jpg.1-1.tmp", 100, Duration::from_secs(1), now),
+            entry("notes.txt", 1000, 40 * DAY, now),
+        ];
+
+        let mut evicted = files_to_evict(entries, now, 30 * DAY, 150);
+        evicted.sort();
+        assert_eq!(
+            evicted,
+            vec![
+                PathBuf::from(".a-250.jpg.1-0.tmp"),
+                PathBuf::from("expired-250.jpg"),
+                PathBuf::from("gone.missing"),
+                PathBuf::from("older-250.jpg"),
+                PathBuf::from("oldest-250.jpg"),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_everything_within_the_limits() {
+        let now = SystemTime::now();
+        let entries = vec![
+            entry("a-250.jpg", 100, DAY, now),
+            entry("a-500.jpg", 300, 2 * DAY, now),
+        ];
+        assert!(files_to_evict(entries, now, 30 * DAY, 400).is_empty());
+    }
+}
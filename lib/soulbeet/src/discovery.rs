@@ -0,0 +1,212 @@
+//! Last.fm based discovery.
+//!
+//! Builds a ranked list of tracks a user is likely to want from their Last.fm
+//! profile: their most played and loved tracks, plus popular tracks of artists
+//! similar to the ones they listen to most. Filtering against the local library is
+//! left to the caller.
+
+use futures::future::join_all;
+use shared::discovery::{DiscoveryPeriod, DiscoverySource, DiscoveryTrack};
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, warn};
+
+use crate::error::Result;
+use crate::lastfm::{LastFmChartTrack, LastFmProvider};
+
+/// Weights applied to each kind of suggestion when ranking.
+const LOVED_WEIGHT: f64 = 3.0;
+const TOP_TRACK_WEIGHT: f64 = 2.0;
+const SIMILAR_WEIGHT: f64 = 1.0;
+
+#[derive(Debug, Clone)]
+pub struct DiscoveryOptions {
+    pub period: DiscoveryPeriod,
+    pub top_tracks: usize,
+    pub loved_tracks: usize,
+    /// Number of the user's most listened artists used as seeds for similar artists
+    pub seed_artists: usize,
+    /// Similar artists fetched per seed artist
+    pub similar_per_seed: usize,
+    /// Top tracks fetched per similar artist
+    pub tracks_per_artist: usize,
+}
+
+impl Default for DiscoveryOptions {
+    fn default() -> Self {
+        Self {
+            period: DiscoveryPeriod::default(),
+            top_tracks: 50,
+            loved_tracks: 50,
+            seed_artists: 3,
+            similar_per_seed: 3,
+            tracks_per_artist: 3,
+        }
+    }
+}
+
+/// Key used to fold the same track coming from several sources.
+pub fn track_key(artist: &str, title: &str) -> String {
+    let normalize = |s: &str| {
+        s.to_lowercase()
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+    };
+    format!("{}|{}", normalize(artist), normalize(title))
+}
+
+#[derive(Default)]
+struct Collector {
+    tracks: Vec<DiscoveryTrack>,
+    index: HashMap<String, usize>,
+}
+
+impl Collector {
+    fn add(&mut self, track: &LastFmChartTrack, source: DiscoverySource, score: f64) {
+        let key = track_key(track.artist(), &track.name);
+        if let Some(&i) = self.index.get(&key) {
+            let existing = &mut self.tracks[i];
+            existing.score += score;
+            if !existing.sources.contains(&source) {
+                existing.sources.push(source);
+            }
+            if existing.cover_url.is_none() {
+                existing.cover_url = track.cover_url();
+            }
+            return;
+        }
+
+        self.index.insert(key, self.tracks.len());
+        self.tracks.push(DiscoveryTrack {
+            artist: track.artist().to_string(),
+            title: track.name.clone(),
+            mbid: track.mbid.clone().filter(|m| !m.is_empty()),
+            cover_url: track.cover_url(),
+            playcount: None,
+            sources: vec![source],
+            score,
+        });
+    }
+
+    fn into_ranked(mut self) -> Vec<DiscoveryTrack> {
+        self.tracks.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        self.tracks
+    }
+}
+
+/// Rank score between 0 and 1 for the item at `position` in a list of `len` items.
+fn rank_score(position: usize, len: usize) -> f64 {
+    1.0 - position as f64 / len.max(1) as f64
+}
+
+/// Artists appearing most often among the given tracks.
+fn most_listened_artists(tracks: &[&LastFmChartTrack], limit: usize) -> Vec<String> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for track in tracks {
+        match counts
+            .iter_mut()
+            .find(|(a, _)| a.eq_ignore_ascii_case(track.artist()))
+        {
+            Some((_, count)) => *count += 1,
+            None => counts.push((track.artist().to_string(), 1)),
+        }
+    }
+    // Stable sort keeps chart order for ties
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts.into_iter().take(limit).map(|(a, _)| a).collect()
+}
+
+/// Build a ranked list of suggestions for a Last.fm user.
+///
+/// Fails only if the user's own charts cannot be fetched; errors on similar artists
+/// are logged and skipped.
+pub async fn discover(
+    lastfm: &LastFmProvider,
+    username: &str,
+    options: &DiscoveryOptions,
+) -> Result<Vec<DiscoveryTrack>> {
+    let (top, loved) = futures::join!(
+        lastfm.user_top_tracks(username, options.period.as_lastfm(), options.top_tracks),
+        lastfm.user_loved_tracks(username, options.loved_tracks),
+    );
+    let top = top?;
+    let loved = loved?;
+    debug!(
+        "Last.fm user {} has {} top and {} loved tracks",
+        username,
+        top.len(),
+        loved.len()
+    );
+
+    let mut collector = Collector::default();
+
+    for (position, track) in loved.iter().enumerate() {
+        collector.add(
+            track,
+            DiscoverySource::Loved,
+            LOVED_WEIGHT * rank_score(position, loved.len()),
+        );
+    }
+    for (position, track) in top.iter().enumerate() {
+        collector.add(
+            track,
+            DiscoverySource::TopTrack,
+            TOP_TRACK_WEIGHT * rank_score(position, top.len()),
+        );
+        let key = track_key(track.artist(), &track.name);
+        if let Some(&i) = collector.index.get(&key) {
+            collector.tracks[i].playcount = track.playcount.as_deref().and_then(|p| p.parse().ok());
+        }
+    }
+
+    let listened: Vec<&LastFmChartTrack> = top.iter().chain(loved.iter()).collect();
+    let seeds = most_listened_artists(&listened, options.seed_artists);
+
+    let similar_lists = join_all(
+        seeds
+            .iter()
+            .map(|seed| lastfm.similar_artists(seed, options.similar_per_seed)),
+    )
+    .await;
+
+    let mut similar = Vec::new();
+    for (seed, result) in seeds.iter().zip(similar_lists) {
+        match result {
+            Ok(artists) => similar.extend(artists),
+            Err(e) => warn!("Could not fetch artists similar to {}: {}", seed, e),
+        }
+    }
+    // The same artist can be similar to several seeds
+    let mut seen = HashSet::new();
+    similar.retain(|a| seen.insert(a.name.to_lowercase()));
+
+    let top_lists = join_all(
+        similar
+            .iter()
+            .map(|artist| lastfm.artist_top_tracks(&artist.name, options.tracks_per_artist)),
+    )
+    .await;
+
+    for (artist, result) in similar.iter().zip(top_lists) {
+        let tracks = match result {
+            Ok(tracks) => tracks,
+            Err(e) => {
+                warn!("Could not fetch top tracks of {}: {}", artist.name, e);
+                continue;
+            }
+        };
+        for (position, track) in tracks.iter().enumerate() {
+            collector.add(
+                track,
+                DiscoverySource::SimilarArtist(artist.name.clone()),
+                SIMILAR_WEIGHT * artist.similarity() * rank_score(position, tracks.len()),
+            );
+        }
+    }
+
+    Ok(collector.into_ranked())
+}
@@ -8,6 +8,9 @@ pub enum SoulseekError {
     #[error("Request error: {0}")]
     Request(#[from] reqwest::Error),
 
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
     #[error("URL parsing error: {0}")]
     UrlParse(#[from] url::ParseError),
 
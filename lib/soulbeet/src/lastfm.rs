@@ -1,11 +1,12 @@
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize};
 use shared::metadata::{Album, AlbumWithTracks, SearchResult, Track};
 use tracing::{info, warn};
 
 use crate::error::{Result, SoulseekError};
 
 const LASTFM_API_BASE: &str = "https://ws.audioscrobbler.com/2.0/";
+const PROVIDER_ID: &str = "lastfm";
 
 #[derive(Debug, Deserialize)]
 struct LastFmImage {
@@ -158,6 +159,108 @@ struct LastFmWiki {
     published: Option<String>,
 }
 
+// Last.fm returns a bare object instead of an array when a list has one element
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(Box<T>),
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            OneOrMany::One(item) => vec![*item],
+            OneOrMany::Many(items) => items,
+        }
+    }
+}
+
+impl<T> Default for OneOrMany<T> {
+    fn default() -> Self {
+        OneOrMany::Many(Vec::new())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LastFmErrorResponse {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NamedArtist {
+    name: String,
+}
+
+/// Track entry of the user and artist charts (top, loved).
+#[derive(Debug, Deserialize)]
+pub struct LastFmChartTrack {
+    pub name: String,
+    #[serde(default)]
+    pub mbid: Option<String>,
+    #[serde(default)]
+    pub playcount: Option<String>,
+    artist: NamedArtist,
+    #[serde(default)]
+    image: Vec<LastFmImage>,
+}
+
+impl LastFmChartTrack {
+    pub fn artist(&self) -> &str {
+        &self.artist.name
+    }
+
+    pub fn cover_url(&self) -> Option<String> {
+        LastFmImage::get_largest(&self.image)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartTracks {
+    #[serde(default)]
+    track: OneOrMany<LastFmChartTrack>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopTracksResponse {
+    toptracks: ChartTracks,
+}
+
+#[derive(Debug, Deserialize)]
+struct LovedTracksResponse {
+    lovedtracks: ChartTracks,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LastFmSimilarArtist {
+    pub name: String,
+    #[serde(default)]
+    pub mbid: Option<String>,
+    /// Similarity between 0 and 1, sent as a string
+    #[serde(rename = "match", default)]
+    similarity: Option<String>,
+}
+
+impl LastFmSimilarArtist {
+    pub fn similarity(&self) -> f64 {
+        self.similarity
+            .as_deref()
+            .and_then(|m| m.parse().ok())
+            .unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SimilarArtists {
+    #[serde(default)]
+    artist: OneOrMany<LastFmSimilarArtist>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SimilarArtistsResponse {
+    similarartists: SimilarArtists,
+}
+
 pub struct LastFmProvider {
     client: Client,
     api_key: String,
@@ -175,6 +278,113 @@ impl LastFmProvider {
         std::env::var("LASTFM_API_KEY").ok().map(Self::new)
     }
 
+    /// Call a Last.fm API method and decode its JSON response.
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: &[(&str, &str)]) -> Result<T> {
+        let mut query = vec![
+            ("method", method),
+            ("api_key", self.api_key.as_str()),
+            ("format", "json"),
+        ];
+        query.extend_from_slice(params);
+
+        let url = reqwest::Url::parse_with_params(LASTFM_API_BASE, &query).map_err(|e| {
+            SoulseekError::Api {
+                status: 500,
+                message: format!("Failed to build URL: {}", e),
+            }
+        })?;
+
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| SoulseekError::Api {
+                status: 500,
+                message: format!("Last.fm request failed: {}", e),
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response
+                .json::<LastFmErrorResponse>()
+                .await
+                .map(|e| e.message)
+                .unwrap_or_else(|_| format!("Last.fm API error: {}", status));
+            return Err(SoulseekError::Api {
+                status: status.as_u16(),
+                message,
+            });
+        }
+
+        response.json().await.map_err(|e| SoulseekError::Api {
+            status: 500,
+            message: format!("Failed to parse Last.fm response: {}", e),
+        })
+    }
+
+    /// Most played tracks of a user over a period (`7day`, `1month`, ..., `overall`).
+    pub async fn user_top_tracks(
+        &self,
+        user: &str,
+        period: &str,
+        limit: usize,
+    ) -> Result<Vec<LastFmChartTrack>> {
+        let limit = limit.to_string();
+        let data: TopTracksResponse = self
+            .call(
+                "user.getTopTracks",
+                &[("user", user), ("period", period), ("limit", &limit)],
+            )
+            .await?;
+        Ok(data.toptracks.track.into_vec())
+    }
+
+    /// Tracks a user marked as loved, most recent first.
+    pub async fn user_loved_tracks(
+        &self,
+        user: &str,
+        limit: usize,
+    ) -> Result<Vec<LastFmChartTrack>> {
+        let limit = limit.to_string();
+        let data: LovedTracksResponse = self
+            .call("user.getLovedTracks", &[("user", user), ("limit", &limit)])
+            .await?;
+        Ok(data.lovedtracks.track.into_vec())
+    }
+
+    /// Artists similar to the given one, most similar first.
+    pub async fn similar_artists(
+        &self,
+        artist: &str,
+        limit: usize,
+    ) -> Result<Vec<LastFmSimilarArtist>> {
+        let limit = limit.to_string();
+        let data: SimilarArtistsResponse = self
+            .call(
+                "artist.getSimilar",
+                &[("artist", artist), ("limit", &limit), ("autocorrect", "1")],
+            )
+            .await?;
+        Ok(data.similarartists.artist.into_vec())
+    }
+
+    /// Most popular tracks of an artist.
+    pub async fn artist_top_tracks(
+        &self,
+        artist: &str,
+        limit: usize,
+    ) -> Result<Vec<LastFmChartTrack>> {
+        let limit = limit.to_string();
+        let data: TopTracksResponse = self
+            .call(
+                "artist.getTopTracks",
+                &[("artist", artist), ("limit", &limit), ("autocorrect", "1")],
+            )
+            .await?;
+        Ok(data.toptracks.track.into_vec())
+    }
+
     async fn search_albums_internal(
         &self,
         artist: Option<&str>,
@@ -339,7 +549,7 @@ fn generate_lastfm_id(artist: &str, name: &str) -> String {
 #[async_trait::async_trait]
 impl crate::MetadataProvider for LastFmProvider {
     fn id(&self) -> &'static str {
-        "lastfm"
+        PROVIDER_ID
     }
 
     fn name(&self) -> &'static str {
@@ -369,6 +579,7 @@ impl crate::MetadataProvider for LastFmProvider {
                     release_date: None,
                     mbid,
                     cover_url,
+                    sources: vec![PROVIDER_ID.to_string()],
                 })
             })
             .collect())
@@ -399,6 +610,7 @@ impl crate::MetadataProvider for LastFmProvider {
                     duration: None,
                     mbid,
                     release_mbid: None,
+                    sources: vec![PROVIDER_ID.to_string()],
                 })
             })
             .collect())
@@ -433,6 +645,7 @@ impl crate::MetadataProvider for LastFmProvider {
                                     duration: format_duration(track.duration),
                                     mbid: track_mbid,
                                     release_mbid: album_mbid.clone(),
+                                    sources: vec![PROVIDER_ID.to_string()],
                                 }
                             })
                             .collect()
@@ -447,6 +660,7 @@ impl crate::MetadataProvider for LastFmProvider {
                         release_date: info.wiki.and_then(|w| w.published),
                         mbid: album_mbid,
                         cover_url,
+                        sources: vec![PROVIDER_ID.to_string()],
                     },
                     tracks,
                 });
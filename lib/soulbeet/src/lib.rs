@@ -1,12 +1,22 @@
 pub mod beets;
+pub mod cover_art;
+pub mod discovery;
 pub mod error;
 pub mod lastfm;
+pub mod lyrics;
+pub mod merge;
+pub mod multi;
 pub mod musicbrainz;
+pub mod playlist;
 pub mod services;
 pub mod slskd;
 pub mod traits;
 
+pub use cover_art::{CoverArtService, CoverSource};
 pub use lastfm::LastFmProvider;
+pub use lyrics::{LrclibProvider, Lyrics, LyricsProvider, LyricsQuery};
+pub use merge::MergingMetadataProvider;
+pub use multi::MultiDownloadBackend;
 pub use services::{Services, ServicesBuilder};
 pub use traits::{
     DownloadBackend, FallbackMetadataProvider, ImportResult, MetadataProvider, MusicImporter,
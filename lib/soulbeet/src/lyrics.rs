@@ -0,0 +1,406 @@
+//! Lyrics lookup for imported tracks.
+//!
+//! Sources implement [`LyricsProvider`]. The bundled [`LrclibProvider`] talks to an
+//! LRCLIB-compatible API; its base URL is configurable so a self-hosted instance or
+//! a local mock can be used instead of the public service.
+
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::debug;
+
+use crate::error::{Result, SoulseekError};
+
+pub const LRCLIB_URL: &str = "https://lrclib.net";
+
+/// Durations further apart than this (in seconds) are considered different recordings.
+const DURATION_TOLERANCE_SECS: i64 = 3;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// What to look lyrics up for
+#[derive(Debug, Clone)]
+pub struct LyricsQuery {
+    pub artist: String,
+    pub title: String,
+    pub album: Option<String>,
+    /// Track length in seconds
+    pub duration: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Lyrics {
+    /// LRC formatted, time-synced lyrics
+    pub synced: Option<String>,
+    /// Plain text lyrics
+    pub plain: Option<String>,
+    pub instrumental: bool,
+}
+
+impl Lyrics {
+    /// Text to embed into tags: synced lyrics when available, plain otherwise
+    pub fn best(&self) -> Option<&str> {
+        self.synced.as_deref().or(self.plain.as_deref())
+    }
+}
+
+#[async_trait]
+pub trait LyricsProvider: Send + Sync {
+    fn id(&self) -> &'static str;
+    fn name(&self) -> &'static str;
+
+    /// Look up lyrics for a track. `Ok(None)` means the source has nothing for it.
+    async fn lookup(&self, query: &LyricsQuery) -> Result<Option<Lyrics>>;
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LrclibRecord {
+    artist_name: String,
+    track_name: String,
+    #[serde(default)]
+    duration: Option<f64>,
+    #[serde(default)]
+    instrumental: bool,
+    plain_lyrics: Option<String>,
+    synced_lyrics: Option<String>,
+}
+
+impl From<LrclibRecord> for Lyrics {
+    fn from(record: LrclibRecord) -> Self {
+        Self {
+            synced: record.synced_lyrics.filter(|s| !s.trim().is_empty()),
+            plain: record.plain_lyrics.filter(|s| !s.trim().is_empty()),
+            instrumental: record.instrumental,
+        }
+    }
+}
+
+pub struct LrclibProvider {
+    client: Client,
+    base_url: String,
+}
+
+impl LrclibProvider {
+    pub fn new() -> Self {
+        Self::with_base_url(LRCLIB_URL)
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent(concat!("soulbeet/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            client,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Exact lookup, needs the duration to match a recording.
+    async fn get(&self, query: &LyricsQuery, duration: u32) -> Result<Option<LrclibRecord>> {
+        let mut params = vec![
+            ("artist_name", query.artist.clone()),
+            ("track_name", query.title.clone()),
+            ("duration", duration.to_string()),
+        ];
+        if let Some(album) = &query.album {
+            params.push(("album_name", album.clone()));
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/api/get", self.base_url))
+            .query(&params)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(Some(response.json().await?)),
+            status => Err(SoulseekError::Api {
+                status: status.as_u16(),
+                message: response.text().await.unwrap_or_default(),
+            }),
+        }
+    }
+
+    /// Fuzzy search, used when the exact lookup misses or the duration is unknown.
+    async fn search(&self, query: &LyricsQuery) -> Result<Option<LrclibRecord>> {
+        let response = self
+            .client
+            .get(format!("{}/api/search", self.base_url))
+            .query(&[
+                ("artist_name", query.artist.as_str()),
+                ("track_name", query.title.as_str()),
+            ])
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(SoulseekError::Api {
+                status: status.as_u16(),
+                message: response.text().await.unwrap_or_default(),
+            });
+        }
+
+        let records: Vec<LrclibRecord> = response.json().await?;
+        let artist = query.artist.to_lowercase();
+        let title = query.title.to_lowercase();
+
+        let mut candidates: Vec<LrclibRecord> = records
+            .into_iter()
+            .filter(|r| {
+                r.artist_name.to_lowercase() == artist && r.track_name.to_lowercase() == title
+            })
+            .filter(|r| match (query.duration, r.duration) {
+                (Some(wanted), Some(found)) => {
+                    (found.round() as i64 - wanted as i64).abs() <= DURATION_TOLERANCE_SECS
+                }
+                _ => true,
+            })
+            .collect();
+
+        // Prefer synced lyrics, then anything with text
+        candidates.sort_by_key(|r| (r.synced_lyrics.is_none(), r.plain_lyrics.is_none()));
+        Ok(candidates.into_iter().next())
+    }
+}
+
+impl Default for LrclibProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl LyricsProvider for LrclibProvider {
+    fn id(&self) -> &'static str {
+        "lrclib"
+    }
+
+    fn name(&self) -> &'static str {
+        "LRCLIB"
+    }
+
+    async fn lookup(&self, query: &LyricsQuery) -> Result<Option<Lyrics>> {
+        if let Some(duration) = query.duration {
+            if let Some(record) = self.get(query, duration).await? {
+                return Ok(Some(record.into()));
+            }
+            debug!(
+                "No exact lyrics match for {} - {}, searching",
+                query.artist, query.title
+            );
+        }
+
+        Ok(self.search(query).await?.map(Into::into))
+    }
+}
+
+/// Write lyrics next to an audio file as `<name>.lrc`.
+///
+/// Synced lyrics are preferred; plain lyrics are written as-is so players still
+/// show them. Returns `None` if there was nothing to write.
+pub async fn write_sidecar(audio_path: &Path, lyrics: &Lyrics) -> Result<Option<PathBuf>> {
+    let Some(text) = lyrics.best() else {
+        return Ok(None);
+    };
+
+    let path = audio_path.with_extension("lrc");
+    tokio::fs::write(&path, text).await?;
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Local stand-in for LRCLIB: `/api/get` answers with `get` or 404, `/api/search`
+    /// with `search`. Returns the base URL and the requested paths with their queries.
+    async fn mock_lrclib(
+        get: Option<&'static str>,
+        search: &'static str,
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let request = String::from_utf8_lossy(&request);
+                let target = request.split(' ').nth(1).unwrap_or_default().to_string();
+                seen.lock().unwrap().push(target.clone());
+
+                let (status, body) = match (target.split('?').next(), get) {
+                    (Some("/api/get"), Some(body)) => ("200 OK", body),
+                    (Some("/api/get"), None) => ("404 Not Found", r#"{"message":"not found"}"#),
+                    (Some("/api/search"), _) => ("200 OK", search),
+                    _ => ("500 Internal Server Error", "boom"),
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        (base_url, requests)
+    }
+
+    fn query(duration: Option<u32>) -> LyricsQuery {
+        LyricsQuery {
+            artist: "Boards of Canada".to_string(),
+            title: "Roygbiv".to_string(),
+            album: Some("Music Has the Right to Children".to_string()),
+            duration,
+        }
+    }
+
+    #[tokio::test]
+    async fn exact_lookup_with_duration() {
+        let (url, requests) = mock_lrclib(
+            Some(
+                r#"{"artistName":"Boards of Canada","trackName":"Roygbiv","duration":151.0,
+                "instrumental":false,"plainLyrics":"","syncedLyrics":"[00:01.00] la"}"#,
+            ),
+            "[]",
+        )
+        .await;
+
+        let lyrics = LrclibProvider::with_base_url(format!("{}/", url))
+            .lookup(&query(Some(151)))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(lyrics.synced.as_deref(), Some("[00:01.00] la"));
+        // Blank plain lyrics count as missing
+        assert_eq!(lyrics.plain, None);
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("/api/get?"));
+        assert!(requests[0].contains("duration=151"));
+        assert!(requests[0].contains("album_name=Music+Has+the+Right+to+Children"));
+    }
+
+    #[tokio::test]
+    async fn search_picks_the_closest_record() {
+        let (url, requests) = mock_lrclib(
+            None,
+            r#"[
+                {"artistName":"Someone Else","trackName":"Roygbiv","duration":151.0,
+                 "syncedLyrics":"[00:01.00] wrong artist"},
+                {"artistName":"Boards of Canada","trackName":"Roygbiv","duration":240.0,
+                 "syncedLyrics":"[00:01.00] other recording"},
+                {"artistName":"boards of canada","trackName":"ROYGBIV","duration":150.0,
+                 "plainLyrics":"plain only"},
+                {"artistName":"Boards of Canada","trackName":"Roygbiv","duration":153.4,
+                 "plainLyrics":"la","syncedLyrics":"[00:01.00] la"}
+            ]"#,
+        )
+        .await;
+
+        let lyrics = LrclibProvider::with_base_url(url)
+            .lookup(&query(Some(151)))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(lyrics.synced.as_deref(), Some("[00:01.00] la"));
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("/api/get?"));
+        assert!(requests[1].starts_with("/api/search?"));
+    }
+
+    #[tokio::test]
+    async fn search_without_duration() {
+        let (url, requests) = mock_lrclib(
+            None,
+            r#"[{"artistName":"Boards of Canada","trackName":"Roygbiv","duration":151.0,
+                 "instrumental":true,"plainLyrics":null,"syncedLyrics":null}]"#,
+        )
+        .await;
+
+        let lyrics = LrclibProvider::with_base_url(url)
+            .lookup(&query(None))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(lyrics.instrumental);
+        assert_eq!(lyrics.best(), None);
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("/api/search?"));
+    }
+
+    #[tokio::test]
+    async fn nothing_found() {
+        let (url, _) = mock_lrclib(None, "[]").await;
+
+        let lyrics = LrclibProvider::with_base_url(url)
+            .lookup(&query(Some(151)))
+            .await
+            .unwrap();
+        assert_eq!(lyrics, None);
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_reported() {
+        let (url, _) = mock_lrclib(None, "[]").await;
+
+        let result = LrclibProvider::with_base_url(format!("{}/broken", url))
+            .lookup(&query(None))
+            .await;
+        assert!(matches!(
+            result,
+            Err(SoulseekError::Api { status: 500, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn sidecar_prefers_synced_lyrics() {
+        let dir = std::env::temp_dir().join(format!("soulbeet-lyrics-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let audio = dir.join("01 Roygbiv.flac");
+
+        let lyrics = Lyrics {
+            synced: Some("[00:01.00] la".to_string()),
+            plain: Some("la".to_string()),
+            instrumental: false,
+        };
+        let path = write_sidecar(&audio, &lyrics).await.unwrap().unwrap();
+        assert_eq!(path, dir.join("01 Roygbiv.lrc"));
+        assert_eq!(
+            tokio::fs::read_to_string(&path).await.unwrap(),
+            "[00:01.00] la"
+        );
+
+        let none = write_sidecar(&audio, &Lyrics::default()).await.unwrap();
+        assert_eq!(none, None);
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
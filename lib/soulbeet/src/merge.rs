@@ -0,0 +1,530 @@
+//! Metadata provider that queries every configured provider and merges the results.
+//!
+//! Unlike [`FallbackMetadataProvider`](crate::FallbackMetadataProvider), which stops at
+//! the first non-empty answer, this provider runs all providers concurrently, folds
+//! duplicates together and lets each provider fill the gaps left by the others
+//! (e.g. cover art from Last.fm, MBIDs and durations from MusicBrainz).
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use regex::Regex;
+use shared::metadata::{Album, AlbumWithTracks, SearchResult, Track};
+use std::sync::LazyLock;
+use tracing::{debug, warn};
+
+use crate::error::{Result, SoulseekError};
+use crate::MetadataProvider;
+
+static RE_BRACKETED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s*[\(\[][^\)\]]*[\)\]]").unwrap());
+static RE_NON_WORD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[^\w\s]").unwrap());
+static RE_FEATURING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s(?:feat|ft|featuring)\s.*$").unwrap());
+
+/// Maximum number of candidates fetched from secondary providers when enriching an album.
+const ENRICH_SEARCH_LIMIT: usize = 5;
+
+/// Normalize an artist or title for duplicate detection.
+///
+/// Lowercases, drops bracketed suffixes ("(Remastered 2011)", "[Deluxe]") and
+/// featured artists ("feat. X", "ft. X"), strips punctuation and collapses whitespace.
+fn normalize(s: &str) -> String {
+    let s = s.to_lowercase().replace('&', " and ");
+    let s = RE_BRACKETED.replace_all(&s, "");
+    let s = RE_NON_WORD.replace_all(&s, " ");
+    let s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    RE_FEATURING.replace(&s, "").into_owned()
+}
+
+fn same_mbid(a: &Option<String>, b: &Option<String>) -> bool {
+    matches!((a, b), (Some(a), Some(b)) if a == b)
+}
+
+fn fill<T: Clone>(target: &mut Option<T>, source: &Option<T>) {
+    if target.is_none() {
+        target.clone_from(source);
+    }
+}
+
+fn add_sources(target: &mut Vec<String>, sources: &[String]) {
+    for source in sources {
+        if !target.contains(source) {
+            target.push(source.clone());
+        }
+    }
+}
+
+fn albums_match(a: &Album, b: &Album) -> bool {
+    if same_mbid(&a.mbid, &b.mbid) {
+        return true;
+    }
+    normalize(&a.artist) == normalize(&b.artist) && normalize(&a.title) == normalize(&b.title)
+}
+
+fn tracks_match(a: &Track, b: &Track) -> bool {
+    if same_mbid(&a.mbid, &b.mbid) {
+        return true;
+    }
+    if normalize(&a.artist) != normalize(&b.artist) || normalize(&a.title) != normalize(&b.title) {
+        return false;
+    }
+    // Same song on different releases stays separate when both sides know the album
+    match (&a.album_title, &b.album_title) {
+        (Some(x), Some(y)) => normalize(x) == normalize(y),
+        _ => true,
+    }
+}
+
+fn merge_album(target: &mut Album, other: &Album) {
+    fill(&mut target.release_date, &other.release_date);
+    fill(&mut target.cover_url, &other.cover_url);
+    if target.mbid.is_none() && other.mbid.is_some() {
+        target.mbid.clone_from(&other.mbid);
+        // MBIDs are resolvable by every provider, provider-specific IDs are not
+        target.id.clone_from(&other.id);
+    }
+    add_sources(&mut target.sources, &other.sources);
+}
+
+fn merge_track(target: &mut Track, other: &Track) {
+    fill(&mut target.album_title, &other.album_title);
+    fill(&mut target.release_date, &other.release_date);
+    fill(&mut target.duration, &other.duration);
+    // Adopt the album of the result that knows the MusicBrainz release, checked
+    // before the release MBID is filled in from it
+    let adopt_album = target.album_id.is_none()
+        || (target.release_mbid.is_none() && other.release_mbid.is_some());
+    fill(&mut target.release_mbid, &other.release_mbid);
+    if adopt_album {
+        target.album_id.clone_from(&other.album_id);
+    }
+    if target.mbid.is_none() && other.mbid.is_some() {
+        target.mbid.clone_from(&other.mbid);
+        target.id.clone_from(&other.id);
+    }
+    add_sources(&mut target.sources, &other.sources);
+}
+
+fn results_match(a: &SearchResult, b: &SearchResult) -> bool {
+    match (a, b) {
+        (SearchResult::Album(a), SearchResult::Album(b)) => albums_match(a, b),
+        (SearchResult::Track(a), SearchResult::Track(b)) => tracks_match(a, b),
+        _ => false,
+    }
+}
+
+fn merge_result(target: &mut SearchResult, other: &SearchResult) {
+    match (target, other) {
+        (SearchResult::Album(a), SearchResult::Album(b)) => merge_album(a, b),
+        (SearchResult::Track(a), SearchResult::Track(b)) => merge_track(a, b),
+        _ => {}
+    }
+}
+
+/// Merge ranked result lists from several providers into one ranked list.
+///
+/// Each provider contributes a rank score between 0 and 1 based on the position of
+/// the result in its own list, so results returned by several providers (and near
+/// the top of their lists) float up.
+pub fn merge_results(lists: Vec<Vec<SearchResult>>, limit: usize) -> Vec<SearchResult> {
+    let mut merged: Vec<(SearchResult, f64)> = Vec::new();
+
+    for list in lists {
+        let len = list.len().max(1) as f64;
+        for (position, result) in list.into_iter().enumerate() {
+            let rank_score = 1.0 - position as f64 / len;
+            match merged.iter_mut().find(|(m, _)| results_match(m, &result)) {
+                Some((existing, score)) => {
+                    merge_result(existing, &result);
+                    *score += rank_score;
+                }
+                None => merged.push((result, rank_score)),
+            }
+        }
+    }
+
+    // Stable sort keeps provider order for ties
+    merged.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    merged.into_iter().take(limit).map(|(r, _)| r).collect()
+}
+
+pub struct MergingMetadataProvider {
+    providers: Vec<Box<dyn MetadataProvider>>,
+}
+
+impl MergingMetadataProvider {
+    pub fn new(providers: Vec<Box<dyn MetadataProvider>>) -> Self {
+        Self { providers }
+    }
+
+    async fn collect<'a, F, Fut>(&'a self, f: F) -> Vec<Vec<SearchResult>>
+    where
+        F: Fn(&'a dyn MetadataProvider) -> Fut,
+        Fut: std::future::Future<Output = Result<Vec<SearchResult>>>,
+    {
+        let results = join_all(self.providers.iter().map(|p| f(p.as_ref()))).await;
+
+        results
+            .into_iter()
+            .zip(&self.providers)
+            .filter_map(|(result, provider)| match result {
+                Ok(list) => {
+                    debug!("{} returned {} results", provider.name(), list.len());
+                    Some(list)
+                }
+                Err(e) => {
+                    warn!("{} failed: {}", provider.name(), e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Look up the same album on another provider and merge what it knows.
+    async fn enrich_album(&self, provider: &dyn MetadataProvider, album: &mut AlbumWithTracks) {
+        let candidates = match provider
+            .search_albums(
+                Some(&album.album.artist),
+                &album.album.title,
+                ENRICH_SEARCH_LIMIT,
+            )
+            .await
+        {
+            Ok(candidates) => candidates,
+            Err(e) => {
+                debug!("{} could not enrich album: {}", provider.name(), e);
+                return;
+            }
+        };
+
+        let Some(candidate) = candidates.into_iter().find_map(|c| match c {
+            SearchResult::Album(a) if albums_match(&album.album, &a) => Some(a),
+            _ => None,
+        }) else {
+            return;
+        };
+
+        let other = match provider.get_album(&candidate.id).await {
+            Ok(other) => other,
+            Err(e) => {
+                debug!(
+                    "{} could not fetch album {}: {}",
+                    provider.name(),
+                    candidate.id,
+                    e
+                );
+                merge_album(&mut album.album, &candidate);
+                return;
+            }
+        };
+
+        merge_album(&mut album.album, &other.album);
+        for track in album.tracks.iter_mut() {
+            if let Some(other_track) = other.tracks.iter().find(|t| {
+                same_mbid(&t.mbid, &track.mbid) || normalize(&t.title) == normalize(&track.title)
+            }) {
+                fill(&mut track.duration, &other_track.duration);
+                fill(&mut track.mbid, &other_track.mbid);
+                fill(&mut track.release_mbid, &other_track.release_mbid);
+                fill(&mut track.release_date, &other_track.release_date);
+                add_sources(&mut track.sources, &other_track.sources);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for MergingMetadataProvider {
+    fn id(&self) -> &'static str {
+        "merged"
+    }
+
+    fn name(&self) -> &'static str {
+        "All providers"
+    }
+
+    async fn search_albums(
+        &self,
+        artist: Option<&str>,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>> {
+        let lists = self
+            .collect(|p| p.search_albums(artist, query, limit))
+            .await;
+        Ok(merge_results(lists, limit))
+    }
+
+    async fn search_tracks(
+        &self,
+        artist: Option<&str>,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>> {
+        let lists = self
+            .collect(|p| p.search_tracks(artist, query, limit))
+            .await;
+        Ok(merge_results(lists, limit))
+    }
+
+    async fn get_album(&self, id: &str) -> Result<AlbumWithTracks> {
+        let mut found = None;
+        for (index, provider) in self.providers.iter().enumerate() {
+            match provider.get_album(id).await {
+                Ok(album) => {
+                    found = Some((index, album));
+                    break;
+                }
+                Err(e) => {
+                    debug!("{} cannot resolve album {}: {}", provider.name(), id, e);
+                }
+            }
+        }
+
+        let Some((primary, mut album)) = found else {
+            return Err(SoulseekError::Api {
+                status: 404,
+                message: "Album not found".to_string(),
+            });
+        };
+
+        for (index, provider) in self.providers.iter().enumerate() {
+            if index != primary {
+                self.enrich_album(provider.as_ref(), &mut album).await;
+            }
+        }
+
+        Ok(album)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn album(source: &str, artist: &str, title: &str) -> Album {
+        Album {
+            id: format!("{}:{}", source, title),
+            title: title.to_string(),
+            artist: artist.to_string(),
+            release_date: None,
+            mbid: None,
+            cover_url: None,
+            sources: vec![source.to_string()],
+        }
+    }
+
+    fn track(source: &str, artist: &str, title: &str) -> Track {
+        Track {
+            id: format!("{}:{}", source, title),
+            title: title.to_string(),
+            artist: artist.to_string(),
+            album_id: None,
+            album_title: None,
+            release_date: None,
+            duration: None,
+            mbid: None,
+            release_mbid: None,
+            sources: vec![source.to_string()],
+        }
+    }
+
+    fn titles(results: &[SearchResult]) -> Vec<&str> {
+        results
+            .iter()
+            .map(|r| match r {
+                SearchResult::Album(a) => a.title.as_str(),
+                SearchResult::Track(t) => t.title.as_str(),
+            })
+            .collect()
+    }
+
+    /// Provider answering searches with fixed results, or failing.
+    struct FixedProvider {
+        id: &'static str,
+        results: Option<Vec<Track>>,
+    }
+
+    #[async_trait]
+    impl MetadataProvider for FixedProvider {
+        fn id(&self) -> &'static str {
+            self.id
+        }
+
+        fn name(&self) -> &'static str {
+            self.id
+        }
+
+        async fn search_albums(
+            &self,
+            _artist: Option<&str>,
+            _query: &str,
+            _limit: usize,
+        ) -> Result<Vec<SearchResult>> {
+            Ok(Vec::new())
+        }
+
+        async fn search_tracks(
+            &self,
+            _artist: Option<&str>,
+            _query: &str,
+            _limit: usize,
+        ) -> Result<Vec<SearchResult>> {
+            match &self.results {
+                Some(tracks) => Ok(tracks.iter().cloned().map(SearchResult::Track).collect()),
+                None => Err(SoulseekError::Api {
+                    status: 503,
+                    message: "unavailable".to_string(),
+                }),
+            }
+        }
+
+        async fn get_album(&self, _id: &str) -> Result<AlbumWithTracks> {
+            Err(SoulseekError::Api {
+                status: 404,
+                message: "Album not found".to_string(),
+            })
+        }
+    }
+
+    #[test]
+    fn normalize_ignores_case_punctuation_and_decorations() {
+        assert_eq!(normalize("  AC/DC  "), "ac dc");
+        assert_eq!(normalize("Simon & Garfunkel"), "simon and garfunkel");
+        assert_eq!(normalize("Don't Stop Me Now!"), "don t stop me now");
+        assert_eq!(normalize("Abbey Road (Remastered 2019)"), "abbey road");
+        assert_eq!(normalize("Abbey Road [Deluxe]"), "abbey road");
+    }
+
+    #[test]
+    fn normalize_drops_featured_artists() {
+        for title in [
+            "Empire State of Mind",
+            "Empire State of Mind (feat. Alicia Keys)",
+            "Empire State of Mind feat. Alicia Keys",
+            "Empire State of Mind ft. Alicia Keys",
+            "Empire State of Mind Featuring Alicia Keys",
+            "EMPIRE STATE OF MIND [FT. ALICIA KEYS]",
+        ] {
+            assert_eq!(normalize(title), "empire state of mind", "{}", title);
+        }
+        // Only a credit followed by a name is dropped
+        assert_eq!(normalize("Little Feat"), "little feat");
+    }
+
+    #[test]
+    fn duplicates_across_providers_are_merged() {
+        let mut from_lastfm = album("lastfm", "Pink Floyd", "The Wall");
+        from_lastfm.cover_url = Some("https://last.fm/wall.png".to_string());
+        let mut from_musicbrainz = album("musicbrainz", "Pink Floyd", "The Wall (Remastered)");
+        from_musicbrainz.mbid = Some("wall-mbid".to_string());
+        from_musicbrainz.release_date = Some("1979-11-30".to_string());
+
+        let merged = merge_results(
+            vec![
+                vec![SearchResult::Album(from_lastfm)],
+                vec![
+                    SearchResult::Album(from_musicbrainz),
+                    SearchResult::Album(album("musicbrainz", "Pink Floyd", "Animals")),
+                ],
+            ],
+            10,
+        );
+
+        assert_eq!(titles(&merged), vec!["The Wall", "Animals"]);
+        let SearchResult::Album(wall) = &merged[0] else {
+            panic!("expected an album");
+        };
+        assert_eq!(wall.cover_url.as_deref(), Some("https://last.fm/wall.png"));
+        assert_eq!(wall.mbid.as_deref(), Some("wall-mbid"));
+        assert_eq!(wall.release_date.as_deref(), Some("1979-11-30"));
+        // The MBID is resolvable everywhere, so its provider's ID is kept
+        assert_eq!(wall.id, "musicbrainz:The Wall (Remastered)");
+        assert_eq!(wall.sources, vec!["lastfm", "musicbrainz"]);
+    }
+
+    #[test]
+    fn tracks_of_different_albums_stay_apart() {
+        let mut live = track("lastfm", "Queen", "Bohemian Rhapsody");
+        live.album_title = Some("Live Killers".to_string());
+        let mut studio = track("musicbrainz", "Queen", "Bohemian Rhapsody");
+        studio.album_title = Some("A Night at the Opera".to_string());
+        let unknown = track("musicbrainz", "Queen", "Bohemian Rhapsody feat. Nobody");
+
+        let merged = merge_results(
+            vec![
+                vec![SearchResult::Track(live)],
+                vec![SearchResult::Track(studio), SearchResult::Track(unknown)],
+            ],
+            10,
+        );
+
+        // The result without an album joins the first one
+        assert_eq!(merged.len(), 2);
+    }
+
+    #[test]
+    fn results_found_by_several_providers_rank_first() {
+        let first = vec![
+            SearchResult::Track(track("lastfm", "Artist", "Only Here")),
+            SearchResult::Track(track("lastfm", "Artist", "Everywhere")),
+            SearchResult::Track(track("lastfm", "Artist", "Rare")),
+        ];
+        let second = vec![
+            SearchResult::Track(track("musicbrainz", "Artist", "Elsewhere")),
+            SearchResult::Track(track("musicbrainz", "ARTIST", "Everywhere!")),
+            SearchResult::Track(track("musicbrainz", "Artist", "Seldom")),
+        ];
+
+        // Scores add up across providers, ties keep the provider order
+        let merged = merge_results(vec![first, second], 10);
+        assert_eq!(
+            titles(&merged),
+            vec!["Everywhere", "Only Here", "Elsewhere", "Rare", "Seldom"]
+        );
+
+        let merged = merge_results(
+            vec![
+                vec![SearchResult::Track(track("lastfm", "A", "One"))],
+                vec![SearchResult::Track(track("musicbrainz", "A", "One"))],
+            ],
+            10,
+        );
+        assert_eq!(merged.len(), 1);
+
+        let limited = merge_results(
+            vec![(0..5)
+                .map(|i| SearchResult::Track(track("lastfm", "A", &i.to_string())))
+                .collect()],
+            3,
+        );
+        assert_eq!(titles(&limited), vec!["0", "1", "2"]);
+    }
+
+    #[tokio::test]
+    async fn a_failing_provider_does_not_hide_the_others() {
+        let merging = MergingMetadataProvider::new(vec![
+            Box::new(FixedProvider {
+                id: "broken",
+                results: None,
+            }),
+            Box::new(FixedProvider {
+                id: "working",
+                results: Some(vec![track("working", "Artist", "Song")]),
+            }),
+        ]);
+
+        let results = merging.search_tracks(None, "song", 10).await.unwrap();
+        assert_eq!(titles(&results), vec!["Song"]);
+
+        let nothing = MergingMetadataProvider::new(vec![Box::new(FixedProvider {
+            id: "broken",
+            results: None,
+        })]);
+        assert!(nothing
+            .search_tracks(None, "song", 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
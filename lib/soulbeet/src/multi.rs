@@ -0,0 +1,499 @@
+//! Download backend that spreads work over several named instances of a backend,
+//! e.g. slskd nodes with different shares or networks.
+//!
+//! Searches run on every instance concurrently and their results are merged: a
+//! folder of a peer found by more than one instance is kept once, from the instance
+//! that reported it first. Every item is tagged with the instance that found it, so
+//! downloads and browsing go back to that instance.
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use shared::download::{
+    DownloadableGroup, DownloadableItem, QueuedDownload, SearchOptions, SearchResult, SearchState,
+    SharedFolder, TransferList,
+};
+use shared::metadata::{Album, Track};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+use crate::error::{Result, SoulseekError};
+use crate::DownloadBackend;
+
+/// How long [`DownloadBackend::poll_search`] waits for new results.
+const LONG_POLL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Search of one instance, part of a merged search.
+struct InstanceSearch {
+    instance: usize,
+    search_id: String,
+    /// Revision of the instance's results already merged
+    revision: u64,
+    /// Final state, `None` while the search runs
+    state: Option<SearchState>,
+    failed: bool,
+}
+
+/// A group of the merged results.
+struct MergedGroup {
+    /// Instance that reported the group
+    instance: usize,
+    /// Merged revision the group last changed in
+    revision: u64,
+    group: DownloadableGroup,
+}
+
+#[derive(Default)]
+struct MergedSearch {
+    searches: Vec<InstanceSearch>,
+    groups: Vec<MergedGroup>,
+    revision: u64,
+}
+
+impl MergedSearch {
+    /// Add a group reported by an instance. Returns whether the results changed.
+    fn merge_group(&mut self, instance: usize, group: DownloadableGroup) -> bool {
+        let revision = self.revision + 1;
+        let existing = self
+            .groups
+            .iter_mut()
+            .find(|g| g.group.source == group.source && g.group.group_id == group.group_id);
+        match existing {
+            // Already found by another instance
+            Some(existing) if existing.instance != instance => false,
+            Some(existing) => {
+                existing.group = group;
+                existing.revision = revision;
+                true
+            }
+            None => {
+                self.groups.push(MergedGroup {
+                    instance,
+                    revision,
+                    group,
+                });
+                true
+            }
+        }
+    }
+
+    fn results_since(&self, since: u64) -> Vec<DownloadableGroup> {
+        self.groups
+            .iter()
+            .filter(|g| g.revision > since)
+            .map(|g| g.group.clone())
+            .collect()
+    }
+
+    /// State of the merged search, in progress while any instance still searches.
+    fn state(&self) -> SearchState {
+        if self.searches.iter().any(|s| s.state.is_none()) {
+            return SearchState::InProgress;
+        }
+        let states: Vec<&SearchState> = self
+            .searches
+            .iter()
+            .filter_map(|s| s.state.as_ref())
+            .collect();
+        if states.contains(&&SearchState::Completed) {
+            SearchState::Completed
+        } else if states.contains(&&SearchState::TimedOut) {
+            SearchState::TimedOut
+        } else {
+            SearchState::NotFound
+        }
+    }
+}
+
+pub struct MultiDownloadBackend {
+    /// Named instances, the first one takes items not tagged with an instance
+    instances: Vec<(String, Arc<dyn DownloadBackend>)>,
+    searches: Mutex<HashMap<String, MergedSearch>>,
+    /// Instance a source was last seen on in search results
+    sources: Mutex<HashMap<String, usize>>,
+}
+
+impl MultiDownloadBackend {
+    pub fn new(instances: Vec<(String, Arc<dyn DownloadBackend>)>) -> Self {
+        Self {
+            instances,
+            searches: Mutex::new(HashMap::new()),
+            sources: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Instance an item is downloaded from.
+    fn instance_of(&self, item: &DownloadableItem) -> usize {
+        item.instance
+            .as_deref()
+            .and_then(|name| self.instances.iter().position(|(n, _)| n == name))
+            .unwrap_or(0)
+    }
+
+    fn tag(&self, instance: usize, items: &mut [DownloadableItem]) {
+        let name = &self.instances[instance].0;
+        for item in items {
+            item.instance = Some(name.clone());
+        }
+    }
+
+    /// Merge the new results of every instance still searching.
+    async fn refresh(&self, search_id: &str) -> Result<SearchState> {
+        let running: Vec<(usize, String, u64)> = {
+            let guard = self.searches.lock().await;
+            let Some(search) = guard.get(search_id) else {
+                return Ok(SearchState::NotFound);
+            };
+            search
+                .searches
+                .iter()
+                .filter(|s| s.state.is_none())
+                .map(|s| (s.instance, s.search_id.clone(), s.revision))
+                .collect()
+        };
+
+        let updates =
+            join_all(running.iter().map(|(instance, id, since)| {
+                self.instances[*instance].1.search_changes(id, *since)
+            }))
+            .await;
+
+        let mut guard = self.searches.lock().await;
+        let Some(search) = guard.get_mut(search_id) else {
+            return Ok(SearchState::NotFound);
+        };
+        let mut changed = false;
+        let mut sources = Vec::new();
+        let mut last_error = None;
+        for ((instance, id, _), update) in running.into_iter().zip(updates) {
+            let Some(entry) = search.searches.iter_mut().find(|s| s.search_id == id) else {
+                continue;
+            };
+            let update = match update {
+                Ok(update) => update,
+                Err(e) => {
+                    warn!(
+                        "Search on instance '{}' failed: {}",
+                        self.instances[instance].0, e
+                    );
+                    entry.state = Some(SearchState::NotFound);
+                    entry.failed = true;
+                    last_error = Some(e);
+                    continue;
+                }
+            };
+            entry.revision = update.revision;
+            if update.state != SearchState::InProgress {
+                entry.state = Some(update.state);
+            }
+            for mut group in update.groups {
+                self.tag(instance, &mut group.items);
+                sources.extend(group.items.iter().map(|i| (i.source.clone(), instance)));
+                changed |= search.merge_group(instance, group);
+            }
+        }
+        if changed {
+            search.revision += 1;
+        }
+
+        if let Some(e) = last_error {
+            if search.searches.iter().all(|s| s.failed) {
+                guard.remove(search_id);
+                return Err(e);
+            }
+        }
+        let state = search.state();
+        drop(guard);
+
+        self.sources.lock().await.extend(sources);
+        Ok(state)
+    }
+
+    async fn results(&self, search_id: &str, since: u64) -> (Vec<DownloadableGroup>, u64) {
+        let guard = self.searches.lock().await;
+        match guard.get(search_id) {
+            Some(search) => (search.results_since(since), search.revision),
+            None => (vec![], since),
+        }
+    }
+
+    /// Forget a merged search, the instances drop their own once done.
+    async fn finish_search(&self, search_id: &str) {
+        self.searches.lock().await.remove(search_id);
+    }
+}
+
+fn search_result(
+    search_id: &str,
+    groups: Vec<DownloadableGroup>,
+    state: SearchState,
+    revision: u64,
+) -> SearchResult {
+    SearchResult {
+        search_id: search_id.to_string(),
+        groups,
+        has_more: state == SearchState::InProgress,
+        state,
+        revision,
+    }
+}
+
+#[async_trait]
+impl DownloadBackend for MultiDownloadBackend {
+    fn id(&self) -> &'static str {
+        "multi"
+    }
+
+    fn name(&self) -> &'static str {
+        "Several instances"
+    }
+
+    async fn start_search(
+        &self,
+        album: Option<&Album>,
+        tracks: &[Track],
+        options: &SearchOptions,
+    ) -> Result<String> {
+        let started = join_all(
+            self.instances
+                .iter()
+                .map(|(_, backend)| backend.start_search(album, tracks, options)),
+        )
+        .await;
+
+        let mut searches = Vec::new();
+        let mut last_error = None;
+        for (instance, result) in started.into_iter().enumerate() {
+            match result {
+                Ok(search_id) => searches.push(InstanceSearch {
+                    instance,
+                    search_id,
+                    revision: 0,
+                    state: None,
+                    failed: false,
+                }),
+                Err(e) => {
+                    warn!(
+                        "Could not start search on instance '{}': {}",
+                        self.instances[instance].0, e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        // Ids of the instances are unique, the first one names the merged search
+        let Some(search_id) = searches.first().map(|s| s.search_id.clone()) else {
+            return Err(last_error.unwrap_or(SoulseekError::NotConfigured));
+        };
+        info!(
+            "Started search {} on {} of {} instances",
+            search_id,
+            searches.len(),
+            self.instances.len()
+        );
+        self.searches.lock().await.insert(
+            search_id.clone(),
+            MergedSearch {
+                searches,
+                ..Default::default()
+            },
+        );
+        Ok(search_id)
+    }
+
+    async fn poll_search(&self, search_id: &str) -> Result<SearchResult> {
+        let poll_start = Instant::now();
+        let start_revision = {
+            let guard = self.searches.lock().await;
+            match guard.get(search_id) {
+                Some(search) => search.revision,
+                None => return Ok(search_result(search_id, vec![], SearchState::NotFound, 0)),
+            }
+        };
+
+        loop {
+            let state = self.refresh(search_id).await?;
+            let (groups, revision) = self.results(search_id, 0).await;
+
+            if state != SearchState::InProgress {
+                self.finish_search(search_id).await;
+                return Ok(search_result(search_id, groups, state, revision));
+            }
+            if revision > start_revision {
+                return Ok(search_result(search_id, groups, state, revision));
+            }
+            if poll_start.elapsed() > LONG_POLL_TIMEOUT {
+                return Ok(search_result(search_id, vec![], state, revision));
+            }
+
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    async fn search_changes(&self, search_id: &str, since: u64) -> Result<SearchResult> {
+        let state = self.refresh(search_id).await?;
+        let (groups, revision) = self.results(search_id, since).await;
+        if state != SearchState::InProgress {
+            self.finish_search(search_id).await;
+        }
+        Ok(search_result(search_id, groups, state, revision))
+    }
+
+    async fn cancel_search(&self, search_id: &str) -> Result<()> {
+        let Some(search) = self.searches.lock().await.remove(search_id) else {
+            return Ok(());
+        };
+        let running: Vec<&InstanceSearch> = search
+            .searches
+            .iter()
+            .filter(|s| s.state.is_none())
+            .collect();
+        let results = join_all(
+            running
+                .iter()
+                .map(|s| self.instances[s.instance].1.cancel_search(&s.search_id)),
+        )
+        .await;
+        for (search, result) in running.iter().zip(results) {
+            if let Err(e) = result {
+                warn!(
+                    "Could not cancel search on instance '{}': {}",
+                    self.instances[search.instance].0, e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn browse(&self, source: &str) -> Result<Vec<SharedFolder>> {
+        // The instance that found the source first, then the others
+        let known = self.sources.lock().await.get(source).copied();
+        let order = known
+            .into_iter()
+            .chain((0..self.instances.len()).filter(|i| Some(*i) != known));
+
+        let mut last_error = None;
+        for instance in order {
+            let (name, backend) = &self.instances[instance];
+            match backend.browse(source).await {
+                Ok(mut folders) => {
+                    for folder in &mut folders {
+                        self.tag(instance, &mut folder.items);
+                    }
+                    return Ok(folders);
+                }
+                Err(e) => {
+                    debug!(
+                        "Could not browse '{}' on instance '{}': {}",
+                        source, name, e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or(SoulseekError::NotConfigured))
+    }
+
+    async fn download(&self, items: Vec<DownloadableItem>) -> Result<Vec<QueuedDownload>> {
+        let mut by_instance: Vec<Vec<DownloadableItem>> = vec![Vec::new(); self.instances.len()];
+        for item in items {
+            by_instance[self.instance_of(&item)].push(item);
+        }
+
+        let queued = join_all(
+            self.instances
+                .iter()
+                .zip(by_instance)
+                .filter(|(_, items)| !items.is_empty())
+                .map(|((name, backend), items)| async move {
+                    match backend.download(items.clone()).await {
+                        Ok(queued) => queued,
+                        Err(e) => {
+                            warn!("Downloads on instance '{}' failed: {}", name, e);
+                            items
+                                .into_iter()
+                                .map(|i| {
+                                    QueuedDownload::failed(
+                                        i.id.clone(),
+                                        i.source,
+                                        i.id,
+                                        e.to_string(),
+                                    )
+                                })
+                                .collect()
+                        }
+                    }
+                }),
+        )
+        .await;
+
+        Ok(queued.into_iter().flatten().collect())
+    }
+
+    /// Transfers of every instance. Instances that could not be listed are named in
+    /// the result, so their transfers are not taken for removed.
+    async fn get_downloads(&self) -> Result<TransferList> {
+        let results = join_all(
+            self.instances
+                .iter()
+                .map(|(_, backend)| backend.get_downloads()),
+        )
+        .await;
+
+        let mut list = TransferList::default();
+        let mut listed_any = false;
+        let mut last_error = None;
+        for ((name, _), result) in self.instances.iter().zip(results) {
+            match result {
+                Ok(listed) => {
+                    listed_any = true;
+                    list.transfers.extend(listed.transfers);
+                }
+                Err(e) => {
+                    warn!("Could not list the transfers of instance '{}': {}", name, e);
+                    list.unavailable.push(name.clone());
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) if !listed_any => Err(e),
+            _ => Ok(list),
+        }
+    }
+
+    async fn cancel_transfer(&self, source: &str, id: &str) -> Result<()> {
+        // Transfer ids are unique, only the instance that has the transfer knows it
+        let results = join_all(
+            self.instances
+                .iter()
+                .map(|(_, backend)| backend.cancel_transfer(source, id)),
+        )
+        .await;
+
+        let mut last_error = None;
+        for result in results {
+            match result {
+                Ok(()) => return Ok(()),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or(SoulseekError::NotConfigured))
+    }
+
+    async fn health_check(&self) -> bool {
+        join_all(
+            self.instances
+                .iter()
+                .map(|(_, backend)| backend.health_check()),
+        )
+        .await
+        .into_iter()
+        .any(|online| online)
+    }
+}
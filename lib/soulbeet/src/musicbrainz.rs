@@ -24,6 +24,8 @@ const BASE_DELAY_MS: u64 = 500;
 /// Maximum backoff delay cap (milliseconds)
 const MAX_BACKOFF_MS: u64 = 5000;
 
+const PROVIDER_ID: &str = "musicbrainz";
+
 // This ensures the client is initialized only once with a proper user agent.
 fn musicbrainz_client() -> &'static MusicBrainzClient {
     static CLIENT: OnceLock<MusicBrainzClient> = OnceLock::new();
@@ -256,6 +258,7 @@ pub async fn search(
                         duration: format_duration(&recording.length),
                         mbid: Some(recording.id.clone()),
                         release_mbid: first_release.map(|r| r.id.clone()),
+                        sources: vec![PROVIDER_ID.to_string()],
                     };
                     unique_tracks.insert(key);
                     results.push(SearchResult::Track(track));
@@ -311,6 +314,7 @@ pub async fn search(
                         release_date: final_release.date.as_ref().map(|d| d.0.clone()),
                         mbid: Some(final_release.id.clone()),
                         cover_url: None,
+                        sources: vec![PROVIDER_ID.to_string()],
                     }));
                 }
             }
@@ -353,6 +357,7 @@ pub async fn find_album(release_id: &str) -> Result<AlbumWithTracks, musicbrainz
                             duration: format_duration(&recording.length),
                             mbid: Some(recording.id.clone()),
                             release_mbid: Some(release.id.clone()),
+                            sources: vec![PROVIDER_ID.to_string()],
                         });
                     }
                 }
@@ -368,6 +373,7 @@ pub async fn find_album(release_id: &str) -> Result<AlbumWithTracks, musicbrainz
         release_date: release.date.map(|d| d.0),
         mbid: Some(release.id),
         cover_url: None,
+        sources: vec![PROVIDER_ID.to_string()],
     };
 
     let album_with_tracks = AlbumWithTracks { album, tracks };
@@ -392,7 +398,7 @@ impl Default for MusicBrainzProvider {
 #[async_trait::async_trait]
 impl crate::MetadataProvider for MusicBrainzProvider {
     fn id(&self) -> &'static str {
-        "musicbrainz"
+        PROVIDER_ID
     }
 
     fn name(&self) -> &'static str {
@@ -0,0 +1,372 @@
+//! Playlist parsing for bulk imports.
+//!
+//! Accepts the track lists other services export: CSV (Spotify exporters, Last.fm
+//! scrobble dumps), JSON (plain arrays, `{"tracks": [...]}` and ListenBrainz JSPF),
+//! M3U/M3U8 with `#EXTINF` lines, and XSPF.
+
+use quick_xml::{events::Event, Reader};
+use reqwest::Client;
+use serde_json::Value;
+use shared::playlist::{PlaylistEntry, PlaylistFormat};
+use std::time::Duration;
+use tracing::debug;
+
+use crate::error::{Result, SoulseekError};
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Column names recognised in CSV headers and JSON objects (lowercased).
+const ARTIST_KEYS: &[&str] = &[
+    "artist",
+    "artist name",
+    "artist name(s)",
+    "artist_name",
+    "artists",
+    "creator",
+];
+const TITLE_KEYS: &[&str] = &["title", "track", "track name", "track_name", "name", "song"];
+const ALBUM_KEYS: &[&str] = &["album", "album name", "album_name", "release_name"];
+const MBID_KEYS: &[&str] = &[
+    "mbid",
+    "recording_mbid",
+    "recording mbid",
+    "musicbrainz id",
+    "musicbrainz_trackid",
+];
+
+fn invalid(message: impl Into<String>) -> SoulseekError {
+    SoulseekError::Api {
+        status: 400,
+        message: message.into(),
+    }
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    let s = s.trim();
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+/// Keep only the first artist of lists like "A, B" or "A; B" exported by Spotify tools.
+fn primary_artist(artist: &str) -> String {
+    artist
+        .split([';', ','])
+        .next()
+        .unwrap_or(artist)
+        .trim()
+        .to_string()
+}
+
+/// Extract a MusicBrainz ID from a bare ID or a musicbrainz.org URL.
+fn extract_mbid(value: &str) -> Option<String> {
+    let candidate = value.trim().trim_end_matches('/').rsplit('/').next()?;
+    let is_uuid =
+        candidate.len() == 36 && candidate.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+    is_uuid.then(|| candidate.to_lowercase())
+}
+
+/// Guess the format from the content itself.
+pub fn detect_format(content: &str) -> Option<PlaylistFormat> {
+    let trimmed = content.trim_start_matches('\u{feff}').trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        Some(PlaylistFormat::Json)
+    } else if trimmed.starts_with("<?xml") || trimmed.starts_with("<playlist") {
+        Some(PlaylistFormat::Xspf)
+    } else if trimmed.starts_with("#EXTM3U") || trimmed.starts_with("#EXTINF") {
+        Some(PlaylistFormat::M3u)
+    } else if trimmed.lines().next().is_some_and(|l| l.contains(',')) {
+        Some(PlaylistFormat::Csv)
+    } else if !trimmed.is_empty() {
+        // Plain list of "Artist - Title" lines
+        Some(PlaylistFormat::M3u)
+    } else {
+        None
+    }
+}
+
+/// Parse a playlist, detecting the format when not given.
+pub fn parse_playlist(content: &str, format: Option<PlaylistFormat>) -> Result<Vec<PlaylistEntry>> {
+    let content = content.trim_start_matches('\u{feff}');
+    let format = format
+        .or_else(|| detect_format(content))
+        .ok_or_else(|| invalid("Empty or unrecognised playlist"))?;
+
+    debug!("Parsing playlist as {}", format);
+
+    let entries = match format {
+        PlaylistFormat::Csv => parse_csv(content)?,
+        PlaylistFormat::Json => parse_json(content)?,
+        PlaylistFormat::M3u => parse_m3u(content),
+        PlaylistFormat::Xspf => parse_xspf(content)?,
+    };
+
+    if entries.is_empty() {
+        return Err(invalid(format!("No tracks found in {} playlist", format)));
+    }
+    Ok(entries)
+}
+
+fn column(headers: &[String], keys: &[&str]) -> Option<usize> {
+    keys.iter()
+        .find_map(|key| headers.iter().position(|h| h == key))
+}
+
+fn parse_csv(content: &str) -> Result<Vec<PlaylistEntry>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| invalid(format!("Invalid CSV header: {}", e)))?
+        .iter()
+        .map(|h| h.to_lowercase())
+        .collect();
+
+    let artist_col =
+        column(&headers, ARTIST_KEYS).ok_or_else(|| invalid("CSV has no artist column"))?;
+    let title_col =
+        column(&headers, TITLE_KEYS).ok_or_else(|| invalid("CSV has no title column"))?;
+    let album_col = column(&headers, ALBUM_KEYS);
+    let mbid_col = column(&headers, MBID_KEYS);
+
+    let mut entries = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| invalid(format!("Invalid CSV row: {}", e)))?;
+        let (Some(artist), Some(title)) = (
+            record.get(artist_col).and_then(non_empty),
+            record.get(title_col).and_then(non_empty),
+        ) else {
+            continue;
+        };
+
+        entries.push(PlaylistEntry {
+            artist: primary_artist(&artist),
+            title,
+            album: album_col.and_then(|c| record.get(c)).and_then(non_empty),
+            mbid: mbid_col.and_then(|c| record.get(c)).and_then(extract_mbid),
+        });
+    }
+    Ok(entries)
+}
+
+fn json_string(obj: &serde_json::Map<String, Value>, keys: &[&str]) -> Option<String> {
+    obj.iter()
+        .find(|(k, _)| keys.contains(&k.to_lowercase().as_str()))
+        .and_then(|(_, v)| match v {
+            Value::String(s) => non_empty(s),
+            // JSPF identifiers and Spotify-style artist arrays
+            Value::Array(values) => values.iter().find_map(|v| {
+                v.as_str()
+                    .or_else(|| v.get("name").and_then(|n| n.as_str()))
+                    .and_then(non_empty)
+            }),
+            Value::Object(o) => o.get("name").and_then(|n| n.as_str()).and_then(non_empty),
+            _ => None,
+        })
+}
+
+fn json_entry(value: &Value) -> Option<PlaylistEntry> {
+    let obj = value.as_object()?;
+    let artist = json_string(obj, ARTIST_KEYS)?;
+    let title = json_string(obj, TITLE_KEYS)?;
+
+    let mbid = json_string(obj, MBID_KEYS)
+        .or_else(|| json_string(obj, &["identifier"]))
+        .and_then(|s| extract_mbid(&s));
+
+    Some(PlaylistEntry {
+        artist: primary_artist(&artist),
+        title,
+        album: json_string(obj, ALBUM_KEYS),
+        mbid,
+    })
+}
+
+fn parse_json(content: &str) -> Result<Vec<PlaylistEntry>> {
+    let value: Value =
+        serde_json::from_str(content).map_err(|e| invalid(format!("Invalid JSON: {}", e)))?;
+
+    // JSPF: {"playlist": {"track": [...]}}, otherwise {"tracks": [...]} or a bare array
+    let tracks = value
+        .pointer("/playlist/track")
+        .or_else(|| value.get("tracks"))
+        .or_else(|| value.get("items"))
+        .unwrap_or(&value);
+
+    let list = tracks
+        .as_array()
+        .ok_or_else(|| invalid("JSON playlist must contain a list of tracks"))?;
+
+    Ok(list
+        .iter()
+        // Spotify API dumps wrap tracks as {"track": {...}}
+        .map(|v| v.get("track").filter(|t| t.is_object()).unwrap_or(v))
+        .filter_map(json_entry)
+        .collect())
+}
+
+/// Split "Artist - Title" as used in EXTINF lines and file names.
+fn split_artist_title(s: &str) -> Option<(String, String)> {
+    let (artist, title) = s.split_once(" - ")?;
+    Some((non_empty(artist)?, non_empty(title)?))
+}
+
+fn parse_m3u(content: &str) -> Vec<PlaylistEntry> {
+    let mut entries = Vec::new();
+    let mut pending_extinf: Option<(String, String)> = None;
+
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            // #EXTINF:<duration>,<Artist> - <Title>
+            pending_extinf = info
+                .split_once(',')
+                .and_then(|(_, rest)| split_artist_title(rest));
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let parsed = pending_extinf.take().or_else(|| {
+            // Fall back to the file name: ".../Artist - Title.flac"
+            let name = line.rsplit(['/', '\\']).next().unwrap_or(line);
+            let stem = name.rsplit_once('.').map(|(s, _)| s).unwrap_or(name);
+            split_artist_title(stem)
+        });
+
+        if let Some((artist, title)) = parsed {
+            entries.push(PlaylistEntry {
+                artist,
+                title,
+                album: None,
+                mbid: None,
+            });
+        }
+    }
+    entries
+}
+
+fn parse_xspf(content: &str) -> Result<Vec<PlaylistEntry>> {
+    let mut reader = Reader::from_str(content);
+    reader.config_mut().trim_text(true);
+
+    let mut entries = Vec::new();
+    let mut in_track = false;
+    let mut field: Option<String> = None;
+    let (mut artist, mut title, mut album, mut mbid) = (None, None, None, None);
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                if name == "track" {
+                    in_track = true;
+                    (artist, title, album, mbid) = (None, None, None, None);
+                } else if in_track {
+                    field = Some(name);
+                }
+            }
+            Ok(Event::Text(text)) => {
+                let (Some(name), true) = (field.as_deref(), in_track) else {
+                    continue;
+                };
+                let value = text
+                    .unescape()
+                    .map_err(|e| invalid(format!("Invalid XSPF: {}", e)))?
+                    .to_string();
+                match name {
+                    "creator" => artist = non_empty(&value),
+                    "title" => title = non_empty(&value),
+                    "album" => album = non_empty(&value),
+                    "identifier" if mbid.is_none() => mbid = extract_mbid(&value),
+                    _ => {}
+                }
+            }
+            Ok(Event::End(e)) => {
+                if e.local_name().as_ref() == b"track" {
+                    in_track = false;
+                    if let (Some(artist), Some(title)) = (artist.take(), title.take()) {
+                        entries.push(PlaylistEntry {
+                            artist,
+                            title,
+                            album: album.take(),
+                            mbid: mbid.take(),
+                        });
+                    }
+                }
+                field = None;
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(invalid(format!("Invalid XSPF: {}", e))),
+            _ => {}
+        }
+    }
+    Ok(entries)
+}
+
+/// Rewrite known playlist page URLs to an endpoint returning a parseable document.
+fn resolve_playlist_url(raw: &str) -> Result<String> {
+    let url = url::Url::parse(raw)?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid("Only http(s) playlist URLs are supported"));
+    }
+
+    match url.host_str() {
+        // https://listenbrainz.org/playlist/<mbid> -> JSPF from the API
+        Some("listenbrainz.org") | Some("www.listenbrainz.org") => {
+            let mbid = url
+                .path_segments()
+                .and_then(|mut s| {
+                    s.find(|seg| *seg == "playlist")?;
+                    s.next()
+                })
+                .and_then(extract_mbid)
+                .ok_or_else(|| invalid("Unrecognised ListenBrainz playlist URL"))?;
+            Ok(format!("https://api.listenbrainz.org/1/playlist/{}", mbid))
+        }
+        Some(host) if host.ends_with("spotify.com") => Err(invalid(
+            "Spotify playlists need to be exported to CSV first (e.g. with Exportify)",
+        )),
+        _ => Ok(raw.to_string()),
+    }
+}
+
+fn format_from_url(url: &str) -> Option<PlaylistFormat> {
+    let path = url.split(['?', '#']).next()?.to_lowercase();
+    if path.ends_with(".csv") {
+        Some(PlaylistFormat::Csv)
+    } else if path.ends_with(".json") || path.ends_with(".jspf") {
+        Some(PlaylistFormat::Json)
+    } else if path.ends_with(".m3u") || path.ends_with(".m3u8") {
+        Some(PlaylistFormat::M3u)
+    } else if path.ends_with(".xspf") {
+        Some(PlaylistFormat::Xspf)
+    } else {
+        None
+    }
+}
+
+/// Download and parse a playlist from a URL.
+pub async fn fetch_playlist(
+    url: &str,
+    format: Option<PlaylistFormat>,
+) -> Result<Vec<PlaylistEntry>> {
+    let resolved = resolve_playlist_url(url)?;
+    let client = Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("soulbeet/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+
+    let response = client.get(&resolved).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(SoulseekError::Api {
+            status: status.as_u16(),
+            message: format!("Failed to fetch playlist from {}", resolved),
+        });
+    }
+
+    let body = response.text().await?;
+    parse_playlist(&body, format.or_else(|| format_from_url(&resolved)))
+}
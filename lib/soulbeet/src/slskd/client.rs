@@ -1,17 +1,24 @@
-use super::processing;
+use super::{
+    processing::{self, GroupKey},
+    queries::{self, SearchStrategy},
+};
 use crate::{
     error::{Result, SoulseekError},
-    slskd::models::{DownloadRequestFile, SearchResponse},
+    slskd::models::{ApplicationResponse, BrowseResponse, DownloadRequestFile, SearchResponse},
 };
 use chrono::{DateTime, Duration, Utc};
 use reqwest::{Client, Method, Response};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use shared::{
+    download::{SearchOptions, SharedFolder},
     metadata::{Album, Track},
-    slskd::{AlbumResult, DownloadResponse, FileEntry, FlattenedFiles, SearchState, TrackResult},
+    slskd::{
+        AlbumResult, DownloadResponse, FileEntry, FlattenedFiles, MatchResult, SearchResult,
+        SearchState, ServerState, ShareState, TrackResult,
+    },
 };
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::Path,
     sync::Arc,
     time::Duration as StdDuration,
@@ -20,11 +27,21 @@ use tokio::sync::Mutex;
 use tracing::{debug, info, warn};
 use url::Url;
 
-const MAX_SEARCH_RESULTS: usize = 50;
+/// Defaults of the search options left unset, see [`SearchOptions`]
+const DEFAULT_SEARCH_TIMEOUT_SECS: u64 = 120;
+const DEFAULT_MIN_UPLOAD_SPEED: u32 = 10;
+const DEFAULT_RESPONSE_LIMIT: u32 = 100;
+const DEFAULT_FILE_LIMIT: u32 = 10_000;
+const DEFAULT_MAX_SEARCH_RESULTS: usize = 50;
+/// Bounds of the search timeout, longer searches would outlive the long polls
+const MIN_SEARCH_TIMEOUT_SECS: u64 = 5;
+const MAX_SEARCH_TIMEOUT_SECS: u64 = 600;
 
 /// HTTP client timeouts
 const HTTP_CONNECT_TIMEOUT_SECS: u64 = 10;
 const HTTP_REQUEST_TIMEOUT_SECS: u64 = 30;
+/// Browsing waits for the peer to send its whole share list
+const BROWSE_TIMEOUT_SECS: u64 = 120;
 
 /// Circuit breaker configuration
 const CIRCUIT_BREAKER_FAILURE_THRESHOLD: u64 = 5;
@@ -123,14 +140,218 @@ impl Default for DownloadConfig {
     }
 }
 
+/// Options of a search with the defaults filled in.
 #[derive(Debug, Clone)]
+struct SearchParams {
+    timeout: Duration,
+    min_upload_speed: u32,
+    min_bitrate: Option<u32>,
+    response_limit: u32,
+    file_limit: u32,
+    include_locked: bool,
+    max_results: usize,
+}
+
+impl From<&SearchOptions> for SearchParams {
+    fn from(options: &SearchOptions) -> Self {
+        let timeout_secs = options
+            .timeout_secs
+            .unwrap_or(DEFAULT_SEARCH_TIMEOUT_SECS)
+            .clamp(MIN_SEARCH_TIMEOUT_SECS, MAX_SEARCH_TIMEOUT_SECS);
+        Self {
+            timeout: Duration::seconds(timeout_secs as i64),
+            min_upload_speed: options.min_upload_speed.unwrap_or(DEFAULT_MIN_UPLOAD_SPEED),
+            min_bitrate: options.min_bitrate.filter(|b| *b > 0),
+            response_limit: options
+                .response_limit
+                .unwrap_or(DEFAULT_RESPONSE_LIMIT)
+                .max(1),
+            file_limit: options.file_limit.unwrap_or(DEFAULT_FILE_LIMIT).max(1),
+            include_locked: options.include_locked.unwrap_or(false),
+            max_results: options
+                .max_results
+                .unwrap_or(DEFAULT_MAX_SEARCH_RESULTS)
+                .max(1),
+        }
+    }
+}
+
+#[derive(Debug)]
 struct SearchContext {
     artist: String,
     album: Option<String>,
     track_titles: Vec<String>,
+    track_artists: Vec<String>,
     start_time: DateTime<Utc>,
-    timeout: Duration,
-    seen_response_count: usize,
+    params: SearchParams,
+    /// slskd searches whose responses are merged, the primary search first.
+    search_ids: Vec<String>,
+    /// Whether searches for tracks missing from the results were started.
+    track_queries_started: bool,
+    /// Number of responses already processed, per slskd search.
+    processed: HashMap<String, usize>,
+    /// Files already scored, by peer and filename, as several queries find the same files.
+    seen_files: HashSet<(String, String)>,
+    /// Every file that matched the search well enough.
+    scored_files: Vec<(MatchResult, SearchResult)>,
+    /// Results by group, with the revision they last changed in.
+    albums: HashMap<GroupKey, (u64, AlbumResult)>,
+    composite: Option<(u64, AlbumResult)>,
+    /// Incremented whenever a result changes.
+    revision: u64,
+}
+
+impl SearchContext {
+    fn new(
+        artist: String,
+        album: Option<String>,
+        tracks: &[Track],
+        params: SearchParams,
+        search_ids: Vec<String>,
+    ) -> Self {
+        Self {
+            artist,
+            album,
+            track_titles: tracks.iter().map(|t| t.title.clone()).collect(),
+            track_artists: tracks.iter().map(|t| t.artist.clone()).collect(),
+            start_time: Utc::now(),
+            params,
+            search_ids,
+            track_queries_started: false,
+            processed: HashMap::new(),
+            seen_files: HashSet::new(),
+            scored_files: Vec::new(),
+            albums: HashMap::new(),
+            composite: None,
+            revision: 0,
+        }
+    }
+
+    /// Score the responses not processed yet and rebuild only the results they touch.
+    ///
+    /// `responses` are all responses of each slskd search so far. Returns whether a
+    /// result changed.
+    fn apply_responses(&mut self, responses: Vec<(String, Vec<SearchResponse>)>) -> bool {
+        let mut new_responses = Vec::new();
+        for (search_id, list) in responses {
+            let processed = self.processed.entry(search_id).or_insert(0);
+            let total = list.len();
+            if total > *processed {
+                new_responses.extend(list.into_iter().skip(*processed));
+                *processed = total;
+            }
+        }
+
+        let min_bitrate = self.params.min_bitrate;
+        for response in &mut new_responses {
+            if self.params.include_locked {
+                let locked = std::mem::take(&mut response.locked_files);
+                response.files.extend(locked);
+            }
+            response.files.retain(|f| {
+                let bitrate_ok = match (min_bitrate, f.bit_rate) {
+                    (Some(min), Some(bitrate)) => bitrate >= min as i32,
+                    _ => true,
+                };
+                bitrate_ok
+                    && self
+                        .seen_files
+                        .insert((response.username.clone(), f.filename.clone()))
+            });
+        }
+
+        let track_titles: Vec<&str> = self.track_titles.iter().map(|s| s.as_str()).collect();
+        let scored = processing::score_responses(
+            &new_responses,
+            &self.artist,
+            self.album.as_deref(),
+            &track_titles,
+        );
+        if scored.is_empty() {
+            return false;
+        }
+
+        let touched: HashSet<GroupKey> = scored
+            .iter()
+            .map(|(rank, search)| processing::group_key(rank, search))
+            .collect();
+        self.scored_files.extend(scored);
+
+        let revision = self.revision + 1;
+        let mut changed = false;
+        for key in touched {
+            let files: Vec<_> = self
+                .scored_files
+                .iter()
+                .filter(|(rank, search)| {
+                    search.username == key.0
+                        && rank.guessed_artist == key.1
+                        && rank.guessed_album == key.2
+                })
+                .cloned()
+                .collect();
+            let Some(album) = processing::find_best_albums(&files, &track_titles)
+                .into_iter()
+                .next()
+            else {
+                continue;
+            };
+            if self
+                .albums
+                .get(&key)
+                .is_none_or(|(_, current)| *current != album)
+            {
+                self.albums.insert(key, (revision, album));
+                changed = true;
+            }
+        }
+
+        if changed {
+            let albums: Vec<&AlbumResult> = self.albums.values().map(|(_, a)| a).collect();
+            if let Some(composite) = processing::composite_album(&albums, &track_titles) {
+                if self
+                    .composite
+                    .as_ref()
+                    .is_none_or(|(_, current)| *current != composite)
+                {
+                    self.composite = Some((revision, composite));
+                }
+            }
+            self.revision = revision;
+        }
+        changed
+    }
+
+    /// Results that changed after revision `since`, best first.
+    fn results_since(&self, since: u64) -> Vec<AlbumResult> {
+        let mut albums: Vec<AlbumResult> = self
+            .albums
+            .values()
+            .chain(&self.composite)
+            .filter(|(revision, _)| *revision > since)
+            .map(|(_, album)| album.clone())
+            .collect();
+        albums.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        albums.truncate(self.params.max_results);
+        albums
+    }
+
+    /// Best result so far, if any.
+    fn best(&self) -> Option<&AlbumResult> {
+        self.albums
+            .values()
+            .chain(&self.composite)
+            .map(|(_, album)| album)
+            .max_by(|a, b| {
+                a.score
+                    .partial_cmp(&b.score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    }
 }
 
 #[derive(Debug)]
@@ -143,6 +364,7 @@ pub struct SoulseekClient {
     max_searches_per_window: usize,
     rate_limit_window: Duration,
     download_config: DownloadConfig,
+    search_strategy: SearchStrategy,
     circuit_breaker: Arc<CircuitBreaker>,
 }
 
@@ -153,6 +375,7 @@ pub struct SoulseekClientBuilder {
     max_searches_per_window: Option<usize>,
     rate_limit_window_seconds: Option<i64>,
     download_config: Option<DownloadConfig>,
+    search_strategy: Option<SearchStrategy>,
 }
 
 impl SoulseekClientBuilder {
@@ -189,6 +412,11 @@ impl SoulseekClientBuilder {
         self
     }
 
+    pub fn search_strategy(mut self, strategy: SearchStrategy) -> Self {
+        self.search_strategy = Some(strategy);
+        self
+    }
+
     pub fn build(self) -> Result<SoulseekClient> {
         let base_url_str = self.base_url.ok_or(SoulseekError::NotConfigured)?;
         let base_url = Url::parse(base_url_str.trim_end_matches('/'))?;
@@ -213,6 +441,7 @@ impl SoulseekClientBuilder {
             max_searches_per_window: self.max_searches_per_window.unwrap_or(35),
             rate_limit_window: Duration::seconds(self.rate_limit_window_seconds.unwrap_or(220)),
             download_config: self.download_config.unwrap_or_default(),
+            search_strategy: self.search_strategy.unwrap_or_default(),
             circuit_breaker: Arc::new(CircuitBreaker::default()),
         })
     }
@@ -224,6 +453,18 @@ impl SoulseekClient {
         method: Method,
         endpoint: &str,
         body: Option<B>,
+    ) -> Result<T> {
+        self.make_request_with_timeout(method, endpoint, body, None)
+            .await
+    }
+
+    /// Same as `make_request`, with a request timeout other than the client's default.
+    async fn make_request_with_timeout<T: DeserializeOwned, B: Serialize>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Option<B>,
+        timeout: Option<StdDuration>,
     ) -> Result<T> {
         // Check circuit breaker before making request
         if self.circuit_breaker.is_open().await {
@@ -247,6 +488,9 @@ impl SoulseekClient {
         if let Some(b) = body {
             request = request.json(&b);
         }
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
 
         let response = match request.send().await {
             Ok(resp) => {
@@ -329,25 +573,26 @@ impl SoulseekClient {
         Ok(())
     }
 
-    pub async fn start_search(
+    /// Take a rate limit slot without waiting, unless only `reserve` slots are left.
+    async fn try_reserve_search(&self, reserve: usize) -> bool {
+        let mut timestamps = self.search_timestamps.lock().await;
+        let now = Utc::now();
+        let window_start = now - self.rate_limit_window;
+        timestamps.retain(|&ts| ts > window_start);
+        if timestamps.len() + reserve >= self.max_searches_per_window {
+            return false;
+        }
+        timestamps.push(now);
+        true
+    }
+
+    /// Start a single slskd search, the caller takes care of the rate limit.
+    async fn post_search(
         &self,
-        album: Option<Album>,
-        tracks: Vec<Track>,
+        query: &str,
+        params: &SearchParams,
         timeout: Duration,
     ) -> Result<String> {
-        self.wait_for_rate_limit().await?;
-
-        let track_titles: Vec<String> = tracks.iter().map(|t| t.title.clone()).collect();
-
-        let query = match album {
-            Some(ref album) => match tracks.len() {
-                1 => format!("{} {}", album.artist.trim(), tracks[0].title.trim()),
-                _ => format!("{} {}", album.artist.trim(), album.title.trim()),
-            },
-            // No album, should be a single track search
-            None => format!("{} {}", tracks[0].artist.trim(), tracks[0].title.trim()),
-        };
-
         info!(
             "Starting search for: '{}' with timeout {}ms",
             query,
@@ -358,15 +603,19 @@ impl SoulseekClient {
         #[serde(rename_all = "camelCase")]
         struct SearchRequest<'a> {
             search_text: &'a str,
-            timeout: i64,
+            search_timeout: i64,
             filter_responses: bool,
             minimum_peer_upload_speed: u32,
+            response_limit: u32,
+            file_limit: u32,
         }
         let request_body = SearchRequest {
-            search_text: &query,
-            timeout: timeout.num_milliseconds(),
+            search_text: query,
+            search_timeout: timeout.num_milliseconds(),
             filter_responses: true,
-            minimum_peer_upload_speed: 10,
+            minimum_peer_upload_speed: params.min_upload_speed,
+            response_limit: params.response_limit,
+            file_limit: params.file_limit,
         };
 
         #[derive(Deserialize)]
@@ -376,27 +625,243 @@ impl SoulseekClient {
         let search_id_resp: SearchId = self
             .make_request(Method::POST, "searches", Some(&request_body))
             .await?;
-        let search_id = search_id_resp.id;
+        Ok(search_id_resp.id)
+    }
 
-        self.active_searches.lock().await.insert(
-            search_id.clone(),
-            SearchContext {
-                album: album.as_ref().map(|a| a.title.clone()),
-                artist: album
-                    .as_ref()
-                    .map(|a| a.artist.clone())
-                    .unwrap_or_else(|| tracks[0].artist.clone()),
-                track_titles,
-                start_time: Utc::now(),
-                timeout,
-                seen_response_count: 0,
-            },
+    /// Start a search for an album or track.
+    ///
+    /// Sends the primary query and the variants of the configured [`SearchStrategy`],
+    /// the returned id stands for all of them. Variants only use rate limit slots
+    /// that are free right away. Options left unset use the built-in defaults.
+    pub async fn start_search(
+        &self,
+        album: Option<Album>,
+        tracks: Vec<Track>,
+        options: &SearchOptions,
+    ) -> Result<String> {
+        self.wait_for_rate_limit().await?;
+
+        let params = SearchParams::from(options);
+        let query = queries::primary_query(album.as_ref(), &tracks);
+        let search_id = self.post_search(&query, &params, params.timeout).await?;
+
+        let strategy = &self.search_strategy;
+        let mut search_ids = vec![search_id.clone()];
+        for variant in queries::variant_queries(album.as_ref(), &tracks, strategy) {
+            if !self.try_reserve_search(strategy.reserved_searches).await {
+                debug!("Search budget low, skipping query '{}'", variant);
+                break;
+            }
+            match self.post_search(&variant, &params, params.timeout).await {
+                Ok(id) => search_ids.push(id),
+                Err(e) => warn!("Failed to start search for '{}': {}", variant, e),
+            }
+        }
+        let query_count = search_ids.len();
+
+        let artist = album
+            .as_ref()
+            .map(|a| a.artist.clone())
+            .unwrap_or_else(|| tracks[0].artist.clone());
+        let context = SearchContext::new(
+            artist,
+            album.as_ref().map(|a| a.title.clone()),
+            &tracks,
+            params,
+            search_ids,
         );
+        self.active_searches
+            .lock()
+            .await
+            .insert(search_id.clone(), context);
 
-        info!("Search initiated with ID: {search_id}");
+        info!("Search initiated with ID: {search_id} ({query_count} queries)");
         Ok(search_id)
     }
 
+    /// Responses of every slskd search of a search, the first being the primary one.
+    ///
+    /// Errors of the primary search are returned, the other searches are skipped.
+    async fn fetch_responses(
+        &self,
+        search_ids: &[String],
+    ) -> Result<Vec<(String, Vec<SearchResponse>)>> {
+        let requests = search_ids.iter().map(|id| {
+            let endpoint = format!("searches/{}/responses", id);
+            async move {
+                self.make_request::<Vec<SearchResponse>, ()>(Method::GET, &endpoint, None)
+                    .await
+            }
+        });
+        let results = futures::future::join_all(requests).await;
+
+        let mut responses = Vec::new();
+        for (i, (id, result)) in search_ids.iter().zip(results).enumerate() {
+            match result {
+                Ok(r) => responses.push((id.clone(), r)),
+                Err(e) if i == 0 => return Err(e),
+                Err(e) => debug!("Skipping responses of search {}: {}", id, e),
+            }
+        }
+        Ok(responses)
+    }
+
+    /// Search the tracks missing from the best result on their own, once per search.
+    async fn start_track_queries(&self, search_id: &str) {
+        let strategy = &self.search_strategy;
+
+        let (artist, missing, params, remaining) = {
+            let mut guard = self.active_searches.lock().await;
+            let Some(ctx) = guard.get_mut(search_id) else {
+                return;
+            };
+            if ctx.track_queries_started || ctx.album.is_none() {
+                return;
+            }
+            let Some(best) = ctx.best() else {
+                return;
+            };
+            if best.track_count >= ctx.track_titles.len() {
+                return;
+            }
+
+            // Give the first responses time to come in before judging the results
+            let elapsed = Utc::now() - ctx.start_time;
+            let remaining = ctx.params.timeout - elapsed;
+            if elapsed < ctx.params.timeout / 3 || remaining < Duration::seconds(10) {
+                return;
+            }
+
+            let various = queries::is_various_artists(&ctx.artist);
+            let missing: Vec<(String, String)> = ctx
+                .track_titles
+                .iter()
+                .zip(&ctx.track_artists)
+                .filter(|(title, _)| !best.tracks.iter().any(|t| &t.title == *title))
+                .take(strategy.max_track_queries)
+                .map(|(title, artist)| (title.clone(), artist.clone()))
+                .collect();
+            ctx.track_queries_started = true;
+            let artist = (!various).then(|| ctx.artist.clone());
+            (artist, missing, ctx.params.clone(), remaining)
+        };
+
+        let mut search_ids = Vec::new();
+        for (title, track_artist) in missing {
+            let query = queries::track_query(artist.as_ref().unwrap_or(&track_artist), &title);
+            if !self.try_reserve_search(strategy.reserved_searches).await {
+                debug!("Search budget low, skipping query '{}'", query);
+                break;
+            }
+            match self.post_search(&query, &params, remaining).await {
+                Ok(id) => search_ids.push(id),
+                Err(e) => warn!("Failed to start search for '{}': {}", query, e),
+            }
+        }
+
+        if let Some(ctx) = self.active_searches.lock().await.get_mut(search_id) {
+            ctx.search_ids.extend(search_ids);
+        }
+    }
+
+    /// Process the responses that arrived since the last refresh of a search.
+    ///
+    /// Returns the state of the search, the caller cleans up once it is no longer
+    /// in progress.
+    async fn refresh_search(&self, search_id: &str) -> Result<SearchState> {
+        let (search_ids, start_time, timeout) = {
+            let guard = self.active_searches.lock().await;
+            match guard.get(search_id) {
+                Some(ctx) => (ctx.search_ids.clone(), ctx.start_time, ctx.params.timeout),
+                None => return Ok(SearchState::NotFound),
+            }
+        };
+
+        if (Utc::now() - start_time) >= timeout {
+            info!("Search timeout reached");
+            return Ok(SearchState::Completed);
+        }
+
+        let responses = match self.fetch_responses(&search_ids).await {
+            Ok(responses) => responses,
+            Err(SoulseekError::Api { status: 404, .. }) => {
+                info!("Search 404");
+                return Ok(SearchState::NotFound);
+            }
+            Err(e) => return Err(e),
+        };
+
+        let (changed, result_count, max_results) = {
+            let mut guard = self.active_searches.lock().await;
+            let Some(ctx) = guard.get_mut(search_id) else {
+                return Ok(SearchState::NotFound);
+            };
+            let changed = ctx.apply_responses(responses);
+            (changed, ctx.albums.len(), ctx.params.max_results)
+        };
+
+        if result_count > max_results {
+            return Ok(SearchState::Completed);
+        }
+        if changed {
+            self.start_track_queries(search_id).await;
+        }
+        Ok(SearchState::InProgress)
+    }
+
+    /// Revision of a search's results, `None` for an unknown search.
+    async fn search_revision(&self, search_id: &str) -> Option<u64> {
+        let guard = self.active_searches.lock().await;
+        guard.get(search_id).map(|ctx| ctx.revision)
+    }
+
+    /// Results of a search that changed after revision `since`, and the current revision.
+    async fn search_results(&self, search_id: &str, since: u64) -> (Vec<AlbumResult>, u64) {
+        let guard = self.active_searches.lock().await;
+        match guard.get(search_id) {
+            Some(ctx) => (ctx.results_since(since), ctx.revision),
+            None => (vec![], since),
+        }
+    }
+
+    /// Forget a search and delete all of its slskd searches.
+    pub async fn finish_search(&self, search_id: &str) {
+        let context = self.active_searches.lock().await.remove(search_id);
+        let search_ids = context
+            .map(|ctx| ctx.search_ids)
+            .unwrap_or_else(|| vec![search_id.to_string()]);
+        for id in &search_ids {
+            let _ = self.delete_search(id).await;
+        }
+    }
+
+    /// Refresh a search once and return the results that changed after revision
+    /// `since`, with the current revision.
+    ///
+    /// Unlike [`poll_search`](Self::poll_search) this does not wait for new
+    /// responses, it is meant to be called on an interval by a stream.
+    pub async fn search_changes(
+        &self,
+        search_id: &str,
+        since: u64,
+    ) -> Result<(Vec<AlbumResult>, u64, SearchState)> {
+        let state = match self.refresh_search(search_id).await {
+            Ok(state) => state,
+            Err(e) => {
+                self.finish_search(search_id).await;
+                warn!("Search {} failed with error, cleaning up: {}", search_id, e);
+                return Err(e);
+            }
+        };
+
+        let (albums, revision) = self.search_results(search_id, since).await;
+        if state != SearchState::InProgress {
+            self.finish_search(search_id).await;
+        }
+        Ok((albums, revision, state))
+    }
+
+    /// Wait up to 10 seconds for new results and return all results so far.
     pub async fn poll_search(
         &self,
         search_id: String,
@@ -405,89 +870,41 @@ impl SoulseekClient {
         // Long-poll duration: hold the request for up to 10 seconds waiting for new data
         let long_poll_timeout = Duration::seconds(10);
 
-        loop {
-            let context = {
-                let guard = self.active_searches.lock().await;
-                guard.get(&search_id).cloned()
-            };
-
-            let context = match context {
-                Some(ctx) => ctx,
-                None => return Ok((vec![], false, SearchState::NotFound)),
-            };
-
-            if (Utc::now() - context.start_time) >= context.timeout {
-                info!("Search timeout reached");
-                self.active_searches.lock().await.remove(&search_id);
-                let _ = self.delete_search(&search_id).await;
-                return Ok((vec![], false, SearchState::Completed));
-            }
+        let Some(start_revision) = self.search_revision(&search_id).await else {
+            return Ok((vec![], false, SearchState::NotFound));
+        };
 
-            let endpoint = format!("searches/{}/responses", search_id);
-            match self
-                .make_request::<Vec<SearchResponse>, ()>(Method::GET, &endpoint, None)
-                .await
-            {
-                Ok(current_responses) => {
-                    let total_len = current_responses.len();
-
-                    if total_len > context.seen_response_count {
-                        // Update seen count
-                        {
-                            let mut guard = self.active_searches.lock().await;
-                            if let Some(ctx) = guard.get_mut(&search_id) {
-                                ctx.seen_response_count = total_len;
-                            }
-                        }
-
-                        let track_titles_ref: Vec<&str> =
-                            context.track_titles.iter().map(|s| s.as_str()).collect();
-                        let mut albums = processing::process_search_responses(
-                            &current_responses,
-                            &context.artist,
-                            context.album.as_deref(),
-                            &track_titles_ref,
-                        );
-
-                        albums.sort_by(|a, b| {
-                            b.score
-                                .partial_cmp(&a.score)
-                                .unwrap_or(std::cmp::Ordering::Equal)
-                        });
-
-                        if albums.len() > MAX_SEARCH_RESULTS {
-                            albums.truncate(MAX_SEARCH_RESULTS);
-                            self.active_searches.lock().await.remove(&search_id);
-                            let _ = self.delete_search(&search_id).await;
-                            return Ok((albums, false, SearchState::Completed));
-                        } else {
-                            return Ok((albums, true, SearchState::InProgress));
-                        }
-                    } else {
-                        // No new data
-                        if (Utc::now() - poll_start) > long_poll_timeout {
-                            // Long poll expired, return "no update" but "in progress"
-                            return Ok((vec![], true, SearchState::InProgress));
-                        }
-
-                        // Wait a bit before retrying slskd
-                        tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
-                        continue;
-                    }
-                }
-                Err(SoulseekError::Api { status: 404, .. }) => {
-                    self.active_searches.lock().await.remove(&search_id);
-                    info!("Search 404");
-                    return Ok((vec![], false, SearchState::NotFound));
-                }
+        loop {
+            let state = match self.refresh_search(&search_id).await {
+                Ok(state) => state,
                 Err(e) => {
                     // Clean up search context on any error to prevent leaks
-                    self.active_searches.lock().await.remove(&search_id);
-                    let _ = self.delete_search(&search_id).await;
+                    self.finish_search(&search_id).await;
                     warn!("Search {} failed with error, cleaning up: {}", search_id, e);
                     return Err(e);
                 }
+            };
+
+            if state != SearchState::InProgress {
+                let (albums, _) = self.search_results(&search_id, 0).await;
+                self.finish_search(&search_id).await;
+                return Ok((albums, false, state));
+            }
+
+            let revision = self.search_revision(&search_id).await.unwrap_or_default();
+            if revision > start_revision {
+                let (albums, _) = self.search_results(&search_id, 0).await;
+                return Ok((albums, true, SearchState::InProgress));
+            }
+
+            // No new data
+            if (Utc::now() - poll_start) > long_poll_timeout {
+                // Long poll expired, return "no update" but "in progress"
+                return Ok((vec![], true, SearchState::InProgress));
             }
+
+            // Wait a bit before retrying slskd
+            tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
         }
     }
 
@@ -537,6 +954,13 @@ impl SoulseekClient {
             }
         }
 
+        if results.iter().any(|r| r.error.is_none() && r.id.is_none()) {
+            match self.get_all_downloads().await {
+                Ok(transfers) => assign_transfer_ids(&mut results, &transfers),
+                Err(e) => warn!("Could not look up the ids of queued transfers: {}", e),
+            }
+        }
+
         Ok(results)
     }
 
@@ -591,6 +1015,7 @@ impl SoulseekClient {
         batch
             .iter()
             .map(|f| DownloadResponse {
+                id: None,
                 username: username.to_string(),
                 filename: f.filename.clone(),
                 size: f.size as u64,
@@ -644,6 +1069,7 @@ impl SoulseekClient {
                 return Ok(batch
                     .iter()
                     .map(|f| DownloadResponse {
+                        id: None,
                         username: username.to_string(),
                         filename: f.filename.clone(),
                         size: f.size as u64,
@@ -686,6 +1112,8 @@ impl SoulseekClient {
         #[derive(Deserialize, Debug)]
         #[serde(rename_all = "camelCase")]
         struct SlskdDownloadResponse {
+            #[serde(default)]
+            id: Option<String>,
             filename: String,
         }
 
@@ -710,6 +1138,7 @@ impl SoulseekClient {
             batch
                 .iter()
                 .map(|f| DownloadResponse {
+                    id: None,
                     username: username.to_string(),
                     filename: f.filename.clone(),
                     size: f.size as u64,
@@ -723,6 +1152,7 @@ impl SoulseekClient {
             batch
                 .iter()
                 .map(|f| DownloadResponse {
+                    id: None,
                     username: username.to_string(),
                     filename: f.filename.clone(),
                     size: f.size as u64,
@@ -825,6 +1255,7 @@ impl SoulseekClient {
                     .enqueued
                     .into_iter()
                     .map(|d| DownloadResponse {
+                        id: d.id,
                         username: username.to_string(),
                         filename: d.filename.clone(),
                         size: find_size(&d.filename),
@@ -855,6 +1286,7 @@ impl SoulseekClient {
                     };
 
                     results.push(DownloadResponse {
+                        id: None,
                         username: username.to_string(),
                         filename: filename.clone(),
                         size: find_size(&filename),
@@ -874,6 +1306,7 @@ impl SoulseekClient {
                 return multi
                     .into_iter()
                     .map(|d| DownloadResponse {
+                        id: d.id,
                         username: username.to_string(),
                         filename: d.filename.clone(),
                         size: find_size(&d.filename),
@@ -886,6 +1319,7 @@ impl SoulseekClient {
         // Try single file response
         if let Ok(single) = serde_json::from_str::<SlskdDownloadResponse>(resp_text) {
             return vec![DownloadResponse {
+                id: single.id,
                 username: username.to_string(),
                 filename: single.filename.clone(),
                 size: find_size(&single.filename),
@@ -932,6 +1366,31 @@ impl SoulseekClient {
         .await
     }
 
+    pub async fn get_all_uploads(&self) -> Result<Vec<FileEntry>> {
+        let flattened: FlattenedFiles = self
+            .make_request(Method::GET, "transfers/uploads", None::<()>)
+            .await?;
+        Ok(flattened.0)
+    }
+
+    /// Connection to the Soulseek server and the username slskd is logged in as.
+    pub async fn server_state(&self) -> Result<ServerState> {
+        self.make_request(Method::GET, "server", None::<()>).await
+    }
+
+    pub async fn share_state(&self) -> Result<ShareState> {
+        let application: ApplicationResponse = self
+            .make_request(Method::GET, "application", None::<()>)
+            .await?;
+        Ok(application.shares)
+    }
+
+    /// Start a scan of the shared folders, slskd runs it in the background.
+    pub async fn rescan_shares(&self) -> Result<()> {
+        info!("Rescanning shares");
+        self.make_request(Method::PUT, "shares", None::<()>).await
+    }
+
     pub async fn delete_search(&self, search_id: &str) -> Result<()> {
         let endpoint = format!("searches/{search_id}");
         debug!("Deleting search {}", search_id);
@@ -945,6 +1404,21 @@ impl SoulseekClient {
         }
     }
 
+    /// Folders shared by a peer that hold audio files.
+    pub async fn browse_user(&self, username: &str) -> Result<Vec<SharedFolder>> {
+        let endpoint = format!("users/{username}/browse");
+        info!("Browsing shares of '{}'", username);
+        let response: BrowseResponse = self
+            .make_request_with_timeout(
+                Method::GET,
+                &endpoint,
+                None::<()>,
+                Some(StdDuration::from_secs(BROWSE_TIMEOUT_SECS)),
+            )
+            .await?;
+        Ok(processing::shared_folders(username, response.directories))
+    }
+
     pub async fn check_connection(&self) -> bool {
         self.make_request::<serde_json::Value, ()>(Method::GET, "session", None)
             .await
@@ -952,6 +1426,25 @@ impl SoulseekClient {
     }
 }
 
+/// Fill in the transfer ids slskd did not return when queueing, from its transfer list.
+///
+/// A file downloaded before is listed once per request, the latest one is taken.
+fn assign_transfer_ids(results: &mut [DownloadResponse], transfers: &[FileEntry]) {
+    for result in results
+        .iter_mut()
+        .filter(|r| r.error.is_none() && r.id.is_none())
+    {
+        result.id = transfers
+            .iter()
+            .filter(|t| t.username == result.username && t.filename == result.filename)
+            .max_by(|a, b| a.requested_at.cmp(&b.requested_at))
+            .map(|t| t.id.clone());
+        if result.id.is_none() {
+            debug!("No transfer found for queued file {}", result.filename);
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl crate::DownloadBackend for SoulseekClient {
     fn id(&self) -> &'static str {
@@ -962,9 +1455,13 @@ impl crate::DownloadBackend for SoulseekClient {
         "Soulseek"
     }
 
-    async fn start_search(&self, album: Option<&Album>, tracks: &[Track]) -> Result<String> {
-        let timeout = Duration::seconds(120);
-        self.start_search(album.cloned(), tracks.to_vec(), timeout)
+    async fn start_search(
+        &self,
+        album: Option<&Album>,
+        tracks: &[Track],
+        options: &SearchOptions,
+    ) -> Result<String> {
+        self.start_search(album.cloned(), tracks.to_vec(), options)
             .await
     }
 
@@ -975,9 +1472,35 @@ impl crate::DownloadBackend for SoulseekClient {
             groups: results.into_iter().map(Into::into).collect(),
             has_more,
             state: state.into(),
+            revision: 0,
         })
     }
 
+    async fn search_changes(
+        &self,
+        search_id: &str,
+        since: u64,
+    ) -> Result<shared::download::SearchResult> {
+        let (results, revision, state) = self.search_changes(search_id, since).await?;
+        Ok(shared::download::SearchResult {
+            search_id: search_id.to_string(),
+            groups: results.into_iter().map(Into::into).collect(),
+            has_more: state == SearchState::InProgress,
+            state: state.into(),
+            revision,
+        })
+    }
+
+    async fn cancel_search(&self, search_id: &str) -> Result<()> {
+        info!("Cancelling search {}", search_id);
+        self.finish_search(search_id).await;
+        Ok(())
+    }
+
+    async fn browse(&self, source: &str) -> Result<Vec<SharedFolder>> {
+        self.browse_user(source).await
+    }
+
     async fn download(&self, items: Vec<shared::download::DownloadableItem>) -> Result<Vec<shared::download::QueuedDownload>> {
         let tracks: Vec<TrackResult> = items
             .into_iter()
@@ -988,9 +1511,16 @@ impl crate::DownloadBackend for SoulseekClient {
         Ok(responses.into_iter().map(Into::into).collect())
     }
 
-    async fn get_downloads(&self) -> Result<Vec<shared::download::DownloadProgress>> {
+    async fn get_downloads(&self) -> Result<shared::download::TransferList> {
         let entries = self.get_all_downloads().await?;
-        Ok(entries.into_iter().map(Into::into).collect())
+        Ok(shared::download::TransferList {
+            transfers: entries.into_iter().map(Into::into).collect(),
+            unavailable: Vec::new(),
+        })
+    }
+
+    async fn cancel_transfer(&self, source: &str, id: &str) -> Result<()> {
+        self.cancel_download(source, id, true).await
     }
 
     async fn health_check(&self) -> bool {
@@ -1,6 +1,8 @@
 mod client;
 mod models;
 mod processing;
+mod queries;
 mod utils;
 
 pub use client::*;
+pub use queries::SearchStrategy;
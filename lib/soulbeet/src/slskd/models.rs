@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use shared::slskd::ShareState;
 
 // Internal structs for deserializing raw API responses
 #[derive(Deserialize, Debug)]
@@ -15,11 +16,45 @@ pub(crate) struct SearchResponseFile {
 pub(crate) struct SearchResponse {
     pub username: String,
     pub files: Vec<SearchResponseFile>,
+    /// Files the peer only shares with some users
+    #[serde(default)]
+    pub locked_files: Vec<SearchResponseFile>,
     pub has_free_upload_slot: bool,
     pub upload_speed: i32,
     pub queue_length: i32,
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BrowseFile {
+    pub filename: String,
+    pub size: i64,
+    pub bit_rate: Option<i32>,
+    pub length: Option<i32>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BrowseDirectory {
+    pub name: String,
+    #[serde(default)]
+    pub files: Vec<BrowseFile>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BrowseResponse {
+    #[serde(default)]
+    pub directories: Vec<BrowseDirectory>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ApplicationResponse {
+    #[serde(default)]
+    pub shares: ShareState,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub(crate) struct DownloadRequestFile {
     pub filename: String,
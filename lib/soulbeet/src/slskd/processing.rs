@@ -1,23 +1,46 @@
 use super::utils;
-use crate::slskd::models::SearchResponse;
+use crate::slskd::models::{BrowseDirectory, SearchResponse};
 use itertools::Itertools;
+use shared::download::SharedFolder;
 use shared::slskd::{AlbumResult, MatchResult, SearchResult, TrackResult};
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-pub fn process_search_responses(
+/// Source of results assembled from the files of several peers.
+const COMPOSITE_SOURCE: &str = "multiple peers";
+
+const AUDIO_EXTENSIONS: [&str; 7] = ["flac", "wav", "m4a", "ogg", "aac", "wma", "mp3"];
+
+fn is_audio_file(filename: &str) -> bool {
+    Path::new(filename)
+        .extension()
+        .and_then(|s| s.to_str())
+        .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Files of a search grouped into one album result: peer, guessed artist and guessed album.
+pub type GroupKey = (String, String, String);
+
+pub fn group_key(rank: &MatchResult, search: &SearchResult) -> GroupKey {
+    (
+        search.username.clone(),
+        rank.guessed_artist.clone(),
+        rank.guessed_album.clone(),
+    )
+}
+
+/// Score the audio files of search responses, dropping those that match too poorly.
+pub fn score_responses(
     responses: &[SearchResponse],
     searched_artist: &str,
     searched_album: Option<&str>,
     expected_tracks: &[&str],
-) -> Vec<AlbumResult> {
+) -> Vec<(MatchResult, SearchResult)> {
     const MIN_SCORE_THRESHOLD: f64 = 0.6;
-    let audio_extensions: HashSet<&str> = ["flac", "wav", "m4a", "ogg", "aac", "wma", "mp3"]
-        .iter()
-        .copied()
-        .collect();
+    let audio_extensions: HashSet<&str> = AUDIO_EXTENSIONS.iter().copied().collect();
 
-    let scored_files: Vec<(MatchResult, SearchResult)> = responses
+    responses
         .iter()
         .flat_map(|resp| {
             resp.files.iter().filter_map(|file| {
@@ -57,12 +80,64 @@ pub fn process_search_responses(
                 Some((rank_result, search_result))
             })
         })
-        .collect();
+        .collect()
+}
 
-    find_best_albums(&scored_files, expected_tracks)
+/// Folders of a peer's browse response that hold audio files, with only those files.
+pub fn shared_folders(username: &str, directories: Vec<BrowseDirectory>) -> Vec<SharedFolder> {
+    directories
+        .into_iter()
+        .filter_map(|dir| {
+            let album = dir
+                .name
+                .rsplit(['\\', '/'])
+                .next()
+                .unwrap_or(&dir.name)
+                .to_string();
+
+            let items: Vec<_> = dir
+                .files
+                .into_iter()
+                .filter(|f| is_audio_file(&f.filename))
+                .map(|f| {
+                    // Browse responses name files relative to their directory
+                    let filename = if f.filename.contains('\\') {
+                        f.filename.clone()
+                    } else {
+                        format!("{}\\{}", dir.name, f.filename)
+                    };
+                    let title = filename.rsplit('\\').next().unwrap_or_default().to_string();
+                    let track = TrackResult {
+                        base: SearchResult {
+                            username: username.to_string(),
+                            filename,
+                            size: f.size,
+                            bitrate: f.bit_rate,
+                            duration: f.length,
+                            // Not part of a browse response
+                            has_free_upload_slot: false,
+                            upload_speed: 0,
+                            queue_length: 0,
+                        },
+                        artist: String::new(),
+                        title,
+                        album: album.clone(),
+                        match_score: 0.0,
+                    };
+                    track.into()
+                })
+                .collect();
+
+            (!items.is_empty()).then(|| SharedFolder {
+                source: username.to_string(),
+                path: dir.name,
+                items,
+            })
+        })
+        .collect()
 }
 
-fn find_best_albums(
+pub fn find_best_albums(
     scored_files: &[(MatchResult, SearchResult)],
     expected_tracks: &[&str],
 ) -> Vec<AlbumResult> {
@@ -70,13 +145,9 @@ fn find_best_albums(
         return vec![];
     }
 
-    let album_groups = scored_files.iter().into_group_map_by(|(rank, search)| {
-        (
-            search.username.clone(),
-            rank.guessed_artist.clone(),
-            rank.guessed_album.clone(),
-        )
-    });
+    let album_groups = scored_files
+        .iter()
+        .into_group_map_by(|(rank, search)| group_key(rank, search));
 
     album_groups
         .into_iter()
@@ -113,50 +184,136 @@ fn find_best_albums(
                 return None;
             }
 
-            let completeness = if !expected_tracks.is_empty() {
-                final_tracks.len() as f64 / expected_tracks.len() as f64
-            } else {
-                1.0
-            };
+            Some(album_result(
+                username,
+                album_title,
+                artist,
+                final_tracks,
+                expected_tracks.len(),
+            ))
+        })
+        .collect()
+}
+
+/// Sum up the tracks picked for an album into a scored result.
+fn album_result(
+    username: String,
+    album_title: String,
+    artist: String,
+    tracks: Vec<TrackResult>,
+    expected_count: usize,
+) -> AlbumResult {
+    let completeness = if expected_count > 0 {
+        tracks.len() as f64 / expected_count as f64
+    } else {
+        1.0
+    };
 
-            let total_size: i64 = final_tracks.iter().map(|t| t.base.size).sum();
-            let dominant_quality = final_tracks
-                .iter()
-                .map(|t| t.base.quality())
-                .counts()
-                .into_iter()
-                .max_by_key(|&(_, count)| count)
-                .map(|(val, _)| val)
-                .unwrap_or_default();
+    let total_size: i64 = tracks.iter().map(|t| t.base.size).sum();
+    let dominant_quality = tracks
+        .iter()
+        .map(|t| t.base.quality())
+        .counts()
+        .into_iter()
+        .max_by_key(|&(_, count)| count)
+        .map(|(val, _)| val)
+        .unwrap_or_default();
 
-            let first_track = final_tracks[0].base.clone();
-            let album_path = first_track.filename.clone();
+    let first_track = tracks[0].base.clone();
+    let album_path = first_track.filename.clone();
 
-            let avg_score: f64 =
-                final_tracks.iter().map(|t| t.match_score).sum::<f64>() / final_tracks.len() as f64;
-            let avg_format_score = final_tracks
-                .iter()
-                .map(|t| t.base.quality_score())
-                .sum::<f64>()
-                / final_tracks.len() as f64;
+    let avg_score: f64 = tracks.iter().map(|t| t.match_score).sum::<f64>() / tracks.len() as f64;
+    let avg_format_score =
+        tracks.iter().map(|t| t.base.quality_score()).sum::<f64>() / tracks.len() as f64;
 
-            let album_quality_score =
-                (avg_score * 0.3) + (completeness * 0.3) + (avg_format_score * 0.4);
+    let album_quality_score = (avg_score * 0.3) + (completeness * 0.3) + (avg_format_score * 0.4);
 
-            Some(AlbumResult {
-                username,
-                album_path,
-                album_title,
-                artist: Some(artist),
-                track_count: final_tracks.len(),
-                total_size,
-                tracks: final_tracks,
-                dominant_quality,
-                has_free_upload_slot: first_track.has_free_upload_slot,
-                upload_speed: first_track.upload_speed,
-                queue_length: first_track.queue_length,
-                score: album_quality_score,
-            })
+    AlbumResult {
+        username,
+        album_path,
+        album_title,
+        artist: Some(artist),
+        track_count: tracks.len(),
+        total_size,
+        tracks,
+        dominant_quality,
+        has_free_upload_slot: first_track.has_free_upload_slot,
+        upload_speed: first_track.upload_speed,
+        queue_length: first_track.queue_length,
+        score: album_quality_score,
+        composite: false,
+    }
+}
+
+/// Assemble the expected tracklist from several peers when no single peer has it all.
+///
+/// Tracks of the most complete result are kept, missing ones are taken from the other
+/// results, preferring files in the same format and at a similar bitrate.
+pub fn composite_album(albums: &[&AlbumResult], expected_tracks: &[&str]) -> Option<AlbumResult> {
+    let anchor = albums.iter().max_by(|a, b| {
+        a.track_count
+            .cmp(&b.track_count)
+            .then_with(|| a.score.partial_cmp(&b.score).unwrap_or(Ordering::Equal))
+    })?;
+    if anchor.track_count >= expected_tracks.len() {
+        return None;
+    }
+
+    let bitrates: Vec<i32> = anchor
+        .tracks
+        .iter()
+        .filter_map(|t| t.base.bitrate)
+        .collect();
+    let anchor_bitrate =
+        (!bitrates.is_empty()).then(|| bitrates.iter().sum::<i32>() as f64 / bitrates.len() as f64);
+
+    // How well a file from another peer fits in with the anchor's files
+    let fit = |track: &TrackResult| -> f64 {
+        let same_format = if track.base.quality() == anchor.dominant_quality {
+            1.0
+        } else {
+            0.0
+        };
+        let similar_bitrate = match (anchor_bitrate, track.base.bitrate) {
+            (Some(a), Some(b)) if a > 0.0 && b > 0 => {
+                1.0 - ((a - b as f64).abs() / a.max(b as f64)).min(1.0)
+            }
+            _ => 0.5,
+        };
+        same_format
+            + similar_bitrate * 0.5
+            + track.match_score * 0.3
+            + track.base.quality_score() * 0.2
+    };
+
+    let tracks: Vec<TrackResult> = expected_tracks
+        .iter()
+        .filter_map(|expected| {
+            if let Some(track) = anchor.tracks.iter().find(|t| t.title == *expected) {
+                return Some(track.clone());
+            }
+            albums
+                .iter()
+                .flat_map(|a| a.tracks.iter())
+                .filter(|t| t.title == *expected)
+                .max_by(|a, b| fit(a).partial_cmp(&fit(b)).unwrap_or(Ordering::Equal))
+                .cloned()
         })
-        .collect()
+        .collect();
+
+    if tracks.len() <= anchor.track_count {
+        return None;
+    }
+
+    let mut album = album_result(
+        COMPOSITE_SOURCE.to_string(),
+        anchor.album_title.clone(),
+        anchor.artist.clone().unwrap_or_default(),
+        tracks,
+        expected_tracks.len(),
+    );
+    // Stable across polls, so the result is updated in place
+    album.album_path = COMPOSITE_SOURCE.to_string();
+    album.composite = true;
+    Some(album)
 }
@@ -0,0 +1,166 @@
+//! Query strings for a Soulseek search.
+//!
+//! Peers share releases under all kinds of folder names, so besides the primary
+//! `"{artist} {album}"` query a search can send a few variants. Their responses
+//! are merged before scoring.
+
+use shared::metadata::{Album, Track};
+
+/// Album titles too common to be searched without the artist.
+const GENERIC_TITLES: &[&str] = &[
+    "best of",
+    "greatest hits",
+    "hits",
+    "live",
+    "singles",
+    "the best of",
+    "the collection",
+    "the greatest hits",
+    "untitled",
+];
+
+/// Which query variants a search sends besides its primary query.
+#[derive(Debug, Clone)]
+pub struct SearchStrategy {
+    /// Search the album title on its own, for folders without the artist name.
+    pub album_only: bool,
+    /// Search artist and album with punctuation removed.
+    pub simplified: bool,
+    /// Search artist and album with the release year.
+    pub with_year: bool,
+    /// Maximum number of per-track searches for tracks missing from the best result.
+    pub max_track_queries: usize,
+    /// Searches of the rate limit window kept free for primary queries.
+    /// Variants are skipped rather than waited for once only these are left.
+    pub reserved_searches: usize,
+}
+
+impl Default for SearchStrategy {
+    fn default() -> Self {
+        Self {
+            album_only: true,
+            simplified: true,
+            with_year: true,
+            max_track_queries: 5,
+            reserved_searches: 10,
+        }
+    }
+}
+
+impl SearchStrategy {
+    /// Only the primary query, as a single search.
+    pub fn single() -> Self {
+        Self {
+            album_only: false,
+            simplified: false,
+            with_year: false,
+            max_track_queries: 0,
+            reserved_searches: 0,
+        }
+    }
+}
+
+/// Whether an album artist stands for a compilation rather than a real artist.
+pub(crate) fn is_various_artists(artist: &str) -> bool {
+    let artist = simplify(artist).to_lowercase();
+    artist == "various artists" || artist == "various" || artist == "va"
+}
+
+/// The query every search sends, and waits for a rate limit slot for.
+pub(crate) fn primary_query(album: Option<&Album>, tracks: &[Track]) -> String {
+    match album {
+        Some(album) => match tracks.len() {
+            1 => {
+                let artist = if is_various_artists(&album.artist) {
+                    &tracks[0].artist
+                } else {
+                    &album.artist
+                };
+                format!("{} {}", artist.trim(), tracks[0].title.trim())
+            }
+            // Compilations are rarely shared with "Various Artists" in the folder name
+            _ if is_various_artists(&album.artist) => album.title.trim().to_string(),
+            _ => format!("{} {}", album.artist.trim(), album.title.trim()),
+        },
+        // No album, should be a single track search
+        None => track_query(&tracks[0].artist, &tracks[0].title),
+    }
+}
+
+/// Additional queries enabled by `strategy`, without the primary query or duplicates.
+pub(crate) fn variant_queries(
+    album: Option<&Album>,
+    tracks: &[Track],
+    strategy: &SearchStrategy,
+) -> Vec<String> {
+    let primary = primary_query(album, tracks);
+    let mut candidates = Vec::new();
+
+    match album {
+        Some(album) if tracks.len() != 1 => {
+            let various = is_various_artists(&album.artist);
+            let base = if various {
+                simplify(&album.title)
+            } else {
+                simplify(&format!("{} {}", album.artist, album.title))
+            };
+
+            if strategy.album_only && !various && is_distinctive(&album.title) {
+                candidates.push(album.title.trim().to_string());
+            }
+            if strategy.simplified {
+                candidates.push(base.clone());
+            }
+            if strategy.with_year {
+                if let Some(year) = release_year(album.release_date.as_deref()) {
+                    candidates.push(format!("{} {}", base, year));
+                }
+            }
+        }
+        _ => {
+            if strategy.simplified {
+                candidates.push(simplify(&primary));
+            }
+        }
+    }
+
+    let mut queries: Vec<String> = Vec::new();
+    for query in candidates {
+        let is_new = !query.is_empty()
+            && !query.eq_ignore_ascii_case(&primary)
+            && !queries.iter().any(|q| q.eq_ignore_ascii_case(&query));
+        if is_new {
+            queries.push(query);
+        }
+    }
+    queries
+}
+
+/// Query for a single track.
+pub(crate) fn track_query(artist: &str, title: &str) -> String {
+    format!("{} {}", artist.trim(), title.trim())
+}
+
+/// Replace punctuation with spaces and collapse whitespace, letters of any script are kept.
+fn simplify(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether an album title is specific enough to find the release without the artist.
+fn is_distinctive(title: &str) -> bool {
+    let simplified = simplify(title).to_lowercase();
+    let words = simplified.split_whitespace().count();
+    let chars = simplified.chars().filter(|c| !c.is_whitespace()).count();
+    (words >= 2 || chars >= 6) && !GENERIC_TITLES.contains(&simplified.as_str())
+}
+
+/// Year of a `YYYY-MM-DD`, `YYYY-MM` or `YYYY` release date.
+fn release_year(date: Option<&str>) -> Option<&str> {
+    let year = date?.get(..4)?;
+    year.chars().all(|c| c.is_ascii_digit()).then_some(year)
+}
@@ -1,7 +1,9 @@
 use async_trait::async_trait;
 use shared::{
-    download::{DownloadProgress, DownloadableItem, QueuedDownload, SearchResult},
-    library::DuplicateReport,
+    download::{
+        DownloadableItem, QueuedDownload, SearchOptions, SearchResult, SharedFolder, TransferList,
+    },
+    library::{DuplicateReport, LibraryTrack},
     metadata::{Album, AlbumWithTracks, SearchResult as MetadataSearchResult, Track},
 };
 use std::path::Path;
@@ -35,10 +37,25 @@ pub trait DownloadBackend: Send + Sync {
     fn id(&self) -> &'static str;
     fn name(&self) -> &'static str;
 
-    async fn start_search(&self, album: Option<&Album>, tracks: &[Track]) -> Result<String>;
+    /// Start a search, unset `options` use the backend's defaults.
+    async fn start_search(
+        &self,
+        album: Option<&Album>,
+        tracks: &[Track],
+        options: &SearchOptions,
+    ) -> Result<String>;
     async fn poll_search(&self, search_id: &str) -> Result<SearchResult>;
+    /// Groups that changed after revision `since`, without waiting for new results.
+    async fn search_changes(&self, search_id: &str, since: u64) -> Result<SearchResult>;
+    /// Stop a search early and drop its results.
+    async fn cancel_search(&self, search_id: &str) -> Result<()>;
+    /// Folders shared by a source, e.g. a peer seen in the search results.
+    async fn browse(&self, source: &str) -> Result<Vec<SharedFolder>>;
     async fn download(&self, items: Vec<DownloadableItem>) -> Result<Vec<QueuedDownload>>;
-    async fn get_downloads(&self) -> Result<Vec<DownloadProgress>>;
+    /// Transfers of the backend, an error when none could be listed.
+    async fn get_downloads(&self) -> Result<TransferList>;
+    /// Stop a transfer and remove it from the backend's transfer list.
+    async fn cancel_transfer(&self, source: &str, id: &str) -> Result<()>;
     async fn health_check(&self) -> bool;
 }
 
@@ -63,6 +80,20 @@ pub trait MusicImporter: Send + Sync {
     ) -> Result<ImportResult>;
 
     async fn find_duplicates(&self, libraries: &[&Path]) -> Result<DuplicateReport>;
+
+    /// All items in the library at `target`.
+    async fn library_tracks(&self, target: &Path) -> Result<Vec<LibraryTrack>>;
+
+    /// Items imported into the library at `target` since the given time.
+    async fn recent_items(
+        &self,
+        target: &Path,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<LibraryTrack>>;
+
+    /// Store lyrics for an imported item, in the library and in the file's tags.
+    async fn set_lyrics(&self, target: &Path, item_path: &Path, lyrics: &str) -> Result<()>;
+
     async fn health_check(&self) -> bool;
 }
 
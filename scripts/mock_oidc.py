@@ -0,0 +1,159 @@
+#!/usr/bin/env python3
+"""Minimal OpenID Connect issuer for trying out SSO locally.
+
+Every authorization request is approved for the configured user, no login page.
+ID tokens are signed with HS256 using the client secret, so set the same value
+in OIDC_CLIENT_SECRET. PKCE (S256) is enforced like a real provider would.
+
+    python3 scripts/mock_oidc.py --user alice --groups soulbeet-admins
+
+    OIDC_ISSUER_URL=http://localhost:9000
+    OIDC_CLIENT_ID=soulbeet
+    OIDC_CLIENT_SECRET=mock-secret
+    OIDC_REDIRECT_URL=http://localhost:9765/api/auth/oidc/callback
+"""
+
+import argparse
+import base64
+import hashlib
+import hmac
+import json
+import secrets
+import time
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from urllib.parse import parse_qs, urlencode, urlparse
+
+# code -> (client_id, redirect_uri, nonce, code_challenge)
+CODES = {}
+
+
+def b64url(data: bytes) -> str:
+    return base64.urlsafe_b64encode(data).rstrip(b"=").decode()
+
+
+def sign_hs256(claims: dict, secret: str) -> str:
+    header = b64url(json.dumps({"alg": "HS256", "typ": "JWT"}).encode())
+    payload = b64url(json.dumps(claims).encode())
+    signature = hmac.new(
+        secret.encode(), f"{header}.{payload}".encode(), hashlib.sha256
+    ).digest()
+    return f"{header}.{payload}.{b64url(signature)}"
+
+
+def make_handler(args):
+    issuer = args.issuer or f"http://localhost:{args.port}"
+
+    class Handler(BaseHTTPRequestHandler):
+        def send_json(self, status, body):
+            data = json.dumps(body).encode()
+            self.send_response(status)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(data)))
+            self.end_headers()
+            self.wfile.write(data)
+
+        def do_GET(self):
+            url = urlparse(self.path)
+            query = {k: v[0] for k, v in parse_qs(url.query).items()}
+
+            if url.path == "/.well-known/openid-configuration":
+                self.send_json(
+                    200,
+                    {
+                        "issuer": issuer,
+                        "authorization_endpoint": f"{issuer}/authorize",
+                        "token_endpoint": f"{issuer}/token",
+                        "jwks_uri": f"{issuer}/jwks",
+                        "response_types_supported": ["code"],
+                        "id_token_signing_alg_values_supported": ["HS256"],
+                        "code_challenge_methods_supported": ["S256"],
+                    },
+                )
+            elif url.path == "/jwks":
+                self.send_json(200, {"keys": []})
+            elif url.path == "/authorize":
+                if query.get("client_id") != args.client_id:
+                    return self.send_json(400, {"error": "unauthorized_client"})
+                if query.get("code_challenge_method") != "S256":
+                    return self.send_json(400, {"error": "invalid_request"})
+
+                code = secrets.token_urlsafe(24)
+                CODES[code] = (
+                    query["client_id"],
+                    query["redirect_uri"],
+                    query.get("nonce"),
+                    query["code_challenge"],
+                )
+                target = query["redirect_uri"] + "?" + urlencode(
+                    {"code": code, "state": query.get("state", "")}
+                )
+                self.send_response(302)
+                self.send_header("Location", target)
+                self.end_headers()
+            else:
+                self.send_json(404, {"error": "not_found"})
+
+        def do_POST(self):
+            if urlparse(self.path).path != "/token":
+                return self.send_json(404, {"error": "not_found"})
+
+            length = int(self.headers.get("Content-Length", 0))
+            form = {k: v[0] for k, v in parse_qs(self.rfile.read(length).decode()).items()}
+
+            entry = CODES.pop(form.get("code", ""), None)
+            if entry is None:
+                return self.send_json(400, {"error": "invalid_grant"})
+            client_id, redirect_uri, nonce, challenge = entry
+
+            verifier = form.get("code_verifier", "")
+            expected = b64url(hashlib.sha256(verifier.encode()).digest())
+            if expected != challenge:
+                return self.send_json(400, {"error": "invalid_grant", "error_description": "PKCE check failed"})
+            if form.get("client_id") != client_id or form.get("redirect_uri") != redirect_uri:
+                return self.send_json(400, {"error": "invalid_grant"})
+            if form.get("client_secret") != args.client_secret:
+                return self.send_json(401, {"error": "invalid_client"})
+
+            now = int(time.time())
+            claims = {
+                "iss": issuer,
+                "aud": client_id,
+                "sub": f"mock-{args.user}",
+                "iat": now,
+                "exp": now + 300,
+                "preferred_username": args.user,
+                "email": f"{args.user}@example.com",
+                "groups": [g for g in args.groups.split(",") if g],
+            }
+            if nonce:
+                claims["nonce"] = nonce
+
+            self.send_json(
+                200,
+                {
+                    "access_token": secrets.token_urlsafe(24),
+                    "token_type": "Bearer",
+                    "expires_in": 300,
+                    "id_token": sign_hs256(claims, args.client_secret),
+                },
+            )
+
+    return Handler
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("--port", type=int, default=9000)
+    parser.add_argument("--issuer", help="defaults to http://localhost:<port>")
+    parser.add_argument("--client-id", default="soulbeet")
+    parser.add_argument("--client-secret", default="mock-secret")
+    parser.add_argument("--user", default="alice")
+    parser.add_argument("--groups", default="", help="comma separated")
+    args = parser.parse_args()
+
+    print(f"Mock OIDC issuer on port {args.port}, logging everyone in as {args.user}")
+    HTTPServer(("", args.port), make_handler(args)).serve_forever()
+
+
+if __name__ == "__main__":
+    main()
@@ -1,4 +1,4 @@
-use api::auth::AuthResponse;
+use api::auth::{AuthResponse, Role};
 use dioxus::prelude::*;
 
 #[derive(Clone, Copy, Debug)]
@@ -31,18 +31,25 @@ impl Auth {
         false
     }
 
-    /// Wraps a server function call to automatically handle authentication errors.
-    pub async fn call<T>(
-        mut self,
-        fut: impl std::future::Future<Output = Result<T, ServerFnError>>,
-    ) -> Result<T, ServerFnError> {
-        match fut.await {
-            Ok(val) => Ok(val),
-            Err(e) => {
-                self.handle_error(&e);
-                Err(e)
+    /// Calls a server function and handles authentication errors.
+    ///
+    /// An expired access token is renewed and the call made once more, the user is
+    /// only logged out when the session itself is gone.
+    pub async fn call<T, F, Fut>(mut self, call: F) -> Result<T, ServerFnError>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<T, ServerFnError>>,
+    {
+        let mut result = call().await;
+        if let Err(ServerFnError::ServerError { code: 401, .. }) = result {
+            if api::refresh_token().await.is_ok() {
+                result = call().await;
             }
         }
+        if let Err(e) = &result {
+            self.handle_error(e);
+        }
+        result
     }
 
     pub fn user_id(&self) -> Option<String> {
@@ -56,6 +63,25 @@ impl Auth {
     pub fn is_logged_in(&self) -> bool {
         self.state.read().is_some()
     }
+
+    pub fn role(&self) -> Option<Role> {
+        self.state.read().as_ref().map(|a| a.role)
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.role().is_some_and(|r| r.is_admin())
+    }
+
+    pub fn can_write(&self) -> bool {
+        self.role().is_some_and(|r| r.can_write())
+    }
+
+    pub fn must_change_password(&self) -> bool {
+        self.state
+            .read()
+            .as_ref()
+            .is_some_and(|a| a.must_change_password)
+    }
 }
 
 pub fn use_auth() -> Auth {
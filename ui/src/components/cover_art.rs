@@ -1,14 +1,35 @@
 use dioxus::prelude::*;
 use shared::metadata::Album;
 
+/// Size requested from the server, covers are displayed at 80px (160px on HiDPI).
+const COVER_SIZE: u32 = 250;
+
+/// Percent-encode a value for use in a query string.
+fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Covers are proxied and cached by the server, the browser never hits third parties.
 fn get_album_cover_url(album: &Album) -> Option<String> {
     if let Some(url) = &album.cover_url {
-        return Some(url.clone());
+        return Some(format!(
+            "/api/covers?url={}&size={}",
+            encode_query_value(url),
+            COVER_SIZE
+        ));
     }
     album
         .mbid
         .as_ref()
-        .map(|mbid| format!("https://coverartarchive.org/release/{}/front-250", mbid))
+        .map(|mbid| format!("/api/covers?mbid={}&size={}", mbid, COVER_SIZE))
 }
 
 #[component]
@@ -0,0 +1,258 @@
+use dioxus::logger::tracing::info;
+use dioxus::prelude::*;
+use shared::discovery::{DiscoveryPeriod, DiscoveryReport, DiscoverySource, DiscoveryTrack};
+use shared::playlist::PlaylistImportReport;
+use std::collections::HashSet;
+
+use crate::playlist::{poll_import, PlaylistReport};
+use crate::settings_context::use_settings;
+use crate::{use_auth, Button, Checkbox};
+
+const INPUT_CLASS: &str = "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono";
+const LABEL_CLASS: &str = "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider";
+
+fn track_id(track: &DiscoveryTrack) -> String {
+    format!("{}|{}", track.artist, track.title)
+}
+
+fn describe_sources(sources: &[DiscoverySource]) -> String {
+    sources
+        .iter()
+        .map(|s| match s {
+            DiscoverySource::TopTrack => "top track".to_string(),
+            DiscoverySource::Loved => "loved".to_string(),
+            DiscoverySource::SimilarArtist(artist) => format!("similar to {artist}"),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Last.fm based suggestions of tracks missing from the user's libraries.
+#[component]
+pub fn Discover() -> Element {
+    let auth = use_auth();
+    let mut settings = use_settings();
+
+    let mut lastfm_username = use_signal(String::new);
+    let mut period = use_signal(DiscoveryPeriod::default);
+    let mut discovery = use_signal::<Option<DiscoveryReport>>(|| None);
+    let mut selected = use_signal(HashSet::<String>::new);
+    let mut folders = use_signal(Vec::new);
+    let mut selected_folder = use_signal(String::new);
+    let mut queue_report = use_signal::<Option<PlaylistImportReport>>(|| None);
+    let mut error = use_signal(String::new);
+    let mut loading = use_signal(|| false);
+    let mut synced = use_signal(|| false);
+
+    use_effect(move || {
+        if settings.is_loaded() && !synced() {
+            lastfm_username.set(settings.lastfm_username().unwrap_or_default());
+            synced.set(true);
+        }
+    });
+
+    use_future(move || async move {
+        if let Ok(user_folders) = auth.call(api::get_user_folders).await {
+            // Read-only shares cannot be downloaded into
+            let user_folders: Vec<_> = user_folders
+                .into_iter()
+                .filter(|f| f.access.can_write())
+                .collect();
+            if user_folders.len() == 1 {
+                selected_folder.set(user_folders[0].path.clone());
+            }
+            folders.set(user_folders);
+        }
+    });
+
+    let run_discovery = move || async move {
+        error.set(String::new());
+        loading.set(true);
+        discovery.set(None);
+
+        let username = lastfm_username().trim().to_string();
+        if settings.lastfm_username().unwrap_or_default() != username {
+            let update = api::UpdateUserSettings {
+                lastfm_username: Some(username.clone()),
+                ..Default::default()
+            };
+            if let Err(e) = settings.update(update).await {
+                info!("Failed to save Last.fm username: {:?}", e);
+            }
+        }
+
+        let query = api::DiscoveryQuery {
+            lastfm_username: Some(username),
+            period: period(),
+        };
+        match auth.call(|| api::discover_missing(query.clone())).await {
+            Ok(report) => {
+                selected.set(report.missing.iter().map(track_id).collect());
+                discovery.set(Some(report));
+            }
+            Err(e) => error.set(format!("Discovery failed: {e}")),
+        }
+        loading.set(false);
+    };
+
+    let queue_selected = move || async move {
+        error.set(String::new());
+        let Some(report) = discovery() else {
+            return;
+        };
+        let chosen = selected.read();
+        let tracks: Vec<DiscoveryTrack> = report
+            .missing
+            .into_iter()
+            .filter(|t| chosen.contains(&track_id(t)))
+            .collect();
+        drop(chosen);
+
+        let request = api::QueueDiscoveryRequest {
+            tracks,
+            target_folder: selected_folder(),
+            provider: Some(settings.default_provider()),
+        };
+        match auth.call(|| api::queue_discovery(request.clone())).await {
+            Ok(started) => {
+                let job_id = started.id.clone();
+                queue_report.set(Some(started));
+                spawn(poll_import(auth, job_id, queue_report));
+            }
+            Err(e) => error.set(format!("Failed to queue tracks: {e}")),
+        }
+    };
+
+    let queue_running = queue_report.read().as_ref().is_some_and(|r| !r.finished);
+
+    rsx! {
+        div { class: "bg-beet-panel border border-white/10 p-6 rounded-lg shadow-2xl relative z-10 w-full",
+            h2 { class: "text-xl font-bold mb-4 text-beet-accent font-display", "Last.fm Discovery" }
+
+            if !error().is_empty() {
+                div { class: "mb-4 p-4 bg-red-900/20 border border-red-500/50 rounded text-red-400 font-mono text-sm",
+                    "{error}"
+                }
+            }
+
+            div { class: "grid grid-cols-1 md:grid-cols-3 gap-4 mb-4",
+                div { class: "md:col-span-2",
+                    label { class: LABEL_CLASS, "Last.fm Username" }
+                    input {
+                        class: INPUT_CLASS,
+                        value: "{lastfm_username}",
+                        oninput: move |e| lastfm_username.set(e.value()),
+                        onkeydown: move |e| {
+                            if e.key() == Key::Enter && !lastfm_username.read().trim().is_empty() {
+                                spawn(run_discovery());
+                            }
+                        },
+                        placeholder: "rj",
+                        "type": "text",
+                    }
+                }
+                div {
+                    label { class: LABEL_CLASS, "Listening Period" }
+                    select {
+                        class: INPUT_CLASS,
+                        value: "{period().as_lastfm()}",
+                        onchange: move |e| period.set(e.value().parse().unwrap_or_default()),
+                        for p in DiscoveryPeriod::all() {
+                            option { value: "{p.as_lastfm()}", "{p.label()}" }
+                        }
+                    }
+                }
+            }
+
+            Button {
+                class: "rounded",
+                disabled: loading() || lastfm_username.read().trim().is_empty(),
+                onclick: move |_| {
+                    spawn(run_discovery());
+                },
+                if loading() {
+                    "DISCOVERING..."
+                } else {
+                    "DISCOVER"
+                }
+            }
+        }
+
+        if loading() {
+            div { class: "flex flex-col justify-center items-center py-10",
+                div { class: "animate-spin rounded-full h-16 w-16 border-t-4 border-b-4 border-beet-accent" }
+            }
+        }
+
+        if let Some(report) = discovery.read().clone() {
+            div { class: "bg-beet-panel/50 border border-white/5 p-6 backdrop-blur-sm rounded-lg w-full",
+                div { class: "flex justify-between items-baseline border-b border-white/10 pb-2 mb-4",
+                    h3 { class: "text-lg font-bold text-white font-display", "Missing From Your Library" }
+                    span { class: "text-xs font-mono text-gray-400",
+                        "{report.missing.len()} missing // {report.owned} owned // {report.candidates} suggested"
+                    }
+                }
+
+                if report.missing.is_empty() {
+                    p { class: "text-gray-500 font-mono italic", "You already have everything we found." }
+                } else {
+                    div { class: "grid grid-cols-1 md:grid-cols-3 gap-4 mb-4 items-end",
+                        div { class: "md:col-span-2",
+                            label { class: LABEL_CLASS, "Target Folder" }
+                            select {
+                                class: INPUT_CLASS,
+                                value: "{selected_folder}",
+                                onchange: move |e| selected_folder.set(e.value()),
+                                option { value: "", disabled: true, "Select a folder" }
+                                for folder in folders.read().iter() {
+                                    option { value: "{folder.path}", "{folder.name}" }
+                                }
+                            }
+                        }
+                        Button {
+                            class: "rounded whitespace-nowrap",
+                            disabled: queue_running || selected.read().is_empty() || selected_folder.read().is_empty(),
+                            onclick: move |_| {
+                                spawn(queue_selected());
+                            },
+                            "DOWNLOAD {selected.read().len()}"
+                        }
+                    }
+
+                    ul { class: "space-y-1 max-h-[50vh] overflow-y-auto",
+                        for track in report.missing.iter() {
+                            {
+                                let id = track_id(track);
+                                let is_selected = selected.read().contains(&id);
+                                let reasons = describe_sources(&track.sources);
+                                rsx! {
+                                    li {
+                                        key: "{id}",
+                                        class: "flex items-center gap-3 px-3 py-2 bg-white/5 rounded text-sm cursor-pointer hover:bg-white/10",
+                                        onclick: move |_| {
+                                            let mut selected = selected.write();
+                                            if !selected.remove(&id) {
+                                                selected.insert(id.clone());
+                                            }
+                                        },
+                                        Checkbox { is_selected }
+                                        div { class: "min-w-0",
+                                            span { class: "text-white truncate block", "{track.title}" }
+                                            span { class: "text-gray-500 text-xs font-mono truncate block",
+                                                "{track.artist} // {reasons}"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(current) = queue_report.read().clone() {
+            PlaylistReport { report: current }
+        }
+    }
+}
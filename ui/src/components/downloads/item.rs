@@ -1,5 +1,5 @@
 use dioxus::prelude::*;
-use shared::download::{DownloadProgress, DownloadState};
+use shared::download::{format_size, DownloadProgress, DownloadState, LyricsStatus};
 
 #[component]
 pub fn DownloadItem(file: DownloadProgress) -> Element {
@@ -12,6 +12,12 @@ pub fn DownloadItem(file: DownloadProgress) -> Element {
             "border border-gray-600 text-gray-400",
             "QUEUED",
         ),
+        DownloadState::Paused => (
+            "Paused by schedule",
+            "border-white/5 opacity-60",
+            "border border-yellow-600 text-yellow-400",
+            "PAUSED",
+        ),
         DownloadState::InProgress => (
             "Downloading",
             "border-beet-accent/50",
@@ -73,6 +79,19 @@ pub fn DownloadItem(file: DownloadProgress) -> Element {
 
     let error_msg = file.error.clone();
 
+    let lyrics_badge = file.lyrics.as_ref().map(|lyrics| match lyrics {
+        LyricsStatus::Searching => ("Looking up lyrics", "text-gray-400 border-gray-600", "LRC..."),
+        LyricsStatus::Synced => ("Synced lyrics", "text-beet-leaf border-beet-leaf/50", "LRC"),
+        LyricsStatus::Plain => ("Unsynced lyrics", "text-green-300 border-green-500/50", "TXT"),
+        LyricsStatus::Instrumental => ("Instrumental", "text-gray-400 border-gray-600", "INST"),
+        LyricsStatus::NotFound => ("No lyrics found", "text-yellow-300 border-yellow-500/50", "NO LRC"),
+        LyricsStatus::Failed(_) => ("Lyrics failed", "text-red-300 border-red-500/50", "LRC ERR"),
+    });
+    let lyrics_error = match &file.lyrics {
+        Some(LyricsStatus::Failed(e)) => e.clone(),
+        _ => String::new(),
+    };
+
     rsx! {
       div { class: "bg-white/5 border {border_class} p-4 rounded-lg hover:border-beet-accent/50 transition-colors group",
         div { class: "flex justify-between items-start mb-2",
@@ -81,10 +100,19 @@ pub fn DownloadItem(file: DownloadProgress) -> Element {
             title: "{file.item}",
             "{display_name}"
           }
-          span {
-            class: "text-[10px] font-mono {badge_class} px-1.5 py-0.5 rounded uppercase cursor-help",
-            title: "{status_text}",
-            "{badge_text}"
+          div { class: "flex gap-1 flex-shrink-0",
+            if let Some((lyrics_text, lyrics_class, lyrics_label)) = lyrics_badge {
+              span {
+                class: "text-[10px] font-mono border {lyrics_class} px-1.5 py-0.5 rounded uppercase cursor-help",
+                title: "{lyrics_text} {lyrics_error}",
+                "{lyrics_label}"
+              }
+            }
+            span {
+              class: "text-[10px] font-mono {badge_class} px-1.5 py-0.5 rounded uppercase cursor-help",
+              title: "{status_text}",
+              "{badge_text}"
+            }
           }
         }
         div { class: "flex justify-between text-xs text-gray-400 font-mono mb-1",
@@ -110,7 +138,7 @@ pub fn DownloadItem(file: DownloadProgress) -> Element {
               style: "background-image: repeating-linear-gradient(45deg, transparent, transparent 5px, rgba(255,255,255,0.5) 5px, rgba(255,255,255,0.5) 10px);",
             }
           }
-        } else if matches!(state, DownloadState::Queued) {
+        } else if matches!(state, DownloadState::Queued | DownloadState::Paused) {
           div { class: "h-1 w-full bg-gray-800 rounded-full mt-2" }
         } else if matches!(state, DownloadState::Failed(_)) {
           if let Some(err) = &error_msg {
@@ -143,19 +171,3 @@ pub fn DownloadItem(file: DownloadProgress) -> Element {
       }
     }
 }
-
-fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if bytes >= GB {
-        format!("{:.2} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.2} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.2} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{bytes} B")
-    }
-}
@@ -28,6 +28,7 @@ pub fn Downloads(mut props: DownloadsProps) -> Element {
             matches!(
                 f.state,
                 DownloadState::Queued
+                    | DownloadState::Paused
                     | DownloadState::InProgress
                     | DownloadState::Importing
                     | DownloadState::Completed // Still needs to be imported
@@ -52,6 +53,7 @@ pub fn Downloads(mut props: DownloadsProps) -> Element {
             matches!(
                 file.state,
                 DownloadState::Queued
+                    | DownloadState::Paused
                     | DownloadState::InProgress
                     | DownloadState::Importing
                     | DownloadState::Completed // Downloads that are completed but not yet imported
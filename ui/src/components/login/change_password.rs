@@ -0,0 +1,102 @@
+use api::models::user::MIN_PASSWORD_LENGTH;
+use dioxus::prelude::*;
+
+use crate::auth::use_auth;
+
+const INPUT_CLASS: &str = "w-full bg-beet-dark border border-white/10 rounded p-3 text-white focus:outline-none focus:border-beet-accent focus:shadow-[0_0_10px_rgba(217,70,239,0.3)] transition-all font-mono";
+const LABEL_CLASS: &str = "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider";
+
+/// Shown instead of the app while the user has to replace their password.
+#[component]
+pub fn ChangePassword() -> Element {
+    let mut auth = use_auth();
+    let mut current_password = use_signal(String::new);
+    let mut new_password = use_signal(String::new);
+    let mut confirm_password = use_signal(String::new);
+    let mut error = use_signal(String::new);
+    let mut saving = use_signal(|| false);
+
+    let handle_submit = move |_| async move {
+        error.set(String::new());
+
+        if new_password() != confirm_password() {
+            error.set("Passwords do not match".to_string());
+            return;
+        }
+
+        saving.set(true);
+        match auth
+            .call(|| api::change_password(current_password(), new_password()))
+            .await
+        {
+            Ok(response) => auth.login(response),
+            Err(e) => error.set(format!("Failed to change password: {e}")),
+        }
+        saving.set(false);
+    };
+
+    rsx! {
+        div { class: "flex flex-col items-center justify-center min-h-screen text-white font-display",
+            div { class: "p-8 bg-beet-panel border border-white/10 rounded-lg shadow-2xl w-full max-w-md relative z-10",
+                h1 { class: "text-xl font-bold text-beet-accent mb-2", "Choose a New Password" }
+                p { class: "text-sm text-gray-400 font-mono mb-6",
+                    "You need to replace your password before continuing. It must be at least {MIN_PASSWORD_LENGTH} characters long."
+                }
+
+                div { class: "space-y-4",
+                    div {
+                        label { class: LABEL_CLASS, "Current Password" }
+                        input {
+                            class: INPUT_CLASS,
+                            value: "{current_password}",
+                            oninput: move |e| current_password.set(e.value()),
+                            "type": "password",
+                        }
+                    }
+                    div {
+                        label { class: LABEL_CLASS, "New Password" }
+                        input {
+                            class: INPUT_CLASS,
+                            value: "{new_password}",
+                            oninput: move |e| new_password.set(e.value()),
+                            "type": "password",
+                        }
+                    }
+                    div {
+                        label { class: LABEL_CLASS, "Confirm New Password" }
+                        input {
+                            class: INPUT_CLASS,
+                            value: "{confirm_password}",
+                            oninput: move |e| confirm_password.set(e.value()),
+                            "type": "password",
+                        }
+                    }
+
+                    if !error().is_empty() {
+                        div { class: "p-3 bg-red-500/10 border border-red-500/50 rounded text-red-400 text-sm font-mono",
+                            "{error}"
+                        }
+                    }
+
+                    button {
+                        class: "w-full retro-btn",
+                        disabled: saving() || new_password.read().is_empty(),
+                        onclick: handle_submit,
+                        if saving() {
+                            "SAVING..."
+                        } else {
+                            "CHANGE PASSWORD"
+                        }
+                    }
+                    button {
+                        class: "w-full text-xs font-mono text-gray-400 hover:text-beet-accent transition-colors underline decoration-dotted",
+                        onclick: move |_| async move {
+                            auth.logout().await;
+                        },
+                        "Log out"
+                    }
+                }
+            }
+        }
+    }
+}
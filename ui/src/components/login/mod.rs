@@ -1,27 +1,61 @@
 use std::future::Future;
 use std::pin::Pin;
 
+use api::auth::{LoginOptions, RegistrationMode, OIDC_LOGIN_ROUTE};
 use dioxus::prelude::*;
 
+mod change_password;
+
+pub use change_password::ChangePassword;
+
 type LoginCallback = Callback<(String, String), Pin<Box<dyn Future<Output = Result<(), String>>>>>;
+type RegisterCallback =
+    Callback<(String, String, Option<String>), Pin<Box<dyn Future<Output = Result<(), String>>>>>;
 
 #[derive(Props, PartialEq, Clone)]
 pub struct Props {
     login: LoginCallback,
+    /// Called with username, password and invite code to create an account
+    register: Option<RegisterCallback>,
+    #[props(default)]
+    registration: RegistrationMode,
+    #[props(default)]
+    options: LoginOptions,
+    /// Error reported by a failed single sign-on attempt
+    #[props(default)]
+    sso_error: Option<String>,
 }
 
 #[component]
 pub fn Login(props: Props) -> Element {
     let mut username = use_signal(|| "".to_string());
     let mut password = use_signal(|| "".to_string());
+    let mut confirm_password = use_signal(|| "".to_string());
+    let mut invite_code = use_signal(|| "".to_string());
     let mut error = use_signal(|| "".to_string());
+    let mut signing_up = use_signal(|| false);
+
+    let can_register = props.register.is_some() && props.registration != RegistrationMode::Disabled;
+    let needs_invite = props.registration == RegistrationMode::Invite;
+    let password_login = props.options.password_login;
 
     let handle_login = move || {
         let user = username.read().to_string();
         let pass = password.read().to_string();
         spawn(async move {
             error.set("".to_string());
-            match props.login.call((user, pass)).await {
+            let result = match props.register {
+                Some(register) if signing_up() => {
+                    if pass != confirm_password() {
+                        error.set("Passwords do not match".to_string());
+                        return;
+                    }
+                    let invite = Some(invite_code()).filter(|c| needs_invite && !c.is_empty());
+                    register.call((user, pass, invite)).await
+                }
+                _ => props.login.call((user, pass)).await,
+            };
+            match result {
                 Ok(_) => {
                     // Login success logic usually handled by parent/router redirect
                 }
@@ -61,8 +95,29 @@ pub fn Login(props: Props) -> Element {
             }
           }
 
+          if let Some(sso_error) = props.sso_error.clone().filter(|_| error().is_empty()) {
+            div { class: "mb-6 p-3 bg-red-500/10 border border-red-500/50 rounded text-red-400 text-sm font-mono",
+              "Single sign-on failed: {sso_error}"
+            }
+          }
+
+          if let Some(oidc_name) = props.options.oidc_name.clone() {
+            a {
+              class: "w-full retro-btn flex justify-center items-center gap-2",
+              href: OIDC_LOGIN_ROUTE,
+              "SIGN IN WITH {oidc_name}"
+            }
+            if password_login {
+              div { class: "flex items-center gap-3 my-6 text-xs font-mono text-gray-500 uppercase tracking-wider",
+                div { class: "flex-1 border-t border-white/10" }
+                "or"
+                div { class: "flex-1 border-t border-white/10" }
+              }
+            }
+          }
+
           // Form
-          div { class: "space-y-6",
+          div { class: "space-y-6", hidden: !password_login,
             div {
               label { class: "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider",
                 "Username"
@@ -98,6 +153,35 @@ pub fn Login(props: Props) -> Element {
               }
             }
 
+            if signing_up() {
+              div {
+                label { class: "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider",
+                  "Confirm Password"
+                }
+                input {
+                  class: "w-full bg-beet-dark border border-white/10 rounded p-3 text-white focus:outline-none focus:border-beet-accent focus:shadow-[0_0_10px_rgba(217,70,239,0.3)] transition-all font-mono",
+                  value: "{confirm_password}",
+                  oninput: move |e| confirm_password.set(e.value()),
+                  "type": "password",
+                  placeholder: "Repeat password",
+                }
+              }
+              if needs_invite {
+                div {
+                  label { class: "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider",
+                    "Invite Code"
+                  }
+                  input {
+                    class: "w-full bg-beet-dark border border-white/10 rounded p-3 text-white focus:outline-none focus:border-beet-accent focus:shadow-[0_0_10px_rgba(217,70,239,0.3)] transition-all font-mono",
+                    value: "{invite_code}",
+                    oninput: move |e| invite_code.set(e.value()),
+                    "type": "text",
+                    placeholder: "Code from your admin",
+                  }
+                }
+              }
+            }
+
             if !error().is_empty() {
               div { class: "p-3 bg-red-500/10 border border-red-500/50 rounded text-red-400 text-sm font-mono flex items-center gap-2",
                 svg {
@@ -119,7 +203,13 @@ pub fn Login(props: Props) -> Element {
             button {
               class: "w-full retro-btn flex justify-center items-center gap-2 group",
               onclick: move |_| handle_login(),
-              span { "AUTHENTICATE" }
+              span {
+                if signing_up() {
+                  "CREATE ACCOUNT"
+                } else {
+                  "AUTHENTICATE"
+                }
+              }
               svg {
                 class: "w-4 h-4 group-hover:translate-x-1 transition-transform",
                 fill: "none",
@@ -133,6 +223,21 @@ pub fn Login(props: Props) -> Element {
                 }
               }
             }
+
+            if can_register {
+              button {
+                class: "w-full text-xs font-mono text-gray-400 hover:text-beet-accent transition-colors underline decoration-dotted",
+                onclick: move |_| {
+                    error.set("".to_string());
+                    signing_up.toggle();
+                },
+                if signing_up() {
+                  "Already have an account? Log in"
+                } else {
+                  "No account yet? Create one"
+                }
+              }
+            }
           }
         }
       }
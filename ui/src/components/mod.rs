@@ -1,9 +1,11 @@
 pub mod album;
 pub mod cover_art;
+pub mod discovery;
 pub mod downloads;
 pub mod footer;
 pub mod login;
 pub mod modal;
+pub mod playlist;
 pub mod search;
 pub mod settings;
 pub mod simple;
@@ -11,10 +13,12 @@ pub mod status;
 
 pub use album::{Album, AlbumHeader};
 pub use cover_art::*;
+pub use discovery::*;
 pub use downloads::*;
 pub use footer::Footer;
-pub use login::Login;
+pub use login::{ChangePassword, Login};
 pub use modal::*;
+pub use playlist::*;
 pub use search::*;
 pub use settings::*;
 pub use simple::*;
@@ -0,0 +1,170 @@
+mod report;
+
+pub use report::{poll_import, PlaylistReport};
+
+use dioxus::logger::tracing::info;
+use dioxus::prelude::*;
+use shared::playlist::{PlaylistFormat, PlaylistImportReport};
+
+use crate::settings_context::use_settings;
+use crate::{use_auth, Button};
+
+const INPUT_CLASS: &str = "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono";
+const LABEL_CLASS: &str = "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider";
+
+fn parse_format(value: &str) -> Option<PlaylistFormat> {
+    match value {
+        "csv" => Some(PlaylistFormat::Csv),
+        "json" => Some(PlaylistFormat::Json),
+        "m3u" => Some(PlaylistFormat::M3u),
+        "xspf" => Some(PlaylistFormat::Xspf),
+        _ => None,
+    }
+}
+
+/// Import a playlist (pasted, uploaded as text or fetched from a URL) into a folder.
+#[component]
+pub fn PlaylistImport() -> Element {
+    let auth = use_auth();
+    let settings = use_settings();
+
+    let mut content = use_signal(String::new);
+    let mut url = use_signal(String::new);
+    let mut name = use_signal(String::new);
+    let mut format = use_signal(String::new);
+    let mut folders = use_signal(Vec::new);
+    let mut selected_folder = use_signal(String::new);
+    let mut report = use_signal::<Option<PlaylistImportReport>>(|| None);
+    let mut error = use_signal(String::new);
+    let mut starting = use_signal(|| false);
+
+    use_future(move || async move {
+        if let Ok(user_folders) = auth.call(api::get_user_folders).await {
+            // Read-only shares cannot be downloaded into
+            let user_folders: Vec<_> = user_folders
+                .into_iter()
+                .filter(|f| f.access.can_write())
+                .collect();
+            if user_folders.len() == 1 {
+                selected_folder.set(user_folders[0].path.clone());
+            }
+            folders.set(user_folders);
+        }
+    });
+
+    let start_import = move || async move {
+        error.set(String::new());
+        starting.set(true);
+
+        let request = api::PlaylistImportRequest {
+            content: Some(content()).filter(|c| !c.trim().is_empty()),
+            url: Some(url()).filter(|u| !u.trim().is_empty()),
+            format: parse_format(&format()),
+            name: Some(name()).filter(|n| !n.trim().is_empty()),
+            target_folder: selected_folder(),
+            provider: Some(settings.default_provider()),
+        };
+
+        match auth.call(|| api::import_playlist(request.clone())).await {
+            Ok(started) => {
+                info!("Playlist import {} started", started.id);
+                let job_id = started.id.clone();
+                report.set(Some(started));
+                spawn(poll_import(auth, job_id, report));
+            }
+            Err(e) => error.set(format!("Failed to import playlist: {e}")),
+        }
+        starting.set(false);
+    };
+
+    let running = report.read().as_ref().is_some_and(|r| !r.finished);
+    let has_source = !content.read().trim().is_empty() || !url.read().trim().is_empty();
+
+    rsx! {
+        div { class: "bg-beet-panel border border-white/10 p-6 rounded-lg shadow-2xl relative z-10 w-full",
+            h2 { class: "text-xl font-bold mb-4 text-beet-accent font-display", "Import Playlist" }
+
+            if !error().is_empty() {
+                div { class: "mb-4 p-4 bg-red-900/20 border border-red-500/50 rounded text-red-400 font-mono text-sm",
+                    "{error}"
+                }
+            }
+
+            div { class: "space-y-4",
+                div {
+                    label { class: LABEL_CLASS, "Playlist URL" }
+                    input {
+                        class: INPUT_CLASS,
+                        value: "{url}",
+                        oninput: move |e| url.set(e.value()),
+                        placeholder: "https://listenbrainz.org/playlist/... or a link to a .m3u/.xspf/.csv file",
+                        "type": "text",
+                    }
+                }
+                div {
+                    label { class: LABEL_CLASS, "Or paste the playlist" }
+                    textarea {
+                        class: "{INPUT_CLASS} h-40 text-sm",
+                        value: "{content}",
+                        oninput: move |e| content.set(e.value()),
+                        placeholder: "CSV, JSON, M3U or XSPF content",
+                    }
+                }
+                div { class: "grid grid-cols-1 md:grid-cols-3 gap-4",
+                    div {
+                        label { class: LABEL_CLASS, "Name (opt)" }
+                        input {
+                            class: INPUT_CLASS,
+                            value: "{name}",
+                            oninput: move |e| name.set(e.value()),
+                            placeholder: "Road trip",
+                            "type": "text",
+                        }
+                    }
+                    div {
+                        label { class: LABEL_CLASS, "Format" }
+                        select {
+                            class: INPUT_CLASS,
+                            value: "{format}",
+                            onchange: move |e| format.set(e.value()),
+                            option { value: "", "Detect" }
+                            option { value: "csv", "CSV" }
+                            option { value: "json", "JSON / JSPF" }
+                            option { value: "m3u", "M3U" }
+                            option { value: "xspf", "XSPF" }
+                        }
+                    }
+                    div {
+                        label { class: LABEL_CLASS, "Target Folder" }
+                        select {
+                            class: INPUT_CLASS,
+                            value: "{selected_folder}",
+                            onchange: move |e| selected_folder.set(e.value()),
+                            option { value: "", disabled: true, "Select a folder" }
+                            for folder in folders.read().iter() {
+                                option { value: "{folder.path}", "{folder.name}" }
+                            }
+                        }
+                    }
+                }
+
+                Button {
+                    class: "rounded",
+                    disabled: starting() || running || !has_source || selected_folder.read().is_empty(),
+                    onclick: move |_| {
+                        spawn(start_import());
+                    },
+                    if running {
+                        "IMPORTING..."
+                    } else {
+                        "IMPORT"
+                    }
+                }
+            }
+        }
+
+        if let Some(current) = report.read().clone() {
+            PlaylistReport { report: current }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use dioxus::logger::tracing::warn;
+use dioxus::prelude::*;
+use shared::playlist::{PlaylistImportReport, PlaylistItemStatus};
+
+use crate::Auth;
+
+fn status_badge(status: &PlaylistItemStatus) -> (&'static str, String) {
+    match status {
+        PlaylistItemStatus::Pending => ("text-gray-500", "Pending".to_string()),
+        PlaylistItemStatus::Resolving => ("text-blue-400 animate-pulse", "Matching".to_string()),
+        PlaylistItemStatus::Searching => ("text-blue-400 animate-pulse", "Searching".to_string()),
+        PlaylistItemStatus::Queued => ("text-beet-leaf", "Queued".to_string()),
+        PlaylistItemStatus::AlreadyOwned => ("text-gray-400", "Owned".to_string()),
+        PlaylistItemStatus::NotFound => ("text-yellow-500", "Not found".to_string()),
+        PlaylistItemStatus::Failed(e) => ("text-red-400", format!("Failed: {e}")),
+    }
+}
+
+/// Poll a playlist import job until it finishes, updating `report` as it goes.
+pub async fn poll_import(
+    auth: Auth,
+    job_id: String,
+    mut report: Signal<Option<PlaylistImportReport>>,
+) {
+    loop {
+        gloo_timers::future::TimeoutFuture::new(2000).await;
+        match auth.call(|| api::get_playlist_import(job_id.clone())).await {
+            Ok(current) => {
+                let finished = current.finished;
+                report.set(Some(current));
+                if finished {
+                    break;
+                }
+            }
+            Err(e) => {
+                warn!("Failed to poll playlist import: {:?}", e);
+                break;
+            }
+        }
+    }
+}
+
+/// Progress counters and per-track status of a playlist import.
+#[component]
+pub fn PlaylistReport(report: PlaylistImportReport) -> Element {
+    rsx! {
+        div { class: "bg-beet-panel/50 border border-white/5 p-6 backdrop-blur-sm rounded-lg w-full",
+            div { class: "flex justify-between items-baseline border-b border-white/10 pb-2 mb-4",
+                h3 { class: "text-lg font-bold text-white font-display",
+                    {report.name.clone().unwrap_or_else(|| "Playlist".to_string())}
+                }
+                span { class: "text-xs font-mono text-gray-400",
+                    "{report.processed()}/{report.total()} processed"
+                }
+            }
+
+            div { class: "grid grid-cols-2 md:grid-cols-5 gap-2 mb-4 text-center font-mono text-xs",
+                div { class: "bg-white/5 rounded p-2",
+                    div { class: "text-lg text-white", "{report.matched()}" }
+                    "matched"
+                }
+                div { class: "bg-white/5 rounded p-2",
+                    div { class: "text-lg text-beet-leaf", "{report.queued()}" }
+                    "queued"
+                }
+                div { class: "bg-white/5 rounded p-2",
+                    div { class: "text-lg text-gray-300", "{report.already_owned()}" }
+                    "owned"
+                }
+                div { class: "bg-white/5 rounded p-2",
+                    div { class: "text-lg text-yellow-500", "{report.not_found()}" }
+                    "not found"
+                }
+                div { class: "bg-white/5 rounded p-2",
+                    div { class: "text-lg text-red-400", "{report.failed()}" }
+                    "failed"
+                }
+            }
+
+            if let Some(job_error) = report.error.clone() {
+                div { class: "mb-4 p-4 bg-red-900/20 border border-red-500/50 rounded text-red-400 font-mono text-sm",
+                    "{job_error}"
+                }
+            }
+
+            ul { class: "space-y-1 max-h-[50vh] overflow-y-auto",
+                for (index, item) in report.items.iter().enumerate() {
+                    {
+                        let (badge_class, badge_text) = status_badge(&item.status);
+                        rsx! {
+                            li {
+                                key: "{index}",
+                                class: "flex justify-between items-center gap-4 px-3 py-2 bg-white/5 rounded text-sm",
+                                div { class: "min-w-0",
+                                    span { class: "text-white truncate block", "{item.entry.title}" }
+                                    span { class: "text-gray-500 text-xs font-mono truncate block",
+                                        "{item.entry.artist}"
+                                        if let Some(detail) = &item.detail {
+                                            " // {detail}"
+                                        }
+                                    }
+                                }
+                                span { class: "text-xs font-mono whitespace-nowrap {badge_class}",
+                                    "{badge_text}"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
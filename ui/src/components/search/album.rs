@@ -1,6 +1,7 @@
 use dioxus::prelude::*;
 use shared::metadata::Album;
 
+use super::source_badges::SourceBadges;
 use crate::CoverArt;
 
 #[derive(Props, PartialEq, Clone)]
@@ -28,6 +29,7 @@ pub fn AlbumResult(props: Props) -> Element {
           if let Some(release_date) = &album.release_date {
             p { class: "text-sm text-gray-500 mt-1 font-mono", "{release_date}" }
           }
+          SourceBadges { sources: album.sources.clone() }
         }
       }
     }
@@ -3,6 +3,7 @@ use dioxus::prelude::*;
 use shared::download::{DownloadableGroup, DownloadableItem};
 use std::collections::HashSet;
 
+use super::peer_browser::PeerBrowser;
 use crate::{use_auth, Checkbox};
 
 #[derive(Props, PartialEq, Clone)]
@@ -10,8 +11,9 @@ pub struct Props {
     pub results: Vec<DownloadableGroup>,
     pub is_searching: bool,
     pub is_downloading: Signal<bool>,
+    /// Selected items, target folder and whether they make up a composite album
     #[props(into)]
-    pub on_download: EventHandler<(Vec<DownloadableItem>, String)>,
+    pub on_download: EventHandler<(Vec<DownloadableItem>, String, bool)>,
     #[props(into)]
     pub on_back: EventHandler<()>,
 }
@@ -22,21 +24,22 @@ struct AlbumResultItemProps {
     selected_tracks: Signal<HashSet<String>>,
     on_album_select_all: EventHandler<DownloadableGroup>,
     on_track_toggle: EventHandler<String>,
+    on_browse: EventHandler<String>,
 }
 
 #[derive(Props, Clone, PartialEq)]
-struct TrackItemProps {
+pub(super) struct TrackItemProps {
     track: DownloadableItem,
     is_selected: bool,
     on_toggle: EventHandler<String>,
 }
 
-fn get_track_id(track: &DownloadableItem) -> String {
+pub(super) fn get_track_id(track: &DownloadableItem) -> String {
     format!("{}{}", track.id, track.source)
 }
 
 #[component]
-fn TrackItem(props: TrackItemProps) -> Element {
+pub(super) fn TrackItem(props: TrackItemProps) -> Element {
     let unique_id = get_track_id(&props.track);
 
     rsx! {
@@ -55,6 +58,13 @@ fn TrackItem(props: TrackItemProps) -> Element {
 #[component]
 fn AlbumResultItem(props: AlbumResultItemProps) -> Element {
     let album = props.album.clone();
+    let source = album.source.clone();
+    let peer_count = album
+        .items
+        .iter()
+        .map(|i| i.source.as_str())
+        .collect::<HashSet<_>>()
+        .len();
 
     rsx! {
         div {
@@ -63,14 +73,29 @@ fn AlbumResultItem(props: AlbumResultItemProps) -> Element {
             div { class: "flex justify-between items-center mb-2",
                 div { class: "flex-grow",
                     h4 { class: "text-md font-bold text-beet-leaf", "{album.title}" }
+                    if album.composite {
+                        p { class: "text-xs text-beet-accent font-mono uppercase tracking-widest",
+                            "Composite from {peer_count} peers, imported as one album"
+                        }
+                    }
                     p { class: "text-sm text-gray-400 font-mono",
                         "{album.artist.clone().unwrap_or_default()} - Quality: {album.quality}, Score: {album.score:.2}"
                     }
                 }
-                button {
-                    class: "font-mono uppercase text-[10px] whitespace-nowrap tracking-widest px-3 py-1 border border-beet-leaf/30 text-beet-leaf hover:bg-beet-leaf hover:text-beet-dark transition-colors cursor-pointer rounded",
-                    onclick: move |_| props.on_album_select_all.call(album.clone()),
-                    "Select All"
+                div { class: "flex gap-2",
+                    if !album.composite {
+                        button {
+                            class: "font-mono uppercase text-[10px] whitespace-nowrap tracking-widest px-3 py-1 border border-white/10 text-gray-400 hover:bg-white/10 hover:text-white transition-colors cursor-pointer rounded",
+                            title: "Browse all folders shared by {source}",
+                            onclick: move |_| props.on_browse.call(source.clone()),
+                            "Browse"
+                        }
+                    }
+                    button {
+                        class: "font-mono uppercase text-[10px] whitespace-nowrap tracking-widest px-3 py-1 border border-beet-leaf/30 text-beet-leaf hover:bg-beet-leaf hover:text-beet-dark transition-colors cursor-pointer rounded",
+                        onclick: move |_| props.on_album_select_all.call(album.clone()),
+                        "Select All"
+                    }
                 }
             }
             ul { class: "space-y-1",
@@ -95,11 +120,19 @@ pub fn DownloadResults(props: Props) -> Element {
     let mut folders = use_signal(std::vec::Vec::new);
     let mut selected_folder = use_signal(|| "".to_string());
     let mut is_downloading = props.is_downloading;
+    let mut browsing = use_signal::<Option<String>>(|| None);
+    // Files listed while browsing peers, selectable alongside the results
+    let browsed_items = use_signal(Vec::<DownloadableItem>::new);
     let auth = use_auth();
 
     use_future(move || async move {
-        if let Ok(user_folders) = auth.call(api::get_user_folders()).await {
+        if let Ok(user_folders) = auth.call(api::get_user_folders).await {
             info!("Fetched {} user folders", user_folders.len());
+            // Read-only shares cannot be downloaded into
+            let user_folders: Vec<_> = user_folders
+                .into_iter()
+                .filter(|f| f.access.can_write())
+                .collect();
 
             // Only select if the user has exactly one folder
             // It could be error prone to auto-select if there are multiple folders
@@ -148,24 +181,45 @@ pub fn DownloadResults(props: Props) -> Element {
 
         let selected_ids = selected_tracks.read();
 
-        let items_to_download: Vec<DownloadableItem> = props
+        let browsed = browsed_items.read();
+        let mut items_to_download: Vec<DownloadableItem> = Vec::new();
+        let candidates = props
             .results
             .iter()
             .flat_map(|group| group.items.iter())
-            .filter(|item| selected_ids.contains(&get_track_id(item)))
-            .cloned()
-            .collect();
+            .chain(browsed.iter());
+        for item in candidates {
+            let id = get_track_id(item);
+            if selected_ids.contains(&id)
+                && !items_to_download.iter().any(|i| get_track_id(i) == id)
+            {
+                items_to_download.push(item.clone());
+            }
+        }
 
         if items_to_download.is_empty() {
             return;
         }
 
+        // Files from several peers picked from a composite result are imported as one album
+        let sources: HashSet<&str> = items_to_download
+            .iter()
+            .map(|i| i.source.as_str())
+            .collect();
+        let composite = sources.len() > 1
+            && props
+                .results
+                .iter()
+                .filter(|group| group.composite)
+                .flat_map(|group| group.items.iter())
+                .any(|item| selected_ids.contains(&get_track_id(item)));
+
         // Set downloading state immediately to prevent double-clicks
         is_downloading.set(true);
 
         props
             .on_download
-            .call((items_to_download, selected_folder()));
+            .call((items_to_download, selected_folder(), composite));
     };
 
     rsx! {
@@ -224,9 +278,19 @@ pub fn DownloadResults(props: Props) -> Element {
                         selected_tracks,
                         on_album_select_all: handle_album_select_all,
                         on_track_toggle: handle_track_toggle,
+                        on_browse: move |source| browsing.set(Some(source)),
                     }
                 }
             }
+            if let Some(source) = browsing() {
+                PeerBrowser {
+                    key: "{source}",
+                    source,
+                    selected_tracks,
+                    browsed_items,
+                    on_close: move |_| browsing.set(None),
+                }
+            }
             div { class: "fixed bottom-8 right-8",
                 button {
                     class: "bg-beet-accent hover:bg-fuchsia-400 text-white font-bold p-4 rounded-full shadow-[0_0_15px_rgba(255,0,255,0.5)] transition-transform hover:scale-105 disabled:bg-gray-600 disabled:cursor-not-allowed disabled:shadow-none flex items-center justify-center cursor-pointer",
@@ -4,8 +4,11 @@ pub mod track;
 
 pub use context::SearchReset;
 
+use dioxus::fullstack::WebSocketOptions;
 use dioxus::logger::tracing::{info, warn};
 use dioxus::prelude::*;
+use futures::channel::oneshot;
+use futures::FutureExt;
 use shared::download::{
     DownloadQuery, DownloadableGroup, DownloadableItem, SearchState as DownloadSearchState,
 };
@@ -21,9 +24,16 @@ use crate::{use_auth, Album, AlbumHeader, Button, Modal, SystemStatus};
 mod download_results;
 use download_results::DownloadResults;
 
+mod peer_browser;
+
+mod search_options;
+pub use search_options::{SearchOptionFields, SearchOptionsEditor};
+
 mod search_type_toggle;
 use search_type_toggle::{SearchType, SearchTypeToggle};
 
+mod source_badges;
+
 #[component]
 pub fn Search() -> Element {
     let auth = use_auth();
@@ -35,7 +45,12 @@ pub fn Search() -> Element {
     let mut loading = use_signal(|| false);
     let mut viewing_album = use_signal::<Option<AlbumWithTracks>>(|| None);
     let mut download_options = use_signal::<Option<Vec<DownloadableGroup>>>(|| None);
+    let mut download_album = use_signal::<Option<shared::metadata::Album>>(|| None);
     let mut is_downloading = use_signal(|| false);
+    let mut search_cancel = use_signal::<Option<oneshot::Sender<()>>>(|| None);
+    let mut show_options = use_signal(|| false);
+    let option_fields = use_signal(SearchOptionFields::default);
+    let mut options_error = use_signal::<Option<String>>(|| None);
     let search_reset = try_use_context::<SearchReset>();
 
     let mut system_status = use_signal(SystemHealth::default);
@@ -63,7 +78,7 @@ pub fn Search() -> Element {
 
     use_future(move || async move {
         loop {
-            if let Ok(health) = auth.call(api::get_system_health()).await {
+            if let Ok(health) = auth.call(api::get_system_health).await {
                 system_status.set(health);
             }
             gloo_timers::future::TimeoutFuture::new(10000).await;
@@ -73,6 +88,9 @@ pub fn Search() -> Element {
     use_effect(move || {
         if let Some(reset) = search_reset {
             if reset.0() > 0 {
+                if let Some(cancel) = search_cancel.take() {
+                    let _ = cancel.send(());
+                }
                 search_results.set(None);
                 search.set(String::new());
                 artist.set(None);
@@ -88,11 +106,25 @@ pub fn Search() -> Element {
     }
 
     let download = move |query: DownloadQuery| async move {
+        let options = match option_fields.read().to_options() {
+            Ok(options) => options,
+            Err(e) => {
+                options_error.set(Some(e));
+                return;
+            }
+        };
+        options_error.set(None);
+        let query = query.options(options);
+
         loading.set(true);
         viewing_album.set(None);
         download_options.set(Some(vec![]));
+        download_album.set(query.album.clone());
 
-        let search_id = match auth.call(api::start_download_search(query)).await {
+        let search_id = match auth
+            .call(|| api::start_download_search(query.clone()))
+            .await
+        {
             Ok(id) => id,
             Err(e) => {
                 warn!("Failed to start download search: {:?}", e);
@@ -101,14 +133,33 @@ pub fn Search() -> Element {
             }
         };
 
+        let mut socket = match auth
+            .call(|| api::download_search_stream(search_id.clone(), None, WebSocketOptions::new()))
+            .await
+        {
+            Ok(socket) => socket,
+            Err(e) => {
+                warn!("Failed to open search stream: {:?}", e);
+                loading.set(false);
+                return;
+            }
+        };
+
+        // Dropping the sender, e.g. for the next search, cancels this one too
+        let (cancel_tx, mut cancelled) = oneshot::channel::<()>();
+        search_cancel.set(Some(cancel_tx));
+
         loop {
-            match auth
-                .call(api::poll_download_search(api::PollQuery {
-                    search_id: search_id.clone(),
-                    backend: None,
-                }))
-                .await
-            {
+            let message = futures::select! {
+                message = socket.recv().fuse() => Some(message),
+                _ = cancelled => None,
+            };
+            let Some(message) = message else {
+                let _ = socket.send(api::SearchStreamCommand::Cancel).await;
+                break;
+            };
+
+            match message {
                 Ok(response) => {
                     download_options.with_mut(|current| {
                         if let Some(list) = current {
@@ -138,7 +189,7 @@ pub fn Search() -> Element {
                     }
                 }
                 Err(e) => {
-                    info!("Failed to poll search: {:?}", e);
+                    info!("Search stream closed: {:?}", e);
                     break;
                 }
             }
@@ -146,20 +197,25 @@ pub fn Search() -> Element {
         loading.set(false);
     };
 
-    let download_tracks = move |(items, folder): (Vec<DownloadableItem>, String)| async move {
-        match auth
-            .call(api::download(api::DownloadRequest {
-                items,
-                target_folder: folder,
-                backend: None,
-            }))
-            .await
-        {
-            Ok(_res) => info!("Downloads started"),
-            Err(e) => warn!("Failed to start downloads: {:?}", e),
-        }
-        is_downloading.set(false);
-    };
+    let download_tracks =
+        move |(items, folder, composite): (Vec<DownloadableItem>, String, bool)| async move {
+            match auth
+                .call(|| {
+                    api::download(api::DownloadRequest {
+                        items: items.clone(),
+                        target_folder: folder.clone(),
+                        backend: None,
+                        album: download_album(),
+                        composite,
+                    })
+                })
+                .await
+            {
+                Ok(_res) => info!("Downloads started"),
+                Err(e) => warn!("Failed to start downloads: {:?}", e),
+            }
+            is_downloading.set(false);
+        };
 
     let perform_search = move || async move {
         loading.set(true);
@@ -174,8 +230,8 @@ pub fn Search() -> Element {
         };
 
         let result = match search_type() {
-            SearchType::Album => auth.call(api::search_album(query_data)).await,
-            SearchType::Track => auth.call(api::search_track(query_data)).await,
+            SearchType::Album => auth.call(|| api::search_album(query_data.clone())).await,
+            SearchType::Track => auth.call(|| api::search_track(query_data.clone())).await,
         };
 
         if let Ok(data) = result {
@@ -188,10 +244,12 @@ pub fn Search() -> Element {
         loading.set(true);
 
         match auth
-            .call(api::find_album(api::AlbumQuery {
-                id: album_id.clone(),
-                provider: Some(provider),
-            }))
+            .call(|| {
+                api::find_album(api::AlbumQuery {
+                    id: album_id.clone(),
+                    provider: Some(provider),
+                })
+            })
             .await
         {
             Ok(album_data) => viewing_album.set(Some(album_data)),
@@ -325,6 +383,26 @@ pub fn Search() -> Element {
           }
         }
 
+        // Search options
+        div { class: "w-full",
+          button {
+            class: "text-xs font-mono text-gray-500 hover:text-beet-accent transition-colors underline decoration-dotted",
+            onclick: move |_| show_options.toggle(),
+            if show_options() { "Hide search options" } else { "Search options" }
+          }
+          if show_options() {
+            div { class: "mt-3 bg-beet-panel/50 border border-white/5 p-4 rounded-lg",
+              p { class: "text-xs text-gray-500 font-mono mb-3",
+                "Used for Soulseek searches, empty fields keep the defaults."
+              }
+              SearchOptionsEditor { fields: option_fields }
+            }
+          }
+          if let Some(e) = options_error() {
+            div { class: "mt-3 text-red-400 font-mono text-sm", "{e}" }
+          }
+        }
+
         SystemStatus { health: system_status.read().clone() }
 
         // Results
@@ -337,6 +415,9 @@ pub fn Search() -> Element {
                 spawn(download_tracks(data));
             },
             on_back: move |_| {
+                if let Some(cancel) = search_cancel.take() {
+                    let _ = cancel.send(());
+                }
                 download_options.set(None);
                 loading.set(false);
             },
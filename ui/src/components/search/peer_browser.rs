@@ -0,0 +1,165 @@
+use dioxus::prelude::*;
+use shared::download::{DownloadableItem, SharedFolder};
+use std::collections::HashSet;
+
+use super::download_results::{get_track_id, TrackItem};
+use crate::{use_auth, Modal};
+
+#[derive(Props, PartialEq, Clone)]
+pub struct Props {
+    /// Source to browse, the username of a search result
+    pub source: String,
+    /// Selection shared with the search results
+    pub selected_tracks: Signal<HashSet<String>>,
+    /// Files of the loaded folders, so selected ones can be queued with the results
+    pub browsed_items: Signal<Vec<DownloadableItem>>,
+    pub on_close: EventHandler,
+}
+
+/// File browser for the shares of a single peer.
+#[component]
+pub fn PeerBrowser(props: Props) -> Element {
+    let auth = use_auth();
+    let mut folders = use_signal::<Option<Vec<SharedFolder>>>(|| None);
+    let mut error = use_signal::<Option<String>>(|| None);
+    let mut filter = use_signal(String::new);
+    let mut expanded = use_signal(HashSet::<String>::new);
+    let mut selected_tracks = props.selected_tracks;
+    let mut browsed_items = props.browsed_items;
+    let source = props.source.clone();
+
+    use_future(move || {
+        let source = source.clone();
+        async move {
+            match auth
+                .call(|| {
+                    api::browse_source(api::BrowseQuery {
+                        source: source.clone(),
+                        backend: None,
+                    })
+                })
+                .await
+            {
+                Ok(result) => {
+                    browsed_items.with_mut(|items| {
+                        for item in result.iter().flat_map(|f| f.items.iter()) {
+                            if !items.iter().any(|i| get_track_id(i) == get_track_id(item)) {
+                                items.push(item.clone());
+                            }
+                        }
+                    });
+                    folders.set(Some(result));
+                }
+                Err(e) => error.set(Some(format!("Failed to browse shares: {e}"))),
+            }
+        }
+    });
+
+    let mut toggle_folder = move |path: String| {
+        let mut expanded = expanded.write();
+        if !expanded.remove(&path) {
+            expanded.insert(path);
+        }
+    };
+
+    let mut select_folder = move |folder: SharedFolder| {
+        let mut selected = selected_tracks.write();
+        let all_selected = folder
+            .items
+            .iter()
+            .all(|t| selected.contains(&get_track_id(t)));
+
+        for item in &folder.items {
+            if all_selected {
+                selected.remove(&get_track_id(item));
+            } else {
+                selected.insert(get_track_id(item));
+            }
+        }
+    };
+
+    let handle_track_toggle = move |id: String| {
+        let mut selected = selected_tracks.write();
+        if !selected.remove(&id) {
+            selected.insert(id);
+        }
+    };
+
+    let needle = filter.read().to_lowercase();
+    let visible: Vec<SharedFolder> = folders
+        .read()
+        .iter()
+        .flatten()
+        .filter(|f| needle.is_empty() || f.path.to_lowercase().contains(&needle))
+        .cloned()
+        .collect();
+
+    rsx! {
+        Modal {
+            on_close: props.on_close,
+            header: rsx! {
+                h3 { class: "text-lg font-bold text-beet-accent truncate", "Shares of {props.source}" }
+            },
+            input {
+                class: "w-full p-2 mb-4 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono text-sm",
+                placeholder: "Filter folders",
+                value: "{filter}",
+                oninput: move |e| filter.set(e.value()),
+            }
+
+            if let Some(e) = error() {
+                div { class: "text-center text-red-400 py-8 font-mono text-sm", "{e}" }
+            } else if folders.read().is_none() {
+                div { class: "flex flex-col items-center justify-center p-4",
+                    div { class: "animate-spin rounded-full h-8 w-8 border-t-2 border-b-2 border-beet-accent mb-2" }
+                    p { class: "text-sm text-gray-300 animate-pulse text-center font-mono",
+                        "Fetching the share list, large shares can take a while."
+                    }
+                }
+            } else if visible.is_empty() {
+                div { class: "text-center text-gray-500 py-8 font-mono", "No folders with audio files" }
+            }
+
+            div { class: "space-y-2",
+                for folder in visible {
+                    div {
+                        key: "{folder.path}",
+                        class: "bg-white/5 border border-white/5 p-3 rounded-md",
+                        div { class: "flex justify-between items-center gap-2",
+                            button {
+                                class: "flex-grow min-w-0 text-left cursor-pointer",
+                                onclick: {
+                                    let path = folder.path.clone();
+                                    move |_| toggle_folder(path.clone())
+                                },
+                                p { class: "text-sm text-beet-leaf font-mono truncate", title: "{folder.path}",
+                                    "{folder.path}"
+                                }
+                                p { class: "text-xs text-gray-500 font-mono", "{folder.items.len()} files" }
+                            }
+                            button {
+                                class: "font-mono uppercase text-[10px] whitespace-nowrap tracking-widest px-3 py-1 border border-beet-leaf/30 text-beet-leaf hover:bg-beet-leaf hover:text-beet-dark transition-colors cursor-pointer rounded",
+                                onclick: {
+                                    let folder = folder.clone();
+                                    move |_| select_folder(folder.clone())
+                                },
+                                "Select All"
+                            }
+                        }
+                        if expanded.read().contains(&folder.path) {
+                            ul { class: "space-y-1 mt-2",
+                                for track in folder.items {
+                                    TrackItem {
+                                        is_selected: selected_tracks.read().contains(&get_track_id(&track)),
+                                        track,
+                                        on_toggle: handle_track_toggle,
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
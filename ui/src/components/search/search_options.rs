@@ -0,0 +1,157 @@
+use dioxus::prelude::*;
+use shared::download::SearchOptions;
+use std::str::FromStr;
+
+const INPUT_CLASS: &str = "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono";
+const LABEL_CLASS: &str = "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider";
+
+/// Form values of a [`SearchOptions`]. Empty means the default.
+#[derive(Clone, Default, PartialEq)]
+pub struct SearchOptionFields {
+    pub timeout_secs: String,
+    pub min_upload_speed: String,
+    pub min_bitrate: String,
+    pub response_limit: String,
+    pub file_limit: String,
+    pub include_locked: Option<bool>,
+    pub max_results: String,
+}
+
+impl SearchOptionFields {
+    pub fn from_options(options: &SearchOptions) -> Self {
+        fn text<T: ToString>(value: Option<T>) -> String {
+            value.map(|v| v.to_string()).unwrap_or_default()
+        }
+        Self {
+            timeout_secs: text(options.timeout_secs),
+            min_upload_speed: text(options.min_upload_speed),
+            min_bitrate: text(options.min_bitrate),
+            response_limit: text(options.response_limit),
+            file_limit: text(options.file_limit),
+            include_locked: options.include_locked,
+            max_results: text(options.max_results),
+        }
+    }
+
+    pub fn to_options(&self) -> Result<SearchOptions, String> {
+        fn number<T: FromStr>(value: &str, name: &str) -> Result<Option<T>, String> {
+            let value = value.trim();
+            if value.is_empty() {
+                return Ok(None);
+            }
+            value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("{} must be a whole number", name))
+        }
+
+        Ok(SearchOptions {
+            timeout_secs: number(&self.timeout_secs, "Timeout")?,
+            min_upload_speed: number(&self.min_upload_speed, "Minimum upload speed")?,
+            min_bitrate: number(&self.min_bitrate, "Minimum bitrate")?,
+            response_limit: number(&self.response_limit, "Response limit")?,
+            file_limit: number(&self.file_limit, "File limit")?,
+            include_locked: self.include_locked,
+            max_results: number(&self.max_results, "Result limit")?,
+        })
+    }
+}
+
+/// Inputs for the options of a Soulseek search, empty ones keep the default.
+#[component]
+pub fn SearchOptionsEditor(mut fields: Signal<SearchOptionFields>) -> Element {
+    let locked = match fields.read().include_locked {
+        None => "default",
+        Some(true) => "yes",
+        Some(false) => "no",
+    };
+
+    rsx! {
+        div { class: "grid grid-cols-1 md:grid-cols-3 gap-4",
+            div {
+                label { class: LABEL_CLASS, "Timeout (s)" }
+                input {
+                    class: INPUT_CLASS,
+                    value: "{fields.read().timeout_secs}",
+                    oninput: move |e| fields.write().timeout_secs = e.value(),
+                    placeholder: "Default",
+                    "type": "number",
+                    min: "5",
+                    max: "600",
+                }
+            }
+            div {
+                label { class: LABEL_CLASS, "Min Upload Speed (B/s)" }
+                input {
+                    class: INPUT_CLASS,
+                    value: "{fields.read().min_upload_speed}",
+                    oninput: move |e| fields.write().min_upload_speed = e.value(),
+                    placeholder: "Default",
+                    "type": "number",
+                    min: "0",
+                }
+            }
+            div {
+                label { class: LABEL_CLASS, "Min Bitrate (kbps)" }
+                input {
+                    class: INPUT_CLASS,
+                    value: "{fields.read().min_bitrate}",
+                    oninput: move |e| fields.write().min_bitrate = e.value(),
+                    placeholder: "Default",
+                    "type": "number",
+                    min: "0",
+                }
+            }
+            div {
+                label { class: LABEL_CLASS, "Responses per Query" }
+                input {
+                    class: INPUT_CLASS,
+                    value: "{fields.read().response_limit}",
+                    oninput: move |e| fields.write().response_limit = e.value(),
+                    placeholder: "Default",
+                    "type": "number",
+                    min: "1",
+                }
+            }
+            div {
+                label { class: LABEL_CLASS, "Files per Query" }
+                input {
+                    class: INPUT_CLASS,
+                    value: "{fields.read().file_limit}",
+                    oninput: move |e| fields.write().file_limit = e.value(),
+                    placeholder: "Default",
+                    "type": "number",
+                    min: "1",
+                }
+            }
+            div {
+                label { class: LABEL_CLASS, "Results Shown" }
+                input {
+                    class: INPUT_CLASS,
+                    value: "{fields.read().max_results}",
+                    oninput: move |e| fields.write().max_results = e.value(),
+                    placeholder: "Default",
+                    "type": "number",
+                    min: "1",
+                }
+            }
+            div {
+                label { class: LABEL_CLASS, "Locked Files" }
+                select {
+                    class: INPUT_CLASS,
+                    value: "{locked}",
+                    onchange: move |e| {
+                        fields.write().include_locked = match e.value().as_str() {
+                            "yes" => Some(true),
+                            "no" => Some(false),
+                            _ => None,
+                        };
+                    },
+                    option { value: "default", "Default" }
+                    option { value: "yes", "Include" }
+                    option { value: "no", "Hide" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use dioxus::prelude::*;
+
+/// Small badges showing which metadata providers contributed to a result.
+#[component]
+pub fn SourceBadges(sources: Vec<String>) -> Element {
+    if sources.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+      div { class: "flex flex-wrap gap-1 mt-2",
+        for source in sources {
+          span {
+            key: "{source}",
+            class: "px-1.5 py-0.5 text-[10px] uppercase font-mono rounded bg-black/30 text-gray-500 border border-white/5",
+            "{source}"
+          }
+        }
+      }
+    }
+}
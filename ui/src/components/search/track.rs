@@ -1,6 +1,8 @@
 use dioxus::prelude::*;
 use shared::metadata::Track;
 
+use super::source_badges::SourceBadges;
+
 #[derive(Props, PartialEq, Clone)]
 pub struct Props {
     pub track: Track,
@@ -32,6 +34,7 @@ pub fn TrackResult(props: Props) -> Element {
                 "from \"{album_title}\""
               }
             }
+            SourceBadges { sources: track.sources.clone() }
           }
 
           if let Some(duration) = &track.duration {
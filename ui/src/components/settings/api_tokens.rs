@@ -0,0 +1,188 @@
+use api::auth::ApiScope;
+use api::{create_api_token, delete_api_token, get_api_tokens};
+use dioxus::prelude::*;
+use std::collections::BTreeSet;
+
+use crate::auth::use_auth;
+
+const INPUT_CLASS: &str = "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono";
+const LABEL_CLASS: &str = "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider";
+
+/// Validity choices offered when creating a token, in days, 0 for no expiry.
+const VALIDITY_OPTIONS: [(i64, &str); 5] = [
+    (0, "Never expires"),
+    (7, "1 week"),
+    (30, "30 days"),
+    (90, "90 days"),
+    (365, "1 year"),
+];
+
+/// Personal API tokens for scripts, sent as `Authorization: Bearer <token>`.
+#[component]
+pub fn ApiTokenManager() -> Element {
+    let mut tokens = use_signal(Vec::new);
+    let mut name = use_signal(String::new);
+    let mut scopes = use_signal(|| BTreeSet::from([ApiScope::Read]));
+    let mut valid_days = use_signal(|| 0i64);
+    let mut new_secret = use_signal(|| None::<String>);
+    let mut error = use_signal(String::new);
+    let auth = use_auth();
+
+    let fetch_tokens = move || async move {
+        match auth.call(get_api_tokens).await {
+            Ok(fetched) => tokens.set(fetched),
+            Err(e) => error.set(format!("Failed to fetch tokens: {e}")),
+        }
+    };
+
+    use_future(move || async move {
+        fetch_tokens().await;
+    });
+
+    let handle_create = move |_| async move {
+        error.set(String::new());
+        new_secret.set(None);
+
+        let selected: Vec<ApiScope> = scopes.read().iter().copied().collect();
+        let days = Some(valid_days()).filter(|d| *d > 0);
+        match auth
+            .call(|| create_api_token(name(), selected.clone(), days))
+            .await
+        {
+            Ok(created) => {
+                new_secret.set(Some(created.secret));
+                name.set(String::new());
+                fetch_tokens().await;
+            }
+            Err(e) => error.set(format!("Failed to create token: {e}")),
+        }
+    };
+
+    let handle_delete = move |id: String| async move {
+        match auth.call(|| delete_api_token(id.clone())).await {
+            Ok(_) => fetch_tokens().await,
+            Err(e) => error.set(format!("Failed to revoke token: {e}")),
+        }
+    };
+
+    // Only offer scopes the account can actually use
+    let available: Vec<ApiScope> = ApiScope::all()
+        .into_iter()
+        .filter(|s| match s {
+            ApiScope::Admin => auth.is_admin(),
+            ApiScope::Download => auth.can_write(),
+            _ => true,
+        })
+        .collect();
+
+    rsx! {
+        div { class: "bg-beet-panel border border-white/10 p-6 rounded-lg shadow-2xl relative z-10",
+            h2 { class: "text-xl font-bold mb-2 text-beet-accent font-display", "API Tokens" }
+            p { class: "text-xs text-gray-500 font-mono mb-4",
+                "Use a token from scripts with the header "
+                code { class: "text-gray-300", "Authorization: Bearer <token>" }
+                ". Each scope includes the ones before it."
+            }
+
+            if !error().is_empty() {
+                div { class: "mb-4 p-4 bg-red-900/20 border border-red-500/50 rounded text-red-400 font-mono text-sm",
+                    "{error}"
+                }
+            }
+            if let Some(secret) = new_secret() {
+                div { class: "mb-4 p-4 bg-green-900/20 border border-green-500/50 rounded text-green-400 font-mono text-sm break-all",
+                    p { class: "mb-2", "Copy your token now, it will not be shown again:" }
+                    code { class: "text-white select-all", "{secret}" }
+                }
+            }
+
+            div { class: "grid grid-cols-1 md:grid-cols-2 gap-4 mb-4",
+                div {
+                    label { class: LABEL_CLASS, "Token Name" }
+                    input {
+                        class: INPUT_CLASS,
+                        value: "{name}",
+                        oninput: move |e| name.set(e.value()),
+                        placeholder: "cron sync",
+                        "type": "text",
+                    }
+                }
+                div {
+                    label { class: LABEL_CLASS, "Valid For" }
+                    select {
+                        class: INPUT_CLASS,
+                        value: "{valid_days}",
+                        onchange: move |e| valid_days.set(e.value().parse().unwrap_or(0)),
+                        for (days, label) in VALIDITY_OPTIONS {
+                            option { value: "{days}", "{label}" }
+                        }
+                    }
+                }
+                div {
+                    label { class: LABEL_CLASS, "Scopes" }
+                    div { class: "flex flex-wrap gap-3 pt-2",
+                        for scope in available {
+                            label { class: "flex items-center gap-1 text-xs font-mono text-gray-300",
+                                input {
+                                    "type": "checkbox",
+                                    checked: scopes.read().contains(&scope),
+                                    onchange: move |e| {
+                                        if e.checked() {
+                                            scopes.write().insert(scope);
+                                        } else {
+                                            scopes.write().remove(&scope);
+                                        }
+                                    },
+                                }
+                                "{scope}"
+                            }
+                        }
+                    }
+                }
+            }
+            button {
+                class: "retro-btn mb-6 rounded",
+                disabled: name.read().trim().is_empty() || scopes.read().is_empty(),
+                onclick: handle_create,
+                "Create Token"
+            }
+
+            if tokens.read().is_empty() {
+                p { class: "text-gray-500 font-mono italic", "No API tokens yet." }
+            } else {
+                ul { class: "space-y-2",
+                    for token in tokens.read().clone() {
+                        {
+                            let id = token.id.clone();
+                            let last_used = token.last_used_label();
+                            let expiry = if token.is_expired() {
+                                "expired".to_string()
+                            } else if token.expires_at.is_some() {
+                                format!("expires {}", token.expires_label())
+                            } else {
+                                "no expiry".to_string()
+                            };
+                            rsx! {
+                                li {
+                                    key: "{token.id}",
+                                    class: "flex justify-between items-center gap-3 bg-white/5 border border-white/5 p-3 rounded",
+                                    div { class: "min-w-0",
+                                        span { class: "text-white font-display block truncate", "{token.name}" }
+                                        span { class: "text-gray-500 text-xs font-mono",
+                                            "{token.scopes} // last used {last_used} // {expiry}"
+                                        }
+                                    }
+                                    button {
+                                        class: "text-xs font-mono text-gray-400 hover:text-red-400 transition-colors underline decoration-dotted",
+                                        onclick: move |_| handle_delete(id.clone()),
+                                        "Revoke"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,23 +1,48 @@
+use api::auth::RegistrationMode;
+use api::SlskdInstance;
 use dioxus::prelude::*;
+use shared::download::{ScheduleAction, ScheduleRule};
 
 use crate::settings_context::use_settings;
+use crate::{SearchOptionFields, SearchOptionsEditor};
 
 #[component]
 pub fn AppConfigManager() -> Element {
     let mut settings = use_settings();
+    // API keys are write-only, the inputs only hold a replacement
     let mut lastfm_api_key = use_signal(String::new);
+    let mut lastfm_api_key_set = use_signal(|| false);
     let mut slskd_url = use_signal(String::new);
     let mut slskd_api_key = use_signal(String::new);
+    let mut slskd_api_key_set = use_signal(|| false);
+    let mut slskd_instances = use_signal(Vec::<SlskdInstance>::new);
+    let mut library_root = use_signal(String::new);
+    let mut registration_mode = use_signal(RegistrationMode::default);
+    let mut search_defaults = use_signal(SearchOptionFields::default);
+    let mut download_schedule = use_signal(Vec::<ScheduleRule>::new);
     let mut error = use_signal(String::new);
     let mut success_msg = use_signal(String::new);
     let mut saving = use_signal(|| false);
     let mut loaded = use_signal(|| false);
 
+    let mut apply = move |config: api::AppConfigValues| {
+        lastfm_api_key.set(String::new());
+        lastfm_api_key_set.set(config.lastfm_api_key_set);
+        slskd_url.set(config.slskd_url.unwrap_or_default());
+        slskd_api_key.set(String::new());
+        slskd_api_key_set.set(config.slskd_api_key_set);
+        slskd_instances.set(config.slskd_instances.unwrap_or_default());
+        library_root.set(config.library_root.unwrap_or_default());
+        registration_mode.set(config.registration_mode.unwrap_or_default());
+        search_defaults.set(SearchOptionFields::from_options(
+            &config.search_defaults.unwrap_or_default(),
+        ));
+        download_schedule.set(config.download_schedule.unwrap_or_default());
+    };
+
     use_future(move || async move {
         if let Ok(config) = api::get_app_config().await {
-            lastfm_api_key.set(config.lastfm_api_key.unwrap_or_default());
-            slskd_url.set(config.slskd_url.unwrap_or_default());
-            slskd_api_key.set(config.slskd_api_key.unwrap_or_default());
+            apply(config);
             loaded.set(true);
         }
     });
@@ -25,16 +50,39 @@ pub fn AppConfigManager() -> Element {
     let handle_save = move |_| async move {
         error.set(String::new());
         success_msg.set(String::new());
+        let search_options = match search_defaults.read().to_options() {
+            Ok(options) => options,
+            Err(e) => {
+                error.set(e);
+                return;
+            }
+        };
         saving.set(true);
 
+        // Empty key inputs keep the stored keys
         let config = api::AppConfigValues {
-            lastfm_api_key: Some(lastfm_api_key()),
+            lastfm_api_key: Some(lastfm_api_key()).filter(|k| !k.is_empty()),
             slskd_url: Some(slskd_url()),
-            slskd_api_key: Some(slskd_api_key()),
+            slskd_api_key: Some(slskd_api_key()).filter(|k| !k.is_empty()),
+            slskd_instances: Some(
+                slskd_instances()
+                    .into_iter()
+                    .map(|i| SlskdInstance {
+                        api_key: i.api_key.clone().filter(|k| !k.is_empty()),
+                        ..i
+                    })
+                    .collect(),
+            ),
+            library_root: Some(library_root()),
+            registration_mode: Some(registration_mode()),
+            search_defaults: Some(search_options),
+            download_schedule: Some(download_schedule()),
+            ..Default::default()
         };
 
         match api::update_app_config(config).await {
-            Ok(_) => {
+            Ok(config) => {
+                apply(config);
                 let _ = settings.refresh_providers().await;
                 success_msg.set("Configuration saved".to_string());
             }
@@ -43,6 +91,19 @@ pub fn AppConfigManager() -> Element {
         saving.set(false);
     };
 
+    let handle_remove_key = move |config: api::AppConfigValues| async move {
+        error.set(String::new());
+        success_msg.set(String::new());
+        match api::update_app_config(config).await {
+            Ok(config) => {
+                apply(config);
+                let _ = settings.refresh_providers().await;
+                success_msg.set("API key removed".to_string());
+            }
+            Err(e) => error.set(format!("Failed to remove API key: {e}")),
+        }
+    };
+
     if !loaded() {
         return rsx! {
             div { class: "bg-beet-panel border border-white/10 p-6 rounded-lg shadow-2xl relative z-10",
@@ -82,17 +143,188 @@ pub fn AppConfigManager() -> Element {
                             }
                         }
                         div {
-                            label { class: "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider",
-                                "slskd API Key"
+                            div { class: "flex justify-between items-center mb-1",
+                                label { class: "block text-xs font-mono text-gray-400 uppercase tracking-wider",
+                                    "slskd API Key"
+                                }
+                                if slskd_api_key_set() {
+                                    button {
+                                        class: "text-xs font-mono text-gray-400 hover:text-red-400 transition-colors underline decoration-dotted",
+                                        onclick: move |_| handle_remove_key(api::AppConfigValues {
+                                            slskd_api_key: Some(String::new()),
+                                            ..Default::default()
+                                        }),
+                                        "Remove"
+                                    }
+                                }
                             }
                             input {
                                 class: "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono",
                                 value: "{slskd_api_key}",
                                 oninput: move |e| slskd_api_key.set(e.value()),
-                                placeholder: "Enter slskd API key",
+                                placeholder: if slskd_api_key_set() { "Saved, type to replace" } else { "Enter slskd API key" },
                                 "type": "password",
                             }
                         }
+                        div {
+                            div { class: "flex justify-between items-center mb-1",
+                                label { class: "block text-xs font-mono text-gray-400 uppercase tracking-wider",
+                                    "Additional Instances"
+                                }
+                                button {
+                                    class: "text-xs font-mono text-gray-400 hover:text-beet-accent transition-colors underline decoration-dotted",
+                                    onclick: move |_| slskd_instances.write().push(SlskdInstance::default()),
+                                    "Add"
+                                }
+                            }
+                            for (index, instance) in slskd_instances().into_iter().enumerate() {
+                                div {
+                                    key: "{index}",
+                                    class: "grid grid-cols-1 md:grid-cols-[1fr_2fr_2fr_auto] gap-2 mb-2 items-center",
+                                    input {
+                                        class: "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono",
+                                        value: "{instance.name}",
+                                        oninput: move |e| slskd_instances.write()[index].name = e.value(),
+                                        placeholder: "Name",
+                                    }
+                                    input {
+                                        class: "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono",
+                                        value: "{instance.url}",
+                                        oninput: move |e| slskd_instances.write()[index].url = e.value(),
+                                        placeholder: "http://other-host:5030",
+                                    }
+                                    input {
+                                        class: "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono",
+                                        value: "{instance.api_key.clone().unwrap_or_default()}",
+                                        oninput: move |e| slskd_instances.write()[index].api_key = Some(e.value()),
+                                        placeholder: if instance.api_key_set { "Saved, type to replace" } else { "Enter API key" },
+                                        "type": "password",
+                                    }
+                                    button {
+                                        class: "text-xs font-mono text-gray-400 hover:text-red-400 transition-colors underline decoration-dotted",
+                                        onclick: move |_| {
+                                            slskd_instances.write().remove(index);
+                                        },
+                                        "Remove"
+                                    }
+                                }
+                            }
+                            p { class: "text-xs text-gray-500 mt-1 font-mono",
+                                "Searches run on every instance and downloads go to the instance that found the files. The instance above is named \"main\". All instances must save downloads to the directory soulbeet imports from."
+                            }
+                        }
+                    }
+                }
+
+                div {
+                    h3 { class: "text-sm font-semibold text-white mb-3", "Search Defaults" }
+                    SearchOptionsEditor { fields: search_defaults }
+                    p { class: "text-xs text-gray-500 mt-2 font-mono",
+                        "Used when a search leaves an option empty. Empty fields use 120 s, 10 B/s, no minimum bitrate, 100 responses and 10000 files per query, hidden locked files and 50 results."
+                    }
+                }
+
+                div {
+                    div { class: "flex justify-between items-center mb-3",
+                        h3 { class: "text-sm font-semibold text-white", "Download Schedule" }
+                        button {
+                            class: "text-xs font-mono text-gray-400 hover:text-beet-accent transition-colors underline decoration-dotted",
+                            onclick: move |_| download_schedule.write().push(ScheduleRule::default()),
+                            "Add"
+                        }
+                    }
+                    for (index, rule) in download_schedule().into_iter().enumerate() {
+                        div {
+                            key: "{index}",
+                            class: "grid grid-cols-2 md:grid-cols-[1fr_1fr_1fr_1fr_auto] gap-2 mb-2 items-center",
+                            input {
+                                class: "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono",
+                                "type": "time",
+                                value: "{rule.start}",
+                                oninput: move |e| download_schedule.write()[index].start = e.value(),
+                            }
+                            input {
+                                class: "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono",
+                                "type": "time",
+                                value: "{rule.end}",
+                                oninput: move |e| download_schedule.write()[index].end = e.value(),
+                            }
+                            select {
+                                class: "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono",
+                                value: if rule.action == ScheduleAction::Pause { "pause" } else { "limit" },
+                                onchange: move |e| {
+                                    download_schedule.write()[index].action = if e.value() == "pause" {
+                                        ScheduleAction::Pause
+                                    } else {
+                                        ScheduleAction::Limit { max_concurrent: 1 }
+                                    };
+                                },
+                                option { value: "pause", "Pause" }
+                                option { value: "limit", "Limit" }
+                            }
+                            if let ScheduleAction::Limit { max_concurrent } = rule.action {
+                                input {
+                                    class: "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono",
+                                    "type": "number",
+                                    min: "1",
+                                    value: "{max_concurrent}",
+                                    oninput: move |e| {
+                                        if let Ok(max_concurrent) = e.value().parse() {
+                                            download_schedule.write()[index].action = ScheduleAction::Limit {
+                                                max_concurrent,
+                                            };
+                                        }
+                                    },
+                                    title: "Downloads per user at once",
+                                }
+                            } else {
+                                div {}
+                            }
+                            button {
+                                class: "text-xs font-mono text-gray-400 hover:text-red-400 transition-colors underline decoration-dotted",
+                                onclick: move |_| {
+                                    download_schedule.write().remove(index);
+                                },
+                                "Remove"
+                            }
+                        }
+                    }
+                    p { class: "text-xs text-gray-500 mt-1 font-mono",
+                        "Daily windows in the server's time zone. Pause starts no downloads and takes the ones still queued off slskd's queue until the window ends, Limit runs at most that many downloads per user at once. Running transfers finish."
+                    }
+                }
+
+                div {
+                    h3 { class: "text-sm font-semibold text-white mb-3", "Library & Accounts" }
+                    div {
+                        label { class: "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider",
+                            "Library Root"
+                        }
+                        input {
+                            class: "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono",
+                            value: "{library_root}",
+                            oninput: move |e| library_root.set(e.value()),
+                            placeholder: "/music",
+                        }
+                        p { class: "text-xs text-gray-500 mt-1 font-mono",
+                            "Non-admin users can only add folders inside this directory. Leave empty to allow any path."
+                        }
+                    }
+                    div { class: "mt-4",
+                        label { class: "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider",
+                            "Registration"
+                        }
+                        select {
+                            class: "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono",
+                            value: "{registration_mode}",
+                            onchange: move |e| registration_mode.set(e.value().parse().unwrap_or_default()),
+                            for mode in RegistrationMode::all() {
+                                option { value: "{mode}", "{mode}" }
+                            }
+                        }
+                        p { class: "text-xs text-gray-500 mt-1 font-mono",
+                            "Open lets anyone sign up from the login page, invite requires a code from Settings > Users."
+                        }
                     }
                 }
 
@@ -100,14 +332,26 @@ pub fn AppConfigManager() -> Element {
                     h3 { class: "text-sm font-semibold text-white mb-3", "Metadata Providers" }
                     div { class: "space-y-4",
                         div {
-                            label { class: "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider",
-                                "Last.fm API Key"
+                            div { class: "flex justify-between items-center mb-1",
+                                label { class: "block text-xs font-mono text-gray-400 uppercase tracking-wider",
+                                    "Last.fm API Key"
+                                }
+                                if lastfm_api_key_set() {
+                                    button {
+                                        class: "text-xs font-mono text-gray-400 hover:text-red-400 transition-colors underline decoration-dotted",
+                                        onclick: move |_| handle_remove_key(api::AppConfigValues {
+                                            lastfm_api_key: Some(String::new()),
+                                            ..Default::default()
+                                        }),
+                                        "Remove"
+                                    }
+                                }
                             }
                             input {
                                 class: "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono",
                                 value: "{lastfm_api_key}",
                                 oninput: move |e| lastfm_api_key.set(e.value()),
-                                placeholder: "Enter Last.fm API key",
+                                placeholder: if lastfm_api_key_set() { "Saved, type to replace" } else { "Enter Last.fm API key" },
                                 "type": "password",
                             }
                             p { class: "text-xs text-gray-500 mt-1 font-mono",
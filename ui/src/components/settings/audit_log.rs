@@ -0,0 +1,104 @@
+use api::models::audit::{AuditEntry, AUDIT_RETENTION_DAYS};
+use api::{get_audit_log, AUDIT_PAGE_SIZE};
+use dioxus::prelude::*;
+
+use crate::auth::use_auth;
+
+/// Security events: logins, password changes and changes to users, folders and config.
+#[component]
+pub fn AuditLogViewer() -> Element {
+    let mut entries = use_signal(Vec::<AuditEntry>::new);
+    let mut has_more = use_signal(|| false);
+    let mut error = use_signal(String::new);
+    let auth = use_auth();
+
+    let fetch_page = move |before_id: Option<i64>| async move {
+        match auth.call(|| get_audit_log(before_id)).await {
+            Ok(page) => {
+                has_more.set(page.len() as i64 == AUDIT_PAGE_SIZE);
+                if before_id.is_some() {
+                    entries.write().extend(page);
+                } else {
+                    entries.set(page);
+                }
+            }
+            Err(e) => error.set(format!("Failed to fetch audit log: {e}")),
+        }
+    };
+
+    use_future(move || async move {
+        fetch_page(None).await;
+    });
+
+    rsx! {
+        div { class: "bg-beet-panel border border-white/10 p-6 rounded-lg shadow-2xl relative z-10",
+            div { class: "flex justify-between items-center mb-2",
+                h2 { class: "text-xl font-bold text-beet-accent font-display", "Audit Log" }
+                button {
+                    class: "text-xs font-mono text-gray-400 hover:text-beet-accent transition-colors underline decoration-dotted",
+                    onclick: move |_| async move {
+                        error.set(String::new());
+                        fetch_page(None).await;
+                    },
+                    "Refresh"
+                }
+            }
+            p { class: "text-xs text-gray-500 font-mono mb-4",
+                "Logins and changes to accounts, folders and config, kept for {AUDIT_RETENTION_DAYS} days."
+            }
+
+            if !error().is_empty() {
+                div { class: "mb-4 p-4 bg-red-900/20 border border-red-500/50 rounded text-red-400 font-mono text-sm",
+                    "{error}"
+                }
+            }
+
+            if entries.read().is_empty() {
+                p { class: "text-gray-500 font-mono italic", "No events recorded yet." }
+            } else {
+                ul { class: "space-y-1 font-mono text-xs",
+                    for entry in entries.read().clone() {
+                        {
+                            let event_class = if entry.event.is_failure() {
+                                "text-red-400"
+                            } else {
+                                "text-beet-leaf"
+                            };
+                            let created = entry.created_label();
+                            let username = entry.username.clone().unwrap_or_else(|| "-".to_string());
+                            let ip = entry.ip.clone().unwrap_or_else(|| "-".to_string());
+                            let target = entry.target.clone().unwrap_or_default();
+                            let details = entry.details.clone().unwrap_or_default();
+                            rsx! {
+                                li {
+                                    key: "{entry.id}",
+                                    class: "flex flex-wrap gap-x-3 bg-white/5 border border-white/5 px-3 py-2 rounded",
+                                    span { class: "text-gray-500", "{created}" }
+                                    span { class: event_class, "{entry.event}" }
+                                    span { class: "text-white", "{username}" }
+                                    span { class: "text-gray-500", "{ip}" }
+                                    if !target.is_empty() {
+                                        span { class: "text-gray-300 break-all", "{target}" }
+                                    }
+                                    if !details.is_empty() {
+                                        span { class: "text-gray-400 break-all", "{details}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                if has_more() {
+                    button {
+                        class: "retro-btn mt-4 rounded",
+                        onclick: move |_| async move {
+                            let last = entries.read().last().map(|e| e.id);
+                            fetch_page(last).await;
+                        },
+                        "Load More"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use api::models::download_limits::{DownloadLimits, UserDownloadLimits};
+use api::{get_download_limits, update_default_download_limits, update_user_download_limits};
+use dioxus::prelude::*;
+
+use crate::auth::use_auth;
+
+const INPUT_CLASS: &str = "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono";
+const LABEL_CLASS: &str = "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider";
+
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Form values of a [`DownloadLimits`], sizes in GB. Empty means no limit.
+#[derive(Clone, Default, PartialEq)]
+struct LimitFields {
+    max_concurrent: String,
+    max_queued: String,
+    daily_gb: String,
+    monthly_gb: String,
+    folder_gb: String,
+}
+
+impl LimitFields {
+    fn from_limits(limits: &DownloadLimits) -> Self {
+        let count = |n: Option<i64>| n.map(|n| n.to_string()).unwrap_or_default();
+        let gb = |b: Option<i64>| {
+            b.map(|b| format!("{}", (b as f64 / GB * 100.0).round() / 100.0))
+                .unwrap_or_default()
+        };
+        Self {
+            max_concurrent: count(limits.max_concurrent),
+            max_queued: count(limits.max_queued),
+            daily_gb: gb(limits.daily_bytes),
+            monthly_gb: gb(limits.monthly_bytes),
+            folder_gb: gb(limits.folder_bytes),
+        }
+    }
+
+    fn to_limits(&self) -> Result<DownloadLimits, String> {
+        fn count(value: &str, name: &str) -> Result<Option<i64>, String> {
+            let value = value.trim();
+            if value.is_empty() {
+                return Ok(None);
+            }
+            value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("{} must be a whole number", name))
+        }
+        fn gb(value: &str, name: &str) -> Result<Option<i64>, String> {
+            let value = value.trim();
+            if value.is_empty() {
+                return Ok(None);
+            }
+            value
+                .parse::<f64>()
+                .map(|gb| Some((gb * GB) as i64))
+                .map_err(|_| format!("{} must be a number of GB", name))
+        }
+
+        Ok(DownloadLimits {
+            max_concurrent: count(&self.max_concurrent, "Concurrent downloads")?,
+            max_queued: count(&self.max_queued, "Queued downloads")?,
+            daily_bytes: gb(&self.daily_gb, "Daily quota")?,
+            monthly_bytes: gb(&self.monthly_gb, "Monthly quota")?,
+            folder_bytes: gb(&self.folder_gb, "Folder size")?,
+        })
+    }
+}
+
+#[component]
+fn LimitFieldsEditor(mut fields: Signal<LimitFields>) -> Element {
+    rsx! {
+        div { class: "grid grid-cols-1 md:grid-cols-3 gap-4",
+            div {
+                label { class: LABEL_CLASS, "Concurrent Files" }
+                input {
+                    class: INPUT_CLASS,
+                    value: "{fields.read().max_concurrent}",
+                    oninput: move |e| fields.write().max_concurrent = e.value(),
+                    placeholder: "No limit",
+                    "type": "number",
+                    min: "1",
+                }
+            }
+            div {
+                label { class: LABEL_CLASS, "Queued Files" }
+                input {
+                    class: INPUT_CLASS,
+                    value: "{fields.read().max_queued}",
+                    oninput: move |e| fields.write().max_queued = e.value(),
+                    placeholder: "No limit",
+                    "type": "number",
+                    min: "1",
+                }
+            }
+            div {
+                label { class: LABEL_CLASS, "Folder Size (GB)" }
+                input {
+                    class: INPUT_CLASS,
+                    value: "{fields.read().folder_gb}",
+                    oninput: move |e| fields.write().folder_gb = e.value(),
+                    placeholder: "No limit",
+                }
+            }
+            div {
+                label { class: LABEL_CLASS, "Per Day (GB)" }
+                input {
+                    class: INPUT_CLASS,
+                    value: "{fields.read().daily_gb}",
+                    oninput: move |e| fields.write().daily_gb = e.value(),
+                    placeholder: "No limit",
+                }
+            }
+            div {
+                label { class: LABEL_CLASS, "Per Month (GB)" }
+                input {
+                    class: INPUT_CLASS,
+                    value: "{fields.read().monthly_gb}",
+                    oninput: move |e| fields.write().monthly_gb = e.value(),
+                    placeholder: "No limit",
+                }
+            }
+        }
+    }
+}
+
+/// Default download limits and per-user overrides.
+#[component]
+pub fn DownloadLimitsManager() -> Element {
+    let mut default_fields = use_signal(LimitFields::default);
+    let mut users = use_signal(Vec::<UserDownloadLimits>::new);
+    let mut editing_user_id = use_signal(|| None::<String>);
+    let mut user_fields = use_signal(LimitFields::default);
+    let mut error = use_signal(String::new);
+    let mut success_msg = use_signal(String::new);
+    let auth = use_auth();
+
+    let fetch_limits = move || async move {
+        match auth.call(get_download_limits).await {
+            Ok(overview) => {
+                default_fields.set(LimitFields::from_limits(&overview.default));
+                users.set(overview.users);
+            }
+            Err(e) => error.set(format!("Failed to fetch download limits: {e}")),
+        }
+    };
+
+    use_future(move || async move {
+        fetch_limits().await;
+    });
+
+    let handle_save_default = move |_| async move {
+        error.set(String::new());
+        success_msg.set(String::new());
+        let limits = match default_fields.read().to_limits() {
+            Ok(limits) => limits,
+            Err(e) => {
+                error.set(e);
+                return;
+            }
+        };
+        match auth
+            .call(|| update_default_download_limits(limits.clone()))
+            .await
+        {
+            Ok(_) => {
+                success_msg.set("Default limits saved".to_string());
+                fetch_limits().await;
+            }
+            Err(e) => error.set(format!("Failed to save limits: {e}")),
+        }
+    };
+
+    let handle_save_user = move |user_id: String, custom: bool| async move {
+        error.set(String::new());
+        success_msg.set(String::new());
+        let limits = if custom {
+            match user_fields.read().to_limits() {
+                Ok(limits) => Some(limits),
+                Err(e) => {
+                    error.set(e);
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        match auth
+            .call(|| update_user_download_limits(user_id.clone(), limits.clone()))
+            .await
+        {
+            Ok(_) => {
+                success_msg.set("User limits saved".to_string());
+                editing_user_id.set(None);
+                fetch_limits().await;
+            }
+            Err(e) => error.set(format!("Failed to save limits: {e}")),
+        }
+    };
+
+    rsx! {
+        div { class: "bg-beet-panel border border-white/10 p-6 rounded-lg shadow-2xl relative z-10",
+            h2 { class: "text-xl font-bold mb-2 text-beet-accent font-display", "Download Limits" }
+            p { class: "text-xs text-gray-500 font-mono mb-4",
+                "Files over the concurrent limit wait in Soulbeet until earlier ones finish. Quotas count finished downloads per UTC day and month."
+            }
+
+            if !error().is_empty() {
+                div { class: "mb-4 p-4 bg-red-900/20 border border-red-500/50 rounded text-red-400 font-mono text-sm",
+                    "{error}"
+                }
+            }
+            if !success_msg().is_empty() {
+                div { class: "mb-4 p-4 bg-green-900/20 border border-green-500/50 rounded text-green-400 font-mono text-sm",
+                    "{success_msg}"
+                }
+            }
+
+            h3 { class: "text-sm font-semibold text-white mb-3", "Defaults" }
+            LimitFieldsEditor { fields: default_fields }
+            button {
+                class: "retro-btn mt-4 mb-6 rounded",
+                onclick: handle_save_default,
+                "Save Defaults"
+            }
+
+            h3 { class: "text-lg font-bold mb-2 text-white font-display border-b border-white/10 pb-2",
+                "Per User"
+            }
+            ul { class: "space-y-2",
+                for user in users.read().clone() {
+                    {
+                        let id_edit = user.user_id.clone();
+                        let id_save = user.user_id.clone();
+                        let id_reset = user.user_id.clone();
+                        let current = user.custom.clone().unwrap_or_default();
+                        let summary = match &user.custom {
+                            Some(limits) => limits.summary(),
+                            None => "defaults".to_string(),
+                        };
+                        rsx! {
+                            li {
+                                key: "{user.user_id}",
+                                class: "bg-white/5 border border-white/5 p-3 rounded",
+                                div { class: "flex justify-between items-center gap-3",
+                                    div { class: "min-w-0",
+                                        span { class: "font-bold text-white font-display block", "{user.username}" }
+                                        span { class: "text-gray-500 text-xs font-mono", "{summary}" }
+                                    }
+                                    if editing_user_id() != Some(user.user_id.clone()) {
+                                        button {
+                                            class: "text-xs font-mono text-gray-400 hover:text-beet-accent transition-colors underline decoration-dotted",
+                                            onclick: move |_| {
+                                                user_fields.set(LimitFields::from_limits(&current));
+                                                editing_user_id.set(Some(id_edit.clone()));
+                                            },
+                                            "Edit"
+                                        }
+                                    }
+                                }
+                                if editing_user_id() == Some(user.user_id.clone()) {
+                                    div { class: "mt-3",
+                                        LimitFieldsEditor { fields: user_fields }
+                                        div { class: "flex gap-2 mt-3",
+                                            button {
+                                                class: "text-xs uppercase tracking-wider font-bold text-beet-leaf hover:text-white transition-colors",
+                                                onclick: move |_| handle_save_user(id_save.clone(), true),
+                                                "[ Save ]"
+                                            }
+                                            button {
+                                                class: "text-xs uppercase tracking-wider font-bold text-gray-400 hover:text-white transition-colors",
+                                                onclick: move |_| handle_save_user(id_reset.clone(), false),
+                                                "[ Use Defaults ]"
+                                            }
+                                            button {
+                                                class: "text-xs uppercase tracking-wider font-bold text-gray-500 hover:text-white transition-colors",
+                                                onclick: move |_| editing_user_id.set(None),
+                                                "[ Cancel ]"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use api::get_download_usage;
+use api::models::download_limits::DownloadUsage;
+use dioxus::prelude::*;
+use shared::download::format_size;
+
+use crate::auth::use_auth;
+
+fn size(bytes: i64) -> String {
+    format_size(bytes.max(0) as u64)
+}
+
+#[component]
+fn UsageRow(label: String, used: String, limit: Option<String>, percent: Option<f64>) -> Element {
+    let bar_class = match percent {
+        Some(p) if p >= 90.0 => "bg-red-400",
+        Some(p) if p >= 70.0 => "bg-yellow-400",
+        _ => "bg-beet-leaf",
+    };
+
+    rsx! {
+        div {
+            div { class: "flex justify-between text-xs font-mono",
+                span { class: "text-gray-400 uppercase tracking-wider", "{label}" }
+                span { class: "text-white",
+                    "{used}"
+                    if let Some(limit) = limit {
+                        span { class: "text-gray-500", " / {limit}" }
+                    }
+                }
+            }
+            if let Some(percent) = percent {
+                div { class: "h-1 mt-1 bg-white/5 rounded overflow-hidden",
+                    div {
+                        class: "h-full {bar_class}",
+                        style: "width: {percent.min(100.0)}%",
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The user's download limits and how much of them is used.
+#[component]
+pub fn DownloadUsageViewer() -> Element {
+    let mut usage = use_signal(|| None::<DownloadUsage>);
+    let mut error = use_signal(String::new);
+    let auth = use_auth();
+
+    let fetch_usage = move || async move {
+        match auth.call(get_download_usage).await {
+            Ok(fetched) => usage.set(Some(fetched)),
+            Err(e) => error.set(format!("Failed to fetch download usage: {e}")),
+        }
+    };
+
+    use_future(move || async move {
+        fetch_usage().await;
+    });
+
+    let percent = |used: i64, limit: Option<i64>| {
+        limit
+            .filter(|l| *l > 0)
+            .map(|l| used as f64 / l as f64 * 100.0)
+    };
+
+    rsx! {
+        div { class: "bg-beet-panel border border-white/10 p-6 rounded-lg shadow-2xl relative z-10",
+            div { class: "flex justify-between items-center mb-4",
+                h2 { class: "text-xl font-bold text-beet-accent font-display", "Download Usage" }
+                button {
+                    class: "text-xs font-mono text-gray-400 hover:text-beet-accent transition-colors underline decoration-dotted",
+                    onclick: move |_| async move {
+                        error.set(String::new());
+                        fetch_usage().await;
+                    },
+                    "Refresh"
+                }
+            }
+
+            if !error().is_empty() {
+                div { class: "mb-4 p-4 bg-red-900/20 border border-red-500/50 rounded text-red-400 font-mono text-sm",
+                    "{error}"
+                }
+            }
+
+            if let Some(usage) = usage() {
+                if usage.limits.is_unlimited() {
+                    p { class: "text-xs text-gray-500 font-mono mb-4", "No download limits apply to your account." }
+                }
+                div { class: "space-y-3",
+                    UsageRow {
+                        label: "Transferring",
+                        used: usage.active.to_string(),
+                        limit: usage.limits.max_concurrent.map(|n| n.to_string()),
+                        percent: percent(usage.active, usage.limits.max_concurrent),
+                    }
+                    UsageRow {
+                        label: "Queued",
+                        used: usage.queued.to_string(),
+                        limit: usage.limits.max_queued.map(|n| n.to_string()),
+                        percent: percent(usage.queued, usage.limits.max_queued),
+                    }
+                    UsageRow {
+                        label: "Today",
+                        used: size(usage.today_bytes),
+                        limit: usage.limits.daily_bytes.map(size),
+                        percent: percent(usage.today_bytes, usage.limits.daily_bytes),
+                    }
+                    UsageRow {
+                        label: "This Month",
+                        used: size(usage.month_bytes),
+                        limit: usage.limits.monthly_bytes.map(size),
+                        percent: percent(usage.month_bytes, usage.limits.monthly_bytes),
+                    }
+                    for folder in usage.folders.iter() {
+                        UsageRow {
+                            key: "{folder.path}",
+                            label: folder.name.clone(),
+                            used: size(folder.bytes),
+                            limit: usage.limits.folder_bytes.map(size),
+                            percent: percent(folder.bytes, usage.limits.folder_bytes),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
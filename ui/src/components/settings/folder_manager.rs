@@ -1,4 +1,8 @@
-use api::{create_user_folder, delete_folder, get_user_folders, update_folder};
+use api::models::folder::FolderAccess;
+use api::{
+    create_user_folder, delete_folder, get_folder_shares, get_user_folders, set_folder_lyrics,
+    share_folder, unshare_folder, update_folder,
+};
 use dioxus::prelude::*;
 
 use crate::auth::use_auth;
@@ -13,12 +17,17 @@ pub fn FolderManager() -> Element {
     let mut edit_folder_name = use_signal(|| "".to_string());
     let mut edit_folder_path = use_signal(|| "".to_string());
 
+    let mut sharing_folder_id = use_signal(|| None::<String>);
+    let mut shares = use_signal(Vec::new);
+    let mut share_username = use_signal(|| "".to_string());
+    let mut share_can_write = use_signal(|| false);
+
     let mut error = use_signal(|| "".to_string());
     let mut success_msg = use_signal(|| "".to_string());
     let auth = use_auth();
 
     let fetch_folders = move || async move {
-        match auth.call(get_user_folders()).await {
+        match auth.call(get_user_folders).await {
             Ok(fetched_folders) => folders.set(fetched_folders),
             Err(e) => error.set(format!("Failed to fetch folders: {e}")),
         }
@@ -38,7 +47,7 @@ pub fn FolderManager() -> Element {
         }
 
         match auth
-            .call(create_user_folder(folder_name(), folder_path()))
+            .call(|| create_user_folder(folder_name(), folder_path()))
             .await
         {
             Ok(_) => {
@@ -52,7 +61,7 @@ pub fn FolderManager() -> Element {
     };
 
     let handle_delete_folder = move |id: String| async move {
-        match auth.call(delete_folder(id)).await {
+        match auth.call(|| delete_folder(id.clone())).await {
             Ok(_) => {
                 success_msg.set("Folder deleted successfully".to_string());
                 fetch_folders().await;
@@ -61,9 +70,63 @@ pub fn FolderManager() -> Element {
         }
     };
 
+    let handle_toggle_lyrics = move |(id, enabled): (String, bool)| async move {
+        match auth.call(|| set_folder_lyrics(id.clone(), enabled)).await {
+            Ok(_) => {
+                let state = if enabled { "enabled" } else { "disabled" };
+                success_msg.set(format!("Lyrics {state} for folder"));
+                fetch_folders().await;
+            }
+            Err(e) => error.set(format!("Failed to update lyrics setting: {e}")),
+        }
+    };
+
+    let fetch_shares = move |id: String| async move {
+        match auth.call(|| get_folder_shares(id.clone())).await {
+            Ok(fetched_shares) => shares.set(fetched_shares),
+            Err(e) => error.set(format!("Failed to fetch shares: {e}")),
+        }
+    };
+
+    let handle_share_folder = move |id: String| async move {
+        error.set("".to_string());
+        success_msg.set("".to_string());
+
+        if share_username().trim().is_empty() {
+            error.set("Username is required".to_string());
+            return;
+        }
+
+        match auth
+            .call(|| share_folder(id.clone(), share_username(), share_can_write()))
+            .await
+        {
+            Ok(_) => {
+                success_msg.set(format!("Folder shared with '{}'", share_username()));
+                share_username.set("".to_string());
+                share_can_write.set(false);
+                fetch_shares(id).await;
+            }
+            Err(e) => error.set(format!("Failed to share folder: {e}")),
+        }
+    };
+
+    let handle_unshare_folder = move |(id, user_id): (String, String)| async move {
+        match auth
+            .call(|| unshare_folder(id.clone(), user_id.clone()))
+            .await
+        {
+            Ok(_) => {
+                success_msg.set("Share removed".to_string());
+                fetch_shares(id).await;
+            }
+            Err(e) => error.set(format!("Failed to remove share: {e}")),
+        }
+    };
+
     let handle_update_folder = move |id: String| async move {
         match auth
-            .call(update_folder(id, edit_folder_name(), edit_folder_path()))
+            .call(|| update_folder(id.clone(), edit_folder_name(), edit_folder_path()))
             .await
         {
             Ok(_) => {
@@ -137,6 +200,22 @@ pub fn FolderManager() -> Element {
                                 let id_edit = folder.id.clone();
                                 let id_delete = folder.id.clone();
                                 let id_update = folder.id.clone();
+                                let id_lyrics = folder.id.clone();
+                                let id_shares = folder.id.clone();
+                                let id_share = folder.id.clone();
+                                let is_owner = folder.access.is_owner();
+                                let is_sharing = sharing_folder_id() == Some(folder.id.clone());
+                                let access_label = match folder.access {
+                                    FolderAccess::Owner => None,
+                                    FolderAccess::Write => Some("shared // read-write"),
+                                    FolderAccess::Read => Some("shared // read-only"),
+                                };
+                                let lyrics_enabled = folder.lyrics_enabled;
+                                let lyrics_class = if lyrics_enabled {
+                                    "text-beet-leaf"
+                                } else {
+                                    "text-gray-500"
+                                };
                                 rsx! {
                                     li { class: "bg-white/5 border border-white/5 p-3 rounded hover:border-beet-accent/30 transition-colors",
                                         if editing_folder_id() == Some(folder.id.clone()) {
@@ -171,21 +250,104 @@ pub fn FolderManager() -> Element {
                                                 div {
                                                     span { class: "font-bold text-white block font-display", "{folder.name}" }
                                                     span { class: "text-gray-500 text-xs font-mono", "{folder.path}" }
+                                                    if let Some(label) = access_label {
+                                                        span { class: "ml-2 text-beet-leaf text-xs font-mono", "[{label}]" }
+                                                    }
                                                 }
-                                                div { class: "flex gap-3",
-                                                    button {
-                                                        class: "text-xs font-mono text-gray-400 hover:text-beet-accent transition-colors underline decoration-dotted",
-                                                        onclick: move |_| {
-                                                            edit_folder_name.set(folder.name.clone());
-                                                            edit_folder_path.set(folder.path.clone());
-                                                            editing_folder_id.set(Some(id_edit.clone()));
-                                                        },
-                                                        "Edit"
+                                                if is_owner {
+                                                    div { class: "flex gap-3",
+                                                        button {
+                                                            class: "text-xs font-mono {lyrics_class} hover:text-white transition-colors underline decoration-dotted",
+                                                            title: "Fetch lyrics for tracks imported into this folder",
+                                                            onclick: move |_| handle_toggle_lyrics((id_lyrics.clone(), !lyrics_enabled)),
+                                                            if lyrics_enabled {
+                                                                "Lyrics: on"
+                                                            } else {
+                                                                "Lyrics: off"
+                                                            }
+                                                        }
+                                                        button {
+                                                            class: "text-xs font-mono text-gray-400 hover:text-beet-accent transition-colors underline decoration-dotted",
+                                                            onclick: move |_| {
+                                                                edit_folder_name.set(folder.name.clone());
+                                                                edit_folder_path.set(folder.path.clone());
+                                                                editing_folder_id.set(Some(id_edit.clone()));
+                                                            },
+                                                            "Edit"
+                                                        }
+                                                        button {
+                                                            class: "text-xs font-mono text-gray-400 hover:text-beet-accent transition-colors underline decoration-dotted",
+                                                            onclick: move |_| {
+                                                                let id = id_shares.clone();
+                                                                async move {
+                                                                    if sharing_folder_id() == Some(id.clone()) {
+                                                                        sharing_folder_id.set(None);
+                                                                    } else {
+                                                                        shares.set(Vec::new());
+                                                                        sharing_folder_id.set(Some(id.clone()));
+                                                                        fetch_shares(id).await;
+                                                                    }
+                                                                }
+                                                            },
+                                                            "Share"
+                                                        }
+                                                        button {
+                                                            class: "text-xs font-mono text-gray-400 hover:text-red-400 transition-colors underline decoration-dotted",
+                                                            onclick: move |_| handle_delete_folder(id_delete.clone()),
+                                                            "Delete"
+                                                        }
                                                     }
-                                                    button {
-                                                        class: "text-xs font-mono text-gray-400 hover:text-red-400 transition-colors underline decoration-dotted",
-                                                        onclick: move |_| handle_delete_folder(id_delete.clone()),
-                                                        "Delete"
+                                                }
+                                            }
+                                            if is_sharing {
+                                                div { class: "mt-3 pt-3 border-t border-white/10 space-y-2",
+                                                    for share in shares.read().clone() {
+                                                        {
+                                                            let folder_id = share.folder_id.clone();
+                                                            let user_id = share.user_id.clone();
+                                                            rsx! {
+                                                                div {
+                                                                    key: "{share.user_id}",
+                                                                    class: "flex justify-between items-center text-xs font-mono",
+                                                                    span { class: "text-white",
+                                                                        "{share.username} "
+                                                                        span { class: "text-gray-500",
+                                                                            if share.can_write {
+                                                                                "read-write"
+                                                                            } else {
+                                                                                "read-only"
+                                                                            }
+                                                                        }
+                                                                    }
+                                                                    button {
+                                                                        class: "text-gray-400 hover:text-red-400 transition-colors underline decoration-dotted",
+                                                                        onclick: move |_| handle_unshare_folder((folder_id.clone(), user_id.clone())),
+                                                                        "Remove"
+                                                                    }
+                                                                }
+                                                            }
+                                                        }
+                                                    }
+                                                    div { class: "flex gap-2 items-center",
+                                                        input {
+                                                            class: "flex-1 p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent text-white font-mono text-sm",
+                                                            value: "{share_username}",
+                                                            oninput: move |e| share_username.set(e.value()),
+                                                            placeholder: "Username",
+                                                        }
+                                                        label { class: "flex items-center gap-1 text-xs font-mono text-gray-400",
+                                                            input {
+                                                                "type": "checkbox",
+                                                                checked: share_can_write(),
+                                                                onchange: move |e| share_can_write.set(e.checked()),
+                                                            }
+                                                            "Can download"
+                                                        }
+                                                        button {
+                                                            class: "text-xs uppercase tracking-wider font-bold text-beet-leaf hover:text-white transition-colors",
+                                                            onclick: move |_| handle_share_folder(id_share.clone()),
+                                                            "[ Share ]"
+                                                        }
                                                     }
                                                 }
                                             }
@@ -0,0 +1,136 @@
+use api::auth::Role;
+use api::{create_invite, delete_invite, get_invites};
+use dioxus::prelude::*;
+
+use crate::auth::use_auth;
+
+const INPUT_CLASS: &str = "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono";
+const LABEL_CLASS: &str = "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider";
+
+/// Validity choices offered when generating an invite, in hours.
+const VALIDITY_OPTIONS: [(i64, &str); 4] = [
+    (24, "1 day"),
+    (24 * 3, "3 days"),
+    (24 * 7, "1 week"),
+    (24 * 30, "30 days"),
+];
+
+#[component]
+pub fn InviteManager() -> Element {
+    let mut invites = use_signal(Vec::new);
+    let mut role = use_signal(Role::default);
+    let mut valid_hours = use_signal(|| 24 * 7);
+    let mut error = use_signal(String::new);
+    let mut success_msg = use_signal(String::new);
+    let auth = use_auth();
+
+    let fetch_invites = move || async move {
+        match auth.call(get_invites).await {
+            Ok(fetched) => invites.set(fetched),
+            Err(e) => error.set(format!("Failed to fetch invites: {e}")),
+        }
+    };
+
+    use_future(move || async move {
+        fetch_invites().await;
+    });
+
+    let handle_create = move |_| async move {
+        error.set(String::new());
+        success_msg.set(String::new());
+        match auth.call(|| create_invite(role(), valid_hours())).await {
+            Ok(invite) => {
+                success_msg.set(format!("Invite code created: {}", invite.token));
+                fetch_invites().await;
+            }
+            Err(e) => error.set(format!("Failed to create invite: {e}")),
+        }
+    };
+
+    let handle_delete = move |token: String| async move {
+        match auth.call(|| delete_invite(token.clone())).await {
+            Ok(_) => fetch_invites().await,
+            Err(e) => error.set(format!("Failed to delete invite: {e}")),
+        }
+    };
+
+    rsx! {
+        div { class: "bg-beet-panel border border-white/10 p-6 rounded-lg shadow-2xl relative z-10",
+            h2 { class: "text-xl font-bold mb-2 text-beet-accent font-display", "Invites" }
+            p { class: "text-xs text-gray-500 font-mono mb-4",
+                "Single-use codes for the sign up form. Only used when registration is set to invite in Config."
+            }
+
+            if !error().is_empty() {
+                div { class: "mb-4 p-4 bg-red-900/20 border border-red-500/50 rounded text-red-400 font-mono text-sm",
+                    "{error}"
+                }
+            }
+            if !success_msg().is_empty() {
+                div { class: "mb-4 p-4 bg-green-900/20 border border-green-500/50 rounded text-green-400 font-mono text-sm break-all",
+                    "{success_msg}"
+                }
+            }
+
+            div { class: "grid grid-cols-1 md:grid-cols-2 gap-4 mb-4",
+                div {
+                    label { class: LABEL_CLASS, "Role" }
+                    select {
+                        class: INPUT_CLASS,
+                        value: "{role}",
+                        onchange: move |e| role.set(e.value().parse().unwrap_or_default()),
+                        for r in Role::all() {
+                            option { value: "{r}", "{r}" }
+                        }
+                    }
+                }
+                div {
+                    label { class: LABEL_CLASS, "Valid For" }
+                    select {
+                        class: INPUT_CLASS,
+                        value: "{valid_hours}",
+                        onchange: move |e| valid_hours.set(e.value().parse().unwrap_or(24 * 7)),
+                        for (hours, label) in VALIDITY_OPTIONS {
+                            option { value: "{hours}", "{label}" }
+                        }
+                    }
+                }
+            }
+            button { class: "retro-btn mb-6 rounded", onclick: handle_create, "Create Invite" }
+
+            if invites.read().is_empty() {
+                p { class: "text-gray-500 font-mono italic", "No invites yet." }
+            } else {
+                ul { class: "space-y-2",
+                    for invite in invites.read().clone() {
+                        {
+                            let token = invite.token.clone();
+                            let state = if invite.is_used() {
+                                "used".to_string()
+                            } else if invite.is_expired() {
+                                "expired".to_string()
+                            } else {
+                                format!("expires {}", invite.expires_label())
+                            };
+                            rsx! {
+                                li {
+                                    key: "{invite.token}",
+                                    class: "flex justify-between items-center gap-3 bg-white/5 border border-white/5 p-3 rounded",
+                                    div { class: "min-w-0",
+                                        span { class: "text-white font-mono text-sm block truncate", "{invite.token}" }
+                                        span { class: "text-gray-500 text-xs font-mono", "{invite.role} // {state}" }
+                                    }
+                                    button {
+                                        class: "text-xs font-mono text-gray-400 hover:text-red-400 transition-colors underline decoration-dotted",
+                                        onclick: move |_| handle_delete(token.clone()),
+                                        "Delete"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,9 +1,23 @@
+mod api_tokens;
 mod app_config;
+mod audit_log;
+mod download_limits;
+mod download_usage;
 mod folder_manager;
+mod invite_manager;
 mod preferences;
+mod sessions;
+mod slskd_admin;
 mod user_manager;
 
+pub use api_tokens::ApiTokenManager;
 pub use app_config::AppConfigManager;
+pub use audit_log::AuditLogViewer;
+pub use download_limits::DownloadLimitsManager;
+pub use download_usage::DownloadUsageViewer;
 pub use folder_manager::FolderManager;
+pub use invite_manager::InviteManager;
 pub use preferences::PreferencesManager;
+pub use sessions::SessionManager;
+pub use slskd_admin::SlskdAdminPanel;
 pub use user_manager::UserManager;
@@ -47,6 +47,7 @@ pub fn PreferencesManager() -> Element {
         let update = api::UpdateUserSettings {
             default_metadata_provider: Some(selected_provider()),
             last_search_type: None,
+            lastfm_username: None,
         };
 
         match settings.update(update).await {
@@ -0,0 +1,87 @@
+use api::{get_sessions, logout_everywhere, revoke_session};
+use dioxus::prelude::*;
+
+use crate::auth::use_auth;
+
+/// Devices the user is logged in on, with a way to sign them out.
+#[component]
+pub fn SessionManager() -> Element {
+    let mut sessions = use_signal(Vec::new);
+    let mut error = use_signal(String::new);
+    let mut auth = use_auth();
+
+    let fetch_sessions = move || async move {
+        match auth.call(get_sessions).await {
+            Ok(fetched) => sessions.set(fetched),
+            Err(e) => error.set(format!("Failed to fetch sessions: {e}")),
+        }
+    };
+
+    use_future(move || async move {
+        fetch_sessions().await;
+    });
+
+    let handle_revoke = move |id: String| async move {
+        match auth.call(|| revoke_session(id.clone())).await {
+            Ok(_) => fetch_sessions().await,
+            Err(e) => error.set(format!("Failed to sign out session: {e}")),
+        }
+    };
+
+    let handle_logout_everywhere = move |_| async move {
+        match auth.call(logout_everywhere).await {
+            // Our own session is gone too
+            Ok(_) => auth.logout().await,
+            Err(e) => error.set(format!("Failed to sign out: {e}")),
+        }
+    };
+
+    rsx! {
+        div { class: "bg-beet-panel border border-white/10 p-6 rounded-lg shadow-2xl relative z-10",
+            h2 { class: "text-xl font-bold mb-2 text-beet-accent font-display", "Sessions" }
+            p { class: "text-xs text-gray-500 font-mono mb-4",
+                "Devices logged in to your account. Changing your password signs out all of them."
+            }
+
+            if !error().is_empty() {
+                div { class: "mb-4 p-4 bg-red-900/20 border border-red-500/50 rounded text-red-400 font-mono text-sm",
+                    "{error}"
+                }
+            }
+
+            ul { class: "space-y-2 mb-6",
+                for session in sessions.read().clone() {
+                    {
+                        let id = session.id.clone();
+                        let device = session.device_label();
+                        let last_seen = session.last_seen_label();
+                        let ip = session.ip.clone().unwrap_or_else(|| "unknown address".to_string());
+                        rsx! {
+                            li {
+                                key: "{session.id}",
+                                class: "flex justify-between items-center gap-3 bg-white/5 border border-white/5 p-3 rounded",
+                                div { class: "min-w-0",
+                                    span { class: "text-white font-display block truncate",
+                                        "{device}"
+                                        if session.current {
+                                            span { class: "ml-2 text-xs font-mono text-beet-leaf", "this device" }
+                                        }
+                                    }
+                                    span { class: "text-gray-500 text-xs font-mono", "{ip} // last seen {last_seen}" }
+                                }
+                                if !session.current {
+                                    button {
+                                        class: "text-xs font-mono text-gray-400 hover:text-red-400 transition-colors underline decoration-dotted",
+                                        onclick: move |_| handle_revoke(id.clone()),
+                                        "Sign out"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            button { class: "retro-btn rounded", onclick: handle_logout_everywhere, "Log Out Everywhere" }
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use api::{clear_slskd_downloads, get_slskd_overview, rescan_slskd_shares};
+use dioxus::prelude::*;
+use shared::download::format_size;
+use shared::slskd::TransferCounts;
+use shared::system::SlskdOverview;
+
+use crate::auth::use_auth;
+
+#[component]
+fn TransferRow(label: &'static str, counts: TransferCounts) -> Element {
+    let failed_class = if counts.failed > 0 {
+        "text-red-400"
+    } else {
+        "text-white"
+    };
+    let speed = format_size(counts.speed as u64);
+
+    rsx! {
+        div { class: "flex flex-wrap justify-between gap-x-3 text-xs font-mono",
+            span { class: "text-gray-400 uppercase tracking-wider", "{label}" }
+            span { class: "text-white",
+                "{counts.queued} queued, {counts.in_progress} running, {counts.succeeded} done, "
+                span { class: failed_class, "{counts.failed} failed" }
+                if counts.in_progress > 0 {
+                    span { class: "text-gray-500", " ({speed}/s)" }
+                }
+            }
+        }
+    }
+}
+
+/// Connection, shares and transfers of the slskd instances, without leaving soulbeet.
+#[component]
+pub fn SlskdAdminPanel() -> Element {
+    let mut overviews = use_signal(Vec::<SlskdOverview>::new);
+    let mut loaded = use_signal(|| false);
+    let mut error = use_signal(String::new);
+    let mut success_msg = use_signal(String::new);
+    let auth = use_auth();
+
+    let fetch_overview = move || async move {
+        match auth.call(get_slskd_overview).await {
+            Ok(fetched) => overviews.set(fetched),
+            Err(e) => error.set(format!("Failed to fetch slskd state: {e}")),
+        }
+        loaded.set(true);
+    };
+
+    use_future(move || async move {
+        fetch_overview().await;
+    });
+
+    let handle_rescan = move |name: String| async move {
+        error.set(String::new());
+        success_msg.set(String::new());
+        match auth.call(|| rescan_slskd_shares(name.clone())).await {
+            Ok(()) => {
+                success_msg.set(format!("Share scan started on {name}"));
+                fetch_overview().await;
+            }
+            Err(e) => error.set(format!("Failed to rescan shares: {e}")),
+        }
+    };
+
+    let handle_clear = move |name: String| async move {
+        error.set(String::new());
+        success_msg.set(String::new());
+        match auth.call(|| clear_slskd_downloads(name.clone())).await {
+            Ok(()) => {
+                success_msg.set(format!("Cleared finished downloads on {name}"));
+                fetch_overview().await;
+            }
+            Err(e) => error.set(format!("Failed to clear downloads: {e}")),
+        }
+    };
+
+    rsx! {
+        div { class: "bg-beet-panel border border-white/10 p-6 rounded-lg shadow-2xl relative z-10",
+            div { class: "flex justify-between items-center mb-4",
+                h2 { class: "text-xl font-bold text-beet-accent font-display", "slskd" }
+                button {
+                    class: "text-xs font-mono text-gray-400 hover:text-beet-accent transition-colors underline decoration-dotted",
+                    onclick: move |_| async move {
+                        error.set(String::new());
+                        success_msg.set(String::new());
+                        fetch_overview().await;
+                    },
+                    "Refresh"
+                }
+            }
+
+            if !error().is_empty() {
+                div { class: "mb-4 p-4 bg-red-900/20 border border-red-500/50 rounded text-red-400 font-mono text-sm",
+                    "{error}"
+                }
+            }
+            if !success_msg().is_empty() {
+                div { class: "mb-4 p-4 bg-green-900/20 border border-green-500/50 rounded text-green-400 font-mono text-sm",
+                    "{success_msg}"
+                }
+            }
+
+            if !loaded() {
+                div { class: "animate-pulse text-gray-400 font-mono", "Loading..." }
+            } else if overviews.read().is_empty() {
+                p { class: "text-gray-500 font-mono italic", "No slskd instance configured." }
+            } else {
+                div { class: "space-y-4",
+                    for overview in overviews.read().clone() {
+                        {
+                            let name = overview.instance.clone();
+                            let rescan_name = name.clone();
+                            let clear_name = name.clone();
+                            let (status, status_class) = if overview.error.is_some() {
+                                ("UNREACHABLE", "text-red-400")
+                            } else if overview.server.is_logged_in {
+                                ("LOGGED IN", "text-beet-leaf")
+                            } else if overview.server.is_connected {
+                                ("CONNECTED", "text-yellow-400")
+                            } else {
+                                ("DISCONNECTED", "text-red-400")
+                            };
+                            let shares = &overview.shares;
+                            let scan_label = if shares.scanning {
+                                format!("scanning, {:.0}%", shares.scan_progress * 100.0)
+                            } else if shares.faulted {
+                                "last scan failed".to_string()
+                            } else {
+                                String::new()
+                            };
+                            let scanning = shares.scanning || shares.scan_pending;
+                            rsx! {
+                                div {
+                                    key: "{name}",
+                                    class: "bg-white/5 border border-white/5 rounded p-4 space-y-3",
+                                    div { class: "flex justify-between items-center",
+                                        h3 { class: "text-sm font-semibold text-white", "{name}" }
+                                        span { class: "text-xs font-mono {status_class}", "{status}" }
+                                    }
+                                    if let Some(e) = overview.error.clone() {
+                                        p { class: "text-xs font-mono text-red-400 break-all", "{e}" }
+                                    } else {
+                                        p { class: "text-xs font-mono text-gray-400",
+                                            "{overview.server.state}"
+                                            if let Some(username) = overview.server.username.clone() {
+                                                " as "
+                                                span { class: "text-white", "{username}" }
+                                            }
+                                            if let Some(address) = overview.server.address.clone() {
+                                                span { class: "text-gray-500", " on {address}" }
+                                            }
+                                        }
+                                        div { class: "flex flex-wrap justify-between items-center gap-x-3 text-xs font-mono",
+                                            span { class: "text-gray-400 uppercase tracking-wider", "Shares" }
+                                            span { class: "text-white",
+                                                "{shares.directories} folders, {shares.files} files"
+                                                if !scan_label.is_empty() {
+                                                    span { class: "text-yellow-400", " ({scan_label})" }
+                                                }
+                                            }
+                                        }
+                                        TransferRow { label: "Downloads", counts: overview.downloads.clone() }
+                                        TransferRow { label: "Uploads", counts: overview.uploads.clone() }
+                                        if !overview.active_uploads.is_empty() {
+                                            ul { class: "space-y-1 font-mono text-xs",
+                                                for upload in overview.active_uploads.clone() {
+                                                    {
+                                                        let file = upload
+                                                            .filename
+                                                            .rsplit(['\\', '/'])
+                                                            .next()
+                                                            .unwrap_or_default()
+                                                            .to_string();
+                                                        let speed = format_size(upload.average_speed as u64);
+                                                        rsx! {
+                                                            li {
+                                                                key: "{upload.id}",
+                                                                class: "flex flex-wrap gap-x-3 bg-white/5 px-3 py-1 rounded",
+                                                                span { class: "text-white", "{upload.username}" }
+                                                                span { class: "text-gray-300 break-all", "{file}" }
+                                                                span { class: "text-gray-500", "{upload.percent_complete:.0}%, {speed}/s" }
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        div { class: "flex gap-4",
+                                            button {
+                                                class: "text-xs font-mono text-gray-400 hover:text-beet-accent transition-colors underline decoration-dotted",
+                                                disabled: scanning,
+                                                onclick: move |_| handle_rescan(rescan_name.clone()),
+                                                "Rescan Shares"
+                                            }
+                                            button {
+                                                class: "text-xs font-mono text-gray-400 hover:text-red-400 transition-colors underline decoration-dotted",
+                                                onclick: move |_| handle_clear(clear_name.clone()),
+                                                "Clear Finished Downloads"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use api::{delete_user, get_users, register, update_user_password};
+use api::auth::Role;
+use api::{create_user, delete_user, get_users, update_user_password, update_user_role};
 use dioxus::prelude::*;
 
 use crate::auth::use_auth;
@@ -7,6 +8,7 @@ use crate::auth::use_auth;
 pub fn UserManager() -> Element {
     let mut new_username = use_signal(|| "".to_string());
     let mut new_password = use_signal(|| "".to_string());
+    let mut new_role = use_signal(Role::default);
     let mut users = use_signal(Vec::new);
 
     let mut editing_user_id = use_signal(|| None::<String>);
@@ -17,7 +19,7 @@ pub fn UserManager() -> Element {
     let auth = use_auth();
 
     let fetch_users = move || async move {
-        match auth.call(get_users()).await {
+        match auth.call(get_users).await {
             Ok(fetched_users) => users.set(fetched_users),
             Err(e) => error.set(format!("Failed to fetch users: {e}")),
         }
@@ -36,11 +38,15 @@ pub fn UserManager() -> Element {
             return;
         }
 
-        match auth.call(register(new_username(), new_password())).await {
+        match auth
+            .call(|| create_user(new_username(), new_password(), new_role()))
+            .await
+        {
             Ok(_) => {
                 success_msg.set(format!("User '{}' created successfully", new_username()));
                 new_username.set("".to_string());
                 new_password.set("".to_string());
+                new_role.set(Role::default());
                 fetch_users().await;
             }
             Err(e) => error.set(format!("Failed to create user: {e}")),
@@ -48,7 +54,7 @@ pub fn UserManager() -> Element {
     };
 
     let handle_delete_user = move |id: String| async move {
-        match auth.call(delete_user(id)).await {
+        match auth.call(|| delete_user(id.clone())).await {
             Ok(_) => {
                 success_msg.set("User deleted successfully".to_string());
                 fetch_users().await;
@@ -57,13 +63,23 @@ pub fn UserManager() -> Element {
         }
     };
 
+    let handle_update_role = move |id: String, role: Role| async move {
+        error.set("".to_string());
+        success_msg.set("".to_string());
+        match auth.call(|| update_user_role(id.clone(), role)).await {
+            Ok(_) => success_msg.set("Role updated successfully".to_string()),
+            Err(e) => error.set(format!("Failed to update role: {e}")),
+        }
+        fetch_users().await;
+    };
+
     let handle_update_user = move |id: String| async move {
         if edit_user_password().is_empty() {
             error.set("Password cannot be empty".to_string());
             return;
         }
         match auth
-            .call(update_user_password(id, edit_user_password()))
+            .call(|| update_user_password(id.clone(), edit_user_password()))
             .await
         {
             Ok(_) => {
@@ -95,7 +111,7 @@ pub fn UserManager() -> Element {
         }
 
         // Create User
-        div { class: "grid grid-cols-1 md:grid-cols-2 gap-4 mb-4",
+        div { class: "grid grid-cols-1 md:grid-cols-3 gap-4 mb-4",
           div {
             label { class: "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider",
               "New Username"
@@ -120,6 +136,19 @@ pub fn UserManager() -> Element {
               "type": "password",
             }
           }
+          div {
+            label { class: "block text-xs font-mono text-gray-400 mb-1 uppercase tracking-wider",
+              "Role"
+            }
+            select {
+              class: "w-full p-2 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-white font-mono",
+              value: "{new_role}",
+              onchange: move |e| new_role.set(e.value().parse().unwrap_or_default()),
+              for role in Role::all() {
+                option { value: "{role}", "{role}" }
+              }
+            }
+          }
         }
         button {
           class: "retro-btn mb-6 rounded",
@@ -144,6 +173,7 @@ pub fn UserManager() -> Element {
                         let id_update = user.id.clone();
                         let id_edit = user.id.clone();
                         let id_delete = user.id.clone();
+                        let id_role = user.id.clone();
                         rsx! {
                           li { class: "bg-white/5 border border-white/5 p-3 rounded hover:border-beet-accent/30 transition-colors",
                             if editing_user_id() == Some(user.id.clone()) {
@@ -171,8 +201,25 @@ pub fn UserManager() -> Element {
                               }
                             } else {
                               div { class: "flex justify-between items-center",
-                                span { class: "font-bold text-white font-display", "{user.username}" }
-                                div { class: "flex gap-3",
+                                span { class: "font-bold text-white font-display",
+                                  "{user.username}"
+                                  if user.external_id.is_some() {
+                                    span { class: "ml-2 text-xs font-mono text-beet-leaf", "sso" }
+                                  }
+                                }
+                                div { class: "flex gap-3 items-center",
+                                  select {
+                                    class: "p-1 rounded bg-beet-dark border border-white/10 focus:border-beet-accent focus:outline-none text-xs text-white font-mono",
+                                    value: "{user.role}",
+                                    onchange: move |e| {
+                                        if let Ok(role) = e.value().parse() {
+                                            spawn(handle_update_role(id_role.clone(), role));
+                                        }
+                                    },
+                                    for role in Role::all() {
+                                      option { value: "{role}", selected: role == user.role, "{role}" }
+                                    }
+                                  }
                                   button {
                                     class: "text-xs font-mono text-gray-400 hover:text-beet-accent transition-colors underline decoration-dotted",
                                     onclick: move |_| {
@@ -18,6 +18,19 @@ pub fn SystemStatus(health: SystemHealth) -> Element {
             "DOWNLOADER OFFLINE"
           }
         }
+        if health.download_instances.len() > 1 {
+          for instance in health.download_instances.iter() {
+            span { key: "{instance.name}", class: "flex items-center gap-2",
+              span {
+                class: format!(
+                    "w-2 h-2 rounded-full {}",
+                    if instance.online { "bg-beet-leaf" } else { "bg-red-500" },
+                ),
+              }
+              "{instance.name.to_uppercase()}"
+            }
+          }
+        }
         span { class: "flex items-center gap-2",
           span {
             class: format!(
@@ -81,6 +81,14 @@ impl Settings {
             .unwrap_or_default()
     }
 
+    /// Get the Last.fm username used for discovery, if set.
+    pub fn lastfm_username(&self) -> Option<String> {
+        self.state
+            .read()
+            .as_ref()
+            .and_then(|s| s.lastfm_username.clone())
+    }
+
     /// Get the list of available metadata providers.
     pub fn providers(&self) -> Vec<ProviderInfo> {
         self.providers.read().clone()
@@ -103,6 +111,7 @@ impl Settings {
         let update = UpdateUserSettings {
             default_metadata_provider: None,
             last_search_type: Some(search_type.as_str().to_string()),
+            lastfm_username: None,
         };
         self.update(update).await?;
         Ok(())
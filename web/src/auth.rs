@@ -2,14 +2,20 @@ use api::auth::AuthResponse;
 use dioxus::prelude::*;
 use ui::Auth;
 
+/// Access tokens last 15 minutes, renew them well before that.
+const REFRESH_INTERVAL_MS: u32 = 10 * 60 * 1000;
+
 pub fn use_auth() -> Auth {
     use_context::<Auth>()
 }
 
 #[component]
 pub fn AuthProvider(children: Element) -> Element {
-    let auth_state =
-        use_resource(move || async move { api::get_current_user().await.ok().flatten() });
+    // The access token is short-lived, renew it before asking who is logged in
+    let auth_state = use_resource(move || async move {
+        let _ = api::refresh_token().await;
+        api::get_current_user().await.ok().flatten()
+    });
 
     let mut auth_signal = use_signal(|| None::<AuthResponse>);
     let mut initialized = use_signal(|| false);
@@ -22,6 +28,16 @@ pub fn AuthProvider(children: Element) -> Element {
 
     use_context_provider(|| Auth::new(auth_signal));
 
+    // Keep the access token fresh while the app is open
+    use_future(move || async move {
+        loop {
+            gloo_timers::future::TimeoutFuture::new(REFRESH_INTERVAL_MS).await;
+            if auth_signal.read().is_some() && api::refresh_token().await.is_err() {
+                auth_signal.set(None);
+            }
+        }
+    });
+
     if !*initialized.read() {
         return rsx! {
             div { class: "flex flex-col items-center justify-center h-screen",
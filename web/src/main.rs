@@ -2,14 +2,16 @@ use auth::{use_auth, AuthProvider};
 use dioxus::prelude::*;
 use shared::download::DownloadProgress;
 use std::collections::HashMap;
+#[cfg(feature = "server")]
+use std::net::SocketAddr;
 
 #[cfg(feature = "web")]
 use dioxus::fullstack::WebSocketOptions;
 #[cfg(feature = "web")]
 use websocket::use_resilient_websocket;
 
-use ui::{Downloads, Layout, Navbar, SearchReset, SettingsProvider};
-use views::{LoginPage, SearchPage, SettingsPage};
+use ui::{ChangePassword, Downloads, Layout, Navbar, SearchReset, SettingsProvider};
+use views::{DiscoverPage, ImportPage, LoginPage, SearchPage, SettingsPage};
 
 mod auth;
 mod views;
@@ -26,6 +28,10 @@ pub enum Route {
         #[layout(WebNavbar)]
             #[route("/")]
             SearchPage {},
+            #[route("/discover")]
+            DiscoverPage {},
+            #[route("/import")]
+            ImportPage {},
             #[route("/settings")]
             SettingsPage {},
 }
@@ -38,11 +44,85 @@ fn main() {
     {
         use tower_cookies::CookieManagerLayer;
 
-        dioxus::serve(|| async move {
+        // One-shot copy of a SQLite database into PostgreSQL, then exit
+        let mut args = std::env::args().skip(1);
+        if args.next().as_deref() == Some("--import-sqlite") {
+            let Some(path) = args.next() else {
+                eprintln!("Usage: --import-sqlite <path to soulbeet.db>");
+                std::process::exit(2);
+            };
+            match api::sqlite_import::run(&path) {
+                Ok(copied) => {
+                    for (table, rows) in copied {
+                        println!("{}: {} rows", table, rows);
+                    }
+                    println!("Import finished");
+                }
+                Err(e) => {
+                    eprintln!("Import failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+
+        let runtime = tokio::runtime::Runtime::new().expect("Failed to start the async runtime");
+        runtime.block_on(async move {
+            dioxus::logger::initialize_default();
+
             // Start background cleanup task for user channels
             api::globals::start_channel_cleanup_task();
 
-            Ok(dioxus::server::router(App).layer(CookieManagerLayer::new()))
+            // Pause or limit downloads in the windows of the download schedule
+            api::server_fns::download::schedule::start_schedule_task();
+
+            // Keep the cover cache under COVER_CACHE_MAX_AGE_DAYS and COVER_CACHE_MAX_SIZE_MB
+            api::covers::start_cover_cache_task();
+
+            // Encrypt stored API keys with the current CONFIG_ENCRYPTION_KEY
+            if let Err(e) = api::models::app_config::AppConfig::reseal_secrets().await {
+                dioxus::logger::tracing::error!("Failed to update stored secrets: {}", e);
+            }
+
+            let router = dioxus::server::router(App)
+                .route(
+                    api::covers::COVER_ROUTE,
+                    axum::routing::get(api::covers::cover_handler),
+                )
+                .route(
+                    api::sso::OIDC_LOGIN_ROUTE,
+                    axum::routing::get(api::sso::oidc_login_handler),
+                )
+                .route(
+                    api::sso::OIDC_CALLBACK_ROUTE,
+                    axum::routing::get(api::sso::oidc_callback_handler),
+                )
+                .layer(CookieManagerLayer::new());
+
+            let config = &api::config::CONFIG;
+            let address = match config.ip.parse::<std::net::IpAddr>() {
+                Ok(ip) => SocketAddr::new(ip, config.port),
+                Err(e) => {
+                    eprintln!("Invalid IP {}: {}", config.ip, e);
+                    std::process::exit(1);
+                }
+            };
+            let listener = match tokio::net::TcpListener::bind(address).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    eprintln!("Failed to listen on {}: {}", address, e);
+                    std::process::exit(1);
+                }
+            };
+            dioxus::logger::tracing::info!("Listening on http://{}", address);
+
+            // Handlers see the peer address: forwarding headers and header login are
+            // only trusted from AUTH_PROXY_TRUSTED_IPS
+            let service = router.into_make_service_with_connect_info::<SocketAddr>();
+            if let Err(e) = axum::serve(listener, service).await {
+                eprintln!("Server error: {}", e);
+                std::process::exit(1);
+            }
         });
     }
 
@@ -86,6 +166,12 @@ fn AuthGuard() -> Element {
         }
     });
 
+    if auth.must_change_password() {
+        return rsx! {
+            ChangePassword {}
+        };
+    }
+
     rsx! {
         Outlet::<Route> {}
     }
@@ -140,6 +226,42 @@ fn WebNavbar() -> Element {
                         }
                     }
                 }
+                Link {
+                    class: "nav-link text-white font-medium border-b-2 border-transparent hover:border-beet-accent pb-0.5",
+                    active_class: "border-beet-accent",
+                    to: Route::DiscoverPage {},
+                    span { class: "hidden md:block", "Discover" }
+                    svg {
+                        class: "md:hidden w-6 h-6",
+                        fill: "none",
+                        stroke: "currentColor",
+                        view_box: "0 0 24 24",
+                        path {
+                            stroke_linecap: "round",
+                            stroke_linejoin: "round",
+                            stroke_width: "2",
+                            d: "M5 3v4M3 5h4M6 17v4m-2-2h4m5-16l2.286 6.857L21 12l-5.714 2.143L13 21l-2.286-6.857L5 12l5.714-2.143L13 3z",
+                        }
+                    }
+                }
+                Link {
+                    class: "nav-link text-white font-medium border-b-2 border-transparent hover:border-beet-accent pb-0.5",
+                    active_class: "border-beet-accent",
+                    to: Route::ImportPage {},
+                    span { class: "hidden md:block", "Import" }
+                    svg {
+                        class: "md:hidden w-6 h-6",
+                        fill: "none",
+                        stroke: "currentColor",
+                        view_box: "0 0 24 24",
+                        path {
+                            stroke_linecap: "round",
+                            stroke_linejoin: "round",
+                            stroke_width: "2",
+                            d: "M9 19V6l12-3v13M9 19c0 1.105-1.343 2-3 2s-3-.895-3-2 1.343-2 3-2 3 .895 3 2zm12-3c0 1.105-1.343 2-3 2s-3-.895-3-2 1.343-2 3-2 3 .895 3 2zM9 10l12-3",
+                        }
+                    }
+                }
                 Link {
                     class: "nav-link text-white font-medium border-b-2 border-transparent hover:border-beet-accent pb-0.5",
                     active_class: "border-beet-accent",
@@ -0,0 +1,20 @@
+use dioxus::prelude::*;
+use ui::Discover;
+
+#[component]
+pub fn DiscoverPage() -> Element {
+    rsx! {
+        div { class: "fixed top-1/4 -left-10 w-64 h-64 bg-beet-accent/10 rounded-full blur-[100px] pointer-events-none" }
+        div { class: "fixed bottom-1/4 -right-10 w-64 h-64 bg-beet-leaf/10 rounded-full blur-[100px] pointer-events-none" }
+
+        div { class: "space-y-6 text-white w-full max-w-3xl z-10 mx-auto",
+            div { class: "text-center mb-6",
+                h1 { class: "text-4xl font-bold text-beet-accent mb-2 font-display", "Discover" }
+                p { class: "text-gray-400 font-mono text-sm",
+                    "Your Last.fm listening // What your library is missing"
+                }
+            }
+            Discover {}
+        }
+    }
+}
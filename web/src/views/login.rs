@@ -1,7 +1,7 @@
 use std::future::Future;
 use std::pin::Pin;
 
-use api::login;
+use api::{get_login_options, get_registration_mode, login};
 use dioxus::prelude::*;
 use ui::Login;
 
@@ -23,13 +23,59 @@ pub fn LoginPage() -> Element {
                     navigator.push(Route::SearchPage {});
                     Ok(())
                 }
+                Err(ServerFnError::ServerError { code: 429, message, .. }) => Err(message),
                 _ => Err("Invalid username or password".to_string()),
             }
         })
     });
 
+    let registration =
+        use_resource(|| async move { get_registration_mode().await.unwrap_or_default() });
+    let options = use_resource(|| async move { get_login_options().await.unwrap_or_default() });
+
+    // A failed OIDC login comes back as /login?sso_error=...
+    let sso_error = use_signal(|| None::<String>);
+    use_effect(move || {
+        #[cfg(feature = "web")]
+        {
+            let mut sso_error = sso_error;
+            let search = web_sys::window()
+                .and_then(|w| w.location().search().ok())
+                .unwrap_or_default();
+            let error = url::form_urlencoded::parse(search.trim_start_matches('?').as_bytes())
+                .find(|(key, _)| key == "sso_error")
+                .map(|(_, value)| value.into_owned());
+            sso_error.set(error);
+        }
+    });
+
+    let register = use_callback(
+        move |(username, password, invite_code): (String, String, Option<String>)|
+            -> Pin<Box<dyn Future<Output = Result<(), String>>>>
+        {
+            Box::pin(async move {
+                api::register(username.clone(), password.clone(), invite_code)
+                    .await
+                    .map_err(|e| format!("Registration failed: {e}"))?;
+                match api::login(username, password).await {
+                    Ok(response) => {
+                        auth.login(response);
+                        navigator.push(Route::SearchPage {});
+                        Ok(())
+                    }
+                    Err(e) => Err(format!("Account created but login failed: {e}")),
+                }
+            })
+        },
+    );
 
     rsx! {
-        Login { login }
+        Login {
+            login,
+            register,
+            registration: registration().unwrap_or_default(),
+            options: options().unwrap_or_default(),
+            sso_error: sso_error(),
+        }
     }
 }
@@ -1,7 +1,11 @@
+mod discover;
+mod import;
 mod login;
 mod search;
 mod settings;
 
+pub use discover::DiscoverPage;
+pub use import::ImportPage;
 pub use login::LoginPage;
 pub use search::SearchPage;
 pub use settings::SettingsPage;
@@ -1,18 +1,28 @@
 use dioxus::prelude::*;
-use ui::settings::{AppConfigManager, FolderManager, PreferencesManager, UserManager};
+use ui::settings::{
+    ApiTokenManager, AppConfigManager, AuditLogViewer, DownloadLimitsManager, DownloadUsageViewer,
+    FolderManager, InviteManager, PreferencesManager, SessionManager, SlskdAdminPanel, UserManager,
+};
+use ui::use_auth;
 
 #[derive(PartialEq, Clone, Copy, Default)]
 enum SettingsTab {
     #[default]
     Search,
     Library,
+    Access,
     Users,
     Config,
+    Slskd,
+    Audit,
 }
 
 #[component]
 pub fn SettingsPage() -> Element {
     let mut active_tab = use_signal(SettingsTab::default);
+    let auth = use_auth();
+    // Admin-only sections are also rejected server side, this only hides them
+    let is_admin = auth.is_admin();
 
     rsx! {
         div { class: "fixed top-1/4 -left-10 w-64 h-64 bg-beet-accent/10 rounded-full blur-[100px] pointer-events-none" }
@@ -40,16 +50,36 @@ pub fn SettingsPage() -> Element {
                     onclick: move |_| active_tab.set(SettingsTab::Library),
                 }
                 TabButton {
-                    label: "Users",
-                    icon_path: "M12 4.354a4 4 0 110 5.292M15 21H3v-1a6 6 0 0112 0v1zm0 0h6v-1a6 6 0 00-9-5.197M13 7a4 4 0 11-8 0 4 4 0 018 0z",
-                    active: active_tab() == SettingsTab::Users,
-                    onclick: move |_| active_tab.set(SettingsTab::Users),
+                    label: "Access",
+                    icon_path: "M15 7a2 2 0 012 2m4 0a6 6 0 01-7.743 5.743L11 17H9v2H7v2H4a1 1 0 01-1-1v-2.586a1 1 0 01.293-.707l5.964-5.964A6 6 0 1121 9z",
+                    active: active_tab() == SettingsTab::Access,
+                    onclick: move |_| active_tab.set(SettingsTab::Access),
                 }
-                TabButton {
-                    label: "Config",
-                    icon_path: "M10.325 4.317c.426-1.756 2.924-1.756 3.35 0a1.724 1.724 0 002.573 1.066c1.543-.94 3.31.826 2.37 2.37a1.724 1.724 0 001.065 2.572c1.756.426 1.756 2.924 0 3.35a1.724 1.724 0 00-1.066 2.573c.94 1.543-.826 3.31-2.37 2.37a1.724 1.724 0 00-2.572 1.065c-.426 1.756-2.924 1.756-3.35 0a1.724 1.724 0 00-2.573-1.066c-1.543.94-3.31-.826-2.37-2.37a1.724 1.724 0 00-1.065-2.572c-1.756-.426-1.756-2.924 0-3.35a1.724 1.724 0 001.066-2.573c-.94-1.543.826-3.31 2.37-2.37.996.608 2.296.07 2.572-1.065z M15 12a3 3 0 11-6 0 3 3 0 016 0z",
-                    active: active_tab() == SettingsTab::Config,
-                    onclick: move |_| active_tab.set(SettingsTab::Config),
+                if is_admin {
+                    TabButton {
+                        label: "Users",
+                        icon_path: "M12 4.354a4 4 0 110 5.292M15 21H3v-1a6 6 0 0112 0v1zm0 0h6v-1a6 6 0 00-9-5.197M13 7a4 4 0 11-8 0 4 4 0 018 0z",
+                        active: active_tab() == SettingsTab::Users,
+                        onclick: move |_| active_tab.set(SettingsTab::Users),
+                    }
+                    TabButton {
+                        label: "Config",
+                        icon_path: "M10.325 4.317c.426-1.756 2.924-1.756 3.35 0a1.724 1.724 0 002.573 1.066c1.543-.94 3.31.826 2.37 2.37a1.724 1.724 0 001.065 2.572c1.756.426 1.756 2.924 0 3.35a1.724 1.724 0 00-1.066 2.573c.94 1.543-.826 3.31-2.37 2.37a1.724 1.724 0 00-2.572 1.065c-.426 1.756-2.924 1.756-3.35 0a1.724 1.724 0 00-2.573-1.066c-1.543.94-3.31-.826-2.37-2.37a1.724 1.724 0 00-1.065-2.572c-1.756-.426-1.756-2.924 0-3.35a1.724 1.724 0 001.066-2.573c-.94-1.543.826-3.31 2.37-2.37.996.608 2.296.07 2.572-1.065z M15 12a3 3 0 11-6 0 3 3 0 016 0z",
+                        active: active_tab() == SettingsTab::Config,
+                        onclick: move |_| active_tab.set(SettingsTab::Config),
+                    }
+                    TabButton {
+                        label: "slskd",
+                        icon_path: "M5 12h14M5 12a2 2 0 01-2-2V6a2 2 0 012-2h14a2 2 0 012 2v4a2 2 0 01-2 2M5 12a2 2 0 00-2 2v4a2 2 0 002 2h14a2 2 0 002-2v-4a2 2 0 00-2-2m-2-4h.01M17 16h.01",
+                        active: active_tab() == SettingsTab::Slskd,
+                        onclick: move |_| active_tab.set(SettingsTab::Slskd),
+                    }
+                    TabButton {
+                        label: "Audit",
+                        icon_path: "M9 5H7a2 2 0 00-2 2v12a2 2 0 002 2h10a2 2 0 002-2V7a2 2 0 00-2-2h-2M9 5a2 2 0 002 2h2a2 2 0 002-2M9 5a2 2 0 012-2h2a2 2 0 012 2m-6 9l2 2 4-4",
+                        active: active_tab() == SettingsTab::Audit,
+                        onclick: move |_| active_tab.set(SettingsTab::Audit),
+                    }
                 }
             }
 
@@ -57,9 +87,31 @@ pub fn SettingsPage() -> Element {
             div { class: "pt-8",
                 match active_tab() {
                     SettingsTab::Search => rsx! { PreferencesManager {} },
-                    SettingsTab::Library => rsx! { FolderManager {} },
-                    SettingsTab::Users => rsx! { UserManager {} },
-                    SettingsTab::Config => rsx! { AppConfigManager {} },
+                    SettingsTab::Library => rsx! {
+                        div { class: "space-y-6",
+                            FolderManager {}
+                            DownloadUsageViewer {}
+                        }
+                    },
+                    SettingsTab::Access => rsx! {
+                        div { class: "space-y-6",
+                            SessionManager {}
+                            ApiTokenManager {}
+                        }
+                    },
+                    SettingsTab::Users if is_admin => rsx! {
+                        div { class: "space-y-6",
+                            UserManager {}
+                            InviteManager {}
+                            DownloadLimitsManager {}
+                        }
+                    },
+                    SettingsTab::Config if is_admin => rsx! { AppConfigManager {} },
+                    SettingsTab::Slskd if is_admin => rsx! { SlskdAdminPanel {} },
+                    SettingsTab::Audit if is_admin => rsx! { AuditLogViewer {} },
+                    SettingsTab::Users | SettingsTab::Config | SettingsTab::Slskd | SettingsTab::Audit => {
+                        rsx! {}
+                    }
                 }
             }
         }